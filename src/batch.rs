@@ -1,37 +1,280 @@
+use crate::plan::{ConversionOptions, MediaKind, Plan, normalize_ext, normalize_ext_name};
 use anyhow::{Context, Result, bail};
-use glob::glob;
-use std::collections::BTreeSet;
+use glob::{Pattern, glob};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::hash::Hasher;
+use std::io::{BufReader, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use walkdir::WalkDir;
 
 pub struct BatchInput {
     pub dest_dir: PathBuf,
     pub to_ext: Option<String>,
+    pub name_template: Option<NameTemplate>,
 }
 
+/// Output file name pattern such as `{parent}-{stem}.{ext}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Stem,
+    Ext,
+    /// `{stem}.{ext}`, so `--to-ext` applies to it too.
+    Name,
+    Parent,
+    Index,
+}
+
+impl NameTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        if template.contains('/') || template.contains('\\') {
+            bail!("name template must not contain path separators: {template}");
+        }
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find(['{', '}']) {
+            if rest[open..].starts_with('}') {
+                bail!("unmatched '}}' in name template: {template}");
+            }
+            if open > 0 {
+                parts.push(TemplatePart::Literal(rest[..open].to_string()));
+            }
+            let close = rest[open..]
+                .find('}')
+                .with_context(|| format!("unclosed '{{' in name template: {template}"))?;
+            let token = &rest[open + 1..open + close];
+            parts.push(match token {
+                "stem" => TemplatePart::Stem,
+                "ext" => TemplatePart::Ext,
+                "name" => TemplatePart::Name,
+                "parent" => TemplatePart::Parent,
+                "n" | "index" => TemplatePart::Index,
+                other => bail!(
+                    "unknown name template token {{{other}}}; expected {{stem}}, {{ext}}, {{name}}, {{parent}} or {{n}}/{{index}}"
+                ),
+            });
+            rest = &rest[open + close + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Literal(rest.to_string()));
+        }
+        if parts.is_empty() {
+            bail!("name template must not be empty");
+        }
+        // Without a per-source token every output would get the same name.
+        if !parts.iter().any(|part| {
+            matches!(
+                part,
+                TemplatePart::Stem | TemplatePart::Name | TemplatePart::Index
+            )
+        }) {
+            bail!(
+                "name template must use {{stem}}, {{name}} or {{n}}/{{index}} so outputs do not collide: {template}"
+            );
+        }
+        Ok(Self { parts })
+    }
+
+    fn render(&self, stem: &str, ext: &str, parent: &str, index: usize) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Literal(text) => text.clone(),
+                TemplatePart::Stem => stem.to_string(),
+                TemplatePart::Ext => ext.to_string(),
+                TemplatePart::Name if ext.is_empty() => stem.to_string(),
+                TemplatePart::Name => format!("{stem}.{ext}"),
+                TemplatePart::Parent => parent.to_string(),
+                TemplatePart::Index => index.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// One batch conversion. Plain inputs carry only a source; `--manifest` jobs may also
+/// name their destination (relative to `--dest-dir`) and their own options.
+#[derive(Debug, Clone)]
+pub struct BatchJob {
+    pub source: PathBuf,
+    pub destination: Option<PathBuf>,
+    pub options: Option<ConversionOptions>,
+}
+
+impl BatchJob {
+    pub fn new(source: PathBuf) -> Self {
+        Self {
+            source,
+            destination: None,
+            options: None,
+        }
+    }
+}
+
+pub struct CollectedSources {
+    pub sources: Vec<PathBuf>,
+    pub duplicates: Vec<DuplicateInput>,
+}
+
+pub struct DuplicateInput {
+    pub input: String,
+    pub path: PathBuf,
+}
+
+/// `--exclude` patterns, compiled once. A path is excluded when a pattern matches either
+/// the whole path or just its file name, so `*.tmp` works at any depth.
+#[derive(Debug, Default)]
+pub struct Excludes {
+    patterns: Vec<Pattern>,
+}
+
+impl Excludes {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                Pattern::new(pattern).with_context(|| format!("invalid exclude pattern: {pattern}"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        self.patterns.iter().any(|pattern| {
+            pattern.matches_path(path)
+                || path
+                    .file_name()
+                    .is_some_and(|name| pattern.matches(&name.to_string_lossy()))
+        })
+    }
+}
+
+/// `--only` and `--ext` selection. Sources it rejects are skipped, not failed; the ones
+/// it keeps convert exactly as they would without it.
+#[derive(Debug, Default)]
+pub struct SourceFilter {
+    pub kind: Option<MediaKind>,
+    /// Normalized extensions without the leading dot.
+    pub exts: Vec<String>,
+}
+
+impl SourceFilter {
+    pub fn new(kind: Option<MediaKind>, exts: &[String]) -> Self {
+        Self {
+            kind,
+            exts: exts.iter().map(|ext| normalize_ext_name(ext)).collect(),
+        }
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        let kind_ok = self
+            .kind
+            .is_none_or(|kind| MediaKind::from_path(path) == kind);
+        let ext_ok =
+            self.exts.is_empty() || normalize_ext(path).is_some_and(|ext| self.exts.contains(&ext));
+        kind_ok && ext_ok
+    }
+}
+
+/// `stdin_sources` is consumed one line at a time, after `sources`, so a long piped
+/// list is never held in memory as a whole. Excludes win over includes: a file that an
+/// input names or matches is still dropped when an exclude matches it too.
 pub fn collect_sources(
     sources: &[String],
-    stdin_sources: Vec<String>,
+    stdin_sources: impl IntoIterator<Item = Result<String>>,
     recursive: bool,
-) -> Result<Vec<PathBuf>> {
+    excludes: &Excludes,
+) -> Result<CollectedSources> {
     let mut paths = BTreeSet::new();
-    for input in sources.iter().chain(stdin_sources.iter()) {
+    let mut duplicates = Vec::new();
+    let mut collect = |input: &str| -> Result<()> {
+        let mut seen = SeenPaths {
+            paths: &mut paths,
+            duplicates: &mut duplicates,
+            input,
+            excludes,
+        };
         if looks_like_glob(input) {
             for path in glob(input).context("invalid glob pattern")?.flatten() {
-                add_path(&mut paths, &path, recursive)?;
+                add_path(&mut seen, &path, recursive)?;
             }
-            continue;
+            return Ok(());
         }
-        add_path(&mut paths, &PathBuf::from(input), recursive)?;
+        add_path(&mut seen, &PathBuf::from(input), recursive)
+    };
+    for input in sources {
+        collect(input)?;
+    }
+    for input in stdin_sources {
+        collect(&input?)?;
     }
-    Ok(paths.into_iter().collect())
+    Ok(CollectedSources {
+        sources: paths.into_iter().collect(),
+        duplicates,
+    })
 }
 
-pub fn dest_for_source(input: &BatchInput, source: &Path) -> Result<PathBuf> {
+/// Entries of an `--input-file` list: one input per line, trimmed, skipping blank lines
+/// and `#` comments. Entries are used as given, like `--input` values, so relative paths
+/// resolve against the working directory.
+pub fn read_input_list(path: &Path) -> Result<Vec<String>> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("read input list {}", path.display()))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Inputs that name no existing path and are not glob patterns, i.e. the ones
+/// `collect_sources` would reject with "input not found".
+pub fn missing_inputs(sources: &[String]) -> Vec<&str> {
+    sources
+        .iter()
+        .map(String::as_str)
+        .filter(|input| !looks_like_glob(input) && !Path::new(input).exists())
+        .collect()
+}
+
+/// `index` is the 1-based position of `source` in the batch, used for `{n}`/`{index}`.
+pub fn dest_for_source(input: &BatchInput, source: &Path, index: usize) -> Result<PathBuf> {
     let file_name = source
         .file_name()
         .and_then(|name| name.to_str())
         .context("source must have a file name")?;
+    if let Some(template) = input.name_template.as_ref() {
+        let stem = source
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .context("source must have a file stem")?;
+        let ext = match input.to_ext.as_deref() {
+            Some(ext) => ext.trim_start_matches('.'),
+            None => source
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or_default(),
+        };
+        let parent = std::path::absolute(source)
+            .ok()
+            .and_then(|path| {
+                path.parent()
+                    .and_then(|parent| parent.file_name())
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_default();
+        return Ok(input
+            .dest_dir
+            .join(template.render(stem, ext, &parent, index)));
+    }
     if let Some(ext) = input.to_ext.as_deref() {
         let stem = source
             .file_stem()
@@ -43,12 +286,315 @@ pub fn dest_for_source(input: &BatchInput, source: &Path) -> Result<PathBuf> {
     Ok(input.dest_dir.join(file_name))
 }
 
-fn add_path(paths: &mut BTreeSet<PathBuf>, path: &Path, recursive: bool) -> Result<()> {
+/// Destinations that more than one batch job would write, or that are themselves a
+/// batch source.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Collision {
+    pub destination: PathBuf,
+    pub sources: Vec<PathBuf>,
+    /// The destination is also one of the batch's inputs.
+    pub overwrites_source: bool,
+}
+
+impl std::fmt::Display for Collision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sources: Vec<String> = self
+            .sources
+            .iter()
+            .map(|source| source.display().to_string())
+            .collect();
+        write!(
+            f,
+            "{} <- {}",
+            self.destination.display(),
+            sources.join(", ")
+        )?;
+        if self.overwrites_source {
+            f.write_str(" (also a batch input)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every job's destination, computed once before anything runs: a manifest destination
+/// relative to `--dest-dir`, else `dest_for_source` with the job's 1-based position.
+pub fn resolve_destinations(input: &BatchInput, jobs: &[BatchJob]) -> Vec<Result<PathBuf>> {
+    jobs.iter()
+        .enumerate()
+        .map(|(index, job)| match job.destination.as_deref() {
+            Some(destination) => Ok(input.dest_dir.join(destination)),
+            None => dest_for_source(input, &job.source, index + 1),
+        })
+        .collect()
+}
+
+/// Collisions among the resolved destinations, in destination order.
+pub fn find_collisions(jobs: &[BatchJob], destinations: &[Result<PathBuf>]) -> Vec<Collision> {
+    let sources: HashSet<PathBuf> = jobs.iter().map(|job| comparable(&job.source)).collect();
+    let mut writers: BTreeMap<PathBuf, (PathBuf, Vec<PathBuf>)> = BTreeMap::new();
+    for (job, destination) in jobs.iter().zip(destinations) {
+        if let Ok(destination) = destination {
+            writers
+                .entry(comparable(destination))
+                .or_insert_with(|| (destination.clone(), Vec::new()))
+                .1
+                .push(job.source.clone());
+        }
+    }
+    writers
+        .into_iter()
+        .filter_map(|(key, (destination, writers))| {
+            // A job writing over its own source is a same-file conversion, which
+            // planning already handles.
+            let overwrites_source =
+                sources.contains(&key) && writers.iter().any(|source| comparable(source) != key);
+            (writers.len() > 1 || overwrites_source).then_some(Collision {
+                destination,
+                sources: writers,
+                overwrites_source,
+            })
+        })
+        .collect()
+}
+
+/// `--dedupe-names`: the first job keeps a contested destination and later ones get
+/// `-1`, `-2`, ... before the extension, skipping names that are batch inputs too.
+pub fn dedupe_destinations(jobs: &[BatchJob], destinations: &mut [Result<PathBuf>]) {
+    let sources: HashSet<PathBuf> = jobs.iter().map(|job| comparable(&job.source)).collect();
+    let mut taken = HashSet::new();
+    for (job, destination) in jobs.iter().zip(destinations.iter_mut()) {
+        let Ok(path) = destination else {
+            continue;
+        };
+        let own_source = comparable(&job.source);
+        let free = |candidate: &Path, taken: &HashSet<PathBuf>| {
+            let key = comparable(candidate);
+            !taken.contains(&key) && (key == own_source || !sources.contains(&key))
+        };
+        if !free(path, &taken) {
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let ext = path
+                .extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_default();
+            *path = (1..)
+                .map(|n| path.with_file_name(format!("{stem}-{n}{ext}")))
+                .find(|candidate| free(candidate, &taken))
+                .expect("an unused suffix exists");
+        }
+        taken.insert(comparable(path));
+    }
+}
+
+/// An absolute form of `path` for comparisons, without touching the filesystem.
+fn comparable(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Append-only record of finished batch sources, one absolute path per line, so an
+/// interrupted batch can be resumed with the same `--state` file.
+pub struct StateFile {
+    completed: HashSet<PathBuf>,
+    file: Mutex<File>,
+}
+
+impl StateFile {
+    pub fn open(path: &Path) -> Result<Self> {
+        let completed = match fs::read_to_string(path) {
+            Ok(contents) => contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashSet::new(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("read state file {}", path.display()));
+            }
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("open state file {}", path.display()))?;
+        Ok(Self {
+            completed,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn is_completed(&self, source: &Path) -> bool {
+        self.completed.contains(&state_key(source))
+    }
+
+    /// Appends and flushes right away so a crash never loses finished entries.
+    pub fn record(&self, source: &Path) -> Result<()> {
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow::anyhow!("state file lock poisoned"))?;
+        writeln!(file, "{}", state_key(source).display())
+            .and_then(|_| file.flush())
+            .context("write state file")
+    }
+}
+
+fn state_key(source: &Path) -> PathBuf {
+    fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf())
+}
+
+/// Content hashes of the sources seen so far in a batch, for `--skip-duplicates`.
+#[derive(Default)]
+pub struct ContentIndex {
+    seen: HashMap<(u64, u64), Vec<PathBuf>>,
+}
+
+impl ContentIndex {
+    /// Returns the earlier source with identical content, or records `source` as new.
+    /// Hash matches are confirmed byte for byte, so a collision never drops a file.
+    pub fn find_duplicate(&mut self, source: &Path) -> Result<Option<PathBuf>> {
+        let key = content_key(source)?;
+        let candidates = self.seen.entry(key).or_default();
+        for candidate in candidates.iter() {
+            if same_content(candidate, source)? {
+                return Ok(Some(candidate.clone()));
+            }
+        }
+        candidates.push(source.to_path_buf());
+        Ok(None)
+    }
+}
+
+/// Outcomes of one backend or media kind in a batch summary.
+#[derive(Default)]
+pub struct GroupCounts {
+    pub ok: usize,
+    pub failed: usize,
+}
+
+/// Batch outcomes grouped by the backend that ran and the media kind written, so a
+/// summary shows where failures cluster.
+#[derive(Default)]
+pub struct Groups {
+    pub by_backend: BTreeMap<&'static str, GroupCounts>,
+    pub by_kind: BTreeMap<&'static str, GroupCounts>,
+}
+
+impl Groups {
+    /// Counts one outcome. A source that failed before it had a plan is `unknown`; a
+    /// plan that runs no tool, such as a copy, is `none`.
+    pub fn record(&mut self, plan: Option<&Plan>, ok: bool) {
+        let backend = match plan {
+            Some(plan) => plan
+                .backend
+                .map(|backend| backend.label())
+                .unwrap_or("none"),
+            None => "unknown",
+        };
+        let kind = plan.map(|plan| plan.dest_kind.label()).unwrap_or("unknown");
+        for counts in [
+            self.by_backend.entry(backend).or_default(),
+            self.by_kind.entry(kind).or_default(),
+        ] {
+            if ok {
+                counts.ok += 1;
+            } else {
+                counts.failed += 1;
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_backend.is_empty()
+    }
+}
+
+/// `imagemagick: 3 ok, ffmpeg: 1 ok 2 failed`.
+pub fn groups_text(groups: &BTreeMap<&'static str, GroupCounts>) -> String {
+    groups
+        .iter()
+        .map(|(name, counts)| {
+            if counts.failed > 0 {
+                format!("{name}: {} ok {} failed", counts.ok, counts.failed)
+            } else {
+                format!("{name}: {} ok", counts.ok)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn content_key(path: &Path) -> Result<(u64, u64)> {
+    let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut len = 0u64;
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .with_context(|| format!("read {}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buffer[..read]);
+        len += read as u64;
+    }
+    Ok((len, hasher.finish()))
+}
+
+fn same_content(a: &Path, b: &Path) -> Result<bool> {
+    let mut a = BufReader::new(File::open(a).with_context(|| format!("open {}", a.display()))?);
+    let mut b = BufReader::new(File::open(b).with_context(|| format!("open {}", b.display()))?);
+    let mut left = [0u8; 64 * 1024];
+    let mut right = [0u8; 64 * 1024];
+    loop {
+        let read = a.read(&mut left)?;
+        if read == 0 {
+            return Ok(b.read(&mut right)? == 0);
+        }
+        if b.read_exact(&mut right[..read]).is_err() || left[..read] != right[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+struct SeenPaths<'a> {
+    paths: &'a mut BTreeSet<PathBuf>,
+    duplicates: &'a mut Vec<DuplicateInput>,
+    input: &'a str,
+    excludes: &'a Excludes,
+}
+
+impl SeenPaths<'_> {
+    fn insert(&mut self, path: PathBuf) {
+        if self.excludes.matches(&path) {
+            return;
+        }
+        if self.paths.contains(&path) {
+            self.duplicates.push(DuplicateInput {
+                input: self.input.to_string(),
+                path,
+            });
+        } else {
+            self.paths.insert(path);
+        }
+    }
+}
+
+fn add_path(seen: &mut SeenPaths<'_>, path: &Path, recursive: bool) -> Result<()> {
     if path.is_dir() {
         if recursive {
-            for entry in WalkDir::new(path).into_iter().filter_map(Result::ok) {
+            // Excluded directories below the root are pruned rather than walked.
+            let walk = WalkDir::new(path)
+                .into_iter()
+                .filter_entry(|entry| entry.depth() == 0 || !seen.excludes.matches(entry.path()));
+            for entry in walk.filter_map(Result::ok) {
                 if entry.file_type().is_file() {
-                    paths.insert(entry.path().to_path_buf());
+                    seen.insert(entry.path().to_path_buf());
                 }
             }
         } else {
@@ -56,14 +602,14 @@ fn add_path(paths: &mut BTreeSet<PathBuf>, path: &Path, recursive: bool) -> Resu
                 let entry = entry?;
                 let entry_path = entry.path();
                 if entry_path.is_file() {
-                    paths.insert(entry_path);
+                    seen.insert(entry_path);
                 }
             }
         }
         return Ok(());
     }
     if path.exists() {
-        paths.insert(path.to_path_buf());
+        seen.insert(path.to_path_buf());
         return Ok(());
     }
     if looks_like_glob(path.to_string_lossy().as_ref()) {
@@ -86,19 +632,295 @@ mod tests {
         let input = BatchInput {
             dest_dir: PathBuf::from("/tmp/out"),
             to_ext: Some("mp3".to_string()),
+            name_template: None,
         };
-        let dest = dest_for_source(&input, Path::new("clip.wav")).unwrap();
+        let dest = dest_for_source(&input, Path::new("clip.wav"), 1).unwrap();
         assert_eq!(dest, PathBuf::from("/tmp/out/clip.mp3"));
     }
 
+    #[test]
+    fn dest_with_name_template() {
+        let mut input = BatchInput {
+            dest_dir: PathBuf::from("/tmp/out"),
+            to_ext: None,
+            name_template: Some(NameTemplate::parse("{parent}-{stem}_{n}.{ext}").unwrap()),
+        };
+        let source = Path::new("/media/trip/clip.wav");
+        let dest = dest_for_source(&input, source, 3).unwrap();
+        assert_eq!(dest, PathBuf::from("/tmp/out/trip-clip_3.wav"));
+
+        input.to_ext = Some(".mp3".to_string());
+        let dest = dest_for_source(&input, source, 4).unwrap();
+        assert_eq!(dest, PathBuf::from("/tmp/out/trip-clip_4.mp3"));
+    }
+
+    #[test]
+    fn collisions_cover_shared_names_and_other_sources() {
+        let input = BatchInput {
+            dest_dir: PathBuf::from("/tmp/out"),
+            to_ext: Some("mp3".to_string()),
+            name_template: None,
+        };
+        let mut jobs: Vec<BatchJob> = ["/a/song.wav", "/b/song.flac", "/c/take.wav"]
+            .into_iter()
+            .map(|source| BatchJob::new(PathBuf::from(source)))
+            .collect();
+        let collisions = find_collisions(&jobs, &resolve_destinations(&input, &jobs));
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(
+            collisions[0].to_string(),
+            "/tmp/out/song.mp3 <- /a/song.wav, /b/song.flac"
+        );
+
+        jobs.push(BatchJob::new(PathBuf::from("/tmp/out/take.mp3")));
+        let collisions = find_collisions(&jobs, &resolve_destinations(&input, &jobs));
+        assert_eq!(collisions.len(), 2);
+        assert!(collisions[1].overwrites_source);
+        assert_eq!(
+            collisions[1].to_string(),
+            "/tmp/out/take.mp3 <- /c/take.wav, /tmp/out/take.mp3 (also a batch input)"
+        );
+    }
+
+    #[test]
+    fn dedupe_suffixes_later_collisions() {
+        let input = BatchInput {
+            dest_dir: PathBuf::from("/tmp/out"),
+            to_ext: Some("mp3".to_string()),
+            name_template: None,
+        };
+        let jobs: Vec<BatchJob> = [
+            "/a/song.wav",
+            "/b/song.flac",
+            "/tmp/out/song-1.mp3",
+            "/c/song.ogg",
+        ]
+        .into_iter()
+        .map(|source| BatchJob::new(PathBuf::from(source)))
+        .collect();
+        let mut destinations = resolve_destinations(&input, &jobs);
+        dedupe_destinations(&jobs, &mut destinations);
+        let names: Vec<PathBuf> = destinations.into_iter().map(Result::unwrap).collect();
+        assert_eq!(
+            names,
+            [
+                PathBuf::from("/tmp/out/song.mp3"),
+                PathBuf::from("/tmp/out/song-2.mp3"),
+                PathBuf::from("/tmp/out/song-1.mp3"),
+                PathBuf::from("/tmp/out/song-3.mp3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_inputs_lists_every_absent_path() {
+        let temp = TempDir::new().unwrap();
+        let present = temp.path().join("a.wav");
+        std::fs::write(&present, "a").unwrap();
+        let inputs = vec![
+            present.display().to_string(),
+            temp.path().join("typo.wav").display().to_string(),
+            temp.path().join("*.flac").display().to_string(),
+            temp.path().join("gone").display().to_string(),
+        ];
+        assert_eq!(missing_inputs(&inputs), vec![&*inputs[1], &*inputs[3]]);
+    }
+
+    #[test]
+    fn name_template_rejects_bad_tokens() {
+        let err = NameTemplate::parse("{stem}-{date}.{ext}").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unknown name template token {date}")
+        );
+        assert!(NameTemplate::parse("{stem").is_err());
+        assert!(NameTemplate::parse("stem}").is_err());
+        assert!(NameTemplate::parse("sub/{stem}").is_err());
+        assert!(NameTemplate::parse("").is_err());
+        let err = NameTemplate::parse("{parent}.{ext}").unwrap_err();
+        assert!(err.to_string().contains("outputs do not collide"), "{err}");
+    }
+
+    #[test]
+    fn name_template_indexes_count_through_the_batch() {
+        let input = BatchInput {
+            dest_dir: PathBuf::from("/tmp/out"),
+            to_ext: None,
+            name_template: Some(NameTemplate::parse("{index}-{name}").unwrap()),
+        };
+        let names: Vec<PathBuf> = ["b.wav", "a.flac", "b.wav"]
+            .iter()
+            .enumerate()
+            .map(|(index, source)| dest_for_source(&input, Path::new(source), index + 1).unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                PathBuf::from("/tmp/out/1-b.wav"),
+                PathBuf::from("/tmp/out/2-a.flac"),
+                PathBuf::from("/tmp/out/3-b.wav"),
+            ]
+        );
+    }
+
+    #[test]
+    fn name_template_name_follows_to_ext() {
+        let mut input = BatchInput {
+            dest_dir: PathBuf::from("/tmp/out"),
+            to_ext: Some("mp3".to_string()),
+            name_template: Some(NameTemplate::parse("{name}.bak").unwrap()),
+        };
+        let dest = dest_for_source(&input, Path::new("clip.wav"), 1).unwrap();
+        assert_eq!(dest, PathBuf::from("/tmp/out/clip.mp3.bak"));
+        input.to_ext = None;
+        let dest = dest_for_source(&input, Path::new("README"), 1).unwrap();
+        assert_eq!(dest, PathBuf::from("/tmp/out/README.bak"));
+    }
+
     #[test]
     fn collect_sources_from_dir() {
         let temp = TempDir::new().unwrap();
         let dir = temp.path();
         std::fs::write(dir.join("a.txt"), "a").unwrap();
         std::fs::write(dir.join("b.txt"), "b").unwrap();
-        let sources =
-            collect_sources(&[dir.to_string_lossy().to_string()], Vec::new(), false).unwrap();
-        assert_eq!(sources.len(), 2);
+        let collected = collect_sources(
+            &[dir.to_string_lossy().to_string()],
+            [],
+            false,
+            &Excludes::default(),
+        )
+        .unwrap();
+        assert_eq!(collected.sources.len(), 2);
+        assert!(collected.duplicates.is_empty());
+    }
+
+    #[test]
+    fn state_file_remembers_completed_sources() {
+        let temp = TempDir::new().unwrap();
+        let done = temp.path().join("done.txt");
+        let todo = temp.path().join("todo.txt");
+        std::fs::write(&done, "a").unwrap();
+        std::fs::write(&todo, "b").unwrap();
+        let state_path = temp.path().join("state.txt");
+
+        let state = StateFile::open(&state_path).unwrap();
+        assert!(!state.is_completed(&done));
+        state.record(&done).unwrap();
+        drop(state);
+
+        let resumed = StateFile::open(&state_path).unwrap();
+        assert!(resumed.is_completed(&done));
+        assert!(!resumed.is_completed(&todo));
+    }
+
+    #[test]
+    fn content_index_skips_identical_files() {
+        let temp = TempDir::new().unwrap();
+        let first = temp.path().join("a.jpg");
+        let copy = temp.path().join("b.jpg");
+        let other = temp.path().join("c.jpg");
+        std::fs::write(&first, "same bytes").unwrap();
+        std::fs::write(&copy, "same bytes").unwrap();
+        std::fs::write(&other, "different").unwrap();
+
+        let mut index = ContentIndex::default();
+        assert_eq!(index.find_duplicate(&first).unwrap(), None);
+        assert_eq!(index.find_duplicate(&copy).unwrap(), Some(first));
+        assert_eq!(index.find_duplicate(&other).unwrap(), None);
+    }
+
+    #[test]
+    fn collect_sources_reports_duplicates() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let file = dir.join("a.txt");
+        std::fs::write(&file, "a").unwrap();
+        let file_input = file.to_string_lossy().to_string();
+        let glob_input = dir.join("*.txt").to_string_lossy().to_string();
+        let collected = collect_sources(
+            &[file_input, glob_input.clone()],
+            [],
+            false,
+            &Excludes::default(),
+        )
+        .unwrap();
+        assert_eq!(collected.sources, vec![file.clone()]);
+        assert_eq!(collected.duplicates.len(), 1);
+        assert_eq!(collected.duplicates[0].input, glob_input);
+        assert_eq!(collected.duplicates[0].path, file);
+    }
+
+    #[test]
+    fn collect_sources_drops_excluded_paths() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("clips/cache")).unwrap();
+        for name in [
+            "keep.mov",
+            "scratch.tmp",
+            "clips/take_proxy.mov",
+            "clips/take.mov",
+            "clips/cache/frame.mov",
+        ] {
+            std::fs::write(dir.join(name), "x").unwrap();
+        }
+        let excludes = Excludes::new(&[
+            "*.tmp".to_string(),
+            "*_proxy.mov".to_string(),
+            "cache".to_string(),
+        ])
+        .unwrap();
+        let glob_input = dir.join("*.*").to_string_lossy().to_string();
+        let collected = collect_sources(
+            &[dir.to_string_lossy().to_string(), glob_input],
+            [],
+            true,
+            &excludes,
+        )
+        .unwrap();
+        assert_eq!(
+            collected.sources,
+            vec![dir.join("clips/take.mov"), dir.join("keep.mov")]
+        );
+        assert!(Excludes::new(&["[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn source_filter_selects_by_kind_and_extension() {
+        let images = SourceFilter::new(Some(MediaKind::Image), &[]);
+        assert!(images.matches(Path::new("photos/a.JPEG")));
+        assert!(images.matches(Path::new("photos/b.cr2")));
+        assert!(!images.matches(Path::new("photos/notes.txt")));
+        assert!(!images.matches(Path::new("photos/clip.mov")));
+
+        let exts = SourceFilter::new(None, &[".jpeg".to_string(), "png".to_string()]);
+        assert!(exts.matches(Path::new("a.jpg")));
+        assert!(!exts.matches(Path::new("b.webp")));
+        assert!(!exts.matches(Path::new("README")));
+        assert!(SourceFilter::default().matches(Path::new("README")));
+    }
+
+    #[test]
+    fn input_list_skips_blank_lines_and_comments() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::fs::write(dir.join("b.md"), "b").unwrap();
+        std::fs::write(dir.join("c.txt"), "c").unwrap();
+        let list = dir.join("list.txt");
+        std::fs::write(
+            &list,
+            format!(
+                "# curated\n  {}  \n\n{}\n",
+                dir.join("a.txt").display(),
+                dir.join("*.md").display()
+            ),
+        )
+        .unwrap();
+        let inputs = read_input_list(&list).unwrap();
+        assert_eq!(inputs.len(), 2);
+        let collected = collect_sources(&inputs, [], false, &Excludes::default()).unwrap();
+        assert_eq!(collected.sources, vec![dir.join("a.txt"), dir.join("b.md")]);
+        assert!(read_input_list(&dir.join("missing.txt")).is_err());
     }
 }
@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::io;
+use std::process::{Child, ExitStatus};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::exit::FailureKind;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// How often a running tool is checked for a pending cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Installs the Ctrl-C handler. The first SIGINT only raises the cancellation flag, so
+/// the running tool is killed and temp files are removed on the normal error path; a
+/// second one exits at once for a run that will not stop.
+pub fn install_handler() -> Result<()> {
+    ctrlc::set_handler(|| {
+        if REQUESTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(i32::from(FailureKind::Interrupted.code()));
+        }
+    })
+    .context("failed to install the Ctrl-C handler")
+}
+
+/// Asks running work to stop, as Ctrl-C does. The TUI calls this for its own Ctrl-C key.
+pub fn request() {
+    REQUESTED.store(true, Ordering::SeqCst);
+}
+
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+/// The run was cancelled with Ctrl-C.
+#[derive(Debug)]
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("interrupted")
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+pub fn is_interrupted(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Interrupted>().is_some()
+}
+
+/// A tool ran longer than `--timeout` and was killed.
+#[derive(Debug)]
+pub struct TimedOut(pub Duration);
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "conversion timed out after {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Waits for `child` like `Child::wait`, but kills it once cancellation is requested or
+/// once it has run for `timeout`.
+pub fn wait_child(child: &mut Child, timeout: Option<Duration>) -> io::Result<ExitStatus> {
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if requested() {
+            return kill_child(child);
+        }
+        check_timeout(child, started, timeout)?;
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Kills `child` when it has run for `timeout` since `started`, failing with an
+/// `io::ErrorKind::TimedOut` error that wraps `TimedOut`.
+pub fn check_timeout(
+    child: &mut Child,
+    started: Instant,
+    timeout: Option<Duration>,
+) -> io::Result<()> {
+    match timeout {
+        Some(limit) if started.elapsed() >= limit => {
+            kill_child(child)?;
+            Err(io::Error::new(io::ErrorKind::TimedOut, TimedOut(limit)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Kills `child` and reaps it, so nothing is still writing into a temp directory that
+/// is about to be removed.
+pub fn kill_child(child: &mut Child) -> io::Result<ExitStatus> {
+    // The child may have exited on its own SIGINT in the meantime.
+    let _ = child.kill();
+    child.wait()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn wait_child_returns_the_exit_status() {
+        // The cancellation flag is process-wide, so only the uncancelled path is tested
+        // here; tests/conversion.rs covers an interrupted run end to end.
+        let mut child = Command::new("sh").args(["-c", "exit 3"]).spawn().unwrap();
+        assert_eq!(wait_child(&mut child, None).unwrap().code(), Some(3));
+        assert!(is_interrupted(
+            &anyhow::Error::new(Interrupted).context("execution failed")
+        ));
+    }
+
+    #[test]
+    fn wait_child_kills_a_child_past_its_timeout() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let started = Instant::now();
+        let err = wait_child(&mut child, Some(Duration::from_secs(1))).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(err.to_string(), "conversion timed out after 1s");
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(child.try_wait().unwrap().is_some());
+    }
+}
@@ -1,33 +1,305 @@
-use crate::plan::{ConversionOptions, FfmpegPreference};
+use crate::batch::BatchJob;
+use crate::plan::{
+    ConversionOptions, FfmpegPreference, normalize_ext_name, parse_ffmpeg_loglevel, parse_hwaccel,
+    parse_metadata, parse_rotation, parse_sample_fmt,
+};
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize, Default)]
-struct ConfigFile {
+pub struct ConfigFile {
     #[serde(default)]
     default: Profile,
     #[serde(default)]
     profile: HashMap<String, Profile>,
+    #[serde(default)]
+    tui: TuiConfig,
+    /// Per destination extension profiles, e.g. `[ext.jpg]`.
+    #[serde(default)]
+    ext: HashMap<String, Profile>,
 }
 
+/// Raw `[tui]` settings. Colors are names or `#rrggbb` hex, parsed by the TUI.
 #[derive(Debug, Deserialize, Default, Clone)]
+pub struct TuiConfig {
+    pub primary: Option<String>,
+    pub accent: Option<String>,
+    pub muted: Option<String>,
+    pub good: Option<String>,
+    pub bad: Option<String>,
+    /// Number of recent paths the wizard remembers.
+    pub history_size: Option<usize>,
+    /// Recent paths file, replacing `$XDG_CONFIG_HOME/mvx/history.txt`.
+    pub history_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
 struct Profile {
     image_quality: Option<u8>,
     video_bitrate: Option<String>,
+    crf: Option<u8>,
     audio_bitrate: Option<String>,
     preset: Option<String>,
     video_codec: Option<String>,
+    hwaccel: Option<String>,
     audio_codec: Option<String>,
+    ogg_opus: Option<bool>,
+    no_upscale_bitrate: Option<bool>,
+    sample_fmt: Option<String>,
+    dither: Option<bool>,
     ffmpeg_preference: Option<String>,
+    ffmpeg_loglevel: Option<String>,
+    two_pass: Option<bool>,
+    mute: Option<bool>,
+    speed: Option<f64>,
+    start: Option<String>,
+    duration: Option<String>,
+    to: Option<String>,
+    normalize: Option<bool>,
+    loudness: Option<f64>,
+    grayscale: Option<bool>,
+    srgb: Option<bool>,
+    icc: Option<PathBuf>,
+    backup_pad: Option<usize>,
+    fit: Option<String>,
+    resize: Option<String>,
+    density: Option<u32>,
+    lossless: Option<bool>,
+    effort: Option<u8>,
+    frame: Option<u32>,
+    all_pages: Option<bool>,
+    check_space: Option<bool>,
+    timeout: Option<u64>,
+    verify: Option<bool>,
+    strict_detect: Option<bool>,
+    preserve: Option<bool>,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+    rotate: Option<String>,
+    tonemap: Option<bool>,
+    metadata: Option<String>,
+    sanitize: Option<bool>,
+    sanitize_lowercase: Option<bool>,
+    sanitize_spaces: Option<bool>,
+    prefer_gs: Option<bool>,
+    output_dir: Option<PathBuf>,
+    /// Defaults for the `--move-source`, `--overwrite` and `--backup` flags, read by
+    /// `load_behavior`; not saved with a profile and not allowed in manifest jobs.
+    move_source: Option<bool>,
+    overwrite: Option<bool>,
+    backup: Option<bool>,
+}
+
+/// Config defaults for the flags that decide what happens to the source and an
+/// existing destination. `None` leaves the flag off unless it is passed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Behavior {
+    pub move_source: Option<bool>,
+    pub overwrite: Option<bool>,
+    pub backup: Option<bool>,
+}
+
+impl Behavior {
+    fn apply(&mut self, profile: &Profile) {
+        self.move_source = profile.move_source.or(self.move_source);
+        self.overwrite = profile.overwrite.or(self.overwrite);
+        self.backup = profile.backup.or(self.backup);
+    }
+}
+
+/// A `--manifest` file: `[[job]]` tables in TOML, or `{"job": [...]}` in JSON.
+#[derive(Debug, Deserialize)]
+struct ManifestFile {
+    #[serde(default)]
+    job: Vec<ManifestEntry>,
 }
 
+/// One manifest job. Every other key is a profile key overriding the global options.
+#[derive(Debug, Deserialize)]
+struct ManifestEntry {
+    source: PathBuf,
+    destination: Option<PathBuf>,
+    #[serde(flatten)]
+    options: Profile,
+}
+
+/// Reads a batch manifest. Relative sources are resolved against the manifest's
+/// directory; each job's options are `base` with the entry's keys applied on top.
+pub fn load_manifest(path: &Path, base: &ConversionOptions) -> Result<Vec<BatchJob>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("read manifest {}", path.display()))?;
+    let parsed: ManifestFile = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&contents)
+            .with_context(|| format!("parse manifest {}", path.display()))?
+    } else {
+        toml::from_str(&contents).with_context(|| format!("parse manifest {}", path.display()))?
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+    parsed
+        .job
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let mut options = base.clone();
+            if entry.options.move_source.is_some()
+                || entry.options.overwrite.is_some()
+                || entry.options.backup.is_some()
+            {
+                anyhow::bail!(
+                    "manifest job {}: move_source, overwrite and backup apply to the whole batch",
+                    index + 1
+                );
+            }
+            apply_profile(&entry.options, &mut options)
+                .with_context(|| format!("manifest job {}", index + 1))?;
+            if entry.options.image_quality.is_some() {
+                options.image_quality_by_ext.clear();
+            }
+            Ok(BatchJob {
+                source: dir.join(entry.source),
+                destination: entry.destination,
+                options: Some(options),
+            })
+        })
+        .collect()
+}
+
+/// `ConfigFile::options` for the config at `path`, or `None` when there is none.
 pub fn load_options(
     path: Option<&Path>,
     profile: Option<&str>,
+    dest_ext: Option<&str>,
 ) -> Result<Option<ConversionOptions>> {
+    read_config(path)?
+        .map(|parsed| parsed.options(profile, dest_ext))
+        .transpose()
+}
+
+impl ConfigFile {
+    /// Options from `[default]`, then the `[ext.*]` block matching `dest_ext`, then the
+    /// named profile; command-line flags go on top of the result. Destinations only known
+    /// later (batches without `--to-ext`) still pick up each block's `image_quality` at
+    /// plan time.
+    pub fn options(
+        &self,
+        profile: Option<&str>,
+        dest_ext: Option<&str>,
+    ) -> Result<ConversionOptions> {
+        let mut options = ConversionOptions::default();
+        apply_profile(&self.default, &mut options)?;
+        if let Some((ext, config)) = ext_profile(self, dest_ext) {
+            apply_profile(config, &mut options).with_context(|| format!("[ext.{ext}]"))?;
+        }
+        for (ext, config) in &self.ext {
+            let Some(quality) = config.image_quality else {
+                continue;
+            };
+            if quality == 0 || quality > 100 {
+                anyhow::bail!("image_quality for [ext.{ext}] must be between 1 and 100");
+            }
+            options
+                .image_quality_by_ext
+                .insert(normalize_ext_name(ext), quality);
+        }
+
+        if let Some(name) = profile {
+            let profile = named_profile(self, name)?;
+            apply_profile(profile, &mut options)?;
+            // A profile that pins a quality means it for every format.
+            if profile.image_quality.is_some() {
+                options.image_quality_by_ext.clear();
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// `move_source`, `overwrite` and `backup`, layered like `options`.
+    pub fn behavior(&self, profile: Option<&str>, dest_ext: Option<&str>) -> Result<Behavior> {
+        let mut behavior = Behavior::default();
+        behavior.apply(&self.default);
+        if let Some((_, config)) = ext_profile(self, dest_ext) {
+            behavior.apply(config);
+        }
+        if let Some(name) = profile {
+            behavior.apply(named_profile(self, name)?);
+        }
+        Ok(behavior)
+    }
+
+    pub fn tui(&self) -> &TuiConfig {
+        &self.tui
+    }
+}
+
+/// The `[ext.*]` block for a destination extension, matched after normalizing both.
+fn ext_profile<'a>(
+    parsed: &'a ConfigFile,
+    dest_ext: Option<&str>,
+) -> Option<(&'a String, &'a Profile)> {
+    let dest_ext = normalize_ext_name(dest_ext?);
+    parsed
+        .ext
+        .iter()
+        .find(|(ext, _)| normalize_ext_name(ext) == dest_ext)
+}
+
+fn named_profile<'a>(parsed: &'a ConfigFile, name: &str) -> Result<&'a Profile> {
+    parsed
+        .profile
+        .get(name)
+        .with_context(|| format!("profile not found in config: {name}"))
+}
+
+/// Writes `options` as `[profile.NAME]`, replacing a profile of the same name and keeping
+/// the other tables. Comments are not preserved. Returns the path written.
+pub fn save_profile(
+    path: Option<&Path>,
+    name: &str,
+    options: &ConversionOptions,
+) -> Result<PathBuf> {
+    let name = name.trim();
+    if name.is_empty() {
+        anyhow::bail!("profile name is required");
+    }
+    let config_path = match path {
+        Some(path) => path.to_path_buf(),
+        None => default_config_path()?,
+    };
+    let mut document = if config_path.exists() {
+        let contents = fs::read_to_string(&config_path)
+            .with_context(|| format!("read {}", config_path.display()))?;
+        contents
+            .parse::<toml::Table>()
+            .with_context(|| format!("parse {}", config_path.display()))?
+    } else {
+        toml::Table::new()
+    };
+    let profile =
+        toml::Table::try_from(Profile::from_options(options)).context("serialize profile")?;
+    let profiles = document
+        .entry("profile")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    let Some(profiles) = profiles.as_table_mut() else {
+        anyhow::bail!("`profile` in {} is not a table", config_path.display());
+    };
+    profiles.insert(name.to_string(), toml::Value::Table(profile));
+
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let contents = toml::to_string(&document).context("serialize config")?;
+    fs::write(&config_path, contents)
+        .with_context(|| format!("write {}", config_path.display()))?;
+    Ok(config_path)
+}
+
+/// Parses the config at `path`, or the default config when it exists; `None` when there
+/// is none. Read once per run and shared by options, behavior and `[tui]` settings.
+pub fn read_config(path: Option<&Path>) -> Result<Option<ConfigFile>> {
     let config_path = match path {
         Some(path) => path.to_path_buf(),
         None => default_config_path()?,
@@ -45,27 +317,105 @@ pub fn load_options(
         .with_context(|| format!("read {}", config_path.display()))?;
     let parsed: ConfigFile =
         toml::from_str(&contents).with_context(|| format!("parse {}", config_path.display()))?;
+    check_ext_keys(&parsed).with_context(|| format!("parse {}", config_path.display()))?;
+    Ok(Some(parsed))
+}
 
-    let mut options = ConversionOptions::default();
-    apply_profile(&parsed.default, &mut options)?;
-
-    if let Some(name) = profile {
-        if let Some(profile) = parsed.profile.get(name) {
-            apply_profile(profile, &mut options)?;
-        } else {
-            anyhow::bail!("profile not found in config: {}", name);
+/// Rejects `[ext.*]` blocks that name the same extension, such as `[ext.jpg]` and
+/// `[ext.JPEG]`; which one applied would otherwise depend on hash order.
+fn check_ext_keys(parsed: &ConfigFile) -> Result<()> {
+    let mut keys: Vec<&String> = parsed.ext.keys().collect();
+    keys.sort();
+    let mut seen: HashMap<String, &String> = HashMap::new();
+    for key in keys {
+        if let Some(first) = seen.insert(normalize_ext_name(key), key) {
+            anyhow::bail!(
+                "[ext.{first}] and [ext.{key}] configure the same extension; keep one of them"
+            );
         }
     }
+    Ok(())
+}
 
-    Ok(Some(options))
+impl Profile {
+    /// The inverse of `apply_profile`. Switches are always written so a saved profile
+    /// also turns off what `[default]` turns on; per-extension qualities are not kept.
+    fn from_options(options: &ConversionOptions) -> Self {
+        Self {
+            image_quality: options.image_quality,
+            video_bitrate: options.video_bitrate.clone(),
+            crf: options.crf,
+            audio_bitrate: options.audio_bitrate.clone(),
+            preset: options.preset.clone(),
+            video_codec: options.video_codec.clone(),
+            hwaccel: options.hwaccel.clone(),
+            audio_codec: options.audio_codec.clone(),
+            ogg_opus: Some(options.ogg_opus),
+            no_upscale_bitrate: Some(options.no_upscale_bitrate),
+            sample_fmt: options.sample_fmt.clone(),
+            dither: Some(options.dither),
+            ffmpeg_preference: Some(
+                match options.ffmpeg_preference {
+                    FfmpegPreference::Auto => "auto",
+                    FfmpegPreference::StreamCopy => "stream-copy",
+                    FfmpegPreference::Transcode => "transcode",
+                }
+                .to_string(),
+            ),
+            ffmpeg_loglevel: options.ffmpeg_loglevel.clone(),
+            two_pass: Some(options.two_pass),
+            mute: Some(options.mute),
+            speed: options.speed,
+            start: options.start.clone(),
+            duration: options.duration.clone(),
+            to: options.to.clone(),
+            normalize: Some(options.normalize),
+            loudness: options.loudness,
+            grayscale: Some(options.grayscale),
+            srgb: Some(options.srgb),
+            icc: options.icc_profile.clone(),
+            backup_pad: options.backup_pad,
+            fit: options.fit.clone(),
+            resize: options.resize.clone(),
+            density: options.density,
+            lossless: Some(options.lossless),
+            effort: options.effort,
+            frame: options.frame,
+            all_pages: Some(options.all_pages),
+            check_space: Some(options.check_space),
+            timeout: options.timeout,
+            verify: Some(options.verify),
+            strict_detect: Some(options.strict_detect),
+            preserve: Some(options.preserve),
+            max_width: options.max_width,
+            max_height: options.max_height,
+            rotate: options.rotate.map(|rotation| rotation.label()),
+            tonemap: Some(options.tonemap),
+            metadata: Some(options.metadata.label().to_string()),
+            sanitize: Some(options.sanitize.is_some()),
+            sanitize_lowercase: options.sanitize.map(|rules| rules.lowercase),
+            sanitize_spaces: options.sanitize.map(|rules| rules.underscore_spaces),
+            prefer_gs: Some(options.prefer_gs),
+            output_dir: options.output_dir.clone(),
+            move_source: None,
+            overwrite: None,
+            backup: None,
+        }
+    }
 }
 
 fn apply_profile(profile: &Profile, options: &mut ConversionOptions) -> Result<()> {
     if let Some(value) = profile.image_quality {
         options.image_quality = Some(value);
     }
+    // A bitrate and a CRF exclude each other, so a later layer's choice replaces the other.
     if let Some(value) = profile.video_bitrate.as_deref() {
         options.video_bitrate = Some(value.to_string());
+        options.crf = None;
+    }
+    if let Some(value) = profile.crf {
+        options.crf = Some(value);
+        options.video_bitrate = None;
     }
     if let Some(value) = profile.audio_bitrate.as_deref() {
         options.audio_bitrate = Some(value.to_string());
@@ -76,12 +426,139 @@ fn apply_profile(profile: &Profile, options: &mut ConversionOptions) -> Result<(
     if let Some(value) = profile.video_codec.as_deref() {
         options.video_codec = Some(value.to_string());
     }
+    if let Some(value) = profile.hwaccel.as_deref() {
+        options.hwaccel = Some(parse_hwaccel(value)?);
+    }
     if let Some(value) = profile.audio_codec.as_deref() {
         options.audio_codec = Some(value.to_string());
     }
+    if let Some(value) = profile.ogg_opus {
+        options.ogg_opus = value;
+    }
+    if let Some(value) = profile.no_upscale_bitrate {
+        options.no_upscale_bitrate = value;
+    }
+    if let Some(value) = profile.sample_fmt.as_deref() {
+        options.sample_fmt = Some(parse_sample_fmt(value)?);
+    }
+    if let Some(value) = profile.dither {
+        options.dither = value;
+    }
     if let Some(value) = profile.ffmpeg_preference.as_deref() {
         options.ffmpeg_preference = parse_preference(value)?;
     }
+    if let Some(value) = profile.ffmpeg_loglevel.as_deref() {
+        options.ffmpeg_loglevel = Some(parse_ffmpeg_loglevel(value)?);
+    }
+    if let Some(value) = profile.two_pass {
+        options.two_pass = value;
+    }
+    if let Some(value) = profile.mute {
+        options.mute = value;
+    }
+    if let Some(value) = profile.speed {
+        options.speed = Some(value);
+    }
+    if let Some(value) = profile.start.as_deref() {
+        options.start = Some(value.to_string());
+    }
+    if let Some(value) = profile.duration.as_deref() {
+        options.duration = Some(value.to_string());
+        options.to = None;
+    }
+    if let Some(value) = profile.to.as_deref() {
+        options.to = Some(value.to_string());
+        options.duration = None;
+    }
+    if let Some(value) = profile.normalize {
+        options.normalize = value;
+    }
+    if let Some(value) = profile.loudness {
+        options.loudness = Some(value);
+    }
+    if let Some(value) = profile.grayscale {
+        options.grayscale = value;
+    }
+    if let Some(value) = profile.srgb {
+        options.srgb = value;
+    }
+    if let Some(value) = profile.icc.as_ref() {
+        options.icc_profile = Some(value.clone());
+    }
+    if let Some(value) = profile.backup_pad {
+        options.backup_pad = Some(value);
+    }
+    if let Some(value) = profile.fit.as_deref() {
+        options.fit = Some(value.to_string());
+    }
+    if let Some(value) = profile.resize.as_deref() {
+        options.resize = Some(value.to_string());
+    }
+    if let Some(value) = profile.density {
+        options.density = Some(value);
+    }
+    if let Some(value) = profile.lossless {
+        options.lossless = value;
+    }
+    if let Some(value) = profile.effort {
+        options.effort = Some(value);
+    }
+    if let Some(value) = profile.frame {
+        options.frame = Some(value);
+    }
+    if let Some(value) = profile.all_pages {
+        options.all_pages = value;
+    }
+    if let Some(value) = profile.check_space {
+        options.check_space = value;
+    }
+    if let Some(value) = profile.timeout {
+        options.timeout = Some(value);
+    }
+    if let Some(value) = profile.verify {
+        options.verify = value;
+    }
+    if let Some(value) = profile.strict_detect {
+        options.strict_detect = value;
+    }
+    if let Some(value) = profile.preserve {
+        options.preserve = value;
+    }
+    if let Some(value) = profile.max_width {
+        options.max_width = Some(value);
+    }
+    if let Some(value) = profile.max_height {
+        options.max_height = Some(value);
+    }
+    if let Some(value) = profile.rotate.as_deref() {
+        options.rotate = Some(parse_rotation(value)?);
+    }
+    if let Some(value) = profile.tonemap {
+        options.tonemap = value;
+    }
+    if let Some(value) = profile.metadata.as_deref() {
+        options.metadata = parse_metadata(value)?;
+    }
+    if let Some(value) = profile.sanitize {
+        options.sanitize = value.then(|| options.sanitize.unwrap_or_default());
+    }
+    // The extra rules imply `sanitize = true`; setting one to false never enables it.
+    if let Some(value) = profile.sanitize_lowercase
+        && (value || options.sanitize.is_some())
+    {
+        options.sanitize.get_or_insert_default().lowercase = value;
+    }
+    if let Some(value) = profile.sanitize_spaces
+        && (value || options.sanitize.is_some())
+    {
+        options.sanitize.get_or_insert_default().underscore_spaces = value;
+    }
+    if let Some(value) = profile.prefer_gs {
+        options.prefer_gs = value;
+    }
+    if let Some(value) = profile.output_dir.as_ref() {
+        options.output_dir = Some(value.clone());
+    }
     Ok(())
 }
 
@@ -104,3 +581,188 @@ fn default_config_path() -> Result<PathBuf> {
     };
     Ok(base.join("mvx").join("config.toml"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::Rotation;
+    use tempfile::TempDir;
+
+    #[test]
+    fn saved_profile_loads_back() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        fs::write(
+            &path,
+            "[default]\ntwo_pass = true\n\n[tui]\nprimary = \"blue\"\n",
+        )
+        .unwrap();
+        let options = ConversionOptions {
+            video_bitrate: Some("2M".to_string()),
+            ffmpeg_preference: FfmpegPreference::Transcode,
+            ffmpeg_loglevel: Some("verbose".to_string()),
+            rotate: Some(Rotation::Set(90)),
+            mute: true,
+            ..ConversionOptions::default()
+        };
+        save_profile(Some(&path), "quick", &options).unwrap();
+
+        let loaded = load_options(Some(&path), Some("quick"), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.video_bitrate.as_deref(), Some("2M"));
+        assert_eq!(loaded.ffmpeg_preference, FfmpegPreference::Transcode);
+        assert_eq!(loaded.ffmpeg_loglevel.as_deref(), Some("verbose"));
+        assert_eq!(loaded.rotate, Some(Rotation::Set(90)));
+        assert!(loaded.mute);
+        assert!(!loaded.two_pass);
+        assert_eq!(
+            read_config(Some(&path))
+                .unwrap()
+                .unwrap()
+                .tui()
+                .primary
+                .as_deref(),
+            Some("blue")
+        );
+        let defaults = load_options(Some(&path), None, None).unwrap().unwrap();
+        assert!(defaults.two_pass);
+    }
+
+    #[test]
+    fn ext_blocks_apply_to_their_destination_extension() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        fs::write(
+            &path,
+            concat!(
+                "[default]\nimage_quality = 75\nvideo_codec = \"libx264\"\npreset = \"fast\"\n\n",
+                "[ext.mkv]\nvideo_codec = \"libx265\"\n\n",
+                "[ext.webp]\nimage_quality = 92\n\n",
+                "[profile.slow]\npreset = \"slow\"\n",
+            ),
+        )
+        .unwrap();
+        let load = |profile, dest_ext| {
+            load_options(Some(&path), profile, dest_ext)
+                .unwrap()
+                .unwrap()
+        };
+
+        let mkv = load(None, Some("mkv"));
+        assert_eq!(mkv.video_codec.as_deref(), Some("libx265"));
+        assert_eq!(mkv.image_quality, Some(75));
+        let mp4 = load(None, Some("MP4"));
+        assert_eq!(mp4.video_codec.as_deref(), Some("libx264"));
+        let webp = load(None, Some(".webp"));
+        assert_eq!(webp.image_quality, Some(92));
+        assert_eq!(webp.video_codec.as_deref(), Some("libx264"));
+
+        let profiled = load(Some("slow"), Some("mkv"));
+        assert_eq!(profiled.video_codec.as_deref(), Some("libx265"));
+        assert_eq!(profiled.preset.as_deref(), Some("slow"));
+        // Unknown destinations still resolve each block's quality when planned.
+        assert_eq!(load(None, None).image_quality_by_ext.get("webp"), Some(&92));
+    }
+
+    #[test]
+    fn behavior_keys_layer_like_options() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        fs::write(
+            &path,
+            concat!(
+                "[default]\nbackup = true\nmove_source = true\n\n",
+                "[ext.mp4]\nmove_source = false\n\n",
+                "[profile.replace]\nbackup = false\noverwrite = true\n",
+            ),
+        )
+        .unwrap();
+        let parsed = read_config(Some(&path)).unwrap().unwrap();
+        let load = |profile, dest_ext| parsed.behavior(profile, dest_ext).unwrap();
+
+        assert_eq!(
+            load(None, None),
+            Behavior {
+                move_source: Some(true),
+                overwrite: None,
+                backup: Some(true),
+            }
+        );
+        assert_eq!(load(None, Some("mp4")).move_source, Some(false));
+        let replace = load(Some("replace"), None);
+        assert_eq!(replace.backup, Some(false));
+        assert_eq!(replace.overwrite, Some(true));
+        assert!(parsed.behavior(Some("missing"), None).is_err());
+    }
+
+    #[test]
+    fn ext_blocks_for_one_extension_are_rejected() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        for (first, second) in [("jpeg", "jpg"), ("JPG", "jpg")] {
+            fs::write(
+                &path,
+                format!(
+                    "[ext.{first}]\nimage_quality = 70\n\n[ext.{second}]\nimage_quality = 90\n"
+                ),
+            )
+            .unwrap();
+            let err = read_config(Some(&path)).unwrap_err();
+            assert!(
+                format!("{err:#}").contains("configure the same extension"),
+                "{err:#}"
+            );
+        }
+    }
+
+    #[test]
+    fn manifest_jobs_override_base_options() {
+        let temp = TempDir::new().unwrap();
+        let toml_path = temp.path().join("jobs.toml");
+        fs::write(
+            &toml_path,
+            concat!(
+                "[[job]]\nsource = \"talk.mp4\"\ndestination = \"talk.mp3\"\n",
+                "audio_bitrate = \"96k\"\n\n",
+                "[[job]]\nsource = \"/abs/clip.mov\"\nmax_height = 720\nmute = true\n",
+            ),
+        )
+        .unwrap();
+        let base = ConversionOptions {
+            audio_bitrate: Some("192k".to_string()),
+            preset: Some("fast".to_string()),
+            ..ConversionOptions::default()
+        };
+        let jobs = load_manifest(&toml_path, &base).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].source, temp.path().join("talk.mp4"));
+        assert_eq!(jobs[0].destination.as_deref(), Some(Path::new("talk.mp3")));
+        let first = jobs[0].options.as_ref().unwrap();
+        assert_eq!(first.audio_bitrate.as_deref(), Some("96k"));
+        assert_eq!(first.preset.as_deref(), Some("fast"));
+        assert_eq!(jobs[1].source, Path::new("/abs/clip.mov"));
+        assert!(jobs[1].destination.is_none());
+        let second = jobs[1].options.as_ref().unwrap();
+        assert_eq!(second.max_height, Some(720));
+        assert!(second.mute);
+        assert_eq!(second.audio_bitrate.as_deref(), Some("192k"));
+
+        let json_path = temp.path().join("jobs.json");
+        fs::write(
+            &json_path,
+            r#"{"job": [{"source": "a.png", "image_quality": 70}]}"#,
+        )
+        .unwrap();
+        let jobs = load_manifest(&json_path, &base).unwrap();
+        assert_eq!(jobs[0].options.as_ref().unwrap().image_quality, Some(70));
+
+        fs::write(
+            &json_path,
+            r#"{"job": [{"source": "a.mp4", "rotate": "sideways"}]}"#,
+        )
+        .unwrap();
+        let err = load_manifest(&json_path, &base).unwrap_err();
+        assert!(format!("{err:#}").starts_with("manifest job 1"), "{err:#}");
+    }
+}
@@ -4,15 +4,90 @@ use std::process::Command;
 #[derive(Debug, Clone)]
 pub struct DetectedType {
     pub mime: Option<String>,
+    /// Extension that goes with `mime`, e.g. `png` for `image/png`.
+    pub mime_ext: Option<String>,
     pub ext_hint: Option<String>,
     pub file_mime: Option<String>,
 }
 
+impl DetectedType {
+    /// Extension for the detected content: infer's, or else one mapped from `file(1)`'s
+    /// MIME type.
+    pub fn content_ext(&self) -> Option<&str> {
+        self.mime_ext.as_deref().or_else(|| {
+            let file_mime = self.file_mime.as_deref()?;
+            MIME_EXTS
+                .iter()
+                .find(|(mime, _)| *mime == file_mime)
+                .map(|(_, ext)| *ext)
+        })
+    }
+
+    /// The extension the content calls for when it contradicts the file's own, e.g. `png`
+    /// for a `.jpg` that is really a PNG. Aliases (`jpeg`/`jpg`, `tiff`/`tif`) and formats
+    /// that share a container (`.m4a` in MP4, `.docx` in ZIP, TIFF-based RAW files) are
+    /// not reported.
+    pub fn extension_mismatch(&self) -> Option<&str> {
+        let content = self.content_ext()?;
+        let named = self.ext_hint.as_deref()?;
+        if content == named || IGNORED_CONTENT.contains(&content) {
+            return None;
+        }
+        let shared = EQUIVALENT_EXTS
+            .iter()
+            .any(|group| group.contains(&content) && group.contains(&named));
+        (!shared).then_some(content)
+    }
+}
+
+/// Signatures too loose to contradict a name: HTML-ish text, shebang scripts, and the
+/// XML prolog every SVG, RSS or XHTML file may start with.
+const IGNORED_CONTENT: [&str; 3] = ["html", "sh", "xml"];
+
+/// `file --mime-type` answers for content infer does not recognize, such as SVG.
+const MIME_EXTS: [(&str, &str); 12] = [
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/tiff", "tif"),
+    ("image/bmp", "bmp"),
+    ("image/svg+xml", "svg"),
+    ("application/pdf", "pdf"),
+    ("audio/mpeg", "mp3"),
+    ("audio/flac", "flac"),
+    ("video/mp4", "mp4"),
+    ("video/quicktime", "mov"),
+];
+
+/// Extensions that legitimately hold the same detected content.
+const EQUIVALENT_EXTS: &[&[&str]] = &[
+    &["jpg", "jpeg", "jpe", "jfif"],
+    &["jp2", "j2k", "jpf", "jpx"],
+    &[
+        "tif", "tiff", "cr2", "dng", "nef", "arw", "orf", "rw2", "pef", "srw",
+    ],
+    &["heif", "heic"],
+    &["mp4", "m4v", "m4a", "m4b", "mov", "3gp", "3g2"],
+    &["mkv", "webm", "mka"],
+    &["ogg", "oga", "ogv", "opus", "spx"],
+    &["mpg", "mpeg", "vob", "m2v"],
+    &["midi", "mid"],
+    &["aiff", "aif", "aifc"],
+    &["wav", "wave"],
+    &["pdf", "ai"],
+    &["ps", "eps"],
+    &["doc", "xls", "ppt", "msi"],
+    &[
+        "zip", "docx", "xlsx", "pptx", "odt", "ods", "odp", "epub", "jar", "apk", "cbz",
+    ],
+    &["gz", "tgz"],
+];
+
 pub fn detect_path(path: &Path) -> DetectedType {
-    let mime = infer::get_from_path(path)
-        .ok()
-        .flatten()
-        .map(|kind| kind.mime_type().to_string());
+    let kind = infer::get_from_path(path).ok().flatten();
+    let mime = kind.map(|kind| kind.mime_type().to_string());
+    let mime_ext = kind.map(|kind| kind.extension().to_string());
     let ext_hint = path
         .extension()
         .and_then(|ext| ext.to_str())
@@ -21,6 +96,7 @@ pub fn detect_path(path: &Path) -> DetectedType {
 
     DetectedType {
         mime,
+        mime_ext,
         ext_hint,
         file_mime,
     }
@@ -44,3 +120,44 @@ fn detect_file_mime(path: &Path) -> Option<String> {
         Some(trimmed.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn flags_content_that_contradicts_the_extension() {
+        let temp = TempDir::new().unwrap();
+        let renamed = temp.path().join("photo.JPG");
+        std::fs::write(&renamed, PNG_SIGNATURE).unwrap();
+        let detected = detect_path(&renamed);
+        assert_eq!(detected.mime.as_deref(), Some("image/png"));
+        assert_eq!(detected.ext_hint.as_deref(), Some("jpg"));
+        assert_eq!(detected.extension_mismatch(), Some("png"));
+
+        let honest = temp.path().join("photo.png");
+        std::fs::write(&honest, PNG_SIGNATURE).unwrap();
+        assert_eq!(detect_path(&honest).extension_mismatch(), None);
+
+        let container = DetectedType {
+            mime: Some("video/mp4".to_string()),
+            mime_ext: Some("mp4".to_string()),
+            ext_hint: Some("m4a".to_string()),
+            file_mime: None,
+        };
+        assert_eq!(container.extension_mismatch(), None);
+
+        let svg = temp.path().join("logo.svg");
+        std::fs::write(
+            &svg,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>\n",
+        )
+        .unwrap();
+        let detected = detect_path(&svg);
+        assert_eq!(detected.mime.as_deref(), Some("text/xml"));
+        assert_eq!(detected.extension_mismatch(), None);
+    }
+}
@@ -1,10 +1,21 @@
-use crate::ffprobe::probe_media;
+use crate::exit::{MissingTool, is_missing_tool};
+use crate::ffprobe::SourceProbe;
 use crate::plan::{
-    Backend, FfmpegMode, MediaKind, Plan, Strategy, default_audio_codec, default_video_codec,
+    Backend, FfmpegMode, LARGE_COPY_BYTES, MediaKind, NULL_DEVICE, PAGE_PATTERN, Plan, Rotation,
+    Strategy, TWO_PASS_LOG_PREFIX, audio_encoder, audio_filters, audio_format_arg,
+    av1_default_args, decide_ffmpeg_mode, estimated_output_bytes, ghostscript_args, normalize_ext,
+    numbered_destination, rasterizes_document, sets_rotation, transpose_filter, video_encoder,
+    video_filters,
 };
+use crate::runlog::{RunLog, TAIL_LINES, tail};
+use crate::style::paint;
+use crate::temps::TEMP_PREFIX;
 use anyhow::{Context, Result, bail};
+use crossterm::style::Color;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
 use std::fs;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::mpsc::Sender;
@@ -34,24 +45,55 @@ pub enum ProgressEvent {
 }
 
 pub enum ProgressMode {
-    Console { json_output: bool },
+    Console { json_output: bool, color: bool },
     Tui { sender: Sender<ProgressEvent> },
 }
 
 pub struct ProgressReporter {
     mode: ProgressMode,
+    log: Option<RunLog>,
 }
 
 impl ProgressReporter {
-    pub fn console(json_output: bool) -> Self {
+    pub fn console(json_output: bool, color: bool) -> Self {
         Self {
-            mode: ProgressMode::Console { json_output },
+            mode: ProgressMode::Console { json_output, color },
+            log: None,
         }
     }
 
     pub fn tui(sender: Sender<ProgressEvent>) -> Self {
         Self {
             mode: ProgressMode::Tui { sender },
+            log: None,
+        }
+    }
+
+    /// Also writes plans, commands, tool output and results to a `--log-file`.
+    pub fn with_log(mut self, log: Option<RunLog>) -> Self {
+        self.log = log;
+        self
+    }
+
+    fn log(&self, label: &str, heading: &str, body: &str) {
+        if let Some(log) = &self.log {
+            log.entry(label, heading, body);
+        }
+    }
+
+    fn log_command(&self, label: &str, command: &Command) {
+        if self.log.is_some() {
+            self.log(label, "command", &describe_command(command));
+        }
+    }
+
+    fn log_output(&self, label: &str, stream: &str, output: &str) {
+        if !output.trim().is_empty() {
+            self.log(
+                label,
+                &format!("{stream} (tail)"),
+                &tail(output, TAIL_LINES),
+            );
         }
     }
 
@@ -94,56 +136,306 @@ impl ProgressReporter {
     }
 
     fn json_output(&self) -> bool {
-        matches!(self.mode, ProgressMode::Console { json_output: true })
+        matches!(
+            self.mode,
+            ProgressMode::Console {
+                json_output: true,
+                ..
+            }
+        )
     }
 
     fn should_print(&self) -> bool {
-        matches!(self.mode, ProgressMode::Console { json_output: false })
+        matches!(
+            self.mode,
+            ProgressMode::Console {
+                json_output: false,
+                ..
+            }
+        )
+    }
+
+    /// Whether `\r` progress rewrites belong on stderr. Redirected stderr gets only
+    /// whole lines, so CI logs are not flooded with carriage-return updates.
+    fn should_animate(&self) -> bool {
+        self.should_print() && std::io::stderr().is_terminal()
+    }
+
+    fn paint(&self, text: &str, color: Color) -> String {
+        let enabled = matches!(self.mode, ProgressMode::Console { color: true, .. });
+        paint(text, color, enabled)
+    }
+
+    fn warn(&self, message: &str) {
+        eprintln!("{} {message}", self.paint("Warning:", Color::Yellow));
+        self.log("mvx", "warning", message);
+    }
+}
+
+/// How a plan that did not fail was handled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Done(ExecutionReport),
+    /// `--overwrite-newer` kept an existing destination that is not older than the source.
+    SkippedNotNewer,
+}
+
+/// What a finished plan actually did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionReport {
+    pub strategy_used: Strategy,
+    /// How ffmpeg ran, which `FfmpegPreference::Auto` leaves to the probe at runtime;
+    /// `None` for other backends.
+    pub ffmpeg_mode: Option<FfmpegMode>,
+    /// Where `--backup` moved the previous destination.
+    pub backup_path: Option<PathBuf>,
+    /// Source and output sizes for a single output whose source size could be read.
+    pub sizes: Option<SizeChange>,
+    /// Total size of everything written, every page with `--all-pages`.
+    pub output_bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl ExecutionReport {
+    /// `stream copy, output 3 MB, 41% of source`, for the TUI's finished row.
+    pub fn describe(&self) -> String {
+        let mode = self.ffmpeg_mode.map(|mode| match mode {
+            FfmpegMode::StreamCopy => "stream copy".to_string(),
+            FfmpegMode::Transcode => "transcode".to_string(),
+        });
+        mode.into_iter()
+            .chain(self.sizes.map(SizeChange::describe))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Files a strategy wrote and, for ffmpeg, the mode it ran in.
+struct Written {
+    outputs: Vec<PathBuf>,
+    ffmpeg_mode: Option<FfmpegMode>,
+}
+
+impl Written {
+    fn file(destination: &Path) -> Self {
+        Self {
+            outputs: vec![destination.to_path_buf()],
+            ffmpeg_mode: None,
+        }
     }
 }
 
-pub fn execute_plan(plan: &Plan, overwrite: bool, json_output: bool) -> Result<()> {
-    let reporter = ProgressReporter::console(json_output);
-    execute_plan_with_reporter(plan, overwrite, &reporter)
+/// Source and output sizes of a finished job, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeChange {
+    pub source: u64,
+    pub output: u64,
+}
+
+impl SizeChange {
+    /// Output size as a fraction of the source size; `None` for an empty source.
+    pub fn ratio(self) -> Option<f64> {
+        (self.source > 0).then(|| self.output as f64 / self.source as f64)
+    }
+
+    /// `output 3 MB, 41% of source`.
+    pub fn describe(self) -> String {
+        match self.ratio() {
+            Some(ratio) => format!(
+                "output {}, {:.0}% of source",
+                format_size(self.output),
+                ratio * 100.0
+            ),
+            None => format!("output {}", format_size(self.output)),
+        }
+    }
+
+    /// `saved 1.2 GB`, or `grew by 3 MB` when the output is larger.
+    pub fn savings(self) -> String {
+        if self.output <= self.source {
+            format!("saved {}", format_size(self.source - self.output))
+        } else {
+            format!("grew by {}", format_size(self.output - self.source))
+        }
+    }
 }
 
 pub fn execute_plan_with_reporter(
     plan: &Plan,
     overwrite: bool,
     reporter: &ProgressReporter,
-) -> Result<()> {
+) -> Result<Outcome> {
     let label = plan.source.display().to_string();
     reporter.started(&label);
-    ensure_parent_dir(&plan.destination)?;
-    if plan.destination.exists() {
+    reporter.log(&label, "plan", &crate::plan::render_plan(plan, overwrite));
+    // Whatever a killed tool or its cleanup reported, the cause was the cancellation.
+    let result = run_plan(plan, overwrite, reporter, &label).map_err(|err| {
+        if crate::cancel::requested() {
+            err.context(crate::cancel::Interrupted)
+        } else {
+            err
+        }
+    });
+    match &result {
+        Ok(Outcome::Done(report)) => reporter.log(&label, "result: ok", &report.describe()),
+        Ok(Outcome::SkippedNotNewer) => {
+            reporter.log(&label, "result: skipped, destination is not older", "")
+        }
+        Err(err) => reporter.log(&label, "result: failed", &format!("{err:#}")),
+    }
+    result
+}
+
+fn run_plan(
+    plan: &Plan,
+    overwrite: bool,
+    reporter: &ProgressReporter,
+    label: &str,
+) -> Result<Outcome> {
+    let started = Instant::now();
+    // `--fit` needs the source's size, which is only read once the plan runs.
+    let settled = crate::plan::settle_fit(plan);
+    let plan = settled.as_ref().unwrap_or(plan);
+    let mut overwrite = overwrite;
+    let mut backup_path = None;
+    // `--all-pages` writes numbered files instead; `finalize_pages` checks those.
+    if plan.destination.exists() && !plan.options.all_pages {
         if plan.backup {
-            backup_existing(&plan.destination)?;
+            backup_path = Some(backup_existing(&plan.destination, plan.options.backup_pad)?);
+        } else if plan.options.overwrite_newer {
+            if !source_is_newer(&plan.source, &plan.destination)? {
+                reporter.finished(label, true, "skipped: destination is not older");
+                return Ok(Outcome::SkippedNotNewer);
+            }
+            overwrite = true;
         } else if !overwrite {
-            bail!("destination exists; pass --overwrite or --backup");
+            return Err(DestinationExists.into());
         }
     }
+    ensure_parent_dir(&plan.destination)?;
+    if plan.options.check_space {
+        ensure_free_space(plan)?;
+    }
 
+    // Read before running: moves and renames remove the source.
+    let source_bytes = fs::metadata(&plan.source).map(|metadata| metadata.len());
     let result = match plan.strategy {
-        Strategy::RenameOnly => rename_only(&plan.source, &plan.destination, overwrite),
-        Strategy::CopyOnly => copy_only(&plan.source, &plan.destination, overwrite),
-        Strategy::Convert => convert(plan, overwrite, reporter, &label),
+        Strategy::RenameOnly => rename_only(
+            &plan.source,
+            &plan.destination,
+            overwrite,
+            plan.options.verify,
+        )
+        .map(|()| Written::file(&plan.destination)),
+        Strategy::CopyOnly => copy_only(&plan.source, &plan.destination, overwrite, &plan.options)
+            .map(|()| Written::file(&plan.destination)),
+        Strategy::Convert | Strategy::Remux => convert(plan, overwrite, reporter, label),
     };
+    let result = result.map(|written| {
+        let lengths: Vec<u64> = written
+            .outputs
+            .iter()
+            .filter_map(|output| fs::metadata(output).ok())
+            .map(|metadata| metadata.len())
+            .collect();
+        let sizes = match (source_bytes.as_ref(), lengths.as_slice()) {
+            (Ok(&source), &[output]) if !plan.options.all_pages => {
+                Some(SizeChange { source, output })
+            }
+            _ => None,
+        };
+        ExecutionReport {
+            strategy_used: plan.strategy,
+            ffmpeg_mode: written.ffmpeg_mode,
+            backup_path,
+            sizes,
+            output_bytes: lengths.iter().sum(),
+            elapsed: started.elapsed(),
+        }
+    });
     let finished_message = match &result {
-        Ok(_) => "ok".to_string(),
+        Ok(report) => match report.describe() {
+            details if details.is_empty() => "ok".to_string(),
+            details => format!("ok, {details}"),
+        },
         Err(err) => err.to_string(),
     };
-    reporter.finished(&label, result.is_ok(), &finished_message);
-    result
+    reporter.finished(label, result.is_ok(), &finished_message);
+    result.map(Outcome::Done)
+}
+
+/// Strictly newer: equal modification times leave the destination alone.
+fn source_is_newer(source: &Path, destination: &Path) -> Result<bool> {
+    let modified = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| format!("read modification time of {}", path.display()))
+    };
+    Ok(modified(source)? > modified(destination)?)
 }
 
-fn rename_only(source: &Path, destination: &Path, overwrite: bool) -> Result<()> {
+fn rename_only(source: &Path, destination: &Path, overwrite: bool, verify: bool) -> Result<()> {
     if overwrite && destination.exists() {
         fs::remove_file(destination).context("failed to remove existing destination")?;
     }
-    fs::rename(source, destination).context("failed to rename source")
+    move_file(source, destination, verify, |from, to| fs::rename(from, to))
+}
+
+/// Renames `source` to `destination`, falling back to a copy when they are on different
+/// filesystems. `rename` is `fs::rename` outside tests.
+fn move_file(
+    source: &Path,
+    destination: &Path,
+    verify: bool,
+    rename: impl FnOnce(&Path, &Path) -> io::Result<()>,
+) -> Result<()> {
+    let expected = verify.then(|| sha256_file(source)).transpose()?;
+    match rename(source, destination) {
+        Ok(()) => {}
+        Err(err) if is_cross_device(&err) => {
+            return move_across_devices(source, destination, verify);
+        }
+        Err(err) => return Err(anyhow::Error::new(err).context("failed to rename source")),
+    }
+    // The renamed file is the only copy left, so a mismatch is reported but never removed.
+    if let Some(expected) = expected {
+        let actual = sha256_file(destination)?;
+        if actual != expected {
+            bail!(
+                "verification failed: {} has SHA-256 {actual} after the rename, the source had \
+                 {expected}",
+                destination.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `rename(2)` cannot cross mounts and fails with `EXDEV` (raw OS error 18 on Linux).
+fn is_cross_device(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::CrossesDevices
+}
+
+/// Moves `source` onto another filesystem: copies it through a temp file next to
+/// `destination` that is persisted atomically, then deletes the source. The copy keeps
+/// the source's time and mode, as a rename would.
+fn move_across_devices(source: &Path, destination: &Path, verify: bool) -> Result<()> {
+    let options = crate::plan::ConversionOptions {
+        verify,
+        preserve: true,
+        ..Default::default()
+    };
+    copy_only(source, destination, false, &options)
+        .context("failed to move the source across filesystems")?;
+    fs::remove_file(source).context("failed to remove the source after copying it")
 }
 
-fn copy_only(source: &Path, destination: &Path, overwrite: bool) -> Result<()> {
+fn copy_only(
+    source: &Path,
+    destination: &Path,
+    overwrite: bool,
+    options: &crate::plan::ConversionOptions,
+) -> Result<()> {
     if overwrite && destination.exists() {
         fs::remove_file(destination).context("failed to remove existing destination")?;
     }
@@ -152,17 +444,94 @@ fn copy_only(source: &Path, destination: &Path, overwrite: bool) -> Result<()> {
         .parent()
         .context("destination must have a parent directory")?;
     let mut temp = Builder::new()
-        .prefix(".mvx.tmp")
+        .prefix(TEMP_PREFIX)
         .tempfile_in(parent)
         .context("failed to create temp file")?;
     let mut input = fs::File::open(source).context("failed to open source")?;
-    io::copy(&mut input, &mut temp).context("failed to copy data")?;
+    let len = input.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    if len >= LARGE_COPY_BYTES {
+        copy_large(input, temp.as_file_mut()).context("failed to copy data")?;
+    } else {
+        io::copy(&mut input, &mut temp).context("failed to copy data")?;
+    }
     temp.persist(destination)
         .context("failed to finalize destination")?;
+    if options.verify {
+        verify_copy(source, destination)?;
+    }
+    if options.preserve {
+        preserve_metadata(source, destination)?;
+    }
+    Ok(())
+}
+
+/// `--preserve`: gives `destination` the source's modification time and, on Unix, its
+/// permission bits. Other platforms only get the time.
+fn preserve_metadata(source: &Path, destination: &Path) -> Result<()> {
+    let metadata = fs::metadata(source).context("failed to read source metadata")?;
+    let modified = metadata
+        .modified()
+        .context("failed to read source modification time")?;
+    // The time goes first: a read-only mode would keep the file from being opened.
+    fs::File::options()
+        .write(true)
+        .open(destination)
+        .and_then(|file| file.set_modified(modified))
+        .with_context(|| format!("set modification time of {}", destination.display()))?;
+    #[cfg(unix)]
+    fs::set_permissions(destination, metadata.permissions())
+        .with_context(|| format!("set permissions of {}", destination.display()))?;
+    Ok(())
+}
+
+/// Read size for `--verify` hashing, so large files are never held in memory.
+const HASH_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Lowercase hex SHA-256 of the file at `path`.
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)
+        .with_context(|| format!("open {} for verification", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; HASH_CHUNK_BYTES];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("read {} for verification", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// `--verify` for copies: compares the SHA-256 of `source` and `destination`, removing
+/// the destination when they differ so a truncated copy is never left behind.
+fn verify_copy(source: &Path, destination: &Path) -> Result<()> {
+    let expected = sha256_file(source)?;
+    let actual = sha256_file(destination)?;
+    if actual != expected {
+        let _ = fs::remove_file(destination);
+        bail!(
+            "verification failed: copy {} has SHA-256 {actual}, source {} has {expected}; \
+             removed the copy",
+            destination.display(),
+            source.display()
+        );
+    }
     Ok(())
 }
 
-fn convert(plan: &Plan, overwrite: bool, reporter: &ProgressReporter, label: &str) -> Result<()> {
+fn convert(
+    plan: &Plan,
+    overwrite: bool,
+    reporter: &ProgressReporter,
+    label: &str,
+) -> Result<Written> {
     let backend = plan
         .backend
         .context("no backend available for conversion")?;
@@ -171,31 +540,50 @@ fn convert(plan: &Plan, overwrite: bool, reporter: &ProgressReporter, label: &st
         .parent()
         .context("destination must have a parent directory")?;
     let temp_dir = Builder::new()
-        .prefix(".mvx.tmp")
+        .prefix(TEMP_PREFIX)
         .tempdir_in(parent)
         .context("failed to create temp directory")?;
     let temp_path = temp_output_path(temp_dir.path(), &plan.destination);
 
+    let mut ffmpeg_mode = None;
     match backend {
+        Backend::ImageMagick if plan.options.all_pages => {
+            let pattern = numbered_destination(&temp_path, PAGE_PATTERN);
+            render_pdf(&plan.source, &pattern, &plan.options, reporter, label)?
+        }
+        Backend::ImageMagick if normalize_ext(&plan.source).as_deref() == Some("pdf") => {
+            render_pdf(&plan.source, &temp_path, &plan.options, reporter, label)?
+        }
         Backend::ImageMagick => {
             run_imagemagick(&plan.source, &temp_path, &plan.options, reporter, label)?
         }
         Backend::Ffmpeg => {
-            let info = match probe_media(&plan.source) {
-                Ok(info) => Some(info),
-                Err(err) => {
-                    let message = err.to_string();
-                    if message.contains("ffprobe not found") {
-                        eprintln!(
-                            "Warning: ffprobe not found; install ffmpeg to enable stream-copy detection."
-                        );
-                    } else {
-                        eprintln!("Warning: ffprobe failed; continuing without it: {err}");
-                    }
-                    None
+            // Planning already probed the source; this only runs ffprobe if it did not.
+            let mut probe = plan.probe.clone();
+            let info = probe.read(&plan.source).cloned();
+            match &probe {
+                SourceProbe::Missing => reporter
+                    .warn("ffprobe not found; install ffmpeg to enable stream-copy detection."),
+                SourceProbe::Failed(err) => {
+                    reporter.warn(&format!("ffprobe failed; continuing without it: {err}"))
                 }
+                SourceProbe::Read(_) | SourceProbe::NotRun => {}
+            }
+            let mode = if plan.strategy == Strategy::Remux {
+                FfmpegMode::StreamCopy
+            } else {
+                decide_ffmpeg_mode(plan, info.as_ref())
             };
-            let mode = decide_ffmpeg_mode(plan, info.as_ref());
+            ffmpeg_mode = Some(mode);
+            if let Some(warning) = info
+                .as_ref()
+                .and_then(|info| crate::plan::hdr_warning(plan, info))
+            {
+                reporter.warn(&warning);
+            }
+            let rotation = RotationArgs::for_plan(plan, info.as_ref());
+            let tonemap = crate::plan::tonemaps(plan, info.as_ref());
+            let extract_audio = crate::plan::extracts_audio(plan, info.as_ref());
             run_ffmpeg(
                 &plan.source,
                 &temp_path,
@@ -203,24 +591,74 @@ fn convert(plan: &Plan, overwrite: bool, reporter: &ProgressReporter, label: &st
                 plan.dest_kind,
                 plan.dest_ext.as_deref(),
                 mode,
-                info.as_ref().and_then(|i| i.duration_seconds),
+                rotation,
+                tonemap,
+                extract_audio,
+                crate::plan::trimmed_seconds(
+                    &plan.options,
+                    info.as_ref().and_then(|i| i.duration_seconds),
+                )
+                .map(|duration| duration / plan.options.speed.unwrap_or(1.0)),
+                reporter,
+                label,
+            )?;
+        }
+        Backend::LibreOffice if rasterizes_document(plan) => {
+            let pdf_path = temp_dir
+                .path()
+                .join(crate::plan::document_intermediate(&plan.source));
+            run_libreoffice(
+                &plan.source,
+                &pdf_path,
                 reporter,
                 label,
+                tool_timeout(&plan.options),
             )?;
+            render_pdf(&pdf_path, &temp_path, &plan.options, reporter, label)?;
         }
         Backend::LibreOffice => {
-            run_libreoffice(&plan.source, &temp_path, reporter, label)?;
+            run_libreoffice(
+                &plan.source,
+                &temp_path,
+                reporter,
+                label,
+                tool_timeout(&plan.options),
+            )?;
+        }
+        Backend::Raw => {
+            let tiff_path = temp_dir.path().join(crate::plan::RAW_INTERMEDIATE);
+            develop_raw(
+                &plan.source,
+                &tiff_path,
+                &temp_path,
+                &plan.options,
+                reporter,
+                label,
+            )?;
         }
     }
 
-    ensure_non_empty(&temp_path)?;
-    finalize_output(&temp_path, &plan.destination, overwrite)?;
+    let outputs = if plan.options.all_pages {
+        finalize_pages(&temp_path, plan, overwrite)?
+    } else {
+        ensure_non_empty(&temp_path)?;
+        finalize_output(&temp_path, &plan.destination, overwrite)?;
+        vec![plan.destination.clone()]
+    };
+    if plan.options.preserve {
+        for output in &outputs {
+            preserve_metadata(&plan.source, output)?;
+        }
+    }
 
     if plan.move_source {
         fs::remove_file(&plan.source).context("failed to remove source")?;
     }
 
-    Ok(())
+    Ok(Written {
+        outputs,
+        ffmpeg_mode,
+    })
 }
 
 fn run_imagemagick(
@@ -230,264 +668,717 @@ fn run_imagemagick(
     reporter: &ProgressReporter,
     label: &str,
 ) -> Result<()> {
-    let mut command = Command::new("magick");
-    if source.extension().and_then(|ext| ext.to_str()) == Some("pdf")
-        && dest
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext != "pdf")
-            == Some(true)
-    {
-        let source_arg = format!("{}[0]", source.display());
-        command.arg(source_arg);
-    } else {
-        command.arg(source);
-    }
-    if let Some(quality) = options.image_quality {
-        command.arg("-quality").arg(quality.to_string());
-    }
-    command.arg(dest);
-    let status = run_command_with_spinner(command, "ImageMagick", reporter, label);
-
-    let status = match status {
-        Ok(status) => status,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
-            let mut command = Command::new("convert");
-            command.arg(source);
-            if let Some(quality) = options.image_quality {
-                command.arg("-quality").arg(quality.to_string());
+    let command = |program: &str| imagemagick_command(program, source, dest, options);
+    let run = |program: &str| {
+        run_command_with_spinner(
+            command(program),
+            "ImageMagick",
+            reporter,
+            label,
+            tool_timeout(options),
+        )
+    };
+    let output = match run("magick") {
+        Ok(output) => output,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => match run("convert") {
+            Ok(output) => output,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Err(MissingTool(
+                    "ImageMagick not found; install it (e.g., apt install imagemagick)",
+                )
+                .into());
             }
-            command.arg(dest);
-            let status = match run_command_with_spinner(command, "ImageMagick", reporter, label) {
-                Ok(status) => status,
-                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
-                    bail!("ImageMagick not found; install it (e.g., apt install imagemagick)");
-                }
-                Err(err) => {
-                    return Err(anyhow::Error::new(err))
-                        .context("failed to execute ImageMagick convert");
-                }
-            };
-            return handle_status(status, "ImageMagick");
-        }
+            Err(err) => {
+                return Err(anyhow::Error::new(err))
+                    .context("failed to execute ImageMagick convert");
+            }
+        },
         Err(err) => {
             return Err(anyhow::Error::new(err)).context("failed to execute ImageMagick");
         }
     };
 
-    handle_status(status, "ImageMagick")
+    if !output.status.success() && is_policy_error(&output.stderr) {
+        return Err(PolicyBlocked.into());
+    }
+    handle_status(output.status, "ImageMagick", &output.stderr)
 }
 
-#[allow(clippy::too_many_arguments)]
-fn run_ffmpeg(
+/// Renders a PDF with ImageMagick, or with Ghostscript when it is preferred or when
+/// ImageMagick's policy refuses PDF input.
+fn render_pdf(
     source: &Path,
     dest: &Path,
     options: &crate::plan::ConversionOptions,
-    dest_kind: MediaKind,
-    dest_ext: Option<&str>,
-    mode: FfmpegMode,
-    duration_seconds: Option<f64>,
     reporter: &ProgressReporter,
     label: &str,
 ) -> Result<()> {
-    let mut command = Command::new("ffmpeg");
-    command
-        .arg("-nostdin")
-        .arg("-y")
-        .arg("-hide_banner")
-        .arg("-nostats")
-        .arg("-loglevel")
-        .arg("error")
-        .arg("-i")
-        .arg(source);
-    if mode == FfmpegMode::StreamCopy {
-        command.arg("-c").arg("copy");
-    } else if dest_kind == MediaKind::Video {
-        let video_codec = options
-            .video_codec
-            .as_deref()
-            .or_else(|| default_video_codec(dest_ext));
-        if let Some(codec) = video_codec {
-            command.arg("-c:v").arg(codec);
-        }
-        if let Some(bitrate) = options.video_bitrate.as_deref() {
-            command.arg("-b:v").arg(bitrate);
-        }
-        if let Some(preset) = options.preset.as_deref() {
-            command.arg("-preset").arg(preset);
-        }
-        let audio_codec = options
-            .audio_codec
-            .as_deref()
-            .or_else(|| default_audio_codec(dest_ext, dest_kind));
-        if let Some(codec) = audio_codec {
-            command.arg("-c:a").arg(codec);
-        }
-        if let Some(bitrate) = options.audio_bitrate.as_deref() {
-            command.arg("-b:a").arg(bitrate);
-        }
-    } else if dest_kind == MediaKind::Audio {
-        let audio_codec = options
-            .audio_codec
-            .as_deref()
-            .or_else(|| default_audio_codec(dest_ext, dest_kind));
-        if let Some(codec) = audio_codec {
-            command.arg("-c:a").arg(codec);
-        }
-        if let Some(bitrate) = options.audio_bitrate.as_deref() {
-            command.arg("-b:a").arg(bitrate);
-        }
+    let dest_ext = dest
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    let Some(gs_args) = ghostscript_args(options, dest_ext.as_deref()) else {
+        return run_imagemagick(source, dest, options, reporter, label);
+    };
+    let timeout = tool_timeout(options);
+    if options.prefer_gs {
+        return run_ghostscript(source, dest, gs_args, reporter, label, timeout);
     }
-    command.arg("-progress").arg("pipe:1");
-    let mut child = match command
-        .arg(dest)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::inherit())
-        .spawn()
-    {
-        Ok(child) => child,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
-            bail!("ffmpeg not found; install it (e.g., apt install ffmpeg)");
-        }
-        Err(err) => {
-            return Err(anyhow::Error::new(err)).context("failed to execute ffmpeg");
+    match run_imagemagick(source, dest, options, reporter, label) {
+        Err(err) if err.is::<PolicyBlocked>() => {
+            reporter.warn("ImageMagick policy blocks PDF input; retrying with Ghostscript");
+            run_ghostscript(source, dest, gs_args, reporter, label, timeout).map_err(|gs_err| {
+                if is_missing_tool(&gs_err) {
+                    err
+                } else {
+                    gs_err
+                }
+            })
         }
-    };
-
-    if let Some(stdout) = child.stdout.take() {
-        stream_progress(stdout, duration_seconds, reporter, label);
+        result => result,
     }
-
-    let status = child.wait().context("failed to wait for ffmpeg")?;
-
-    handle_status(status, "ffmpeg")
 }
 
-fn run_libreoffice(
+/// Develops a camera RAW file with the first installed RAW tool. Tools that write a
+/// TIFF hand it to ImageMagick for the destination format and image options.
+fn develop_raw(
     source: &Path,
+    tiff_path: &Path,
     dest: &Path,
+    options: &crate::plan::ConversionOptions,
     reporter: &ProgressReporter,
     label: &str,
 ) -> Result<()> {
-    if dest.extension().and_then(|ext| ext.to_str()) != Some("pdf") {
-        bail!("LibreOffice conversions only support PDF output");
-    }
-    let out_dir = dest
-        .parent()
-        .context("destination must have a parent directory")?;
-    let status = run_command_with_spinner(
-        {
-            let mut command = Command::new("soffice");
-            command
-                .arg("--headless")
-                .arg("--convert-to")
-                .arg("pdf")
-                .arg("--outdir")
-                .arg(out_dir)
-                .arg(source);
-            command
-        },
-        "LibreOffice",
+    let tool = crate::raw::find_raw_tool().ok_or(MissingTool(crate::raw::MISSING_RAW_TOOL))?;
+    let Some(args) = crate::raw::develop_args(tool, source, tiff_path) else {
+        return run_imagemagick(source, dest, options, reporter, label).context(
+            "ImageMagick could not develop the RAW file; install LibRaw's dcraw_emu \
+                 (e.g., apt install libraw-bin) or darktable",
+        );
+    };
+    let mut command = Command::new(tool.label());
+    command.args(args);
+    let output = run_command_with_spinner(
+        command,
+        tool.label(),
         reporter,
         label,
-    );
+        tool_timeout(options),
+    )
+    .with_context(|| format!("failed to execute {}", tool.label()))?;
+    handle_status(output.status, tool.label(), &output.stderr)?;
+    ensure_non_empty(tiff_path)?;
+    run_imagemagick(tiff_path, dest, options, reporter, label)
+}
 
-    let status = match status {
-        Ok(status) => status,
+fn run_ghostscript(
+    source: &Path,
+    dest: &Path,
+    args: Vec<String>,
+    reporter: &ProgressReporter,
+    label: &str,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let mut command = Command::new("gs");
+    command
+        .args(args)
+        .arg(format!("-sOutputFile={}", dest.display()))
+        .arg(source);
+    let output = match run_command_with_spinner(command, "Ghostscript", reporter, label, timeout) {
+        Ok(output) => output,
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
-            bail!("LibreOffice not found; install libreoffice (e.g., apt install libreoffice)");
+            return Err(MissingTool(
+                "Ghostscript not found; install it (e.g., apt install ghostscript)",
+            )
+            .into());
         }
         Err(err) => {
-            return Err(anyhow::Error::new(err)).context("failed to execute LibreOffice");
+            return Err(anyhow::Error::new(err)).context("failed to execute Ghostscript");
         }
     };
+    handle_status(output.status, "Ghostscript", &output.stderr)
+}
 
-    handle_status(status, "LibreOffice")?;
+/// The destination is already there and neither `--overwrite` nor `--backup` allows
+/// replacing it. Batches count this as a skip unless `--strict` is set.
+#[derive(Debug)]
+pub struct DestinationExists;
 
-    let expected = out_dir.join(
-        source
-            .file_stem()
-            .and_then(|stem| stem.to_str())
-            .map(|stem| format!("{stem}.pdf"))
-            .context("source file must have a name")?,
-    );
-    if expected != dest {
-        if dest.exists() {
-            fs::remove_file(dest).context("failed to remove existing destination")?;
-        }
-        fs::rename(&expected, dest).context("failed to finalize LibreOffice output")?;
+impl std::fmt::Display for DestinationExists {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("destination exists; pass --overwrite or --backup")
     }
-    Ok(())
 }
 
-fn decide_ffmpeg_mode(plan: &Plan, info: Option<&crate::ffprobe::MediaInfo>) -> FfmpegMode {
-    match plan.options.ffmpeg_preference {
-        crate::plan::FfmpegPreference::StreamCopy => return FfmpegMode::StreamCopy,
-        crate::plan::FfmpegPreference::Transcode => return FfmpegMode::Transcode,
-        crate::plan::FfmpegPreference::Auto => {}
-    }
-    if plan.dest_kind == MediaKind::Audio {
-        return FfmpegMode::Transcode;
-    }
-    let dest_ext = match plan.dest_ext.as_deref() {
-        Some(ext) => ext,
-        None => return FfmpegMode::Transcode,
-    };
-    let Some(info) = info else {
-        return FfmpegMode::Transcode;
-    };
-    let Some(video) = info.video_codec.as_deref() else {
-        return FfmpegMode::Transcode;
-    };
-    let audio = info.audio_codec.as_deref();
+impl std::error::Error for DestinationExists {}
 
-    if dest_ext == "mkv" {
-        return FfmpegMode::StreamCopy;
-    }
+/// ImageMagick refused the file because of its `policy.xml`. Distros ship policies
+/// that deny the PDF coder (a Ghostscript hardening measure).
+#[derive(Debug)]
+pub struct PolicyBlocked;
 
-    match dest_ext {
-        "mp4" | "mov" => {
-            let video_ok = matches!(video, "h264" | "hevc" | "mpeg4" | "av1");
-            let audio_ok = audio
-                .map(|codec| matches!(codec, "aac" | "mp3" | "alac"))
-                .unwrap_or(true);
-            if video_ok && audio_ok {
-                FfmpegMode::StreamCopy
-            } else {
-                FfmpegMode::Transcode
-            }
-        }
-        "webm" => {
-            let video_ok = matches!(video, "vp8" | "vp9" | "av1");
-            let audio_ok = audio
-                .map(|codec| matches!(codec, "opus" | "vorbis"))
-                .unwrap_or(true);
-            if video_ok && audio_ok {
-                FfmpegMode::StreamCopy
-            } else {
-                FfmpegMode::Transcode
-            }
-        }
-        _ => FfmpegMode::Transcode,
+impl std::fmt::Display for PolicyBlocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(
+            "ImageMagick's security policy blocks this format (usually PDF); allow it by setting \
+             rights=\"read|write\" on the PDF coder policy in /etc/ImageMagick-6/policy.xml \
+             (or /etc/ImageMagick-7/policy.xml)",
+        )
     }
 }
 
-fn stream_progress(
-    stdout: impl std::io::Read,
-    duration_seconds: Option<f64>,
-    reporter: &ProgressReporter,
+impl std::error::Error for PolicyBlocked {}
+
+fn is_policy_error(stderr: &str) -> bool {
+    stderr.contains("not authorized") || stderr.contains("security policy")
+}
+
+fn imagemagick_command(
+    program: &str,
+    source: &Path,
+    dest: &Path,
+    options: &crate::plan::ConversionOptions,
+) -> Command {
+    let mut command = Command::new(program);
+    command.args(crate::plan::density_args(source, options));
+    command.arg(crate::plan::imagemagick_source(
+        source,
+        crate::plan::normalize_ext(dest).as_deref(),
+        options,
+    ));
+    add_imagemagick_args(
+        &mut command,
+        options,
+        crate::plan::normalize_ext(dest).as_deref(),
+    );
+    command.arg(dest);
+    command
+}
+
+fn add_imagemagick_args(
+    command: &mut Command,
+    options: &crate::plan::ConversionOptions,
+    dest_ext: Option<&str>,
+) {
+    if options.metadata == crate::plan::Metadata::Strip {
+        command.arg("-strip");
+    }
+    if let Some(quality) = crate::plan::imagemagick_quality(options, dest_ext) {
+        command.arg("-quality").arg(quality.to_string());
+    }
+    for define in crate::plan::encoder_defines(options, dest_ext) {
+        command.arg("-define").arg(define);
+    }
+    if let Some(fit) = options.fit.as_deref() {
+        command.arg("-resize").arg(format!("{fit}>"));
+    }
+    if let Some(geometry) = options.resize.as_deref() {
+        command.arg("-resize").arg(geometry);
+    }
+    if options.grayscale {
+        command.arg("-colorspace").arg("Gray");
+    }
+    if options.srgb {
+        command.arg("-colorspace").arg("sRGB");
+    }
+    match options.icc_profile.as_deref() {
+        Some(profile) => {
+            command.arg("-profile").arg(profile);
+        }
+        None if options.srgb => {
+            command.arg("+profile").arg("icc");
+        }
+        None => {}
+    }
+    if options.all_pages {
+        command.arg("-scene").arg("1");
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_ffmpeg(
+    source: &Path,
+    dest: &Path,
+    options: &crate::plan::ConversionOptions,
+    dest_kind: MediaKind,
+    dest_ext: Option<&str>,
+    mode: FfmpegMode,
+    rotation: RotationArgs,
+    tonemap: bool,
+    extract_audio: bool,
+    duration_seconds: Option<f64>,
+    reporter: &ProgressReporter,
+    label: &str,
+) -> Result<()> {
+    // Stream copies encode nothing, so only a transcode can fail on the hardware encoder.
+    let hw_encoder = crate::plan::hw_encoder(options, dest_kind, dest_ext)
+        .filter(|_| mode == FfmpegMode::Transcode);
+    if mode == FfmpegMode::Transcode && dest_kind == MediaKind::Video && options.two_pass {
+        // The pass log lives next to the temp output so it is removed with the temp dir.
+        let passlog = dest
+            .parent()
+            .context("destination must have a parent directory")?
+            .join(TWO_PASS_LOG_PREFIX);
+        let mut command = ffmpeg_command(source, options, hw_encoder, rotation);
+        add_video_args(&mut command, source, options, dest_ext, rotation, tonemap);
+        command
+            .arg("-pass")
+            .arg("1")
+            .arg("-passlogfile")
+            .arg(&passlog)
+            .arg("-an")
+            .arg("-f")
+            .arg("null");
+        spawn_ffmpeg(
+            command,
+            Path::new(NULL_DEVICE),
+            options,
+            duration_seconds,
+            Pass {
+                number: 1,
+                count: 2,
+            },
+            hw_encoder,
+            reporter,
+            label,
+        )?;
+
+        let mut command = ffmpeg_command(source, options, hw_encoder, rotation);
+        add_video_args(&mut command, source, options, dest_ext, rotation, tonemap);
+        if crate::plan::drops_audio(options, dest_kind, dest_ext) {
+            command.arg("-an");
+        } else {
+            add_audio_args(&mut command, options, dest_kind, dest_ext);
+        }
+        rotation.add_metadata(&mut command);
+        command
+            .arg("-pass")
+            .arg("2")
+            .arg("-passlogfile")
+            .arg(&passlog);
+        return spawn_ffmpeg(
+            command,
+            dest,
+            options,
+            duration_seconds,
+            Pass {
+                number: 2,
+                count: 2,
+            },
+            hw_encoder,
+            reporter,
+            label,
+        );
+    }
+
+    let command = ffmpeg_output_command(
+        source,
+        options,
+        dest_kind,
+        dest_ext,
+        mode,
+        rotation,
+        tonemap,
+        extract_audio,
+    );
+    spawn_ffmpeg(
+        command,
+        dest,
+        options,
+        duration_seconds,
+        Pass::ONLY,
+        hw_encoder,
+        reporter,
+        label,
+    )
+}
+
+/// Single-pass ffmpeg command up to, but not including, the progress flags and the
+/// destination that `spawn_ffmpeg` appends.
+#[allow(clippy::too_many_arguments)]
+fn ffmpeg_output_command(
+    source: &Path,
+    options: &crate::plan::ConversionOptions,
+    dest_kind: MediaKind,
+    dest_ext: Option<&str>,
+    mode: FfmpegMode,
+    rotation: RotationArgs,
+    tonemap: bool,
+    extract_audio: bool,
+) -> Command {
+    let mute = crate::plan::drops_audio(options, dest_kind, dest_ext);
+    let hw_encoder = crate::plan::hw_encoder(options, dest_kind, dest_ext);
+    let mut command = ffmpeg_command(source, options, hw_encoder, rotation);
+    if mode == FfmpegMode::StreamCopy {
+        command.arg("-c").arg("copy");
+        if mute {
+            command.arg("-an");
+        }
+        if dest_kind == MediaKind::Audio {
+            command.arg("-vn");
+        }
+        rotation.add_metadata(&mut command);
+    } else if dest_kind == MediaKind::Video {
+        add_video_args(&mut command, source, options, dest_ext, rotation, tonemap);
+        if mute {
+            command.arg("-an");
+        } else {
+            add_audio_args(&mut command, options, dest_kind, dest_ext);
+        }
+        rotation.add_metadata(&mut command);
+    } else if dest_kind == MediaKind::Audio {
+        if extract_audio {
+            command.arg("-vn");
+        }
+        add_audio_args(&mut command, options, dest_kind, dest_ext);
+    }
+    command
+}
+
+fn ffmpeg_command(
+    source: &Path,
+    options: &crate::plan::ConversionOptions,
+    hw_encoder: Option<&str>,
+    rotation: RotationArgs,
+) -> Command {
+    let mut command = Command::new("ffmpeg");
+    command
+        .arg("-nostdin")
+        .arg("-y")
+        .arg("-hide_banner")
+        .arg("-nostats")
+        .arg("-loglevel")
+        .arg(ffmpeg_loglevel(options));
+    // Rotation is handled explicitly, so ffmpeg must not rotate on its own as well.
+    command.args(crate::plan::ffmpeg_input_args(
+        options,
+        rotation.metadata.is_some(),
+        hw_encoder,
+    ));
+    command.arg("-i").arg(source);
+    command.args(crate::plan::ffmpeg_metadata_args(options));
+    command
+}
+
+/// How `--rotate` applies to one run: an optional transpose filter (auto mode on a
+/// rotated source) and the display rotation written to the output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct RotationArgs {
+    transpose: Option<&'static str>,
+    metadata: Option<u16>,
+}
+
+impl RotationArgs {
+    fn for_plan(plan: &Plan, info: Option<&crate::ffprobe::MediaInfo>) -> Self {
+        if let Some(degrees) = sets_rotation(plan) {
+            return Self {
+                transpose: None,
+                metadata: Some(degrees),
+            };
+        }
+        if plan.options.rotate != Some(Rotation::Auto) || plan.dest_kind != MediaKind::Video {
+            return Self::default();
+        }
+        match info
+            .and_then(|info| info.rotation)
+            .and_then(transpose_filter)
+        {
+            Some(filter) => Self {
+                transpose: Some(filter),
+                metadata: Some(0),
+            },
+            None => Self::default(),
+        }
+    }
+
+    fn add_metadata(self, command: &mut Command) {
+        if let Some(degrees) = self.metadata {
+            command
+                .arg("-metadata:s:v:0")
+                .arg(format!("rotate={degrees}"));
+        }
+    }
+}
+
+fn add_video_args(
+    command: &mut Command,
+    source: &Path,
+    options: &crate::plan::ConversionOptions,
+    dest_ext: Option<&str>,
+    rotation: RotationArgs,
+    tonemap: bool,
+) {
+    let hw_encoder = crate::plan::hw_encoder(options, MediaKind::Video, dest_ext);
+    let encoder = hw_encoder.or_else(|| video_encoder(options, dest_ext));
+    if let Some(codec) = encoder {
+        command.arg("-c:v").arg(codec);
+        command.args(av1_default_args(codec, options));
+    }
+    // The GIF encoder has no bitrate, CRF or presets.
+    if dest_ext != Some("gif") {
+        if let Some(bitrate) = options.video_bitrate.as_deref() {
+            command.arg("-b:v").arg(bitrate);
+        }
+        command.args(crate::plan::crf_args(options, encoder));
+        if let Some(preset) = options.preset.as_deref() {
+            command.arg("-preset").arg(preset);
+        }
+    }
+    let source_ext = normalize_ext(source);
+    if let Some(format) = crate::plan::gif_pixel_format(source_ext.as_deref(), dest_ext) {
+        command.arg("-pix_fmt").arg(format);
+    }
+    let mut filters: Vec<String> = rotation.transpose.map(str::to_string).into_iter().collect();
+    if tonemap {
+        filters.push(crate::plan::TONEMAP_FILTER.to_string());
+    }
+    filters.extend(video_filters(options));
+    filters.extend(crate::plan::gif_filters(source_ext.as_deref(), dest_ext));
+    filters.extend(crate::plan::hw_upload_filter(hw_encoder).map(str::to_string));
+    if !filters.is_empty() {
+        command.arg("-filter:v").arg(filters.join(","));
+    }
+}
+
+fn add_audio_args(
+    command: &mut Command,
+    options: &crate::plan::ConversionOptions,
+    dest_kind: MediaKind,
+    dest_ext: Option<&str>,
+) {
+    if let Some(codec) = audio_encoder(options, dest_ext, dest_kind) {
+        command.arg("-c:a").arg(codec);
+        if let Some(format) = audio_format_arg(codec, dest_ext) {
+            command.arg("-f").arg(format);
+        }
+    }
+    if let Some(bitrate) = options.audio_bitrate.as_deref() {
+        command.arg("-b:a").arg(bitrate);
+    }
+    command.args(crate::plan::sample_format_args(options, dest_kind));
+    let filters = audio_filters(options);
+    if !filters.is_empty() {
+        command.arg("-filter:a").arg(filters.join(","));
+    }
+}
+
+/// `--timeout` as a per-tool limit; each pass of a two-pass encode gets the full time.
+fn tool_timeout(options: &crate::plan::ConversionOptions) -> Option<Duration> {
+    options.timeout.map(Duration::from_secs)
+}
+
+fn ffmpeg_loglevel(options: &crate::plan::ConversionOptions) -> &str {
+    options
+        .ffmpeg_loglevel
+        .as_deref()
+        .unwrap_or(crate::plan::DEFAULT_FFMPEG_LOGLEVEL)
+}
+
+/// One ffmpeg run out of the passes a conversion makes. Progress is scaled so the gauge
+/// fills once across all of them instead of restarting for the second pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pass {
+    number: u8,
+    count: u8,
+}
+
+impl Pass {
+    const ONLY: Pass = Pass {
+        number: 1,
+        count: 1,
+    };
+
+    /// Overall percent for `percent` of this pass.
+    fn scale(self, percent: f64) -> f64 {
+        (f64::from(self.number - 1) * 100.0 + percent) / f64::from(self.count)
+    }
+
+    /// Seconds of media the passes after this one still have to work through.
+    fn later_seconds(self, duration: f64) -> f64 {
+        duration * f64::from(self.count - self.number)
+    }
+
+    fn name(self) -> String {
+        if self.count == 1 {
+            "ffmpeg".to_string()
+        } else {
+            format!("ffmpeg pass {}/{}", self.number, self.count)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_ffmpeg(
+    mut command: Command,
+    dest: &Path,
+    options: &crate::plan::ConversionOptions,
+    duration_seconds: Option<f64>,
+    pass: Pass,
+    hw_encoder: Option<&str>,
+    reporter: &ProgressReporter,
+    label: &str,
+) -> Result<()> {
+    command.arg("-progress").arg("pipe:1").arg(dest);
+    reporter.log_command(label, &command);
+    let mut child = match command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(
+                MissingTool("ffmpeg not found; install it (e.g., apt install ffmpeg)").into(),
+            );
+        }
+        Err(err) => {
+            return Err(anyhow::Error::new(err)).context("failed to execute ffmpeg");
+        }
+    };
+
+    // A raised loglevel is a request to see what ffmpeg said, so a run log keeps all of
+    // it; otherwise only the tail a failure quotes is held.
+    let verbose = crate::plan::ffmpeg_loglevel_is_verbose(ffmpeg_loglevel(options));
+    let keep = if verbose && reporter.log.is_some() {
+        usize::MAX
+    } else {
+        CAPTURE_BYTES
+    };
+    let stderr = child
+        .stderr
+        .take()
+        .map(|pipe| collect_lines(pipe, echoes_output(reporter), keep));
+    // Progress is read on its own thread so this one can kill ffmpeg on Ctrl-C; the
+    // pipe closes when ffmpeg exits either way.
+    let status = std::thread::scope(|scope| {
+        if let Some(stdout) = child.stdout.take() {
+            scope.spawn(|| stream_progress(stdout, duration_seconds, pass, reporter, label));
+        }
+        crate::cancel::wait_child(&mut child, tool_timeout(options))
+    })
+    .map_err(|err| match err.kind() {
+        // The timeout already says what happened; "failed to wait" would only blur it.
+        io::ErrorKind::TimedOut => anyhow::Error::new(err),
+        _ => anyhow::Error::new(err).context("failed to wait for ffmpeg"),
+    })?;
+    let stderr = stderr
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+    // A failure's error carries the tail either way, where the TUI shows it.
+    if verbose && !stderr.trim().is_empty() {
+        reporter.log(label, "ffmpeg stderr", &stderr);
+    } else {
+        reporter.log_output(label, "ffmpeg stderr", &stderr);
+    }
+    // Hardware encoders fail when the device or driver is missing, which only ffmpeg's
+    // own message explains.
+    if let Some(encoder) = hw_encoder
+        && !status.success()
+        && !crate::cancel::requested()
+    {
+        bail!(
+            "ffmpeg could not encode with {encoder}; check that the hardware and its driver \
+             are available, or pass --hwaccel none\n{}",
+            error_tail(&stderr)
+        );
+    }
+
+    handle_status(status, "ffmpeg", &stderr)
+}
+
+fn run_libreoffice(
+    source: &Path,
+    dest: &Path,
+    reporter: &ProgressReporter,
+    label: &str,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let dest_ext = normalize_ext(dest).context("LibreOffice output needs an extension")?;
+    let out_dir = dest
+        .parent()
+        .context("destination must have a parent directory")?;
+    let output = run_command_with_spinner(
+        {
+            let mut command = Command::new("soffice");
+            command
+                .arg("--headless")
+                .arg("--convert-to")
+                .arg(crate::plan::libreoffice_target(&dest_ext))
+                .arg("--outdir")
+                .arg(out_dir)
+                .arg(source);
+            command
+        },
+        "LibreOffice",
+        reporter,
+        label,
+        timeout,
+    );
+
+    let output = match output {
+        Ok(output) => output,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(MissingTool(
+                "LibreOffice not found; install libreoffice (e.g., apt install libreoffice)",
+            )
+            .into());
+        }
+        Err(err) => {
+            return Err(anyhow::Error::new(err)).context("failed to execute LibreOffice");
+        }
+    };
+
+    handle_status(output.status, "LibreOffice", &output.stderr)?;
+
+    let expected = out_dir.join(
+        source
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(|stem| format!("{stem}.{dest_ext}"))
+            .context("source file must have a name")?,
+    );
+    if expected != dest {
+        if dest.exists() {
+            fs::remove_file(dest).context("failed to remove existing destination")?;
+        }
+        fs::rename(&expected, dest).context("failed to finalize LibreOffice output")?;
+    }
+    Ok(())
+}
+
+/// Without a usable duration (live streams, image sequences) only elapsed media time is
+/// known. ffmpeg reports it several times a second, and a fast encode advances it by
+/// many seconds per report, so those updates follow the wall clock instead.
+const ELAPSED_TICK: Duration = Duration::from_millis(500);
+
+fn stream_progress(
+    stdout: impl std::io::Read,
+    duration_seconds: Option<f64>,
+    pass: Pass,
+    reporter: &ProgressReporter,
     label: &str,
 ) {
     let reader = BufReader::new(stdout);
+    let duration_seconds = duration_seconds.filter(|duration| *duration > 0.0);
+    let later = duration_seconds.map_or(0.0, |duration| pass.later_seconds(duration));
+    let name = pass.name();
     let mut last_percent: Option<f64> = None;
-    let mut last_elapsed: Option<f64> = None;
+    let mut last_tick: Option<Instant> = None;
     for line in reader.lines().map_while(Result::ok) {
         if line == "progress=end" {
-            if reporter.should_print()
+            // Stream copies can finish before ffmpeg reports a usable out_time, and
+            // inputs without a duration never report a percentage, so the gauge is
+            // filled here instead of waiting on the last time sample.
+            reporter.progress(label, pass.scale(100.0), Some(later));
+            if reporter.should_animate()
                 && duration_seconds.is_some()
-                && last_percent.is_none_or(|percent| percent < 99.5)
+                && last_percent.is_none_or(|percent| percent < pass.scale(99.5))
             {
-                eprintln!("\rffmpeg 100%");
+                eprintln!(
+                    "\r{} {:.0}%",
+                    reporter.paint(&name, Color::Cyan),
+                    pass.scale(100.0)
+                );
             }
             continue;
         }
@@ -499,66 +1390,183 @@ fn stream_progress(
         };
         let elapsed = ms as f64 / 1_000_000.0;
         if let Some(duration) = duration_seconds {
-            if duration <= 0.0 {
-                continue;
-            }
-            let percent = ((elapsed / duration) * 100.0).min(100.0);
-            reporter.progress(label, percent, Some((duration - elapsed).max(0.0)));
-            if !reporter.should_print() {
+            let percent = pass.scale(((elapsed / duration) * 100.0).min(100.0));
+            let remaining = (duration - elapsed).max(0.0) + later;
+            reporter.progress(label, percent, Some(remaining));
+            if !reporter.should_animate() {
                 continue;
             }
             if last_percent.is_none_or(|last| (percent - last).abs() >= 1.0) {
-                let remaining = (duration - elapsed).max(0.0);
-                eprint!("\rffmpeg {:.0}% eta {:.1}s", percent, remaining);
+                eprint!(
+                    "\r{} {:.0}% eta {:.1}s",
+                    reporter.paint(&name, Color::Cyan),
+                    percent,
+                    remaining
+                );
                 last_percent = Some(percent);
             }
-        } else if last_elapsed.is_none_or(|last| (elapsed - last).abs() >= 1.0) {
-            reporter.progress(label, 0.0, None);
-            if !reporter.should_print() {
+        } else if last_tick.is_none_or(|tick| tick.elapsed() >= ELAPSED_TICK) {
+            last_tick = Some(Instant::now());
+            reporter.spinner_tick(label, elapsed as f32, &format!("{name} encoding"));
+            if !reporter.should_animate() {
                 continue;
             }
-            eprint!("\rffmpeg {:.1}s elapsed", elapsed);
-            last_elapsed = Some(elapsed);
+            eprint!(
+                "\r{} {:.1}s elapsed",
+                reporter.paint(&name, Color::Cyan),
+                elapsed
+            );
         }
     }
-    if reporter.should_print() {
+    if reporter.should_animate() {
         eprintln!();
     }
 }
 
+struct ToolOutput {
+    status: std::process::ExitStatus,
+    stderr: String,
+}
+
+/// Runs a tool that reports no progress. Its stderr is captured so failures can be
+/// classified, and echoed unless the TUI owns the terminal. Stdout is only kept for the
+/// run log.
 fn run_command_with_spinner(
     mut command: Command,
     label: &str,
     reporter: &ProgressReporter,
     source_label: &str,
+    timeout: Option<Duration>,
+) -> std::io::Result<ToolOutput> {
+    reporter.log_command(source_label, &command);
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .map(|pipe| collect_lines(pipe, false, CAPTURE_BYTES));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|pipe| collect_lines(pipe, echoes_output(reporter), CAPTURE_BYTES));
+    let status = wait_with_spinner(&mut child, label, reporter, source_label, timeout)?;
+    let joined = |reader: Option<std::thread::JoinHandle<String>>| {
+        reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default()
+    };
+    let stdout = joined(stdout);
+    let stderr = joined(stderr);
+    reporter.log_output(source_label, &format!("{label} stdout"), &stdout);
+    reporter.log_output(source_label, &format!("{label} stderr"), &stderr);
+    Ok(ToolOutput { status, stderr })
+}
+
+fn echoes_output(reporter: &ProgressReporter) -> bool {
+    !matches!(reporter.mode, ProgressMode::Tui { .. })
+}
+
+/// How much of a tool's output is held while it runs: enough for the error tail and the
+/// log, without growing with a chatty tool's whole run.
+const CAPTURE_BYTES: usize = 4096;
+
+/// Reads a child's pipe to the end on its own thread, optionally echoing to stderr, and
+/// returns the last `keep` bytes' worth of lines. Lines are decoded lossily so one bad
+/// byte cannot end the capture early.
+fn collect_lines(
+    pipe: impl std::io::Read + Send + 'static,
+    echo: bool,
+    keep: usize,
+) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        let mut lines: VecDeque<String> = VecDeque::new();
+        let mut held = 0;
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let line = String::from_utf8_lossy(&buf)
+                .trim_end_matches(['\n', '\r'])
+                .to_string();
+            if echo {
+                eprintln!("{line}");
+            }
+            held += line.len() + 1;
+            lines.push_back(line);
+            while held > keep && lines.len() > 1 {
+                held -= lines.pop_front().map_or(0, |line| line.len() + 1);
+            }
+        }
+        lines.into_iter().fold(String::new(), |mut captured, line| {
+            captured.push_str(&line);
+            captured.push('\n');
+            captured
+        })
+    })
+}
+
+/// A command line as it was run, for the run log.
+fn describe_command(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn wait_with_spinner(
+    child: &mut std::process::Child,
+    label: &str,
+    reporter: &ProgressReporter,
+    source_label: &str,
+    timeout: Option<Duration>,
 ) -> std::io::Result<std::process::ExitStatus> {
     if reporter.json_output() {
-        return command
-            .stdout(Stdio::null())
-            .stderr(Stdio::inherit())
-            .status();
+        return crate::cancel::wait_child(child, timeout);
     }
 
-    let mut child = command
-        .stdout(Stdio::null())
-        .stderr(Stdio::inherit())
-        .spawn()?;
-
     let start = Instant::now();
     loop {
         match child.try_wait() {
             Ok(Some(status)) => {
                 let elapsed = start.elapsed().as_secs_f32();
                 if reporter.should_print() {
-                    eprintln!("\r{label} done in {:.1}s", elapsed);
+                    eprintln!(
+                        "{}{} done in {:.1}s",
+                        if reporter.should_animate() { "\r" } else { "" },
+                        reporter.paint(label, Color::Cyan),
+                        elapsed
+                    );
                 }
                 return Ok(status);
             }
+            Ok(None) if crate::cancel::requested() => {
+                if reporter.should_animate() {
+                    eprintln!();
+                }
+                return crate::cancel::kill_child(child);
+            }
             Ok(None) => {
+                if let Err(err) = crate::cancel::check_timeout(child, start, timeout) {
+                    if reporter.should_animate() {
+                        eprintln!();
+                    }
+                    return Err(err);
+                }
                 let elapsed = start.elapsed().as_secs_f32();
-                reporter.spinner_tick(source_label, elapsed, label);
-                if reporter.should_print() {
-                    eprint!("\r{label} ... {:.1}s", elapsed);
+                reporter.spinner_tick(source_label, elapsed, &spinner_stage(label, elapsed));
+                if reporter.should_animate() {
+                    eprint!(
+                        "\r{} ... {:.1}s",
+                        reporter.paint(label, Color::Cyan),
+                        elapsed
+                    );
                 }
                 std::thread::sleep(Duration::from_millis(150));
             }
@@ -567,14 +1575,44 @@ fn run_command_with_spinner(
     }
 }
 
-fn handle_status(status: std::process::ExitStatus, name: &str) -> Result<()> {
+const LIBREOFFICE_STARTUP_SECS: f32 = 3.0;
+
+/// Tools run under the spinner report nothing while they work, so the message carries a
+/// coarse stage hint instead. LibreOffice spends its first seconds booting a headless
+/// instance before it touches the document.
+fn spinner_stage(label: &str, elapsed: f32) -> String {
+    if label == "LibreOffice" && elapsed < LIBREOFFICE_STARTUP_SECS {
+        format!("{label} starting")
+    } else {
+        format!("{label} converting")
+    }
+}
+
+/// Fails a tool that exited unsuccessfully, quoting the tail of its stderr so the
+/// error says what went wrong, not just the exit code.
+fn handle_status(status: std::process::ExitStatus, name: &str, stderr: &str) -> Result<()> {
     if status.success() {
         Ok(())
-    } else {
+    } else if stderr.trim().is_empty() {
         bail!("{name} exited with status {status}")
+    } else {
+        bail!("{name} exited with status {status}\n{}", error_tail(stderr))
     }
 }
 
+/// Most of a tool's stderr quoted in an error, so one runaway line cannot swamp it.
+const ERROR_TAIL_BYTES: usize = 4096;
+
+/// The last `TAIL_LINES` lines of `stderr`, cut further to its last `ERROR_TAIL_BYTES`.
+fn error_tail(stderr: &str) -> String {
+    let lines = tail(stderr.trim_end(), TAIL_LINES);
+    let mut start = lines.len().saturating_sub(ERROR_TAIL_BYTES);
+    while !lines.is_char_boundary(start) {
+        start += 1;
+    }
+    lines[start..].to_string()
+}
+
 fn temp_output_path(temp_dir: &Path, destination: &Path) -> PathBuf {
     let suffix = destination
         .extension()
@@ -584,6 +1622,41 @@ fn temp_output_path(temp_dir: &Path, destination: &Path) -> PathBuf {
     temp_dir.join(format!("output{}", suffix))
 }
 
+const COPY_BUFFER_BYTES: usize = 1024 * 1024;
+
+/// Large copies read and write in 1 MiB chunks, which keeps slow disks streaming
+/// instead of seeking between small 8 KiB requests. The loop is spelled out because
+/// `io::copy` between two files hands the work to `copy_file_range` or `sendfile` on
+/// Linux, which would bypass both the buffer and the readahead hint.
+fn copy_large(mut input: fs::File, output: &mut fs::File) -> io::Result<u64> {
+    advise_sequential(&input);
+    let mut buffer = vec![0u8; COPY_BUFFER_BYTES];
+    let mut copied = 0;
+    loop {
+        let read = match input.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        output.write_all(&buffer[..read])?;
+        copied += read as u64;
+    }
+    Ok(copied)
+}
+
+#[cfg(target_os = "linux")]
+fn advise_sequential(file: &fs::File) {
+    use std::os::fd::AsRawFd;
+    // Only a readahead hint; a failure changes nothing about correctness.
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_SEQUENTIAL);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn advise_sequential(_file: &fs::File) {}
+
 fn ensure_non_empty(path: &Path) -> Result<()> {
     let metadata = fs::metadata(path).context("failed to stat output")?;
     if metadata.len() == 0 {
@@ -600,6 +1673,41 @@ fn finalize_output(temp_path: &Path, destination: &Path, overwrite: bool) -> Res
     Ok(())
 }
 
+/// Moves the numbered pages an `--all-pages` run wrote next to `temp_path` into place and
+/// returns their destinations. Existing pages are backed up or replaced as the flags
+/// allow, checked before any moves.
+fn finalize_pages(temp_path: &Path, plan: &Plan, overwrite: bool) -> Result<Vec<PathBuf>> {
+    let pages: Vec<(PathBuf, PathBuf)> = (1..)
+        .map(|page: u32| {
+            let number = page.to_string();
+            (
+                numbered_destination(temp_path, &number),
+                numbered_destination(&plan.destination, &number),
+            )
+        })
+        .take_while(|(temp, _)| temp.exists())
+        .collect();
+    if pages.is_empty() {
+        bail!("no pages were written");
+    }
+    for (temp, destination) in &pages {
+        ensure_non_empty(temp)?;
+        if destination.exists() && !plan.backup && !overwrite {
+            return Err(DestinationExists.into());
+        }
+    }
+    for (temp, destination) in &pages {
+        if plan.backup && destination.exists() {
+            backup_existing(destination, plan.options.backup_pad)?;
+        }
+        finalize_output(temp, destination, overwrite)?;
+    }
+    Ok(pages
+        .into_iter()
+        .map(|(_, destination)| destination)
+        .collect())
+}
+
 fn ensure_parent_dir(destination: &Path) -> Result<()> {
     let parent = destination
         .parent()
@@ -608,26 +1716,848 @@ fn ensure_parent_dir(destination: &Path) -> Result<()> {
     Ok(())
 }
 
-fn backup_existing(destination: &Path) -> Result<()> {
-    let backup_path = next_backup_path(destination)?;
-    fs::rename(destination, &backup_path).context("failed to backup destination")?;
+fn ensure_free_space(plan: &Plan) -> Result<()> {
+    let Some(needed) = estimated_output_bytes(plan) else {
+        return Ok(());
+    };
+    let parent = match plan.destination.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let Some(available) = available_space(parent) else {
+        return Ok(());
+    };
+    if needed > available {
+        bail!(
+            "insufficient space: need ~{}, have {}",
+            format_size(needed),
+            format_size(available)
+        );
+    }
     Ok(())
 }
 
-fn next_backup_path(destination: &Path) -> Result<PathBuf> {
+#[cfg(target_os = "linux")]
+fn available_space(dir: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stats = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stats` is only read after statvfs succeeds.
+    let stats = unsafe {
+        if libc::statvfs(path.as_ptr(), stats.as_mut_ptr()) != 0 {
+            return None;
+        }
+        stats.assume_init()
+    };
+    Some(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn available_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1000.0;
+    let bytes_f = bytes as f64;
+    if bytes_f >= KB * KB * KB {
+        format!("{:.1} GB", bytes_f / (KB * KB * KB))
+    } else if bytes_f >= KB * KB {
+        format!("{:.0} MB", bytes_f / (KB * KB))
+    } else if bytes_f >= KB {
+        format!("{:.0} KB", bytes_f / KB)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+fn backup_existing(destination: &Path, pad: Option<usize>) -> Result<PathBuf> {
+    let backup_path = next_backup_path(destination, pad)?;
+    // Renaming over the reserved placeholder is atomic, so the slot never frees up for
+    // another task in between.
+    if let Err(err) = fs::rename(destination, &backup_path) {
+        let _ = fs::remove_file(&backup_path);
+        return Err(anyhow::Error::new(err)).context("failed to backup destination");
+    }
+    Ok(backup_path)
+}
+
+/// Reserves the first free `<dest>.bak`, then `<dest>.bak.N`, by creating an empty
+/// placeholder there. Creation fails if anything already holds the name, including
+/// directories and dangling symlinks, so concurrent jobs never pick the same slot the
+/// way a check-then-rename would. With `pad`, N is zero-padded to that width so
+/// numbered backups sort correctly; it simply grows past the width.
+fn next_backup_path(destination: &Path, pad: Option<usize>) -> Result<PathBuf> {
     let mut base = destination.as_os_str().to_os_string();
     base.push(".bak");
-    let candidate = PathBuf::from(&base);
-    if !candidate.exists() {
-        return Ok(candidate);
+    let candidates =
+        std::iter::once(PathBuf::from(&base)).chain((1..=MAX_BACKUP_INDEX).map(|index| {
+            let mut next = base.clone();
+            next.push(format!(".{:0width$}", index, width = pad.unwrap_or(0)));
+            PathBuf::from(next)
+        }));
+    for candidate in candidates {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&candidate)
+        {
+            Ok(_) => return Ok(candidate),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => {
+                return Err(anyhow::Error::new(err))
+                    .with_context(|| format!("failed to reserve {}", candidate.display()));
+            }
+        }
+    }
+    bail!(
+        "could not find available backup path for {} (.bak through .bak.{} are taken)",
+        destination.display(),
+        MAX_BACKUP_INDEX
+    );
+}
+
+const MAX_BACKUP_INDEX: usize = 1000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn set_mtime(path: &Path, secs: u64) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(std::time::UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    fn overwrite_newer_plan(source: &Path, destination: &Path) -> Plan {
+        let options = crate::plan::ConversionOptions {
+            overwrite_newer: true,
+            ..Default::default()
+        };
+        crate::plan::build_plan(source, destination, false, false, options).unwrap()
+    }
+
+    #[test]
+    fn overwrite_newer_replaces_older_destination() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("in.txt");
+        let dest = temp.path().join("out.txt");
+        fs::write(&source, "new").unwrap();
+        fs::write(&dest, "old").unwrap();
+        set_mtime(&source, 2_000_000);
+        set_mtime(&dest, 1_000_000);
+        let outcome = execute_plan_with_reporter(
+            &overwrite_newer_plan(&source, &dest),
+            false,
+            &ProgressReporter::console(true, false),
+        )
+        .unwrap();
+        let Outcome::Done(report) = outcome else {
+            panic!("expected the destination to be replaced");
+        };
+        assert_eq!(
+            report.sizes,
+            Some(SizeChange {
+                source: 3,
+                output: 3
+            })
+        );
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
+    }
+
+    #[test]
+    fn report_names_the_strategy_and_backup() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("in.txt");
+        let dest = temp.path().join("out.txt");
+        fs::write(&source, "fresh").unwrap();
+        fs::write(&dest, "old").unwrap();
+        let plan =
+            crate::plan::build_plan(&source, &dest, false, true, Default::default()).unwrap();
+        let outcome =
+            execute_plan_with_reporter(&plan, false, &ProgressReporter::console(true, false))
+                .unwrap();
+        let Outcome::Done(report) = outcome else {
+            panic!("expected a copy");
+        };
+        assert_eq!(report.strategy_used, Strategy::CopyOnly);
+        assert_eq!(report.ffmpeg_mode, None);
+        assert_eq!(report.backup_path, Some(temp.path().join("out.txt.bak")));
+        assert_eq!(report.output_bytes, 5);
+        assert_eq!(report.describe(), "output 5 B, 100% of source");
+        assert_eq!(
+            fs::read_to_string(temp.path().join("out.txt.bak")).unwrap(),
+            "old"
+        );
+    }
+
+    #[test]
+    fn overwrite_newer_skips_newer_or_equal_destination() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("in.txt");
+        let dest = temp.path().join("out.txt");
+        fs::write(&source, "new").unwrap();
+        fs::write(&dest, "kept").unwrap();
+        for dest_secs in [3_000_000, 2_000_000] {
+            set_mtime(&source, 2_000_000);
+            set_mtime(&dest, dest_secs);
+            let outcome = execute_plan_with_reporter(
+                &overwrite_newer_plan(&source, &dest),
+                false,
+                &ProgressReporter::console(true, false),
+            )
+            .unwrap();
+            assert_eq!(outcome, Outcome::SkippedNotNewer);
+            assert_eq!(fs::read_to_string(&dest).unwrap(), "kept");
+        }
+    }
+
+    fn video_info(codec: &str) -> crate::ffprobe::MediaInfo {
+        crate::ffprobe::MediaInfo {
+            duration_seconds: Some(10.0),
+            video_codec: Some(codec.to_string()),
+            audio_codec: Some("opus".to_string()),
+            width: None,
+            height: None,
+            rotation: None,
+            audio_bitrate: None,
+            color_transfer: None,
+            color_primaries: None,
+        }
+    }
+
+    #[test]
+    fn av1_streams_copy_unless_another_encoder_is_requested() {
+        let plan = |dest: &str, codec: Option<&str>| {
+            let options = crate::plan::ConversionOptions {
+                video_codec: codec.map(str::to_string),
+                ..Default::default()
+            };
+            crate::plan::build_plan(Path::new("in.mkv"), Path::new(dest), false, false, options)
+                .unwrap()
+        };
+        let av1 = video_info("av1");
+        let h264 = video_info("h264");
+        assert_eq!(
+            decide_ffmpeg_mode(&plan("out.webm", None), Some(&av1)),
+            FfmpegMode::StreamCopy
+        );
+        assert_eq!(
+            decide_ffmpeg_mode(&plan("out.mkv", Some("libsvtav1")), Some(&av1)),
+            FfmpegMode::StreamCopy
+        );
+        assert_eq!(
+            decide_ffmpeg_mode(&plan("out.mkv", Some("libsvtav1")), Some(&h264)),
+            FfmpegMode::Transcode
+        );
+    }
+
+    #[test]
+    fn opus_copies_into_opus_ogg_webm_and_m4a() {
+        let info = crate::ffprobe::MediaInfo {
+            duration_seconds: Some(10.0),
+            video_codec: None,
+            audio_codec: Some("opus".to_string()),
+            width: None,
+            height: None,
+            rotation: None,
+            audio_bitrate: None,
+            color_transfer: None,
+            color_primaries: None,
+        };
+        let mode = |source: &str, dest: &str, options: crate::plan::ConversionOptions| {
+            let plan =
+                crate::plan::build_plan(Path::new(source), Path::new(dest), false, false, options)
+                    .unwrap();
+            decide_ffmpeg_mode(&plan, Some(&info))
+        };
+        for (source, dest) in [
+            ("in.ogg", "out.opus"),
+            ("in.opus", "out.ogg"),
+            ("in.opus", "out.webm"),
+            ("in.opus", "out.m4a"),
+            ("in.webm", "out.opus"),
+        ] {
+            assert_eq!(
+                mode(source, dest, Default::default()),
+                FfmpegMode::StreamCopy,
+                "{source} -> {dest}"
+            );
+        }
+        for dest in ["out.mp3", "out.flac", "out.aac"] {
+            assert_eq!(
+                mode("in.opus", dest, Default::default()),
+                FfmpegMode::Transcode,
+                "{dest}"
+            );
+        }
+        let vorbis = crate::plan::ConversionOptions {
+            audio_codec: Some("libvorbis".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(mode("in.opus", "out.ogg", vorbis), FfmpegMode::Transcode);
+        let ogg_opus = crate::plan::ConversionOptions {
+            ogg_opus: true,
+            ..Default::default()
+        };
+        assert_eq!(mode("in.opus", "out.ogg", ogg_opus), FfmpegMode::StreamCopy);
+        let bitrate = crate::plan::ConversionOptions {
+            audio_bitrate: Some("96k".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(mode("in.opus", "out.ogg", bitrate), FfmpegMode::Transcode);
+    }
+
+    fn progress_events(output: &str, duration: Option<f64>) -> Vec<ProgressEvent> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let reporter = ProgressReporter::tui(sender);
+        stream_progress(output.as_bytes(), duration, Pass::ONLY, &reporter, "clip");
+        drop(reporter);
+        receiver.into_iter().collect()
+    }
+
+    #[test]
+    fn instant_copy_still_reports_full_progress() {
+        let events = progress_events("out_time_ms=N/A\nprogress=end\n", Some(12.0));
+        assert!(matches!(
+            events.as_slice(),
+            [ProgressEvent::Progress { percent, .. }] if *percent == 100.0
+        ));
+
+        let events = progress_events(
+            "out_time_ms=0\nprogress=continue\nout_time_ms=11990000\nprogress=end\n",
+            Some(12.0),
+        );
+        assert!(matches!(
+            events.last(),
+            Some(ProgressEvent::Progress { percent, eta: Some(eta), .. })
+                if *percent == 100.0 && *eta == 0.0
+        ));
+    }
+
+    #[test]
+    fn two_pass_progress_spans_both_passes() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let reporter = ProgressReporter::tui(sender);
+        let first = Pass {
+            number: 1,
+            count: 2,
+        };
+        let second = Pass {
+            number: 2,
+            count: 2,
+        };
+        stream_progress(
+            "out_time_ms=5000000\nprogress=end\n".as_bytes(),
+            Some(10.0),
+            first,
+            &reporter,
+            "clip",
+        );
+        stream_progress(
+            "out_time_ms=5000000\n".as_bytes(),
+            Some(10.0),
+            second,
+            &reporter,
+            "clip",
+        );
+        drop(reporter);
+        let progress: Vec<(f64, Option<f64>)> = receiver
+            .into_iter()
+            .filter_map(|event| match event {
+                ProgressEvent::Progress { percent, eta, .. } => Some((percent, eta)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            progress,
+            vec![(25.0, Some(15.0)), (50.0, Some(10.0)), (75.0, Some(5.0))]
+        );
+    }
+
+    #[test]
+    fn unknown_duration_fills_gauge_at_end() {
+        let events = progress_events("out_time_ms=500000\nprogress=end\n", None);
+        assert!(matches!(
+            events.as_slice(),
+            [
+                ProgressEvent::Spinner { .. },
+                ProgressEvent::Progress { percent, eta: Some(eta), .. },
+            ] if *percent == 100.0 && *eta == 0.0
+        ));
+    }
+
+    #[test]
+    fn elapsed_updates_follow_the_wall_clock() {
+        // A fast image-sequence encode: a minute of output reported in a burst.
+        let lines: String = (1..=600)
+            .map(|tenth| format!("out_time_ms={}\nprogress=continue\n", tenth * 100_000))
+            .collect();
+        let events = progress_events(&format!("{lines}progress=end\n"), Some(0.0));
+        let ticks = events
+            .iter()
+            .filter(|event| matches!(event, ProgressEvent::Spinner { .. }))
+            .count();
+        assert_eq!(ticks, 1);
+        assert!(matches!(
+            events.last(),
+            Some(ProgressEvent::Progress { percent, .. }) if *percent == 100.0
+        ));
+    }
+
+    #[test]
+    fn backup_path_is_unpadded_by_default() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("out.mp3");
+        fs::write(temp.path().join("out.mp3.bak"), "x").unwrap();
+        let next = next_backup_path(&dest, None).unwrap();
+        assert_eq!(next, temp.path().join("out.mp3.bak.1"));
+    }
+
+    #[test]
+    fn backup_path_zero_pads_index() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("out.mp3");
+        fs::write(temp.path().join("out.mp3.bak"), "x").unwrap();
+        fs::write(temp.path().join("out.mp3.bak.001"), "x").unwrap();
+        let next = next_backup_path(&dest, Some(3)).unwrap();
+        assert_eq!(next, temp.path().join("out.mp3.bak.002"));
+    }
+
+    #[test]
+    fn backup_path_rolls_over_padding_width() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("out.mp3");
+        fs::write(temp.path().join("out.mp3.bak"), "x").unwrap();
+        for index in 1..=9 {
+            fs::write(temp.path().join(format!("out.mp3.bak.{index}")), "x").unwrap();
+        }
+        let next = next_backup_path(&dest, Some(1)).unwrap();
+        assert_eq!(next, temp.path().join("out.mp3.bak.10"));
+    }
+
+    #[test]
+    fn backup_path_skips_existing_directory() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("out.mp3");
+        fs::create_dir(temp.path().join("out.mp3.bak")).unwrap();
+        let next = next_backup_path(&dest, None).unwrap();
+        assert_eq!(next, temp.path().join("out.mp3.bak.1"));
     }
-    for index in 1..=1000 {
-        let mut next = base.clone();
-        next.push(format!(".{}", index));
-        let candidate = PathBuf::from(next);
-        if !candidate.exists() {
-            return Ok(candidate);
+
+    #[cfg(unix)]
+    #[test]
+    fn backup_path_skips_dangling_symlink() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("out.mp3");
+        std::os::unix::fs::symlink(temp.path().join("missing"), temp.path().join("out.mp3.bak"))
+            .unwrap();
+        let next = next_backup_path(&dest, None).unwrap();
+        assert_eq!(next, temp.path().join("out.mp3.bak.1"));
+    }
+
+    #[test]
+    fn concurrent_backups_reserve_distinct_paths() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("out.mp3");
+        let threads = 32;
+        let barrier = std::sync::Barrier::new(threads);
+        let paths: Vec<PathBuf> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        next_backup_path(&dest, Some(2)).unwrap()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        let unique: std::collections::BTreeSet<_> = paths.iter().collect();
+        assert_eq!(unique.len(), threads);
+        assert!(paths.iter().all(|path| path.is_file()));
+        assert!(unique.contains(&temp.path().join("out.mp3.bak")));
+        assert!(unique.contains(&temp.path().join(format!("out.mp3.bak.{:02}", threads - 1))));
+    }
+
+    #[test]
+    fn backup_moves_destination_into_reserved_slot() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("out.mp3");
+        fs::write(temp.path().join("out.mp3.bak"), "older").unwrap();
+        fs::write(&dest, "current").unwrap();
+        backup_existing(&dest, None).unwrap();
+        assert!(!dest.exists());
+        let backup = temp.path().join("out.mp3.bak.1");
+        assert_eq!(fs::read_to_string(backup).unwrap(), "current");
+    }
+
+    #[test]
+    fn large_copy_preserves_contents() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("in.bin");
+        let data: Vec<u8> = (0..COPY_BUFFER_BYTES * 2 + 17)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        fs::write(&source, &data).unwrap();
+        let mut output = fs::File::create(temp.path().join("out.bin")).unwrap();
+        let copied = copy_large(fs::File::open(&source).unwrap(), &mut output).unwrap();
+        assert_eq!(copied, data.len() as u64);
+        assert_eq!(fs::read(temp.path().join("out.bin")).unwrap(), data);
+    }
+
+    #[test]
+    fn cross_device_renames_fall_back_to_copying() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("in.txt");
+        let dest = temp.path().join("out.txt");
+        fs::write(&source, "moved").unwrap();
+        set_mtime(&source, 1_000_000);
+        move_file(&source, &dest, true, |_, _| {
+            Err(io::Error::from(io::ErrorKind::CrossesDevices))
+        })
+        .unwrap();
+        assert!(!source.exists());
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "moved");
+        assert_eq!(
+            fs::metadata(&dest).unwrap().modified().unwrap(),
+            std::time::UNIX_EPOCH + Duration::from_secs(1_000_000)
+        );
+
+        // Other rename errors are reported as they are, and the source stays put.
+        fs::write(&source, "kept").unwrap();
+        let err = move_file(&source, &temp.path().join("other.txt"), false, |_, _| {
+            Err(io::Error::from(io::ErrorKind::PermissionDenied))
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("failed to rename source"));
+        assert!(source.exists());
+        #[cfg(target_os = "linux")]
+        assert!(is_cross_device(&io::Error::from_raw_os_error(18)));
+    }
+
+    #[test]
+    fn preserve_copies_time_and_mode_onto_the_output() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("in.txt");
+        fs::write(&source, "data").unwrap();
+        set_mtime(&source, 1_000_000);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&source, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        let preserve = crate::plan::ConversionOptions {
+            preserve: true,
+            ..Default::default()
+        };
+        let dest = temp.path().join("out.txt");
+        copy_only(&source, &dest, false, &preserve).unwrap();
+
+        let metadata = fs::metadata(&dest).unwrap();
+        assert_eq!(
+            metadata.modified().unwrap(),
+            std::time::UNIX_EPOCH + Duration::from_secs(1_000_000)
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o640);
+        }
+    }
+
+    /// Flips one byte partway through the stream, like a flaky mount dropping a bit.
+    struct CorruptingWriter<W> {
+        inner: W,
+        written: usize,
+        corrupt_at: usize,
+    }
+
+    impl<W: Write> Write for CorruptingWriter<W> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut chunk = buf.to_vec();
+            if let Some(byte) = self
+                .corrupt_at
+                .checked_sub(self.written)
+                .and_then(|offset| chunk.get_mut(offset))
+            {
+                *byte ^= 0xff;
+            }
+            self.inner.write_all(&chunk)?;
+            self.written += chunk.len();
+            Ok(chunk.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    #[test]
+    fn verify_catches_a_copy_corrupted_mid_stream() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("in.bin");
+        let data: Vec<u8> = (0..HASH_CHUNK_BYTES * 3).map(|i| (i % 251) as u8).collect();
+        fs::write(&source, &data).unwrap();
+
+        let good = temp.path().join("good.bin");
+        let verify = crate::plan::ConversionOptions {
+            verify: true,
+            ..Default::default()
+        };
+        copy_only(&source, &good, false, &verify).unwrap();
+        assert_eq!(fs::read(&good).unwrap(), data);
+
+        let bad = temp.path().join("bad.bin");
+        let mut writer = CorruptingWriter {
+            inner: fs::File::create(&bad).unwrap(),
+            written: 0,
+            corrupt_at: HASH_CHUNK_BYTES + 5,
+        };
+        io::copy(&mut fs::File::open(&source).unwrap(), &mut writer).unwrap();
+        let message = verify_copy(&source, &bad).unwrap_err().to_string();
+        assert!(
+            message.contains(&sha256_file(&source).unwrap()),
+            "{message}"
+        );
+        assert!(message.contains("removed the copy"), "{message}");
+        assert!(!bad.exists());
+    }
+
+    #[test]
+    fn formats_sizes_for_space_errors() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(500_000_000), "500 MB");
+        assert_eq!(format_size(2_000_000_000), "2.0 GB");
+    }
+
+    #[test]
+    fn size_changes_describe_the_output_share() {
+        let shrunk = SizeChange {
+            source: 8_000_000,
+            output: 3_280_000,
+        };
+        assert_eq!(shrunk.describe(), "output 3 MB, 41% of source");
+        assert_eq!(shrunk.savings(), "saved 5 MB");
+        let empty = SizeChange {
+            source: 0,
+            output: 512,
+        };
+        assert_eq!(empty.ratio(), None);
+        assert_eq!(empty.describe(), "output 512 B");
+        assert_eq!(empty.savings(), "grew by 512 B");
+    }
+
+    #[test]
+    fn available_space_reports_for_existing_dirs() {
+        let temp = TempDir::new().unwrap();
+        if cfg!(target_os = "linux") {
+            assert!(available_space(temp.path()).is_some());
+        }
+        assert!(available_space(&temp.path().join("missing")).is_none());
+    }
+
+    #[test]
+    fn detects_imagemagick_policy_errors() {
+        assert!(is_policy_error(
+            "convert-im6.q16: attempt to perform an operation not allowed by the security \
+             policy `PDF' @ error/constitute.c/IsCoderAuthorized/421."
+        ));
+        assert!(is_policy_error(
+            "convert-im6.q16: not authorized `doc.pdf' @ error/constitute.c/ReadImage/412."
+        ));
+        assert!(!is_policy_error(
+            "magick: unable to open image 'missing.png': No such file or directory"
+        ));
+    }
+
+    /// argv after the program name, minus ffmpeg's fixed logging preamble.
+    fn argv(command: &Command) -> Vec<String> {
+        const PREAMBLE: [&str; 6] = [
+            "-nostdin",
+            "-y",
+            "-hide_banner",
+            "-nostats",
+            "-loglevel",
+            "error",
+        ];
+        let args: Vec<String> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let skip = if args.iter().zip(PREAMBLE).all(|(arg, fixed)| arg == fixed) {
+            PREAMBLE.len()
+        } else {
+            0
+        };
+        args[skip..].to_vec()
+    }
+
+    /// Preview tokens between the program name and the destination.
+    fn preview_argv(plan: &Plan) -> Vec<String> {
+        let preview = crate::plan::command_preview(plan).unwrap();
+        let tokens: Vec<String> = preview.split_whitespace().map(str::to_string).collect();
+        tokens[1..tokens.len() - 1].to_vec()
+    }
+
+    #[test]
+    fn ffmpeg_preview_matches_argv_with_input_flags_first() {
+        use crate::plan::FfmpegPreference;
+        for preference in [FfmpegPreference::StreamCopy, FfmpegPreference::Transcode] {
+            let options = crate::plan::ConversionOptions {
+                ffmpeg_preference: preference,
+                rotate: Some(Rotation::Set(90)),
+                video_bitrate: Some("2M".to_string()),
+                ..Default::default()
+            };
+            let plan = crate::plan::build_plan(
+                Path::new("in.mp4"),
+                Path::new("out.mkv"),
+                false,
+                false,
+                options,
+            )
+            .unwrap();
+            let mode = match preference {
+                FfmpegPreference::StreamCopy => FfmpegMode::StreamCopy,
+                _ => FfmpegMode::Transcode,
+            };
+            let command = ffmpeg_output_command(
+                &plan.source,
+                &plan.options,
+                plan.dest_kind,
+                plan.dest_ext.as_deref(),
+                mode,
+                RotationArgs::for_plan(&plan, None),
+                false,
+                false,
+            );
+            let args = argv(&command);
+            assert_eq!(args, preview_argv(&plan));
+            let input = args.iter().position(|arg| arg == "-i").unwrap();
+            let noautorotate = args.iter().position(|arg| arg == "-noautorotate").unwrap();
+            assert!(noautorotate < input);
+        }
+    }
+
+    #[test]
+    fn audio_from_video_drops_the_video_stream() {
+        let transcode = |source: &str, extract_audio: bool| {
+            let plan = crate::plan::build_plan(
+                Path::new(source),
+                Path::new("out.mp3"),
+                false,
+                false,
+                crate::plan::ConversionOptions {
+                    ffmpeg_preference: crate::plan::FfmpegPreference::Transcode,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let command = ffmpeg_output_command(
+                &plan.source,
+                &plan.options,
+                plan.dest_kind,
+                plan.dest_ext.as_deref(),
+                FfmpegMode::Transcode,
+                RotationArgs::default(),
+                false,
+                extract_audio,
+            );
+            (plan, argv(&command))
+        };
+        let (plan, args) = transcode("clip.mp4", true);
+        assert_eq!(args, preview_argv(&plan));
+        assert!(args.iter().any(|arg| arg == "-vn"));
+        assert!(
+            plan.notes
+                .iter()
+                .any(|note| note == "extracting audio track only")
+        );
+
+        let (plan, args) = transcode("song.wav", false);
+        assert_eq!(args, preview_argv(&plan));
+        assert!(!args.iter().any(|arg| arg == "-vn"));
+        assert!(!plan.notes.iter().any(|note| note.contains("audio track")));
+    }
+
+    #[test]
+    fn ffmpeg_loglevel_replaces_the_quiet_default() {
+        let loglevel = |options: &crate::plan::ConversionOptions| {
+            let command =
+                ffmpeg_command(Path::new("in.mp4"), options, None, RotationArgs::default());
+            let args: Vec<String> = command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            let flag = args.iter().position(|arg| arg == "-loglevel").unwrap();
+            args[flag + 1].clone()
+        };
+        assert_eq!(loglevel(&Default::default()), "error");
+        let verbose = crate::plan::ConversionOptions {
+            ffmpeg_loglevel: Some("verbose".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(loglevel(&verbose), "verbose");
+    }
+
+    #[test]
+    fn imagemagick_preview_matches_argv() {
+        let options = crate::plan::ConversionOptions {
+            image_quality: Some(80),
+            grayscale: true,
+            ..Default::default()
+        };
+        let plan = crate::plan::build_plan(
+            Path::new("scan.pdf"),
+            Path::new("page.png"),
+            false,
+            false,
+            options,
+        )
+        .unwrap();
+        let command = imagemagick_command("magick", &plan.source, &plan.destination, &plan.options);
+        let mut args = argv(&command);
+        assert_eq!(args.pop().as_deref(), Some("page.png"));
+        assert_eq!(args, preview_argv(&plan));
+        assert_eq!(args[0], "scan.pdf[0]");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failures_quote_a_bounded_stderr_tail() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let failed = std::process::ExitStatus::from_raw(1 << 8);
+        let err = handle_status(failed, "ffmpeg", "").unwrap_err();
+        assert_eq!(err.to_string(), "ffmpeg exited with status exit status: 1");
+
+        let stderr = "Unknown encoder 'nosuchcodec'\n";
+        let err = handle_status(failed, "ffmpeg", stderr).unwrap_err();
+        assert!(err.to_string().ends_with("\nUnknown encoder 'nosuchcodec'"));
+
+        let long = format!("{}\nlast line\n", "é".repeat(ERROR_TAIL_BYTES));
+        let quoted = error_tail(&long);
+        assert!(quoted.len() <= ERROR_TAIL_BYTES);
+        assert!(quoted.ends_with("\nlast line"));
+    }
+
+    #[test]
+    fn captured_output_is_bounded_and_survives_bad_utf8() {
+        let mut output = Vec::new();
+        for n in 0..10_000 {
+            output.extend_from_slice(format!("frame {n}\n").as_bytes());
         }
+        output.extend_from_slice(b"bad \xff byte\r\nUnknown encoder 'nosuchcodec'\n");
+        let captured = collect_lines(std::io::Cursor::new(output), false, CAPTURE_BYTES)
+            .join()
+            .expect("reader thread");
+        assert!(captured.len() <= CAPTURE_BYTES);
+        assert!(captured.ends_with("bad \u{fffd} byte\nUnknown encoder 'nosuchcodec'\n"));
+        assert!(!captured.contains("frame 0\n"));
     }
-    bail!("could not find available backup path");
 }
@@ -0,0 +1,88 @@
+use std::fmt;
+use std::process::ExitCode;
+
+/// Failure classes surfaced as process exit codes. Scripts depend on the numbers, so
+/// only ever add new ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    General,
+    Usage,
+    MissingTool,
+    Conversion,
+    PartialBatch,
+    /// Ctrl-C; 130 is the shell's code for a SIGINT death.
+    Interrupted,
+}
+
+impl FailureKind {
+    pub fn code(self) -> u8 {
+        match self {
+            FailureKind::General => 1,
+            FailureKind::Usage => 2,
+            FailureKind::MissingTool => 3,
+            FailureKind::Conversion => 4,
+            FailureKind::PartialBatch => 5,
+            FailureKind::Interrupted => 130,
+        }
+    }
+}
+
+/// An external tool (ImageMagick, ffmpeg, LibreOffice, ...) is not installed.
+#[derive(Debug)]
+pub struct MissingTool(pub &'static str);
+
+impl fmt::Display for MissingTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for MissingTool {}
+
+pub fn is_missing_tool(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<MissingTool>().is_some()
+}
+
+#[derive(Debug)]
+pub struct Failure {
+    pub kind: FailureKind,
+    pub error: anyhow::Error,
+}
+
+impl Failure {
+    /// An interruption or a missing tool wins over whatever phase the error surfaced in.
+    pub fn new(kind: FailureKind, error: anyhow::Error) -> Self {
+        let kind = if crate::cancel::is_interrupted(&error) {
+            FailureKind::Interrupted
+        } else if is_missing_tool(&error) {
+            FailureKind::MissingTool
+        } else {
+            kind
+        };
+        Self { kind, error }
+    }
+
+    pub fn usage(error: anyhow::Error) -> Self {
+        Self::new(FailureKind::Usage, error)
+    }
+
+    pub fn conversion(error: anyhow::Error) -> Self {
+        Self::new(FailureKind::Conversion, error)
+    }
+
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(self.kind.code())
+    }
+}
+
+impl From<anyhow::Error> for Failure {
+    fn from(error: anyhow::Error) -> Self {
+        Self::new(FailureKind::General, error)
+    }
+}
+
+impl From<serde_json::Error> for Failure {
+    fn from(error: serde_json::Error) -> Self {
+        Self::new(FailureKind::General, error.into())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::exit::{MissingTool, is_missing_tool};
 use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::path::Path;
@@ -8,6 +9,28 @@ pub struct MediaInfo {
     pub duration_seconds: Option<f64>,
     pub video_codec: Option<String>,
     pub audio_codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Clockwise display rotation of the first video stream (0, 90, 180 or 270).
+    pub rotation: Option<u16>,
+    /// Bits per second of the first audio stream; for audio-only files without a
+    /// stream figure, the container's overall bitrate.
+    pub audio_bitrate: Option<u64>,
+    /// Transfer characteristics and color primaries of the first video stream, as
+    /// ffprobe names them (e.g. `smpte2084`, `bt2020`).
+    pub color_transfer: Option<String>,
+    pub color_primaries: Option<String>,
+}
+
+impl MediaInfo {
+    /// The HDR transfer of the first video stream: `PQ` (SMPTE ST 2084) or `HLG`.
+    pub fn hdr_transfer(&self) -> Option<&'static str> {
+        match self.color_transfer.as_deref()? {
+            "smpte2084" => Some("PQ"),
+            "arib-std-b67" => Some("HLG"),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -19,12 +42,50 @@ struct ProbeOutput {
 #[derive(Debug, Deserialize)]
 struct ProbeFormat {
     duration: Option<String>,
+    bit_rate: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ProbeStream {
     codec_type: Option<String>,
     codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    bit_rate: Option<String>,
+    color_transfer: Option<String>,
+    color_primaries: Option<String>,
+    side_data_list: Option<Vec<ProbeSideData>>,
+    tags: Option<ProbeTags>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeSideData {
+    rotation: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeTags {
+    rotate: Option<String>,
+}
+
+impl ProbeStream {
+    /// The display matrix reports counter-clockwise degrees (e.g. -90 for a portrait
+    /// phone clip); older files carry a clockwise `rotate` tag instead.
+    fn rotation(&self) -> Option<u16> {
+        let matrix = self
+            .side_data_list
+            .iter()
+            .flatten()
+            .find_map(|side_data| side_data.rotation)
+            .map(|degrees| -degrees);
+        let tag = self
+            .tags
+            .as_ref()
+            .and_then(|tags| tags.rotate.as_deref())
+            .and_then(|value| value.parse::<f64>().ok());
+        let degrees = matrix.or(tag)?.round() as i64;
+        Some(degrees.rem_euclid(360) as u16)
+    }
 }
 
 pub fn probe_media(path: &Path) -> Result<MediaInfo> {
@@ -39,7 +100,9 @@ pub fn probe_media(path: &Path) -> Result<MediaInfo> {
         .output()
         .map_err(|err| {
             if err.kind() == std::io::ErrorKind::NotFound {
-                anyhow::anyhow!("ffprobe not found; install ffmpeg (e.g., apt install ffmpeg)")
+                anyhow::Error::new(MissingTool(
+                    "ffprobe not found; install ffmpeg (e.g., apt install ffmpeg)",
+                ))
             } else {
                 anyhow::Error::new(err).context("failed to execute ffprobe")
             }
@@ -59,23 +122,126 @@ pub fn probe_media(path: &Path) -> Result<MediaInfo> {
         .and_then(|d| d.parse::<f64>().ok());
     let mut video_codec = None;
     let mut audio_codec = None;
+    let mut width = None;
+    let mut height = None;
+    let mut rotation = None;
+    let mut audio_bitrate = None;
+    let mut color_transfer = None;
+    let mut color_primaries = None;
     if let Some(streams) = parsed.streams {
         for stream in streams {
             match stream.codec_type.as_deref() {
                 Some("video") if video_codec.is_none() => {
+                    rotation = stream.rotation();
                     video_codec = stream.codec_name;
+                    width = stream.width;
+                    height = stream.height;
+                    color_transfer = stream.color_transfer;
+                    color_primaries = stream.color_primaries;
                 }
                 Some("audio") if audio_codec.is_none() => {
                     audio_codec = stream.codec_name;
+                    audio_bitrate = stream.bit_rate.and_then(|rate| rate.parse().ok());
                 }
                 _ => {}
             }
         }
     }
 
+    if audio_bitrate.is_none() && video_codec.is_none() && audio_codec.is_some() {
+        audio_bitrate = parsed
+            .format
+            .as_ref()
+            .and_then(|fmt| fmt.bit_rate.as_deref())
+            .and_then(|rate| rate.parse().ok());
+    }
+
     Ok(MediaInfo {
         duration_seconds,
         video_codec,
         audio_codec,
+        width,
+        height,
+        rotation,
+        audio_bitrate,
+        color_transfer,
+        color_primaries,
     })
 }
+
+/// ffprobe's reading of a plan's source. It is taken at most once, while planning, and
+/// the plan notes, `--probe` and the conversion all reuse it.
+#[derive(Debug, Clone, Default)]
+pub enum SourceProbe {
+    /// Nothing has needed it yet.
+    #[default]
+    NotRun,
+    Read(MediaInfo),
+    /// ffprobe is not installed.
+    Missing,
+    /// ffprobe ran but could not read the source.
+    Failed(String),
+}
+
+impl SourceProbe {
+    pub fn run(path: &Path) -> Self {
+        match probe_media(path) {
+            Ok(info) => Self::Read(info),
+            Err(err) if is_missing_tool(&err) => Self::Missing,
+            Err(err) => Self::Failed(format!("{err:#}")),
+        }
+    }
+
+    /// Probes `path` unless that already happened, then returns what was read.
+    pub fn read(&mut self, path: &Path) -> Option<&MediaInfo> {
+        if matches!(self, Self::NotRun) {
+            *self = Self::run(path);
+        }
+        self.info()
+    }
+
+    pub fn info(&self) -> Option<&MediaInfo> {
+        match self {
+            Self::Read(info) => Some(info),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(json: &str) -> ProbeStream {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn reads_rotation_from_display_matrix_and_tag() {
+        let matrix =
+            stream(r#"{"side_data_list":[{"side_data_type":"Display Matrix","rotation":-90}]}"#);
+        assert_eq!(matrix.rotation(), Some(90));
+        let tag = stream(r#"{"tags":{"rotate":"270"}}"#);
+        assert_eq!(tag.rotation(), Some(270));
+        assert_eq!(stream("{}").rotation(), None);
+    }
+
+    #[test]
+    fn recognizes_hdr_transfers() {
+        let info = |transfer: Option<&str>| MediaInfo {
+            duration_seconds: None,
+            video_codec: Some("hevc".to_string()),
+            audio_codec: None,
+            width: None,
+            height: None,
+            rotation: None,
+            audio_bitrate: None,
+            color_transfer: transfer.map(str::to_string),
+            color_primaries: Some("bt2020".to_string()),
+        };
+        assert_eq!(info(Some("smpte2084")).hdr_transfer(), Some("PQ"));
+        assert_eq!(info(Some("arib-std-b67")).hdr_transfer(), Some("HLG"));
+        assert_eq!(info(Some("bt709")).hdr_transfer(), None);
+        assert_eq!(info(None).hdr_transfer(), None);
+    }
+}
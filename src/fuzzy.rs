@@ -0,0 +1,96 @@
+/// Scores `candidate` against a typed `query`, fzf style: every query character must
+/// appear in order, case-insensitively. Plain substring matches always outrank scattered
+/// ones; higher is better and `None` means no match.
+pub fn score(query: &str, candidate: &str) -> Option<i64> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let candidate = candidate.to_lowercase();
+    if let Some(start) = candidate.find(&query) {
+        let at_boundary = candidate[..start]
+            .chars()
+            .next_back()
+            .is_none_or(is_separator);
+        let prefix = candidate[..start].chars().count() as i64;
+        return Some(SUBSTRING_SCORE + if at_boundary { BOUNDARY_BONUS } else { 0 } - prefix);
+    }
+    subsequence_score(&query, &candidate)
+}
+
+/// Sorts `items` best match first, dropping those `query` does not match. Ties keep their
+/// original order.
+pub fn rank<T>(query: &str, items: Vec<T>, key: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut scored: Vec<(i64, T)> = items
+        .into_iter()
+        .filter_map(|item| score(query, key(&item)).map(|value| (value, item)))
+        .collect();
+    scored.sort_by_key(|(value, _)| std::cmp::Reverse(*value));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+const SUBSTRING_SCORE: i64 = 1000;
+const BOUNDARY_BONUS: i64 = 8;
+const CONSECUTIVE_BONUS: i64 = 5;
+
+fn subsequence_score(query: &str, candidate: &str) -> Option<i64> {
+    let mut wanted = query.chars().peekable();
+    let mut total = 0;
+    let mut previous: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+    for (index, ch) in candidate.chars().enumerate() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+        if ch == next {
+            wanted.next();
+            total += 1;
+            if previous.is_none_or(is_separator) {
+                total += BOUNDARY_BONUS;
+            }
+            match last_match {
+                Some(last) if last + 1 == index => total += CONSECUTIVE_BONUS,
+                Some(last) => total -= (index - last - 1).min(10) as i64,
+                None => {}
+            }
+            last_match = Some(index);
+        }
+        previous = Some(ch);
+    }
+    wanted.peek().is_none().then_some(total)
+}
+
+fn is_separator(ch: char) -> bool {
+    matches!(ch, '/' | '\\' | '-' | '_' | '.' | ' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_scattered_characters_in_order() {
+        assert!(score("dwnmv", "Downloads/movies").is_some());
+        assert!(score("mvdwn", "Downloads/movies").is_none());
+        assert!(score("xyz", "Downloads/movies").is_none());
+    }
+
+    #[test]
+    fn substring_matches_outrank_fuzzy_ones() {
+        let ranked = rank(
+            "mov",
+            vec!["my/old/video", "clips/movie.mp4", "remove.txt"],
+            |item| item,
+        );
+        assert_eq!(
+            ranked,
+            vec!["clips/movie.mp4", "remove.txt", "my/old/video"]
+        );
+    }
+
+    #[test]
+    fn word_boundaries_and_runs_score_higher() {
+        assert!(score("mv", "my/videos").unwrap() > score("mv", "amove").unwrap());
+        assert!(score("abd", "abcd").unwrap() > score("abd", "axbxd").unwrap());
+    }
+}
@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Pixel dimensions of the first frame of an image, via ImageMagick `identify`.
+/// Returns `None` when ImageMagick is missing or cannot read the file.
+pub fn image_dimensions(path: &Path) -> Result<Option<(u32, u32)>> {
+    let target = format!("{}[0]", path.display());
+    let mut output = Command::new("magick")
+        .args(["identify", "-format", "%w %h"])
+        .arg(&target)
+        .output();
+    if matches!(&output, Err(err) if err.kind() == std::io::ErrorKind::NotFound) {
+        output = Command::new("identify")
+            .args(["-format", "%w %h"])
+            .arg(&target)
+            .output();
+    }
+    let output = match output {
+        Ok(output) => output,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(anyhow::Error::new(err)).context("failed to execute identify"),
+    };
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut parts = text.split_whitespace().map(|part| part.parse::<u32>());
+    match (parts.next(), parts.next()) {
+        (Some(Ok(width)), Some(Ok(height))) => Ok(Some((width, height))),
+        _ => Ok(None),
+    }
+}
+
+/// Number of frames (GIF/WebP animation frames, TIFF pages, ICO sizes) in an image,
+/// via ImageMagick `identify`. Returns `None` when ImageMagick is missing or cannot
+/// read the file.
+pub fn image_frame_count(path: &Path) -> Result<Option<u32>> {
+    // `%n` is the image count; it is printed once per frame, so only the first line counts.
+    let mut output = Command::new("magick")
+        .args(["identify", "-format", "%n\\n"])
+        .arg(path)
+        .output();
+    if matches!(&output, Err(err) if err.kind() == std::io::ErrorKind::NotFound) {
+        output = Command::new("identify")
+            .args(["-format", "%n\\n"])
+            .arg(path)
+            .output();
+    }
+    let output = match output {
+        Ok(output) => output,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(anyhow::Error::new(err)).context("failed to execute identify"),
+    };
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    Ok(text
+        .lines()
+        .next()
+        .and_then(|line| line.trim().parse::<u32>().ok()))
+}
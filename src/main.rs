@@ -1,16 +1,31 @@
 mod batch;
+mod cancel;
 mod config;
 mod detect;
 mod execute;
+mod exit;
 mod ffprobe;
+mod fuzzy;
+mod identify;
+mod output;
 mod pdf;
 mod plan;
+mod raw;
+mod runlog;
+mod style;
+mod temps;
 mod tui;
+mod verify;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use clap::Parser;
+use crossterm::style::Color;
+use exit::Failure;
+use std::collections::BTreeMap;
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -29,30 +44,101 @@ struct Cli {
     /// Alias for --plan
     #[arg(long)]
     dry_run: bool,
+    /// With --batch --plan, add strategy counts and destination conflicts (an object
+    /// wrapping the plans with --json)
+    #[arg(long, requires = "batch")]
+    audit: bool,
+    /// With --plan --json, add what ffprobe reports about ffmpeg sources (`media_info`)
+    #[arg(long, requires = "json")]
+    probe: bool,
     /// Overwrite destination if it exists
     #[arg(long)]
     overwrite: bool,
+    /// Overwrite an existing destination only when the source is newer; skip otherwise
+    #[arg(long)]
+    overwrite_newer: bool,
     /// Backup destination if it exists (adds .bak, .bak.1, ...)
     #[arg(long)]
     backup: bool,
+    /// Skip sources whose destination already exists and is non-empty (an empty one is
+    /// converted again), for resuming an interrupted run
+    #[arg(
+        long,
+        conflicts_with_all = ["overwrite", "overwrite_newer", "backup", "strict", "verify_only"]
+    )]
+    skip_existing: bool,
+    /// Zero-pad numbered backups to this width (e.g. 3 for .bak.001)
+    #[arg(long)]
+    backup_pad: Option<usize>,
     /// Enable batch mode
     #[arg(long)]
     batch: bool,
     /// Destination directory for batch mode
     #[arg(long, requires = "batch")]
     dest_dir: Option<PathBuf>,
+    /// Directory for a single destination given as a bare file name (default: the source's)
+    #[arg(long, value_name = "DIR", conflicts_with = "batch")]
+    output_dir: Option<PathBuf>,
     /// Additional inputs for batch mode (repeatable)
     #[arg(long)]
     input: Vec<String>,
     /// Read inputs from stdin (newline-separated)
     #[arg(long)]
     stdin: bool,
+    /// Read batch inputs from a file, one per line; `#` comments are ignored (repeatable)
+    #[arg(long, value_name = "FILE", requires = "batch")]
+    input_file: Vec<PathBuf>,
     /// Recurse into directories for batch mode
     #[arg(long)]
     recursive: bool,
+    /// Skip batch sources matching this glob, on the full path or file name (repeatable)
+    #[arg(long, value_name = "PATTERN", requires = "batch")]
+    exclude: Vec<String>,
+    /// Only convert batch sources of this kind: image, audio, video, or document
+    #[arg(long, value_name = "KIND", value_parser = plan::parse_media_kind, requires = "batch")]
+    only: Option<plan::MediaKind>,
+    /// Only convert batch sources with these extensions (comma-separated)
+    #[arg(long, value_name = "LIST", value_delimiter = ',', requires = "batch")]
+    ext: Vec<String>,
+    /// Record finished batch sources in this file and skip them on the next run
+    #[arg(long, requires = "batch")]
+    state: Option<PathBuf>,
+    /// Skip batch sources whose content matches an earlier source in the same run
+    #[arg(long, requires = "batch")]
+    skip_duplicates: bool,
+    /// Rename later batch outputs that would share a destination (name-1.ext, name-2.ext)
+    /// instead of refusing to start
+    #[arg(long, requires = "batch")]
+    dedupe_names: bool,
+    /// Process only the first N batch sources (after state-file and duplicate skips)
+    #[arg(long, value_name = "N", requires = "batch")]
+    limit: Option<usize>,
+    /// Check that each batch source's existing output is present, non-empty and
+    /// probes cleanly, without converting anything
+    #[arg(long, requires = "batch", conflicts_with_all = ["plan", "dry_run", "state", "tui"])]
+    verify_only: bool,
+    /// Count batch destinations that already exist as failures instead of skips
+    #[arg(long, requires = "batch")]
+    strict: bool,
+    /// Create a missing --dest-dir without asking
+    #[arg(long, requires = "batch")]
+    create_dirs: bool,
+    /// TOML or JSON file of batch jobs, each with a source, an optional destination and
+    /// option overrides
+    #[arg(long, value_name = "PATH", requires = "batch")]
+    manifest: Option<PathBuf>,
+    /// Number of batch conversions the TUI runs at once
+    #[arg(long, default_value_t = 1)]
+    jobs: usize,
+    /// Warn when batch inputs resolve to an already-collected path
+    #[arg(long)]
+    warn_duplicates: bool,
     /// Change destination extension for batch mode (e.g., mp3)
     #[arg(long)]
     to_ext: Option<String>,
+    /// Batch output name pattern using {stem}, {ext}, {name}, {parent} and {index} (or its short form, n in braces)
+    #[arg(long, value_name = "TEMPLATE", requires = "batch")]
+    name_template: Option<String>,
     /// Path to config file (defaults to XDG config path)
     #[arg(long)]
     config: Option<PathBuf>,
@@ -68,6 +154,10 @@ struct Cli {
     /// Video bitrate (e.g. 2500k) for ffmpeg conversions
     #[arg(long)]
     video_bitrate: Option<String>,
+    /// Constant-quality factor (0-63, lower is better) for ffmpeg video encodes, instead
+    /// of a video bitrate
+    #[arg(long, value_name = "N", conflicts_with_all = ["video_bitrate", "two_pass"])]
+    crf: Option<u8>,
     /// Audio bitrate (e.g. 192k) for ffmpeg conversions
     #[arg(long)]
     audio_bitrate: Option<String>,
@@ -77,18 +167,176 @@ struct Cli {
     /// ffmpeg video codec (e.g. libx264, libx265, vp9)
     #[arg(long)]
     video_codec: Option<String>,
+    /// Encode video on the GPU: nvenc, vaapi, videotoolbox, or none
+    #[arg(long, value_name = "KIND", value_parser = plan::parse_hwaccel)]
+    hwaccel: Option<String>,
     /// ffmpeg audio codec (e.g. aac, libopus, flac)
     #[arg(long)]
     audio_codec: Option<String>,
+    /// Encode .ogg outputs as Opus instead of Vorbis
+    #[arg(long)]
+    ogg_opus: bool,
+    /// Cap --audio-bitrate at the source's audio bitrate instead of upscaling
+    #[arg(long)]
+    no_upscale_bitrate: bool,
+    /// Audio sample format for audio outputs (s16, s24, s32, flt, dbl, ...); forces transcode
+    #[arg(long, value_name = "FMT", value_parser = plan::parse_sample_fmt)]
+    sample_fmt: Option<String>,
+    /// Triangular dither when --sample-fmt lowers the bit depth
+    #[arg(long)]
+    dither: bool,
     /// Force ffmpeg stream copy (no re-encode) when possible
     #[arg(long)]
     stream_copy: bool,
     /// Force ffmpeg transcode (re-encode)
     #[arg(long)]
     transcode: bool,
+    /// ffmpeg -loglevel (quiet, panic, fatal, error, warning, info, verbose, debug, trace);
+    /// above error, ffmpeg's stderr is kept in errors and the log
+    #[arg(long, value_name = "LEVEL", value_parser = plan::parse_ffmpeg_loglevel)]
+    ffmpeg_loglevel: Option<String>,
+    /// Use two-pass ffmpeg encoding for video (requires --video-bitrate)
+    #[arg(long)]
+    two_pass: bool,
+    /// Drop the audio track from video outputs
+    #[arg(long)]
+    mute: bool,
+    /// Playback speed factor for ffmpeg conversions (e.g. 2 for double speed)
+    #[arg(long)]
+    speed: Option<f64>,
+    /// Start ffmpeg conversions at this time (seconds or [HH:]MM:SS[.ms])
+    #[arg(long, value_name = "TIME")]
+    start: Option<String>,
+    /// Keep this much of the input after the start
+    #[arg(long, value_name = "TIME")]
+    duration: Option<String>,
+    /// Stop at this time in the input
+    #[arg(long, value_name = "TIME", conflicts_with = "duration")]
+    to: Option<String>,
+    /// Normalize audio loudness with ffmpeg's loudnorm filter (forces transcode)
+    #[arg(long)]
+    normalize: bool,
+    /// Integrated loudness target for --normalize, in LUFS (default -16)
+    #[arg(long, value_name = "LUFS", allow_negative_numbers = true)]
+    loudness: Option<f64>,
+    /// Convert images and video to grayscale
+    #[arg(long)]
+    grayscale: bool,
+    /// Convert images to the sRGB color space
+    #[arg(long)]
+    srgb: bool,
+    /// Convert images to the given ICC profile
+    #[arg(long)]
+    icc: Option<PathBuf>,
+    /// Abort before converting when the destination lacks space for the estimated output
+    #[arg(long)]
+    check_space: bool,
+    /// Kill a conversion tool (ffmpeg, ImageMagick, ...) that runs longer than this
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+    /// After a plain copy or rename, compare SHA-256 digests of the source and destination
+    #[arg(long)]
+    verify: bool,
+    /// Refuse sources whose content contradicts their extension (e.g. a .jpg that is a PNG)
+    #[arg(long)]
+    strict_detect: bool,
+    /// Give outputs the source's modification time and (on Unix) permissions
+    #[arg(long)]
+    preserve: bool,
+    /// Cap video width in pixels (never upscales)
+    #[arg(long, value_name = "PX")]
+    max_width: Option<u32>,
+    /// Cap video height in pixels (never upscales)
+    #[arg(long, value_name = "PX")]
+    max_height: Option<u32>,
+    /// Video rotation: 0, 90, 180 or 270 sets the rotation flag; auto turns rotated clips upright
+    #[arg(long, value_name = "DEG", value_parser = plan::parse_rotation)]
+    rotate: Option<plan::Rotation>,
+    /// Tone-map HDR (PQ/HLG) video to SDR BT.709 when transcoding (needs ffmpeg with zscale)
+    #[arg(long)]
+    tonemap: bool,
+    /// Strip metadata (EXIF, GPS, tags): ImageMagick -strip, ffmpeg -map_metadata -1
+    #[arg(long)]
+    strip_metadata: bool,
+    /// Keep the source's metadata in ffmpeg outputs (-map_metadata 0)
+    #[arg(long)]
+    keep_metadata: bool,
+    /// Replace characters FAT/NTFS reject in output file names (: ? * ...) with _
+    #[arg(long)]
+    sanitize: bool,
+    /// Also lowercase sanitized output names (implies --sanitize)
+    #[arg(long)]
+    sanitize_lowercase: bool,
+    /// Also turn spaces into _ in sanitized output names (implies --sanitize)
+    #[arg(long)]
+    sanitize_spaces: bool,
+    /// Render PDFs with Ghostscript instead of ImageMagick
+    #[arg(long)]
+    prefer_gs: bool,
+    /// Shrink images to fit within WIDTHxHEIGHT (never upscales)
+    #[arg(long, value_name = "WxH")]
+    fit: Option<String>,
+    /// Resize images to an ImageMagick geometry: 1920x1080, 800x, x600, 50%, with an
+    /// optional >, <, ^ or ! flag
+    #[arg(long, value_name = "GEOMETRY")]
+    resize: Option<String>,
+    /// Rasterization resolution in DPI for SVG and PDF sources
+    #[arg(long, value_name = "DPI")]
+    density: Option<u32>,
+    /// Encode WebP and AVIF output losslessly (replaces --image-quality)
+    #[arg(long)]
+    lossless: bool,
+    /// WebP/AVIF encoder effort, from 0 (fastest) to 6 (smallest output)
+    #[arg(long, value_name = "0-6")]
+    effort: Option<u8>,
+    /// Convert only frame N (from 0) of an animated GIF/WebP, multi-page TIFF, ICO or PDF
+    #[arg(long, value_name = "N")]
+    frame: Option<u32>,
+    /// Convert every page of a multi-page PDF to numbered images (out-1.png, out-2.png, ...)
+    #[arg(long, conflicts_with = "frame")]
+    all_pages: bool,
+    /// Print what mvx detects about PATH (MIME type, extension) without planning
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = [
+            "source",
+            "destination",
+            "batch",
+            "plan",
+            "dry_run",
+            "verify_only",
+            "clean_temps",
+            "list_conversions",
+            "tui",
+        ]
+    )]
+    detect: Option<PathBuf>,
+    /// Remove leftover .mvx.tmp* files in DIR (with --plan, only list them)
+    #[arg(long, value_name = "DIR")]
+    clean_temps: Option<PathBuf>,
+    /// List the supported source -> destination conversions and their backends
+    #[arg(long)]
+    list_conversions: bool,
+    /// Empty the TUI recent-paths history file
+    #[arg(long)]
+    clear_history: bool,
+    /// Append a timestamped transcript of plans, commands, tool output and results
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
     /// Emit JSON output
     #[arg(long)]
     json: bool,
+    /// Stream batch progress as newline-delimited JSON: start, result and summary events
+    #[arg(
+        long,
+        requires = "batch",
+        conflicts_with_all = ["json", "tui", "plan", "dry_run", "verify_only"]
+    )]
+    json_lines: bool,
+    /// When to style console and TUI output (honors NO_COLOR in auto mode)
+    #[arg(long, value_enum, default_value_t = style::ColorChoice::Auto)]
+    color: style::ColorChoice,
     /// Enable interactive TUI
     #[arg(long)]
     tui: bool,
@@ -97,32 +345,130 @@ struct Cli {
     no_tui: bool,
 }
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
+    let color = cli.color.enabled(std::io::stderr().is_terminal());
+    match run(cli, color) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!(
+                "{} {:?}",
+                style::paint("Error:", Color::Red, color),
+                failure.error
+            );
+            failure.exit_code()
+        }
+    }
+}
+
+fn run(mut cli: Cli, color: bool) -> Result<(), Failure> {
     if cli.stream_copy && cli.transcode {
-        anyhow::bail!("--stream-copy and --transcode are mutually exclusive");
+        return Err(Failure::usage(anyhow!(
+            "--stream-copy and --transcode are mutually exclusive"
+        )));
     }
-    if cli.overwrite && cli.backup {
-        anyhow::bail!("--overwrite and --backup are mutually exclusive");
+    if cli.strip_metadata && cli.keep_metadata {
+        return Err(Failure::usage(anyhow!(
+            "--strip-metadata and --keep-metadata are mutually exclusive"
+        )));
     }
     if cli.tui && cli.json {
-        anyhow::bail!("--tui and --json are mutually exclusive");
+        return Err(Failure::usage(anyhow!(
+            "--tui and --json are mutually exclusive"
+        )));
+    }
+    if cli.jobs == 0 {
+        return Err(Failure::usage(anyhow!("--jobs must be at least 1")));
+    }
+    if cli.audit && !(cli.plan || cli.dry_run) {
+        return Err(Failure::usage(anyhow!(
+            "--audit requires --plan or --dry-run"
+        )));
+    }
+    if cli.probe && !(cli.plan || cli.dry_run) {
+        return Err(Failure::usage(anyhow!(
+            "--probe requires --plan or --dry-run"
+        )));
+    }
+    if cli.limit == Some(0) {
+        return Err(Failure::usage(anyhow!("--limit must be at least 1")));
     }
     if cli.tui && cli.no_tui {
-        anyhow::bail!("--tui and --no-tui are mutually exclusive");
+        return Err(Failure::usage(anyhow!(
+            "--tui and --no-tui are mutually exclusive"
+        )));
+    }
+    if let Some(path) = cli.detect.as_deref() {
+        return detect(path, cli.json, color);
+    }
+    if let Some(dir) = cli.clean_temps.as_deref() {
+        return clean_temps(&cli, dir);
+    }
+    if cli.list_conversions {
+        return list_conversions(cli.json);
+    }
+    // Read once; options, behavior and `[tui]` settings all come from it.
+    let config_file = config::read_config(cli.config.as_deref())?;
+    let tui_config = config_file
+        .as_ref()
+        .map(|parsed| parsed.tui().clone())
+        .unwrap_or_default();
+    let history = tui::HistorySettings::new(&tui_config);
+    if cli.clear_history {
+        let path = tui::clear_history(&history)?;
+        println!("Cleared history: {}", path.display());
+        return Ok(());
     }
     let mut options = plan::ConversionOptions::default();
-    if let Some(config_options) =
-        config::load_options(cli.config.as_deref(), cli.profile.as_deref())?
-    {
-        options = config_options;
+    // `[ext.*]` config blocks follow the batch target extension or the destination's.
+    let dest_ext = cli
+        .to_ext
+        .clone()
+        .or_else(|| cli.destination.as_deref().and_then(plan::normalize_ext));
+    if let Some(parsed) = config_file.as_ref() {
+        options = parsed.options(cli.profile.as_deref(), dest_ext.as_deref())?;
+    }
+    // Config defaults can only switch these flags on, so the flags win; conflicts are
+    // checked once both are merged.
+    let behavior = config_file
+        .as_ref()
+        .map(|parsed| parsed.behavior(cli.profile.as_deref(), dest_ext.as_deref()))
+        .transpose()?
+        .unwrap_or_default();
+    let configured = |flag: bool, value: Option<bool>| !flag && value == Some(true);
+    let configured_overwrite = configured(cli.overwrite, behavior.overwrite);
+    let configured_backup = configured(cli.backup, behavior.backup);
+    cli.move_source |= behavior.move_source == Some(true);
+    cli.overwrite |= configured_overwrite;
+    cli.backup |= configured_backup;
+    let origin = config_origin(configured_overwrite, configured_backup);
+    if cli.overwrite && cli.backup {
+        return Err(Failure::usage(anyhow!(
+            "--overwrite and --backup are mutually exclusive{origin}"
+        )));
+    }
+    if cli.overwrite_newer && (cli.overwrite || cli.backup) {
+        return Err(Failure::usage(anyhow!(
+            "--overwrite-newer cannot be combined with --overwrite or --backup{origin}"
+        )));
+    }
+    if cli.skip_existing && (cli.overwrite || cli.backup) {
+        return Err(Failure::usage(anyhow!(
+            "--skip-existing cannot be combined with --overwrite or --backup{origin}"
+        )));
     }
 
     if let Some(value) = cli.image_quality {
         options.image_quality = Some(value);
+        options.image_quality_by_ext.clear();
     }
     if let Some(value) = cli.video_bitrate.as_deref() {
         options.video_bitrate = Some(value.to_string());
+        options.crf = None;
+    }
+    if let Some(value) = cli.crf {
+        options.crf = Some(value);
+        options.video_bitrate = None;
     }
     if let Some(value) = cli.audio_bitrate.as_deref() {
         options.audio_bitrate = Some(value.to_string());
@@ -133,9 +479,133 @@ fn main() -> Result<()> {
     if let Some(value) = cli.video_codec.as_deref() {
         options.video_codec = Some(value.to_string());
     }
+    if let Some(value) = cli.hwaccel.as_ref() {
+        options.hwaccel = Some(value.clone());
+    }
     if let Some(value) = cli.audio_codec.as_deref() {
         options.audio_codec = Some(value.to_string());
     }
+    if cli.ogg_opus {
+        options.ogg_opus = true;
+    }
+    if cli.no_upscale_bitrate {
+        options.no_upscale_bitrate = true;
+    }
+    if let Some(value) = cli.sample_fmt.as_ref() {
+        options.sample_fmt = Some(value.clone());
+    }
+    if cli.dither {
+        options.dither = true;
+    }
+    if cli.two_pass {
+        options.two_pass = true;
+    }
+    if cli.mute {
+        options.mute = true;
+    }
+    if let Some(value) = cli.speed {
+        options.speed = Some(value);
+    }
+    if let Some(value) = cli.start.as_deref() {
+        options.start = Some(value.to_string());
+    }
+    // A length and an end time are alternatives, so either one replaces a configured other.
+    if let Some(value) = cli.duration.as_deref() {
+        options.duration = Some(value.to_string());
+        options.to = None;
+    }
+    if let Some(value) = cli.to.as_deref() {
+        options.to = Some(value.to_string());
+        options.duration = None;
+    }
+    if cli.normalize {
+        options.normalize = true;
+    }
+    if let Some(value) = cli.loudness {
+        options.loudness = Some(value);
+    }
+    if cli.grayscale {
+        options.grayscale = true;
+    }
+    if cli.srgb {
+        options.srgb = true;
+    }
+    if let Some(value) = cli.icc.as_ref() {
+        options.icc_profile = Some(value.clone());
+    }
+    if let Some(value) = cli.backup_pad {
+        options.backup_pad = Some(value);
+    }
+    if let Some(value) = cli.output_dir.as_ref() {
+        options.output_dir = Some(value.clone());
+    }
+    if let Some(value) = cli.fit.as_deref() {
+        options.fit = Some(value.to_string());
+    }
+    if let Some(value) = cli.resize.as_deref() {
+        options.resize = Some(value.to_string());
+    }
+    if let Some(value) = cli.density {
+        options.density = Some(value);
+    }
+    if cli.lossless {
+        options.lossless = true;
+    }
+    if let Some(value) = cli.effort {
+        options.effort = Some(value);
+    }
+    if let Some(value) = cli.frame {
+        options.frame = Some(value);
+    }
+    if cli.all_pages {
+        options.all_pages = true;
+    }
+    if cli.check_space {
+        options.check_space = true;
+    }
+    if let Some(value) = cli.timeout {
+        options.timeout = Some(value);
+    }
+    if cli.verify {
+        options.verify = true;
+    }
+    if cli.strict_detect {
+        options.strict_detect = true;
+    }
+    if cli.preserve {
+        options.preserve = true;
+    }
+    if cli.overwrite_newer {
+        options.overwrite_newer = true;
+    }
+    if let Some(value) = cli.max_width {
+        options.max_width = Some(value);
+    }
+    if let Some(value) = cli.max_height {
+        options.max_height = Some(value);
+    }
+    if let Some(value) = cli.rotate {
+        options.rotate = Some(value);
+    }
+    if cli.tonemap {
+        options.tonemap = true;
+    }
+    if cli.strip_metadata {
+        options.metadata = plan::Metadata::Strip;
+    } else if cli.keep_metadata {
+        options.metadata = plan::Metadata::Keep;
+    }
+    if cli.sanitize || cli.sanitize_lowercase || cli.sanitize_spaces {
+        let rules = options.sanitize.get_or_insert_default();
+        rules.lowercase |= cli.sanitize_lowercase;
+        rules.underscore_spaces |= cli.sanitize_spaces;
+    }
+    if cli.prefer_gs {
+        options.prefer_gs = true;
+    }
+    if let Some(value) = cli.ffmpeg_loglevel.as_ref() {
+        options.ffmpeg_loglevel = Some(value.clone());
+    }
     options.ffmpeg_preference = if cli.stream_copy {
         plan::FfmpegPreference::StreamCopy
     } else if cli.transcode {
@@ -144,9 +614,19 @@ fn main() -> Result<()> {
         options.ffmpeg_preference
     };
 
+    let log = cli
+        .log_file
+        .as_deref()
+        .map(runlog::RunLog::open)
+        .transpose()
+        .map_err(Failure::usage)?;
+
+    cancel::install_handler()?;
+
     let use_tui = if cli.tui {
         true
-    } else if cli.no_tui || cli.json || cli.plan || cli.dry_run {
+    } else if cli.no_tui || cli.json || cli.json_lines || cli.plan || cli.dry_run || cli.verify_only
+    {
         false
     } else {
         std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
@@ -158,225 +638,945 @@ fn main() -> Result<()> {
             destination: cli.destination.clone(),
             batch: cli.batch,
             dest_dir: cli.dest_dir.clone(),
-            inputs: cli.input.clone(),
+            output_dir: options.output_dir.clone(),
+            inputs: listed_inputs(&cli)?,
             recursive: cli.recursive,
             to_ext: cli.to_ext.clone(),
+            name_template: cli.name_template.clone(),
             move_source: cli.move_source,
             overwrite: cli.overwrite,
+            overwrite_newer: options.overwrite_newer,
             backup: cli.backup,
             image_quality: options.image_quality,
             video_bitrate: options.video_bitrate.clone(),
+            crf: options.crf,
             audio_bitrate: options.audio_bitrate.clone(),
             preset: options.preset.clone(),
             video_codec: options.video_codec.clone(),
+            hwaccel: options.hwaccel.clone(),
             audio_codec: options.audio_codec.clone(),
+            ogg_opus: options.ogg_opus,
+            no_upscale_bitrate: options.no_upscale_bitrate,
+            sample_fmt: options.sample_fmt.clone(),
+            dither: options.dither,
             ffmpeg_preference: options.ffmpeg_preference,
+            ffmpeg_loglevel: options.ffmpeg_loglevel.clone(),
+            two_pass: options.two_pass,
+            mute: options.mute,
+            speed: options.speed,
+            start: options.start.clone(),
+            duration: options.duration.clone(),
+            to: options.to.clone(),
+            normalize: options.normalize,
+            loudness: options.loudness,
+            grayscale: options.grayscale,
+            srgb: options.srgb,
+            icc_profile: options.icc_profile.clone(),
+            backup_pad: options.backup_pad,
+            fit: options.fit.clone(),
+            resize: options.resize.clone(),
+            density: options.density,
+            lossless: options.lossless,
+            effort: options.effort,
+            frame: options.frame,
+            all_pages: options.all_pages,
+            jobs: cli.jobs,
+            check_space: options.check_space,
+            timeout: options.timeout,
+            verify: options.verify,
+            strict_detect: options.strict_detect,
+            preserve: options.preserve,
+            prefer_gs: options.prefer_gs,
+            max_width: options.max_width,
+            max_height: options.max_height,
+            rotate: options.rotate,
+            tonemap: options.tonemap,
+            metadata: options.metadata,
+            sanitize: options.sanitize,
             config_path: cli.config.clone(),
             profile: cli.profile.clone(),
             plan_only: cli.plan || cli.dry_run,
+            theme: tui_theme(cli.color, &tui_config)?,
+            history,
+            log,
         };
-        return tui::run_interactive(defaults);
+        return Ok(tui::run_interactive(defaults)?);
     }
 
     if cli.batch {
-        run_batch(&cli, options)?;
-        return Ok(());
+        return run_batch(&cli, options, &tui_config, color, log);
     }
 
-    let source = cli.source.context("source is required")?;
-    let destination = cli.destination.context("destination is required")?;
+    let source = cli
+        .source
+        .context("source is required")
+        .map_err(Failure::usage)?;
+    let destination = cli
+        .destination
+        .context("destination is required")
+        .map_err(Failure::usage)?;
     let plan = plan::build_plan(&source, &destination, cli.move_source, cli.backup, options)
-        .context("failed to build plan")?;
+        .context("failed to build plan")
+        .map_err(Failure::usage)?;
+    if cli.skip_existing && already_converted(&plan.destination) {
+        if cli.json {
+            let mut output = output::SuccessJson::new(&plan.source, &plan.destination);
+            output.status = "skipped";
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            eprintln!(
+                "{} {} already exists",
+                style::paint("Skip:", Color::Yellow, color),
+                plan.destination.display()
+            );
+        }
+        return Ok(());
+    }
+    // Whatever is left at the destination is an empty leftover that may be replaced.
+    let overwrite = cli.overwrite || cli.skip_existing;
 
     if cli.plan || cli.dry_run {
         if cli.json {
-            println!("{}", plan::render_plan_json(&plan, cli.overwrite)?);
+            println!("{}", plan::render_plan_json(&plan, overwrite, cli.probe)?);
         } else {
-            println!("{}", plan::render_plan(&plan, cli.overwrite));
+            println!("{}", plan::render_plan(&plan, overwrite));
         }
         return Ok(());
     }
+    if let Some(parent) = plan.destination.parent() {
+        warn_leftover_temps(parent, color);
+    }
 
     if cli.tui {
-        match tui::run_single_tui(&plan, cli.overwrite)? {
+        match tui::run_single_tui(&plan, overwrite, tui_theme(cli.color, &tui_config)?, log)? {
             tui::RunOutcome::Exit | tui::RunOutcome::Back => {}
         }
         return Ok(());
     }
 
-    execute::execute_plan(&plan, cli.overwrite, cli.json).context("execution failed")?;
+    let reporter = execute::ProgressReporter::console(cli.json, color).with_log(log);
+    let outcome = execute::execute_plan_with_reporter(&plan, overwrite, &reporter)
+        .context("execution failed")
+        .map_err(Failure::conversion)?;
+    let report = match outcome {
+        execute::Outcome::Done(report) => Some(report),
+        execute::Outcome::SkippedNotNewer => None,
+    };
     if cli.json {
-        let output = serde_json::json!({
-            "status": "ok",
-            "source": plan.source.display().to_string(),
-            "destination": plan.destination.display().to_string()
-        });
+        let output = match &report {
+            Some(report) => {
+                output::SuccessJson::from_report(&plan.source, &plan.destination, report)
+            }
+            None => {
+                let mut output = output::SuccessJson::new(&plan.source, &plan.destination);
+                output.status = "skipped";
+                output
+            }
+        };
         println!("{}", serde_json::to_string_pretty(&output)?);
+    } else if let Some(report) = &report {
+        if let Some(sizes) = report.sizes {
+            print_done(&plan.destination, sizes, color);
+        }
+    } else {
+        eprintln!(
+            "{} {} is not older than {}",
+            style::paint("Skip:", Color::Yellow, color),
+            plan.destination.display(),
+            plan.source.display()
+        );
     }
     Ok(())
 }
 
-fn run_batch(cli: &Cli, options: plan::ConversionOptions) -> Result<()> {
+fn run_batch(
+    cli: &Cli,
+    options: plan::ConversionOptions,
+    tui_config: &config::TuiConfig,
+    color: bool,
+    log: Option<runlog::RunLog>,
+) -> Result<(), Failure> {
     let dest_dir = cli
         .dest_dir
         .as_ref()
-        .context("batch mode requires --dest-dir")?;
+        .context("batch mode requires --dest-dir")
+        .map_err(Failure::usage)?;
 
     let mut inputs = Vec::new();
     if let Some(source) = cli.source.as_ref() {
         inputs.push(source.to_string_lossy().to_string());
     }
-    inputs.extend(cli.input.iter().cloned());
+    inputs.extend(listed_inputs(cli)?);
 
-    let stdin_sources = if cli.stdin {
-        read_stdin_lines()?
-    } else {
-        Vec::new()
+    let stdin_sources = cli.stdin.then(stdin_lines).into_iter().flatten();
+    let excludes = batch::Excludes::new(&cli.exclude).map_err(Failure::usage)?;
+    let collected = batch::collect_sources(&inputs, stdin_sources, cli.recursive, &excludes)?;
+    if cli.warn_duplicates {
+        for duplicate in &collected.duplicates {
+            eprintln!(
+                "{} input {} resolved to already-seen path {}",
+                style::paint("Warning:", Color::Yellow, color),
+                duplicate.input,
+                duplicate.path.display()
+            );
+        }
+    }
+    let filter = batch::SourceFilter::new(cli.only, &cli.ext);
+    let selected = collected.sources.len();
+    let mut sources: Vec<batch::BatchJob> = collected
+        .sources
+        .into_iter()
+        .filter(|source| filter.matches(source))
+        .map(batch::BatchJob::new)
+        .collect();
+    let skipped_filtered = selected - sources.len();
+    if let Some(path) = cli.manifest.as_deref() {
+        sources.extend(config::load_manifest(path, &options).map_err(Failure::usage)?);
+    }
+    // A batch whose every source was filtered out reports them as skipped.
+    if sources.is_empty() && skipped_filtered == 0 {
+        return Err(Failure::usage(anyhow!("no inputs provided for batch mode")));
+    }
+    let state = cli
+        .state
+        .as_deref()
+        .map(batch::StateFile::open)
+        .transpose()?
+        .map(Arc::new);
+    let mut report = BatchReport {
+        skip_existing: cli.skip_existing,
+        json_lines: cli.json_lines,
+        skipped_filtered,
+        ..BatchReport::default()
     };
-
-    let sources = batch::collect_sources(&inputs, stdin_sources, cli.recursive)?;
-    if sources.is_empty() {
-        anyhow::bail!("no inputs provided for batch mode");
+    // `--json-lines` events; nothing is printed without it.
+    let emit = |event: output::BatchEventJson| -> Result<(), Failure> {
+        if cli.json_lines {
+            println!("{}", serde_json::to_string(&event)?);
+        }
+        Ok(())
+    };
+    // Sources that fail before conversion starts still get their `result` event.
+    let failed_event = |source: &Path, destination: Option<&Path>, err: &anyhow::Error| {
+        output::BatchEventJson::Result(output::ResultJson::failed(
+            source,
+            destination,
+            err.to_string(),
+        ))
+    };
+    if let Some(state) = state.as_deref() {
+        let before = sources.len();
+        sources.retain(|job| !state.is_completed(&job.source));
+        report.skipped = before - sources.len();
+    }
+    if cli.skip_duplicates {
+        let mut index = batch::ContentIndex::default();
+        let mut unique = Vec::new();
+        for job in sources {
+            match index.find_duplicate(&job.source) {
+                Ok(Some(original)) => {
+                    eprintln!(
+                        "{} {} has the same content as {}",
+                        style::paint("Skip:", Color::Yellow, color),
+                        job.source.display(),
+                        original.display()
+                    );
+                    report.skipped_duplicates += 1;
+                }
+                Ok(None) => unique.push(job),
+                Err(err) => {
+                    emit(failed_event(&job.source, None, &err))?;
+                    report.record_failure(None, job.source, err);
+                }
+            }
+        }
+        sources = unique;
+    }
+    if let Some(limit) = cli.limit
+        && sources.len() > limit
+    {
+        report.limited = Some((limit, sources.len()));
+        sources.truncate(limit);
     }
 
+    let name_template = cli
+        .name_template
+        .as_deref()
+        .map(batch::NameTemplate::parse)
+        .transpose()
+        .map_err(Failure::usage)?;
     let batch_input = batch::BatchInput {
         dest_dir: dest_dir.clone(),
         to_ext: cli.to_ext.clone(),
+        name_template,
     };
 
-    let mut ok = 0usize;
-    let mut failed = Vec::new();
+    // With --skip-existing, a destination still present when its plan runs is an empty
+    // leftover of an interrupted run, which is replaced.
+    let overwrite = cli.overwrite || cli.skip_existing;
     let mut plans = Vec::new();
 
-    for source in sources {
-        let destination = match batch::dest_for_source(&batch_input, &source) {
+    // Destinations are settled before anything runs, so two sources never race for one
+    // output and no output replaces a source still waiting its turn.
+    let mut destinations = batch::resolve_destinations(&batch_input, &sources);
+    if cli.dedupe_names {
+        batch::dedupe_destinations(&sources, &mut destinations);
+    }
+    let collisions = batch::find_collisions(&sources, &destinations);
+    // `--audit` exists to report these, so it lists them instead.
+    if !collisions.is_empty() && !cli.audit {
+        let lines: Vec<String> = collisions
+            .iter()
+            .map(|collision| format!("  {collision}"))
+            .collect();
+        return Err(Failure::usage(anyhow!(
+            "batch destinations collide; rename the inputs, use --name-template or pass --dedupe-names:\n{}",
+            lines.join("\n")
+        )));
+    }
+
+    for (job, destination) in sources.into_iter().zip(destinations) {
+        let source = job.source;
+        let destination = match destination {
             Ok(dest) => dest,
             Err(err) => {
-                failed.push((source, err));
+                emit(failed_event(&source, None, &err))?;
+                report.record_failure(None, source, err);
                 continue;
             }
         };
+        // Checked before planning, so finished sources are not probed again.
+        if cli.skip_existing && already_converted(&destination) {
+            report.skipped_existing += 1;
+            continue;
+        }
         let plan = match plan::build_plan(
             &source,
             &destination,
             cli.move_source,
             cli.backup,
-            options.clone(),
+            job.options.unwrap_or_else(|| options.clone()),
         ) {
             Ok(plan) => plan,
             Err(err) => {
-                failed.push((source, err));
+                emit(failed_event(&source, Some(&destination), &err))?;
+                report.record_failure(None, source, err);
                 continue;
             }
         };
         if cli.plan || cli.dry_run {
-            if cli.json {
-                println!("{}", plan::render_plan_json(&plan, cli.overwrite)?);
-            } else {
+            if !cli.json {
                 println!("---");
-                println!("{}", plan::render_plan(&plan, cli.overwrite));
+                println!("{}", plan::render_plan(&plan, overwrite));
             }
-            ok += 1;
-        } else {
-            plans.push(plan);
+            report.record_ok(&plan);
         }
+        plans.push(plan);
     }
 
+    if cli.verify_only {
+        return verify_outputs(&plans, report, cli.json, color);
+    }
     if cli.plan || cli.dry_run {
-        let total = ok + failed.len();
         if cli.json {
-            let output = serde_json::json!({
-                "status": if failed.is_empty() { "ok" } else { "failed" },
-                "total": total,
-                "succeeded": ok,
-                "failed": failed.len(),
-                "failures": failed.iter().map(|(source, err)| {
-                    serde_json::json!({
-                        "source": source.display().to_string(),
-                        "error": err.to_string()
-                    })
-                }).collect::<Vec<_>>()
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
-        } else {
-            println!(
-                "Batch summary: total {total}, succeeded {ok}, failed {}",
-                failed.len()
-            );
-        }
-        if !failed.is_empty() {
-            if !cli.json {
-                for (source, err) in failed {
-                    println!("Fail: {} -> {}", source.display(), err);
-                }
+            if cli.audit {
+                let audit = plan::render_plan_audit_json(
+                    &plans,
+                    overwrite,
+                    cli.probe,
+                    report.failures_json(),
+                )?;
+                println!("{audit}");
+            } else {
+                println!("{}", plan::render_plans_json(&plans, overwrite, cli.probe)?);
             }
-            anyhow::bail!("batch completed with failures");
+            return report.finish_plan_json(color);
         }
-        return Ok(());
+        if cli.audit {
+            println!("---");
+            println!("{}", plan::render_plan_audit(&plans));
+        }
+        return report.finish(false, color);
     }
+    ensure_dest_dir(dest_dir, cli.create_dirs)?;
+    warn_leftover_temps(dest_dir, color);
 
     if cli.tui {
-        if !failed.is_empty() {
-            for (source, err) in failed {
-                eprintln!("Fail: {} -> {}", source.display(), err);
+        if !report.failed.is_empty() {
+            for (source, err) in report.failed {
+                eprintln!(
+                    "{} {} -> {}",
+                    style::paint("Fail:", Color::Red, color),
+                    source.display(),
+                    err
+                );
             }
-            anyhow::bail!("batch preparation failed");
+            return Err(Failure::usage(anyhow!("batch preparation failed")));
         }
-        match tui::run_batch_tui(plans, cli.overwrite)? {
+        let theme = tui_theme(cli.color, tui_config)?;
+        match tui::run_batch_tui(plans, overwrite, theme, cli.jobs, state, log)? {
             tui::RunOutcome::Exit | tui::RunOutcome::Back => {}
         }
         return Ok(());
     }
 
+    let quiet = cli.json || cli.json_lines;
+    let reporter = execute::ProgressReporter::console(quiet, color).with_log(log);
     for plan in plans {
-        let source = plan.source.clone();
-        match execute::execute_plan(&plan, cli.overwrite, cli.json) {
-            Ok(_) => ok += 1,
-            Err(err) => failed.push((source, err)),
+        if cancel::requested() {
+            break;
+        }
+        let skipped = || {
+            output::BatchEventJson::Result(output::ResultJson::skipped(
+                &plan.source,
+                &plan.destination,
+            ))
+        };
+        // An earlier source in this run may have written the same destination.
+        if cli.skip_existing && already_converted(&plan.destination) {
+            report.skipped_existing += 1;
+            emit(skipped())?;
+            continue;
+        }
+        emit(output::BatchEventJson::Start(output::StartJson::new(
+            &plan.source,
+            &plan.destination,
+        )))?;
+        match execute::execute_plan_with_reporter(&plan, overwrite, &reporter) {
+            Ok(execute::Outcome::Done(done)) => {
+                if let Some(state) = state.as_deref() {
+                    state.record(&plan.source)?;
+                }
+                report.record_ok(&plan);
+                if let Some(sizes) = done.sizes {
+                    report.record_sizes(sizes);
+                    if !quiet {
+                        print_done(&plan.destination, sizes, color);
+                    }
+                }
+                emit(output::BatchEventJson::Result(output::ResultJson {
+                    result: output::SuccessJson::from_report(
+                        &plan.source,
+                        &plan.destination,
+                        &done,
+                    ),
+                    error: None,
+                }))?;
+            }
+            Ok(execute::Outcome::SkippedNotNewer) => {
+                if let Some(state) = state.as_deref() {
+                    state.record(&plan.source)?;
+                }
+                report.skipped_not_newer += 1;
+                emit(skipped())?;
+            }
+            Err(err) if !cli.strict && err.is::<execute::DestinationExists>() => {
+                report.skipped_existing += 1;
+                emit(skipped())?;
+            }
+            Err(err) => {
+                emit(failed_event(&plan.source, Some(&plan.destination), &err))?;
+                report.record_failure(Some(&plan), plan.source.clone(), err);
+            }
         }
     }
 
-    let total = ok + failed.len();
+    if cancel::requested() {
+        // The summary still covers what finished; the exit code reports the interruption.
+        let _ = report.finish(cli.json, color);
+        return Err(anyhow::Error::new(cancel::Interrupted).into());
+    }
+    report.finish(cli.json, color)
+}
+
+/// The TUI's colors, built only when a TUI runs so a bad `[tui]` color cannot fail a
+/// plain conversion. The TUI only runs on a terminal, so a redirected stderr should not
+/// strip its colors.
+fn tui_theme(
+    color: style::ColorChoice,
+    tui_config: &config::TuiConfig,
+) -> Result<tui::Theme, Failure> {
+    // Validated even when color is off, so a bad `[tui]` table fails the same way
+    // whatever `--color` says.
+    let theme = tui::Theme::new(tui_config)?;
+    Ok(if color.enabled(true) {
+        theme
+    } else {
+        tui::Theme::monochrome()
+    })
+}
+
+/// Names the config keys behind a flag conflict, e.g. ` (backup = true in the config)`.
+fn config_origin(overwrite: bool, backup: bool) -> String {
+    let keys: Vec<&str> = [(overwrite, "overwrite"), (backup, "backup")]
+        .into_iter()
+        .filter_map(|(configured, key)| configured.then_some(key))
+        .collect();
+    if keys.is_empty() {
+        String::new()
+    } else {
+        format!(" ({} = true in the config)", keys.join(" and "))
+    }
+}
+
+/// `--skip-existing`: a non-empty destination counts as converted. An empty one is most
+/// likely left over from an interrupted run and is converted again.
+fn already_converted(destination: &Path) -> bool {
+    std::fs::metadata(destination).is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+}
+
+/// `Done: out.jpg (output 3 MB, 41% of source)` on stderr, next to `Skip:` and `Warning:`.
+fn print_done(destination: &Path, sizes: execute::SizeChange, color: bool) {
+    eprintln!(
+        "{} {} ({})",
+        style::paint("Done:", Color::Green, color),
+        destination.display(),
+        sizes.describe()
+    );
+}
+
+/// `--verify-only`: counts each healthy output as a success and each missing, empty or
+/// broken one as a failure, so the usual batch summary doubles as the health report.
+fn verify_outputs(
+    plans: &[plan::Plan],
+    mut report: BatchReport,
+    json: bool,
+    color: bool,
+) -> Result<(), Failure> {
+    let mut unverified: BTreeMap<&'static str, usize> = BTreeMap::new();
+    for plan in plans {
+        let health = verify::check_output(&plan.destination, plan.dest_kind);
+        match health.problem() {
+            Some(problem) => report.record_failure(
+                Some(plan),
+                plan.source.clone(),
+                anyhow!("{problem}: {}", plan.destination.display()),
+            ),
+            None => {
+                if let verify::Health::Unverified(reason) = health {
+                    *unverified.entry(reason).or_default() += 1;
+                }
+                report.record_ok(plan);
+            }
+        }
+    }
+    if !json {
+        for (reason, count) in unverified {
+            println!("Present but not probed ({reason}): {count}");
+        }
+    }
+    report.finish(json, color)
+}
+
+#[derive(Default)]
+struct BatchReport {
+    ok: usize,
+    skipped: usize,
+    skipped_duplicates: usize,
+    skipped_not_newer: usize,
+    skipped_existing: usize,
+    /// Sources left out by `--only`/`--ext`.
+    skipped_filtered: usize,
+    /// Existing destinations were skipped on purpose with --skip-existing.
+    skip_existing: bool,
+    /// The summary is the last `--json-lines` event rather than a pretty object.
+    json_lines: bool,
+    /// `(kept, available)` when `--limit` cut the sources down.
+    limited: Option<(usize, usize)>,
+    /// Source and output bytes of the conversions that succeeded.
+    bytes_in: u64,
+    bytes_out: u64,
+    failed: Vec<(PathBuf, anyhow::Error)>,
+    groups: batch::Groups,
+}
+
+impl BatchReport {
+    fn record_ok(&mut self, plan: &plan::Plan) {
+        self.ok += 1;
+        self.groups.record(Some(plan), true);
+    }
+
+    fn record_sizes(&mut self, sizes: execute::SizeChange) {
+        self.bytes_in += sizes.source;
+        self.bytes_out += sizes.output;
+    }
+
+    fn record_failure(&mut self, plan: Option<&plan::Plan>, source: PathBuf, err: anyhow::Error) {
+        self.failed.push((source, err));
+        self.groups.record(plan, false);
+    }
+
+    /// Fails with a partial-batch code when anything succeeded, otherwise with the code
+    /// a single conversion would have produced.
+    fn finish(self, json: bool, color: bool) -> Result<(), Failure> {
+        let total = self.ok + self.failed.len();
+        let skipped = self.skipped
+            + self.skipped_duplicates
+            + self.skipped_not_newer
+            + self.skipped_existing
+            + self.skipped_filtered;
+        if json || self.json_lines {
+            let output = output::BatchSummaryJson {
+                schema_version: output::SCHEMA_VERSION,
+                status: if self.failed.is_empty() {
+                    "ok"
+                } else {
+                    "failed"
+                },
+                total,
+                succeeded: self.ok,
+                failed: self.failed.len(),
+                skipped,
+                skipped_duplicates: self.skipped_duplicates,
+                skipped_existing: self.skipped_existing,
+                skipped_filtered: self.skipped_filtered,
+                limited_from: self.limited.map(|(_, available)| available),
+                bytes_in: self.bytes_in,
+                bytes_out: self.bytes_out,
+                failures: self.failures_json(),
+                by_backend: groups_json(&self.groups.by_backend),
+                by_kind: groups_json(&self.groups.by_kind),
+            };
+            if self.json_lines {
+                let event = output::BatchEventJson::Summary(output);
+                println!("{}", serde_json::to_string(&event)?);
+            } else {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+        } else {
+            let skipped = if skipped > 0 {
+                format!(", skipped {skipped}")
+            } else {
+                String::new()
+            };
+            println!(
+                "Batch summary: total {total}, succeeded {}, failed {}{skipped}",
+                self.ok,
+                self.failed.len()
+            );
+            if let Some((kept, available)) = self.limited {
+                println!("Processing {kept} of {available} inputs (limited)");
+            }
+            if self.bytes_in > 0 {
+                let sizes = execute::SizeChange {
+                    source: self.bytes_in,
+                    output: self.bytes_out,
+                };
+                println!(
+                    "Size: {} in, {} ({})",
+                    execute::format_size(self.bytes_in),
+                    sizes.describe(),
+                    sizes.savings()
+                );
+            }
+            if self.skipped > 0 {
+                println!("Skipped (already in state file): {}", self.skipped);
+            }
+            if self.skipped_duplicates > 0 {
+                println!("Skipped (duplicate content): {}", self.skipped_duplicates);
+            }
+            if self.skipped_filtered > 0 {
+                println!(
+                    "Skipped (not selected by --only/--ext): {}",
+                    self.skipped_filtered
+                );
+            }
+            if self.skipped_not_newer > 0 {
+                println!(
+                    "Skipped (destination not older): {}",
+                    self.skipped_not_newer
+                );
+            }
+            if self.skipped_existing > 0 && self.skip_existing {
+                println!("Skipped (destination exists): {}", self.skipped_existing);
+            } else if self.skipped_existing > 0 {
+                println!(
+                    "Skipped (destination exists; pass --overwrite or --backup): {}",
+                    self.skipped_existing
+                );
+            }
+            if !self.groups.is_empty() {
+                println!(
+                    "By backend: {}",
+                    batch::groups_text(&self.groups.by_backend)
+                );
+                println!("By kind: {}", batch::groups_text(&self.groups.by_kind));
+            }
+        }
+        if !json && !self.json_lines {
+            for (source, err) in &self.failed {
+                println!(
+                    "{} {} -> {}",
+                    style::paint("Fail:", Color::Red, color),
+                    source.display(),
+                    err
+                );
+            }
+        }
+        self.outcome()
+    }
+
+    fn failures_json(&self) -> Vec<output::FailureJson> {
+        self.failed
+            .iter()
+            .map(|(source, err)| output::FailureJson {
+                source: source.display().to_string(),
+                error: err.to_string(),
+            })
+            .collect()
+    }
+
+    /// `--plan --json` keeps stdout a single plan array, so failures go to stderr and
+    /// no summary object is printed.
+    fn finish_plan_json(self, color: bool) -> Result<(), Failure> {
+        for (source, err) in &self.failed {
+            eprintln!(
+                "{} {} -> {}",
+                style::paint("Fail:", Color::Red, color),
+                source.display(),
+                err
+            );
+        }
+        self.outcome()
+    }
+
+    fn outcome(self) -> Result<(), Failure> {
+        if !self.failed.is_empty() {
+            let error = anyhow!("batch completed with failures");
+            if self.ok == 0 {
+                let kind = if self
+                    .failed
+                    .iter()
+                    .all(|(_, err)| exit::is_missing_tool(err))
+                {
+                    exit::FailureKind::MissingTool
+                } else {
+                    exit::FailureKind::Conversion
+                };
+                return Err(Failure::new(kind, error));
+            }
+            return Err(Failure::new(exit::FailureKind::PartialBatch, error));
+        }
+        Ok(())
+    }
+}
+
+fn groups_json(
+    groups: &BTreeMap<&'static str, batch::GroupCounts>,
+) -> BTreeMap<String, output::GroupJson> {
+    groups
+        .iter()
+        .map(|(name, counts)| {
+            (
+                name.to_string(),
+                output::GroupJson {
+                    ok: counts.ok,
+                    failed: counts.failed,
+                },
+            )
+        })
+        .collect()
+}
+
+fn clean_temps(cli: &Cli, dir: &std::path::Path) -> Result<(), Failure> {
+    let leftovers = temps::find_leftovers(dir, cli.recursive).map_err(Failure::usage)?;
+    let dry_run = cli.plan || cli.dry_run;
+    if !dry_run {
+        for path in &leftovers {
+            temps::remove_leftover(path)?;
+        }
+    }
     if cli.json {
-        let output = serde_json::json!({
-            "status": if failed.is_empty() { "ok" } else { "failed" },
-            "total": total,
-            "succeeded": ok,
-            "failed": failed.len(),
-            "failures": failed.iter().map(|(source, err)| {
-                serde_json::json!({
-                    "source": source.display().to_string(),
-                    "error": err.to_string()
-                })
-            }).collect::<Vec<_>>()
-        });
+        let output = output::CleanTempsJson {
+            schema_version: output::SCHEMA_VERSION,
+            status: "ok",
+            dry_run,
+            removed: leftovers
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect(),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for path in &leftovers {
+        println!("{verb}: {}", path.display());
+    }
+    println!(
+        "{} {} leftover temp entr{}",
+        if dry_run { "Found" } else { "Cleaned" },
+        leftovers.len(),
+        if leftovers.len() == 1 { "y" } else { "ies" }
+    );
+    Ok(())
+}
+
+/// `--detect`: the detection a plan starts from, plus a warning for a misnamed file.
+fn detect(path: &Path, json: bool, color: bool) -> Result<(), Failure> {
+    if !path.is_file() {
+        return Err(Failure::usage(anyhow!("not a file: {}", path.display())));
+    }
+    let detected = detect::detect_path(path);
+    let mismatch = detected.extension_mismatch();
+    if json {
+        let output = output::DetectJson {
+            schema_version: output::SCHEMA_VERSION,
+            path: path.display().to_string(),
+            mime: detected.mime.clone(),
+            file_mime: detected.file_mime.clone(),
+            extension: detected.ext_hint.clone(),
+            content_extension: detected.content_ext().map(str::to_string),
+            extension_mismatch: mismatch.is_some(),
+        };
         println!("{}", serde_json::to_string_pretty(&output)?);
     } else {
+        println!("Path: {}", path.display());
         println!(
-            "Batch summary: total {total}, succeeded {ok}, failed {}",
-            failed.len()
+            "Detected: {}",
+            detected.mime.as_deref().unwrap_or("unknown")
         );
-    }
-    if !failed.is_empty() {
-        if !cli.json {
-            for (source, err) in failed {
-                println!("Fail: {} -> {}", source.display(), err);
-            }
+        if let Some(mime) = detected.file_mime.as_deref() {
+            println!("Detected (file): {mime}");
         }
-        anyhow::bail!("batch completed with failures");
+        if let Some(ext) = detected.ext_hint.as_deref() {
+            println!("Detected extension: {ext}");
+        }
+    }
+    if let (Some(content), Some(named)) = (mismatch, detected.ext_hint.as_deref()) {
+        eprintln!(
+            "{} content looks like .{content} but the file is named .{named}; it may be misnamed",
+            style::paint("Warning:", Color::Yellow, color)
+        );
+    }
+    Ok(())
+}
+
+fn list_conversions(json: bool) -> Result<(), Failure> {
+    if json {
+        let output = output::ConversionsJson {
+            schema_version: output::SCHEMA_VERSION,
+            conversions: plan::ROUTES
+                .iter()
+                .map(|route| output::ConversionJson {
+                    source: route.source.label(),
+                    destination: route.destination.label(),
+                    backend: route.backend.label(),
+                    reason: route.reason,
+                })
+                .collect(),
+            categories: plan::Category::ALL
+                .iter()
+                .map(|category| (category.label(), category.extensions()))
+                .collect(),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+    println!("Supported conversions:");
+    for route in plan::ROUTES {
+        println!(
+            "  {:<20} {:<12} {}",
+            format!("{} -> {}", route.source.label(), route.destination.label()),
+            route.backend.label(),
+            route.reason
+        );
+    }
+    println!("Categories:");
+    for category in plan::Category::ALL {
+        println!(
+            "  {:<9} {}",
+            format!("{}:", category.label()),
+            category.extensions().join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// A missing `--dest-dir` is more often a typo than a request for a new folder, so a
+/// batch only creates it with `--create-dirs` or after a yes at a terminal prompt.
+fn ensure_dest_dir(dest_dir: &std::path::Path, create: bool) -> Result<(), Failure> {
+    if dest_dir.as_os_str().is_empty() || dest_dir.is_dir() {
+        return Ok(());
+    }
+    if dest_dir.exists() {
+        return Err(Failure::usage(anyhow!(
+            "--dest-dir {} is not a directory",
+            dest_dir.display()
+        )));
+    }
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    let create = create
+        || (interactive
+            && confirm(&format!(
+                "--dest-dir {} does not exist. Create it?",
+                dest_dir.display()
+            ))?);
+    if !create {
+        return Err(Failure::usage(anyhow!(
+            "--dest-dir {} does not exist; pass --create-dirs to create it",
+            dest_dir.display()
+        )));
     }
+    std::fs::create_dir_all(dest_dir)
+        .with_context(|| format!("failed to create {}", dest_dir.display()))?;
     Ok(())
 }
 
-fn read_stdin_lines() -> Result<Vec<String>> {
-    use std::io::Read;
-    let mut input = String::new();
+/// Asks a yes/no question on stderr; anything but `y` or `yes` is a no.
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::Write;
+    eprint!("{question} [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Leftovers mean an earlier run crashed; point at the cleanup command without
+/// touching them, since another mvx may still be writing there.
+fn warn_leftover_temps(dir: &std::path::Path, color: bool) {
+    let dir = if dir.as_os_str().is_empty() {
+        std::path::Path::new(".")
+    } else {
+        dir
+    };
+    let Ok(leftovers) = temps::find_leftovers(dir, false) else {
+        return;
+    };
+    if !leftovers.is_empty() {
+        eprintln!(
+            "{} {} leftover temp entr{} in {} (remove with `mvx --clean-temps {}`)",
+            style::paint("Warning:", Color::Yellow, color),
+            leftovers.len(),
+            if leftovers.len() == 1 { "y" } else { "ies" },
+            dir.display(),
+            dir.display()
+        );
+    }
+}
+
+/// `--input` values followed by the entries of every `--input-file`.
+fn listed_inputs(cli: &Cli) -> Result<Vec<String>, Failure> {
+    let mut inputs = cli.input.clone();
+    for list in &cli.input_file {
+        inputs.extend(batch::read_input_list(list).map_err(Failure::usage)?);
+    }
+    Ok(inputs)
+}
+
+/// Trimmed, non-blank stdin lines, read as they arrive rather than all up front.
+fn stdin_lines() -> impl Iterator<Item = Result<String>> {
+    use std::io::BufRead;
     std::io::stdin()
-        .read_to_string(&mut input)
-        .context("read stdin")?;
-    Ok(input
+        .lock()
         .lines()
-        .map(|line| line.trim().to_string())
-        .filter(|line| !line.is_empty())
-        .collect())
+        .map(|line| {
+            line.context("read stdin")
+                .map(|line| line.trim().to_string())
+        })
+        .filter(|line| !matches!(line, Ok(line) if line.is_empty()))
 }
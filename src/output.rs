@@ -0,0 +1,291 @@
+use crate::execute::ExecutionReport;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Version of the `--json` output shapes. Bump it when a field is renamed, removed or
+/// changes type; new fields keep the current version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A single conversion that finished.
+#[derive(Debug, Serialize)]
+pub struct SuccessJson {
+    pub schema_version: u32,
+    pub status: &'static str,
+    pub source: String,
+    /// `null` for a batch source that failed before its destination was named.
+    pub destination: Option<String>,
+    /// Source and output sizes in bytes; `null` when skipped or unreadable.
+    pub source_bytes: Option<u64>,
+    pub output_bytes: Option<u64>,
+    /// `output_bytes / source_bytes`.
+    pub size_ratio: Option<f64>,
+    /// What ran; `null` when skipped. `ffmpeg_mode` is `stream-copy` or `transcode`
+    /// for ffmpeg conversions.
+    pub strategy: Option<&'static str>,
+    pub ffmpeg_mode: Option<&'static str>,
+    pub backup_path: Option<String>,
+    pub elapsed_seconds: Option<f64>,
+}
+
+impl SuccessJson {
+    pub fn new(source: &Path, destination: &Path) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            status: "ok",
+            source: source.display().to_string(),
+            destination: Some(destination.display().to_string()),
+            source_bytes: None,
+            output_bytes: None,
+            size_ratio: None,
+            strategy: None,
+            ffmpeg_mode: None,
+            backup_path: None,
+            elapsed_seconds: None,
+        }
+    }
+
+    /// A finished conversion described by its `ExecutionReport`.
+    pub fn from_report(source: &Path, destination: &Path, report: &ExecutionReport) -> Self {
+        let mut output = Self::new(source, destination);
+        output.source_bytes = report.sizes.map(|sizes| sizes.source);
+        output.output_bytes = Some(report.output_bytes);
+        output.size_ratio = report.sizes.and_then(|sizes| sizes.ratio());
+        output.strategy = Some(report.strategy_used.label());
+        output.ffmpeg_mode = report.ffmpeg_mode.map(|mode| mode.label());
+        output.backup_path = report
+            .backup_path
+            .as_ref()
+            .map(|path| path.display().to_string());
+        output.elapsed_seconds = Some(report.elapsed.as_secs_f64());
+        output
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchSummaryJson {
+    pub schema_version: u32,
+    pub status: &'static str,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// All skipped sources, including `skipped_duplicates` and `--overwrite-newer` skips.
+    pub skipped: usize,
+    pub skipped_duplicates: usize,
+    /// Destinations that already existed without `--overwrite`/`--backup`; always 0
+    /// with `--strict`, which counts them as failures.
+    pub skipped_existing: usize,
+    /// Sources left out by `--only`/`--ext`.
+    pub skipped_filtered: usize,
+    /// Sources available before `--limit`; `null` when the batch was not limited.
+    pub limited_from: Option<usize>,
+    /// Total source and output bytes of the conversions that succeeded.
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub failures: Vec<FailureJson>,
+    pub by_backend: BTreeMap<String, GroupJson>,
+    pub by_kind: BTreeMap<String, GroupJson>,
+}
+
+/// One line of `--json-lines` batch output, tagged by its `event` field.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BatchEventJson {
+    /// A source is about to be converted.
+    Start(StartJson),
+    /// A source finished, failed or was skipped.
+    Result(ResultJson),
+    /// The batch summary, always the last line.
+    Summary(BatchSummaryJson),
+}
+
+#[derive(Debug, Serialize)]
+pub struct StartJson {
+    pub schema_version: u32,
+    pub source: String,
+    pub destination: String,
+}
+
+impl StartJson {
+    pub fn new(source: &Path, destination: &Path) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            source: source.display().to_string(),
+            destination: destination.display().to_string(),
+        }
+    }
+}
+
+/// The fields of a single-conversion `--json` result, plus the error of a failed one.
+#[derive(Debug, Serialize)]
+pub struct ResultJson {
+    #[serde(flatten)]
+    pub result: SuccessJson,
+    pub error: Option<String>,
+}
+
+impl ResultJson {
+    pub fn skipped(source: &Path, destination: &Path) -> Self {
+        let mut result = SuccessJson::new(source, destination);
+        result.status = "skipped";
+        Self {
+            result,
+            error: None,
+        }
+    }
+
+    /// A source that failed, during planning or conversion. `destination` is `None` when
+    /// the failure came before one was named.
+    pub fn failed(source: &Path, destination: Option<&Path>, error: String) -> Self {
+        let mut result = SuccessJson::new(source, destination.unwrap_or(Path::new("")));
+        result.destination = destination.map(|destination| destination.display().to_string());
+        result.status = "failed";
+        Self {
+            result,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FailureJson {
+    pub source: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupJson {
+    pub ok: usize,
+    pub failed: usize,
+}
+
+/// `--clean-temps` results; `removed` lists what would be removed on a dry run.
+#[derive(Debug, Serialize)]
+pub struct CleanTempsJson {
+    pub schema_version: u32,
+    pub status: &'static str,
+    pub dry_run: bool,
+    pub removed: Vec<String>,
+}
+
+/// `--detect` results. `content_extension` is the extension that goes with `mime`;
+/// `extension_mismatch` is set when it contradicts the file's own extension.
+#[derive(Debug, Serialize)]
+pub struct DetectJson {
+    pub schema_version: u32,
+    pub path: String,
+    pub mime: Option<String>,
+    pub file_mime: Option<String>,
+    pub extension: Option<String>,
+    pub content_extension: Option<String>,
+    pub extension_mismatch: bool,
+}
+
+/// `--list-conversions`: the routing table plus the extensions in each category.
+#[derive(Debug, Serialize)]
+pub struct ConversionsJson {
+    pub schema_version: u32,
+    pub conversions: Vec<ConversionJson>,
+    pub categories: BTreeMap<&'static str, Vec<&'static str>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConversionJson {
+    pub source: &'static str,
+    pub destination: &'static str,
+    pub backend: &'static str,
+    pub reason: &'static str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn success_shape() {
+        let output = SuccessJson::new(Path::new("in.png"), Path::new("out.jpg"));
+        assert_eq!(
+            serde_json::to_string(&output).unwrap(),
+            concat!(
+                r#"{"schema_version":1,"status":"ok","source":"in.png","destination":"out.jpg","#,
+                r#""source_bytes":null,"output_bytes":null,"size_ratio":null,"strategy":null,"#,
+                r#""ffmpeg_mode":null,"backup_path":null,"elapsed_seconds":null}"#
+            )
+        );
+    }
+
+    #[test]
+    fn batch_summary_shape() {
+        let output = BatchSummaryJson {
+            schema_version: SCHEMA_VERSION,
+            status: "failed",
+            total: 2,
+            succeeded: 1,
+            failed: 1,
+            skipped: 0,
+            skipped_duplicates: 0,
+            skipped_existing: 0,
+            skipped_filtered: 0,
+            limited_from: None,
+            bytes_in: 2000,
+            bytes_out: 500,
+            failures: vec![FailureJson {
+                source: "b.wav".to_string(),
+                error: "boom".to_string(),
+            }],
+            by_backend: BTreeMap::from([("ffmpeg".to_string(), GroupJson { ok: 1, failed: 1 })]),
+            by_kind: BTreeMap::from([("audio".to_string(), GroupJson { ok: 1, failed: 1 })]),
+        };
+        assert_eq!(
+            serde_json::to_string(&output).unwrap(),
+            concat!(
+                r#"{"schema_version":1,"status":"failed","total":2,"succeeded":1,"failed":1,"#,
+                r#""skipped":0,"skipped_duplicates":0,"skipped_existing":0,"#,
+                r#""skipped_filtered":0,"limited_from":null,"#,
+                r#""bytes_in":2000,"bytes_out":500,"#,
+                r#""failures":[{"source":"b.wav","error":"boom"}],"#,
+                r#""by_backend":{"ffmpeg":{"ok":1,"failed":1}},"#,
+                r#""by_kind":{"audio":{"ok":1,"failed":1}}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn batch_event_shapes() {
+        let start = BatchEventJson::Start(StartJson::new(Path::new("a.wav"), Path::new("a.mp3")));
+        assert_eq!(
+            serde_json::to_string(&start).unwrap(),
+            r#"{"event":"start","schema_version":1,"source":"a.wav","destination":"a.mp3"}"#
+        );
+        let failed = BatchEventJson::Result(ResultJson::failed(
+            Path::new("b.wav"),
+            Some(Path::new("b.mp3")),
+            "boom".to_string(),
+        ));
+        let value = serde_json::to_value(&failed).unwrap();
+        assert_eq!(value["event"], "result");
+        assert_eq!(value["status"], "failed");
+        assert_eq!(value["source"], "b.wav");
+        assert_eq!(value["destination"], "b.mp3");
+        assert_eq!(value["output_bytes"], serde_json::Value::Null);
+        assert_eq!(value["error"], "boom");
+
+        let unnamed = ResultJson::failed(Path::new("c.wav"), None, "no name".to_string());
+        let value = serde_json::to_value(&unnamed).unwrap();
+        assert_eq!(value["destination"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn clean_temps_shape() {
+        let output = CleanTempsJson {
+            schema_version: SCHEMA_VERSION,
+            status: "ok",
+            dry_run: true,
+            removed: vec![".mvx.tmpA".to_string()],
+        };
+        assert_eq!(
+            serde_json::to_string(&output).unwrap(),
+            r#"{"schema_version":1,"status":"ok","dry_run":true,"removed":[".mvx.tmpA"]}"#
+        );
+    }
+}
@@ -1,7 +1,11 @@
 use crate::detect::{DetectedType, detect_path};
+use crate::ffprobe::{MediaInfo, SourceProbe};
+use crate::identify::{image_dimensions, image_frame_count};
 use crate::pdf::pdf_page_count;
+use crate::raw::{MISSING_RAW_TOOL, develop_args, find_raw_tool};
 use anyhow::{Context, Result, bail};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -9,6 +13,20 @@ pub enum Strategy {
     RenameOnly,
     CopyOnly,
     Convert,
+    /// A container change whose streams the new container takes as they are, so
+    /// ffmpeg copies them (`-c copy`) instead of re-encoding.
+    Remux,
+}
+
+impl Strategy {
+    pub fn label(self) -> &'static str {
+        match self {
+            Strategy::RenameOnly => "rename",
+            Strategy::CopyOnly => "copy",
+            Strategy::Convert => "convert",
+            Strategy::Remux => "remux",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -16,6 +34,19 @@ pub enum Backend {
     ImageMagick,
     Ffmpeg,
     LibreOffice,
+    /// A RAW developer (see `raw::find_raw_tool`) followed by ImageMagick.
+    Raw,
+}
+
+impl Backend {
+    pub fn label(self) -> &'static str {
+        match self {
+            Backend::ImageMagick => "imagemagick",
+            Backend::Ffmpeg => "ffmpeg",
+            Backend::LibreOffice => "libreoffice",
+            Backend::Raw => "raw",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -23,25 +54,103 @@ pub struct Plan {
     pub source: PathBuf,
     pub destination: PathBuf,
     pub detected: DetectedType,
+    /// Size of the source when planned; `None` when it could not be read.
+    pub source_bytes: Option<u64>,
     pub strategy: Strategy,
     pub backend: Option<Backend>,
+    /// Why `backend` was (or could not be) chosen; `None` when nothing is converted.
+    pub backend_reason: Option<String>,
     pub notes: Vec<String>,
     pub move_source: bool,
     pub backup: bool,
     pub options: ConversionOptions,
     pub dest_ext: Option<String>,
     pub dest_kind: MediaKind,
+    /// The source's streams, probed once for ffmpeg conversions.
+    pub probe: SourceProbe,
 }
 
 #[derive(Debug, Clone)]
 pub struct ConversionOptions {
     pub image_quality: Option<u8>,
     pub video_bitrate: Option<String>,
+    /// Constant-quality factor passed as `-crf`; excludes `video_bitrate`.
+    pub crf: Option<u8>,
     pub audio_bitrate: Option<String>,
     pub preset: Option<String>,
     pub video_codec: Option<String>,
+    /// `--hwaccel` kind from `HWACCEL_KINDS`; swaps in a hardware video encoder.
+    pub hwaccel: Option<String>,
     pub audio_codec: Option<String>,
     pub ffmpeg_preference: FfmpegPreference,
+    /// ffmpeg's `-loglevel`; `None` keeps the quiet `error` default.
+    pub ffmpeg_loglevel: Option<String>,
+    pub two_pass: bool,
+    pub mute: bool,
+    pub speed: Option<f64>,
+    /// Trim start for ffmpeg, as seconds or `[HH:]MM:SS[.ms]`; passed as `-ss` before `-i`.
+    pub start: Option<String>,
+    /// Trim length; excludes `to`.
+    pub duration: Option<String>,
+    /// Trim end, measured on the source's timeline.
+    pub to: Option<String>,
+    /// EBU R128 loudness normalization (`loudnorm`) of the audio; forces transcode.
+    pub normalize: bool,
+    /// Integrated loudness target in LUFS for `normalize`; `DEFAULT_LOUDNESS` when unset.
+    pub loudness: Option<f64>,
+    pub grayscale: bool,
+    pub srgb: bool,
+    pub icc_profile: Option<PathBuf>,
+    pub backup_pad: Option<usize>,
+    /// Shrink-only bounding box for images, as `WxH`.
+    pub fit: Option<String>,
+    /// ImageMagick `-resize` geometry for images, e.g. `1920x1080`, `50%` or `800x`.
+    pub resize: Option<String>,
+    /// Rasterization resolution in DPI for SVG and PDF sources, passed as `-density`.
+    pub density: Option<u32>,
+    /// Lossless WebP/AVIF encoding; replaces `image_quality` for those formats.
+    pub lossless: bool,
+    /// WebP/AVIF encoder effort from 0 (fastest) to `MAX_EFFORT` (smallest output).
+    pub effort: Option<u8>,
+    /// Zero-based frame, layer or PDF page to convert from a multi-frame image source.
+    pub frame: Option<u32>,
+    /// Write every page of a multi-page PDF going to an image as `<stem>-N.<ext>`.
+    pub all_pages: bool,
+    pub check_space: bool,
+    /// Kill a conversion tool that runs longer than this many seconds.
+    pub timeout: Option<u64>,
+    /// Compare SHA-256 digests after copy and rename strategies.
+    pub verify: bool,
+    /// Fail planning when the source's content contradicts its extension.
+    pub strict_detect: bool,
+    /// Copy the source's modification time and Unix mode bits onto the output.
+    pub preserve: bool,
+    /// Encode `.ogg` audio as Opus instead of Vorbis.
+    pub ogg_opus: bool,
+    /// Lower `audio_bitrate` to the source's when it asks for more.
+    pub no_upscale_bitrate: bool,
+    /// Audio sample format for audio outputs, one of `SAMPLE_FORMATS`.
+    pub sample_fmt: Option<String>,
+    /// Triangular dither when `sample_fmt` reduces the bit depth.
+    pub dither: bool,
+    /// Replace an existing destination only when the source was modified more recently.
+    pub overwrite_newer: bool,
+    /// Shrink-only caps on video resolution; smaller clips are never upscaled.
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    pub rotate: Option<Rotation>,
+    /// Whether source metadata (EXIF, GPS, tags) is stripped, kept or left to the backend.
+    pub metadata: Metadata,
+    /// Tone-map HDR (PQ/HLG) sources to SDR BT.709 when transcoding video.
+    pub tonemap: bool,
+    /// Rewrite the destination file name so it is valid on FAT/exFAT/NTFS.
+    pub sanitize: Option<Sanitize>,
+    /// Render PDFs with Ghostscript instead of trying ImageMagick first.
+    pub prefer_gs: bool,
+    /// Directory for destinations given as a bare file name; see `resolve_destination`.
+    pub output_dir: Option<PathBuf>,
+    /// `[ext.<name>] image_quality` overrides, keyed by normalized destination extension.
+    pub image_quality_by_ext: BTreeMap<String, u8>,
 }
 
 impl Default for ConversionOptions {
@@ -49,15 +158,258 @@ impl Default for ConversionOptions {
         Self {
             image_quality: None,
             video_bitrate: None,
+            crf: None,
             audio_bitrate: None,
             preset: None,
             video_codec: None,
+            hwaccel: None,
             audio_codec: None,
             ffmpeg_preference: FfmpegPreference::Auto,
+            metadata: Metadata::Default,
+            ffmpeg_loglevel: None,
+            two_pass: false,
+            mute: false,
+            speed: None,
+            start: None,
+            duration: None,
+            to: None,
+            normalize: false,
+            loudness: None,
+            grayscale: false,
+            srgb: false,
+            icc_profile: None,
+            backup_pad: None,
+            fit: None,
+            resize: None,
+            density: None,
+            lossless: false,
+            effort: None,
+            frame: None,
+            all_pages: false,
+            check_space: false,
+            timeout: None,
+            verify: false,
+            strict_detect: false,
+            preserve: false,
+            ogg_opus: false,
+            no_upscale_bitrate: false,
+            sample_fmt: None,
+            dither: false,
+            overwrite_newer: false,
+            max_width: None,
+            max_height: None,
+            rotate: None,
+            tonemap: false,
+            sanitize: None,
+            prefer_gs: false,
+            output_dir: None,
+            image_quality_by_ext: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Rotate the pixels to match the source's rotation flag, then clear the flag.
+    Auto,
+    /// Write this display rotation (degrees clockwise) without touching the pixels.
+    Set(u16),
+}
+
+impl Rotation {
+    pub fn label(self) -> String {
+        match self {
+            Rotation::Auto => "auto".to_string(),
+            Rotation::Set(degrees) => degrees.to_string(),
+        }
+    }
+}
+
+/// What a conversion does with the source's metadata.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Metadata {
+    /// Whatever the backend does on its own.
+    #[default]
+    Default,
+    /// `-strip` for ImageMagick, `-map_metadata -1` for ffmpeg.
+    Strip,
+    /// `-map_metadata 0` for ffmpeg, which otherwise drops some tags.
+    Keep,
+}
+
+impl Metadata {
+    pub fn label(self) -> &'static str {
+        match self {
+            Metadata::Default => "default",
+            Metadata::Strip => "strip",
+            Metadata::Keep => "keep",
         }
     }
 }
 
+/// Parses the `metadata` config value: `default`, `strip` or `keep`.
+pub fn parse_metadata(value: &str) -> Result<Metadata> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "default" => Ok(Metadata::Default),
+        "strip" => Ok(Metadata::Strip),
+        "keep" => Ok(Metadata::Keep),
+        other => bail!("metadata must be default, strip or keep (got {other})"),
+    }
+}
+
+/// Parses `--rotate` values: `0`, `90`, `180`, `270` or `auto`.
+pub fn parse_rotation(value: &str) -> Result<Rotation> {
+    match value.trim() {
+        "auto" => Ok(Rotation::Auto),
+        "0" => Ok(Rotation::Set(0)),
+        "90" => Ok(Rotation::Set(90)),
+        "180" => Ok(Rotation::Set(180)),
+        "270" => Ok(Rotation::Set(270)),
+        other => bail!("rotate must be 0, 90, 180, 270 or auto (got {other})"),
+    }
+}
+
+/// ffmpeg's `-loglevel` names, quietest first.
+const FFMPEG_LOGLEVELS: [&str; 9] = [
+    "quiet", "panic", "fatal", "error", "warning", "info", "verbose", "debug", "trace",
+];
+
+pub const DEFAULT_FFMPEG_LOGLEVEL: &str = "error";
+
+/// Parses `--ffmpeg-loglevel` values against ffmpeg's level names.
+pub fn parse_ffmpeg_loglevel(value: &str) -> Result<String> {
+    let level = value.trim().to_ascii_lowercase();
+    if FFMPEG_LOGLEVELS.contains(&level.as_str()) {
+        Ok(level)
+    } else {
+        bail!(
+            "ffmpeg loglevel must be one of {} (got {value})",
+            FFMPEG_LOGLEVELS.join(", ")
+        )
+    }
+}
+
+/// Whether `level` makes ffmpeg say more than the default, so its stderr is worth
+/// carrying into errors and the run log in full.
+pub fn ffmpeg_loglevel_is_verbose(level: &str) -> bool {
+    let rank = |name: &str| FFMPEG_LOGLEVELS.iter().position(|known| *known == name);
+    rank(level) > rank(DEFAULT_FFMPEG_LOGLEVEL)
+}
+
+/// `--hwaccel` kinds; `none` keeps the software encoders, overriding a config default.
+const HWACCEL_KINDS: [&str; 4] = ["nvenc", "vaapi", "videotoolbox", "none"];
+
+/// Parses `--hwaccel` values against `HWACCEL_KINDS`.
+pub fn parse_hwaccel(value: &str) -> Result<String> {
+    let kind = value.trim().to_ascii_lowercase();
+    if HWACCEL_KINDS.contains(&kind.as_str()) {
+        Ok(kind)
+    } else {
+        bail!(
+            "hwaccel must be one of {} (got {value})",
+            HWACCEL_KINDS.join(", ")
+        )
+    }
+}
+
+/// `--sample-fmt` values: ffmpeg's sample formats plus `s24`, which ffmpeg stores as
+/// `s32` and writes as 24-bit PCM in `.wav`.
+const SAMPLE_FORMATS: [&str; 12] = [
+    "u8", "s16", "s24", "s32", "s64", "flt", "dbl", "u8p", "s16p", "s32p", "fltp", "dblp",
+];
+
+/// Parses `--sample-fmt` values against `SAMPLE_FORMATS`.
+pub fn parse_sample_fmt(value: &str) -> Result<String> {
+    let format = value.trim().to_ascii_lowercase();
+    if SAMPLE_FORMATS.contains(&format.as_str()) {
+        Ok(format)
+    } else {
+        bail!(
+            "sample format must be one of {} (got {value})",
+            SAMPLE_FORMATS.join(", ")
+        )
+    }
+}
+
+/// The `-sample_fmt` value ffmpeg understands for a `--sample-fmt` value.
+fn ffmpeg_sample_fmt(format: &str) -> &str {
+    if format == "s24" { "s32" } else { format }
+}
+
+/// The PCM codec a `.wav` needs to hold samples of `format`.
+fn wav_codec(format: &str) -> Option<&'static str> {
+    match format.trim_end_matches('p') {
+        "u8" => Some("pcm_u8"),
+        "s16" => Some("pcm_s16le"),
+        "s24" => Some("pcm_s24le"),
+        "s32" => Some("pcm_s32le"),
+        "s64" => Some("pcm_s64le"),
+        "flt" => Some("pcm_f32le"),
+        "dbl" => Some("pcm_f64le"),
+        _ => None,
+    }
+}
+
+/// `-sample_fmt` and `-dither_method` for an audio output; both are audio-only options.
+pub fn sample_format_args(options: &ConversionOptions, dest_kind: MediaKind) -> Vec<String> {
+    let Some(format) = options.sample_fmt.as_deref() else {
+        return Vec::new();
+    };
+    if dest_kind != MediaKind::Audio {
+        return Vec::new();
+    }
+    let mut args = vec![
+        "-sample_fmt".to_string(),
+        ffmpeg_sample_fmt(format).to_string(),
+    ];
+    if options.dither {
+        args.extend(["-dither_method".to_string(), "triangular".to_string()]);
+    }
+    args
+}
+
+/// Extra rules for `--sanitize`; illegal characters are always replaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sanitize {
+    pub lowercase: bool,
+    pub underscore_spaces: bool,
+}
+
+/// Characters FAT, exFAT and NTFS refuse in file names.
+const ILLEGAL_NAME_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Replaces illegal and control characters with `_`, then applies the optional rules.
+pub fn sanitize_file_name(name: &str, rules: Sanitize) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control()
+                || ILLEGAL_NAME_CHARS.contains(&c)
+                || (rules.underscore_spaces && c == ' ')
+            {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if rules.lowercase {
+        sanitized.to_lowercase()
+    } else {
+        sanitized
+    }
+}
+
+/// Returns the rewritten destination, or `None` when its file name is already clean.
+fn sanitize_destination(destination: &Path, rules: Sanitize) -> Option<PathBuf> {
+    let name = destination.file_name()?.to_string_lossy();
+    let sanitized = sanitize_file_name(&name, rules);
+    if sanitized == name {
+        return None;
+    }
+    Some(destination.with_file_name(sanitized))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MediaKind {
     Image,
@@ -67,6 +419,36 @@ pub enum MediaKind {
     Other,
 }
 
+impl MediaKind {
+    pub fn from_path(path: &Path) -> Self {
+        classify_dest_kind(None, normalize_ext(path).as_deref())
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MediaKind::Image => "image",
+            MediaKind::Audio => "audio",
+            MediaKind::Video => "video",
+            MediaKind::Document => "document",
+            MediaKind::Other => "other",
+        }
+    }
+}
+
+/// `--only` values: every kind but `other`, which would select nothing useful.
+pub fn parse_media_kind(value: &str) -> Result<MediaKind> {
+    let value = value.trim().to_ascii_lowercase();
+    [
+        MediaKind::Image,
+        MediaKind::Audio,
+        MediaKind::Video,
+        MediaKind::Document,
+    ]
+    .into_iter()
+    .find(|kind| kind.label() == value)
+    .with_context(|| format!("kind must be one of image, audio, video, document (got {value})"))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FfmpegPreference {
     Auto,
@@ -80,6 +462,45 @@ pub enum FfmpegMode {
     Transcode,
 }
 
+impl FfmpegMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            FfmpegMode::StreamCopy => "stream-copy",
+            FfmpegMode::Transcode => "transcode",
+        }
+    }
+}
+
+/// Where a destination lands: a path with a directory part is used as given, while a
+/// bare file name goes into `output_dir` when set and next to the source otherwise.
+/// Returns the path and, when it moved, the directory that was chosen.
+pub fn resolve_destination(
+    source: &Path,
+    destination: &Path,
+    output_dir: Option<&Path>,
+) -> (PathBuf, Option<String>) {
+    let bare = destination
+        .parent()
+        .is_some_and(|parent| parent.as_os_str().is_empty());
+    if !bare {
+        return (destination.to_path_buf(), None);
+    }
+    let (dir, origin) = match output_dir {
+        Some(dir) => (dir, "output directory"),
+        None => match source.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => (dir, "source directory"),
+            _ => return (destination.to_path_buf(), None),
+        },
+    };
+    (
+        dir.join(destination),
+        Some(format!(
+            "bare destination name placed in the {origin} {}",
+            dir.display()
+        )),
+    )
+}
+
 pub fn build_plan(
     source: &Path,
     destination: &Path,
@@ -87,54 +508,203 @@ pub fn build_plan(
     backup: bool,
     options: ConversionOptions,
 ) -> Result<Plan> {
+    let (destination, placement_note) =
+        resolve_destination(source, destination, options.output_dir.as_deref());
+    let destination = destination.as_path();
+    let sanitized = options
+        .sanitize
+        .and_then(|rules| sanitize_destination(destination, rules));
+    let sanitize_note = sanitized.as_ref().map(|renamed| {
+        format!(
+            "output name sanitized: {} -> {}",
+            destination
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy(),
+            renamed.file_name().unwrap_or_default().to_string_lossy()
+        )
+    });
+    let destination = sanitized.as_deref().unwrap_or(destination);
     if source == destination {
         bail!("source and destination must differ");
     }
 
     let detected = detect_path(source);
+    let mismatch_note = detected.extension_mismatch().map(|content| {
+        format!(
+            "source extension says {} but content is {content}",
+            detected.ext_hint.as_deref().unwrap_or_default()
+        )
+    });
+    if let Some(note) = &mismatch_note
+        && options.strict_detect
+    {
+        bail!("{note} (--strict-detect)");
+    }
     let source_ext = normalize_ext(source);
     let dest_ext = normalize_ext(destination);
-    let dest_kind = classify_dest_kind(dest_ext.as_deref());
+    let dest_kind = classify_dest_kind(source_ext.as_deref(), dest_ext.as_deref());
 
+    let mut options = options;
+    if let Some(&quality) = dest_ext
+        .as_deref()
+        .and_then(|ext| options.image_quality_by_ext.get(ext))
+    {
+        options.image_quality = Some(quality);
+    }
     validate_options(&options)?;
+    let fit_note = fit_note(source_ext.as_deref(), dest_kind, &options);
+    let frame_note = resolve_frame(source, source_ext.as_deref(), dest_kind, &mut options)?;
+    let pages_note = resolve_all_pages(
+        source,
+        destination,
+        source_ext.as_deref(),
+        dest_kind,
+        &mut options,
+    );
+    let mut probe = SourceProbe::NotRun;
+    let scale_note = resolve_video_caps(
+        source,
+        source_ext.as_deref(),
+        dest_kind,
+        &mut options,
+        &mut probe,
+    );
+    let bitrate_note = resolve_audio_bitrate(
+        source,
+        source_ext.as_deref(),
+        dest_kind,
+        &mut options,
+        &mut probe,
+    );
 
-    let strategy = match (source_ext.as_deref(), dest_ext.as_deref()) {
-        (Some(src), Some(dest)) if src == dest => {
-            if move_source {
-                Strategy::RenameOnly
-            } else {
-                Strategy::CopyOnly
-            }
+    let same_format = matches!(
+        (source_ext.as_deref(), dest_ext.as_deref()),
+        (Some(src), Some(dest)) if src == dest
+    );
+    let strategy = if same_format && !transforms_output(&options, dest_kind, dest_ext.as_deref()) {
+        if move_source {
+            Strategy::RenameOnly
+        } else {
+            Strategy::CopyOnly
         }
-        _ => Strategy::Convert,
+    } else {
+        Strategy::Convert
     };
 
-    let backend = if strategy == Strategy::Convert {
-        select_backend(source_ext.as_deref(), dest_ext.as_deref())
+    let (backend, backend_reason) = if strategy == Strategy::Convert {
+        let (backend, reason) = select_backend(source_ext.as_deref(), dest_ext.as_deref());
+        (backend, Some(reason))
     } else {
-        None
+        (None, None)
     };
 
     let mut notes = Vec::new();
+    notes.extend(placement_note);
+    notes.extend(sanitize_note);
+    notes.extend(mismatch_note);
+    if strategy == Strategy::RenameOnly && options.preserve {
+        notes.push("preserve has no effect: a move keeps the source's time and mode".to_string());
+    }
     if strategy == Strategy::Convert {
         if backend.is_none() {
             notes.push("no supported backend found for this conversion".to_string());
         }
-        if backend == Some(Backend::Ffmpeg) {
+        if options.verify {
             notes.push(
-                "ffprobe may be used at runtime to choose stream copy vs transcode".to_string(),
+                "verify skipped: conversions change the content; only copies and renames are checked"
+                    .to_string(),
             );
         }
+        if backend == Some(Backend::Ffmpeg) {
+            notes.push(RUNTIME_PROBE_NOTE.to_string());
+            notes.extend(hwaccel_note(&options, dest_kind, dest_ext.as_deref()));
+            if let Some(bitrate) = options.video_bitrate.as_deref()
+                && options.two_pass
+                && dest_kind == MediaKind::Video
+            {
+                notes.push(format!(
+                    "two-pass encode at {bitrate}: pass 1 analyzes the video and writes only a \
+                     log, pass 2 encodes with it; progress spans both passes"
+                ));
+            }
+        }
         if is_pdf_image_pair(source_ext.as_deref(), dest_ext.as_deref())
             && source_ext.as_deref() == Some("pdf")
+            && !options.all_pages
         {
-            notes.push("PDF to image converts the first page only".to_string());
+            notes.push(match options.frame {
+                Some(frame) => format!("PDF to image converts page {} only", frame + 1),
+                None => "PDF to image converts the first page only".to_string(),
+            });
             if let Ok(Some(pages)) = pdf_page_count(source)
                 && pages > 1
             {
                 notes.push(format!("PDF has {pages} pages"));
             }
         }
+        if is_image_ext(dest_ext.as_deref())
+            && (source_ext.as_deref() == Some("pdf") || is_document_ext(source_ext.as_deref()))
+        {
+            notes.push(pdf_renderer_note(&options, dest_ext.as_deref()));
+        }
+        if backend == Some(Backend::Raw) {
+            notes.push(match find_raw_tool() {
+                Some(tool) if tool.uses_intermediate() => format!(
+                    "RAW developed with {} into a TIFF, then encoded with ImageMagick",
+                    tool.label()
+                ),
+                Some(tool) => format!("RAW developed with {}", tool.label()),
+                None => format!("{MISSING_RAW_TOOL}; the conversion will fail"),
+            });
+        }
+        if is_vector_ext(source_ext.as_deref()) && backend == Some(Backend::ImageMagick) {
+            notes.push(format!(
+                "SVG rasterization requires an ImageMagick SVG delegate (librsvg or Inkscape); \
+                 rendered at {}",
+                match options.density {
+                    Some(dpi) => format!("{dpi} DPI"),
+                    None => "the delegate's default density".to_string(),
+                }
+            ));
+        }
+        if is_document_image_pair(source_ext.as_deref(), dest_ext.as_deref()) {
+            notes.push(
+                "document is converted to an intermediate PDF, then its first page is rendered"
+                    .to_string(),
+            );
+        }
+        if is_video_to_gif(source_ext.as_deref(), dest_ext.as_deref()) {
+            notes.push(format!(
+                "GIF is rendered at {GIF_FPS} fps with a palette built from the clip; audio is dropped"
+            ));
+        }
+        if is_gif_to_video(source_ext.as_deref(), dest_ext.as_deref()) {
+            notes.push(
+                "GIF frames are encoded as yuv420p video, rounded down to even dimensions"
+                    .to_string(),
+            );
+        }
+    }
+    notes.extend(fit_note);
+    notes.extend(frame_note);
+    notes.extend(pages_note);
+    notes.extend(scale_note);
+    notes.extend(bitrate_note);
+    if strategy == Strategy::CopyOnly
+        && let Ok(metadata) = std::fs::metadata(source)
+        && metadata.len() >= LARGE_COPY_BYTES
+    {
+        notes.push("large file; copy uses the buffered sequential path".to_string());
+    }
+    if same_format
+        && strategy != Strategy::Convert
+        && has_transform_options(&options, dest_ext.as_deref())
+    {
+        notes.push(format!(
+            "requested options do not change {} output; copying instead of converting",
+            dest_kind.label()
+        ));
     }
     if !move_source {
         notes.push("source will be kept".to_string());
@@ -147,24 +717,192 @@ pub fn build_plan(
         dest_ext.as_deref(),
     ));
 
-    Ok(Plan {
+    // Every ffmpeg conversion reads the source's streams, so the notes below and the
+    // conversion itself share this one probe.
+    if backend == Some(Backend::Ffmpeg) {
+        probe.read(source);
+    }
+    let mut plan = Plan {
         source: source.to_path_buf(),
         destination: destination.to_path_buf(),
         detected,
+        source_bytes: std::fs::metadata(source)
+            .ok()
+            .map(|metadata| metadata.len()),
         strategy,
         backend,
+        backend_reason,
         notes,
         move_source,
         backup,
         options,
         dest_ext,
         dest_kind,
+        probe,
+    };
+    if !same_format {
+        classify_remux(&mut plan);
+    }
+    note_hdr(&mut plan);
+    note_audio_extraction(&mut plan);
+    Ok(plan)
+}
+
+/// Which encoder `--hwaccel` will try for a video output, or why it stays in software.
+fn hwaccel_note(
+    options: &ConversionOptions,
+    dest_kind: MediaKind,
+    dest_ext: Option<&str>,
+) -> Option<String> {
+    let kind = options.hwaccel.as_deref().filter(|kind| *kind != "none")?;
+    if dest_kind != MediaKind::Video {
+        return Some(format!("hwaccel {kind} ignored for non-video output"));
+    }
+    Some(match hw_encoder(options, dest_kind, dest_ext) {
+        Some(encoder) => format!(
+            "transcodes encode with {encoder} ({kind}); it fails if the device or driver is missing"
+        ),
+        None => format!(
+            "no {kind} encoder for {}; encoding in software",
+            video_encoder(options, dest_ext).unwrap_or("this output")
+        ),
     })
 }
 
+/// Notes audio output from a video source, which keeps only the audio track.
+fn note_audio_extraction(plan: &mut Plan) {
+    if !matches!(plan.strategy, Strategy::Convert | Strategy::Remux)
+        || plan.backend != Some(Backend::Ffmpeg)
+        || !extracts_audio(plan, None)
+    {
+        return;
+    }
+    if extracts_audio(plan, plan.probe.info()) {
+        plan.notes.push("extracting audio track only".to_string());
+    }
+}
+
+/// Audio output from a video source. ffmpeg then gets `-vn` so the audio muxer is not
+/// handed the picture; a probe that finds no video stream rules it out.
+pub fn extracts_audio(plan: &Plan, info: Option<&MediaInfo>) -> bool {
+    plan.dest_kind == MediaKind::Audio
+        && is_video_ext(normalize_ext(&plan.source).as_deref())
+        && info.is_none_or(|info| info.video_codec.is_some())
+}
+
+/// Probes video transcodes for HDR footage, which turns washed out when encoded as SDR
+/// without tone mapping.
+fn note_hdr(plan: &mut Plan) {
+    if plan.strategy != Strategy::Convert
+        || plan.backend != Some(Backend::Ffmpeg)
+        || plan.dest_kind != MediaKind::Video
+    {
+        return;
+    }
+    let Some(info) = plan.probe.info().cloned() else {
+        return;
+    };
+    if tonemaps(plan, Some(&info)) {
+        plan.notes.push(format!(
+            "{} source is tone-mapped to SDR BT.709 (needs ffmpeg with zscale)",
+            hdr_label(&info)
+        ));
+    } else if let Some(warning) = hdr_warning(plan, &info) {
+        plan.notes.push(warning);
+    }
+}
+
+/// Warning for an HDR source that would be transcoded to a likely-SDR encode without
+/// `--tonemap`.
+pub fn hdr_warning(plan: &Plan, info: &MediaInfo) -> Option<String> {
+    info.hdr_transfer()?;
+    if plan.options.tonemap
+        || plan.dest_kind != MediaKind::Video
+        || decide_ffmpeg_mode(plan, Some(info)) != FfmpegMode::Transcode
+        || keeps_hdr(&plan.options, plan.dest_ext.as_deref())
+    {
+        return None;
+    }
+    Some(format!(
+        "{} source transcoded to SDR will look washed out; pass --tonemap to tone-map it",
+        hdr_label(info)
+    ))
+}
+
+fn hdr_label(info: &MediaInfo) -> String {
+    let transfer = info.hdr_transfer().unwrap_or("HDR");
+    match info.color_primaries.as_deref() {
+        Some(primaries) => format!("HDR ({transfer}, {primaries})"),
+        None => format!("HDR ({transfer})"),
+    }
+}
+
+/// Encoders that can carry 10-bit HDR through; everything else mvx picks (H.264 by
+/// default) ends up SDR.
+fn keeps_hdr(options: &ConversionOptions, dest_ext: Option<&str>) -> bool {
+    video_encoder(options, dest_ext)
+        .and_then(encoder_codec)
+        .is_some_and(|codec| matches!(codec, "hevc" | "av1" | "vp9"))
+}
+
+/// zscale/tonemap chain from HDR to SDR BT.709: linearize, map the primaries, compress
+/// the highlights with Hable's curve, then re-encode the BT.709 transfer.
+pub const TONEMAP_FILTER: &str = "zscale=t=linear:npl=100,format=gbrpf32le,zscale=p=bt709,\
+     tonemap=tonemap=hable:desat=0,zscale=t=bt709:m=bt709:r=tv,format=yuv420p";
+
+/// `--tonemap` on a probed HDR source, which needs the tone-mapping filter.
+pub fn tonemaps(plan: &Plan, info: Option<&MediaInfo>) -> bool {
+    plan.options.tonemap
+        && plan.dest_kind == MediaKind::Video
+        && info.and_then(MediaInfo::hdr_transfer).is_some()
+}
+
+/// Marks an ffmpeg container change as a remux when the probed streams can be copied
+/// into the destination as they are, so the plan shows the fast path up front.
+fn classify_remux(plan: &mut Plan) {
+    if plan.strategy != Strategy::Convert
+        || plan.backend != Some(Backend::Ffmpeg)
+        || plan.options.ffmpeg_preference != FfmpegPreference::Auto
+        || forces_transcode(plan)
+    {
+        return;
+    }
+    if let Some(info) = plan.probe.info().cloned() {
+        apply_remux(plan, &info);
+    }
+}
+
+fn apply_remux(plan: &mut Plan, info: &MediaInfo) {
+    if decide_ffmpeg_mode(plan, Some(info)) != FfmpegMode::StreamCopy {
+        return;
+    }
+    plan.strategy = Strategy::Remux;
+    let note = format!(
+        "streams fit {} as they are; remuxing with -c copy (no re-encode)",
+        plan.dest_ext.as_deref().unwrap_or("the destination")
+    );
+    match plan
+        .notes
+        .iter()
+        .position(|note| note == RUNTIME_PROBE_NOTE)
+    {
+        Some(index) => plan.notes[index] = note,
+        None => plan.notes.push(note),
+    }
+}
+
+const RUNTIME_PROBE_NOTE: &str =
+    "ffprobe may be used at runtime to choose stream copy vs transcode";
+
 pub fn render_plan(plan: &Plan, overwrite: bool) -> String {
     let mut lines = Vec::new();
     lines.push(format!("Source: {}", plan.source.display()));
+    if let Some(bytes) = plan.source_bytes {
+        lines.push(format!(
+            "Source size: {}",
+            crate::execute::format_size(bytes)
+        ));
+    }
     lines.push(format!("Destination: {}", plan.destination.display()));
     lines.push(format!(
         "Detected: {}",
@@ -176,43 +914,29 @@ pub fn render_plan(plan: &Plan, overwrite: bool) -> String {
     if let Some(ext) = plan.detected.ext_hint.as_deref() {
         lines.push(format!("Detected extension: {}", ext));
     }
-    lines.push(format!(
-        "Strategy: {}",
-        match plan.strategy {
-            Strategy::RenameOnly => "rename",
-            Strategy::CopyOnly => "copy",
-            Strategy::Convert => "convert",
-        }
-    ));
+    lines.push(format!("Strategy: {}", plan.strategy.label()));
     if let Some(ext) = plan.dest_ext.as_deref() {
         lines.push(format!("Destination extension: {}", ext));
     }
-    if let Some(backend) = &plan.backend {
-        lines.push(format!(
-            "Backend: {}",
-            match backend {
-                Backend::ImageMagick => "imagemagick",
-                Backend::Ffmpeg => "ffmpeg",
-                Backend::LibreOffice => "libreoffice",
-            }
-        ));
+    if let Some(backend) = plan.backend {
+        lines.push(format!("Backend: {}", backend.label()));
     }
-    lines.push(format!(
-        "Destination kind: {}",
-        match plan.dest_kind {
-            MediaKind::Image => "image",
-            MediaKind::Audio => "audio",
-            MediaKind::Video => "video",
-            MediaKind::Document => "document",
-            MediaKind::Other => "other",
-        }
-    ));
+    if let Some(reason) = plan.backend_reason.as_deref() {
+        lines.push(format!("Backend decision: {}", reason));
+    }
+    lines.push(format!("Destination kind: {}", plan.dest_kind.label()));
     if let Some(quality) = plan.options.image_quality {
         lines.push(format!("Image quality: {}", quality));
     }
     if let Some(bitrate) = plan.options.video_bitrate.as_deref() {
         lines.push(format!("Video bitrate: {}", bitrate));
     }
+    if let Some(crf) = plan.options.crf {
+        lines.push(format!("CRF: {crf}"));
+    }
+    if let Some(kind) = plan.options.hwaccel.as_deref() {
+        lines.push(format!("Hardware acceleration: {kind}"));
+    }
     if let Some(bitrate) = plan.options.audio_bitrate.as_deref() {
         lines.push(format!("Audio bitrate: {}", bitrate));
     }
@@ -225,6 +949,66 @@ pub fn render_plan(plan: &Plan, overwrite: bool) -> String {
     if let Some(codec) = plan.options.audio_codec.as_deref() {
         lines.push(format!("Audio codec: {}", codec));
     }
+    if plan.options.two_pass {
+        lines.push("Two-pass: yes".to_string());
+    }
+    if plan.options.mute {
+        lines.push("Mute: yes".to_string());
+    }
+    if let Some(speed) = plan.options.speed {
+        lines.push(format!("Speed: {}x", speed));
+    }
+    if let Some(trim) = describe_trim(&plan.options) {
+        lines.push(format!("Trim: {trim}"));
+    }
+    if plan.options.normalize {
+        lines.push(format!(
+            "Normalize: {} LUFS",
+            target_loudness(&plan.options)
+        ));
+    }
+    if plan.options.grayscale {
+        lines.push("Grayscale: yes".to_string());
+    }
+    if plan.options.srgb {
+        lines.push("sRGB: yes".to_string());
+    }
+    if let Some(profile) = plan.options.icc_profile.as_deref() {
+        lines.push(format!("ICC profile: {}", profile.display()));
+    }
+    if let Some(fit) = plan.options.fit.as_deref() {
+        lines.push(format!("Fit: {} (shrink only)", fit));
+    }
+    if let Some(geometry) = plan.options.resize.as_deref() {
+        lines.push(format!("Resize: {geometry}"));
+    }
+    if let Some(dpi) = plan.options.density {
+        lines.push(format!("Density: {dpi} DPI"));
+    }
+    if plan.options.lossless {
+        lines.push("Lossless: yes".to_string());
+    }
+    if let Some(effort) = plan.options.effort {
+        lines.push(format!("Effort: {effort}"));
+    }
+    if plan.options.metadata != Metadata::Default {
+        lines.push(format!("Metadata: {}", plan.options.metadata.label()));
+    }
+    if let Some(rotation) = plan.options.rotate {
+        lines.push(format!("Rotate: {}", rotation.label()));
+    }
+    if let Some(filter) = scale_cap_filter(&plan.options) {
+        lines.push(format!("Max resolution: {} (shrink only)", filter));
+    }
+    if plan.options.check_space {
+        lines.push("Check space: yes".to_string());
+    }
+    if let Some(seconds) = plan.options.timeout {
+        lines.push(format!("Timeout: {seconds}s"));
+    }
+    if plan.options.prefer_gs {
+        lines.push("PDF renderer: Ghostscript".to_string());
+    }
     if let Some(backend) = &plan.backend
         && *backend == Backend::Ffmpeg
     {
@@ -242,12 +1026,23 @@ pub fn render_plan(plan: &Plan, overwrite: bool) -> String {
     }
     lines.push(format!(
         "Overwrite: {}",
-        if overwrite { "yes" } else { "no" }
+        if plan.options.overwrite_newer {
+            "if source is newer"
+        } else if overwrite {
+            "yes"
+        } else {
+            "no"
+        }
     ));
     lines.push(format!(
         "Backup: {}",
         if plan.backup { "yes" } else { "no" }
     ));
+    if plan.backup
+        && let Some(width) = plan.options.backup_pad
+    {
+        lines.push(format!("Backup padding: {}", width));
+    }
     for note in &plan.notes {
         lines.push(format!("Note: {}", note));
     }
@@ -257,13 +1052,17 @@ pub fn render_plan(plan: &Plan, overwrite: bool) -> String {
 
 #[derive(Serialize)]
 struct PlanJson {
+    schema_version: u32,
     source: String,
     destination: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_bytes: Option<u64>,
     detected_mime: Option<String>,
     detected_file_mime: Option<String>,
     detected_extension: Option<String>,
     strategy: String,
     backend: Option<String>,
+    backend_reason: Option<String>,
     destination_kind: String,
     destination_extension: Option<String>,
     overwrite: bool,
@@ -271,131 +1070,544 @@ struct PlanJson {
     options: OptionsJson,
     notes: Vec<String>,
     command_preview: Option<String>,
+    /// Present only with `--probe`; `null` when ffprobe could not read the source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media_info: Option<Option<MediaInfoJson>>,
+}
+
+#[derive(Serialize)]
+struct MediaInfoJson {
+    duration_seconds: Option<f64>,
+    video_codec: Option<String>,
+    audio_codec: Option<String>,
 }
 
 #[derive(Serialize)]
 struct OptionsJson {
     image_quality: Option<u8>,
     video_bitrate: Option<String>,
+    crf: Option<u8>,
     audio_bitrate: Option<String>,
     preset: Option<String>,
     video_codec: Option<String>,
+    hwaccel: Option<String>,
     audio_codec: Option<String>,
     ffmpeg_mode: String,
+    two_pass: bool,
+    mute: bool,
+    speed: Option<f64>,
+    start: Option<String>,
+    duration: Option<String>,
+    to: Option<String>,
+    normalize: bool,
+    loudness: Option<f64>,
+    grayscale: bool,
+    srgb: bool,
+    icc_profile: Option<String>,
+    backup_pad: Option<usize>,
+    fit: Option<String>,
+    resize: Option<String>,
+    density: Option<u32>,
+    lossless: bool,
+    effort: Option<u8>,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+    rotate: Option<String>,
+    metadata: &'static str,
+    check_space: bool,
+    timeout: Option<u64>,
+    prefer_ghostscript: bool,
 }
 
-pub fn render_plan_json(plan: &Plan, overwrite: bool) -> Result<String> {
-    let output = PlanJson {
-        source: plan.source.display().to_string(),
-        destination: plan.destination.display().to_string(),
-        detected_mime: plan.detected.mime.clone(),
-        detected_file_mime: plan.detected.file_mime.clone(),
-        detected_extension: plan.detected.ext_hint.clone(),
-        strategy: match plan.strategy {
-            Strategy::RenameOnly => "rename".to_string(),
-            Strategy::CopyOnly => "copy".to_string(),
-            Strategy::Convert => "convert".to_string(),
-        },
-        backend: plan.backend.map(|backend| match backend {
-            Backend::ImageMagick => "imagemagick".to_string(),
-            Backend::Ffmpeg => "ffmpeg".to_string(),
-            Backend::LibreOffice => "libreoffice".to_string(),
-        }),
-        destination_kind: match plan.dest_kind {
-            MediaKind::Image => "image".to_string(),
-            MediaKind::Audio => "audio".to_string(),
-            MediaKind::Video => "video".to_string(),
-            MediaKind::Document => "document".to_string(),
-            MediaKind::Other => "other".to_string(),
-        },
-        destination_extension: plan.dest_ext.clone(),
-        overwrite,
-        backup: plan.backup,
-        options: OptionsJson {
-            image_quality: plan.options.image_quality,
-            video_bitrate: plan.options.video_bitrate.clone(),
-            audio_bitrate: plan.options.audio_bitrate.clone(),
-            preset: plan.options.preset.clone(),
-            video_codec: plan.options.video_codec.clone(),
+/// `probe` adds what ffprobe reports about ffmpeg sources as `media_info`.
+pub fn render_plan_json(plan: &Plan, overwrite: bool, probe: bool) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&plan_json(
+        plan, overwrite, probe,
+    ))?)
+}
+
+/// Batch dry runs print every plan as one JSON array.
+pub fn render_plans_json(plans: &[Plan], overwrite: bool, probe: bool) -> Result<String> {
+    let output: Vec<PlanJson> = plans
+        .iter()
+        .map(|plan| plan_json(plan, overwrite, probe))
+        .collect();
+    Ok(serde_json::to_string_pretty(&output)?)
+}
+
+/// Plans per strategy for `--audit`; conversions with no backend count as unsupported
+/// rather than convert.
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct PlanCounts {
+    pub convert: usize,
+    pub remux: usize,
+    pub copy: usize,
+    pub rename: usize,
+    pub unsupported: usize,
+}
+
+impl PlanCounts {
+    pub fn of(plans: &[Plan]) -> Self {
+        let mut counts = Self::default();
+        for plan in plans {
+            let count = match plan.strategy {
+                Strategy::Convert if plan.backend.is_none() => &mut counts.unsupported,
+                Strategy::Convert => &mut counts.convert,
+                Strategy::Remux => &mut counts.remux,
+                Strategy::CopyOnly => &mut counts.copy,
+                Strategy::RenameOnly => &mut counts.rename,
+            };
+            *count += 1;
+        }
+        counts
+    }
+}
+
+/// Destinations that more than one plan would write, with the sources targeting each.
+pub fn destination_conflicts(plans: &[Plan]) -> BTreeMap<&Path, Vec<&Path>> {
+    let mut targets: BTreeMap<&Path, Vec<&Path>> = BTreeMap::new();
+    for plan in plans {
+        targets
+            .entry(plan.destination.as_path())
+            .or_default()
+            .push(plan.source.as_path());
+    }
+    targets.retain(|_, sources| sources.len() > 1);
+    targets
+}
+
+#[derive(Serialize)]
+struct PlanAuditJson {
+    schema_version: u32,
+    summary: PlanSummaryJson,
+    conflicts: Vec<ConflictJson>,
+    failures: Vec<crate::output::FailureJson>,
+    plans: Vec<AuditedPlanJson>,
+}
+
+#[derive(Serialize)]
+struct PlanSummaryJson {
+    total: usize,
+    #[serde(flatten)]
+    counts: PlanCounts,
+    failed: usize,
+    conflicts: usize,
+}
+
+#[derive(Serialize)]
+struct ConflictJson {
+    destination: String,
+    sources: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct AuditedPlanJson {
+    #[serde(flatten)]
+    plan: PlanJson,
+    /// Other sources that would write the same destination.
+    conflicts_with: Vec<String>,
+}
+
+/// `--audit` with `--json`: every plan plus strategy counts, destination conflicts and
+/// the sources that could not be planned.
+pub fn render_plan_audit_json(
+    plans: &[Plan],
+    overwrite: bool,
+    probe: bool,
+    failures: Vec<crate::output::FailureJson>,
+) -> Result<String> {
+    let conflicts = destination_conflicts(plans);
+    let audited = plans
+        .iter()
+        .map(|plan| AuditedPlanJson {
+            plan: plan_json(plan, overwrite, probe),
+            conflicts_with: conflicts
+                .get(plan.destination.as_path())
+                .into_iter()
+                .flatten()
+                .filter(|source| **source != plan.source)
+                .map(|source| source.display().to_string())
+                .collect(),
+        })
+        .collect();
+    let output = PlanAuditJson {
+        schema_version: crate::output::SCHEMA_VERSION,
+        summary: PlanSummaryJson {
+            total: plans.len() + failures.len(),
+            counts: PlanCounts::of(plans),
+            failed: failures.len(),
+            conflicts: conflicts.len(),
+        },
+        conflicts: conflicts
+            .iter()
+            .map(|(destination, sources)| ConflictJson {
+                destination: destination.display().to_string(),
+                sources: sources
+                    .iter()
+                    .map(|source| source.display().to_string())
+                    .collect(),
+            })
+            .collect(),
+        failures,
+        plans: audited,
+    };
+    Ok(serde_json::to_string_pretty(&output)?)
+}
+
+/// `--audit` without `--json`: strategy counts and one line per destination conflict.
+pub fn render_plan_audit(plans: &[Plan]) -> String {
+    let counts = PlanCounts::of(plans);
+    let mut lines = vec![format!(
+        "Plan audit: convert {}, remux {}, copy {}, rename {}, unsupported {}",
+        counts.convert, counts.remux, counts.copy, counts.rename, counts.unsupported
+    )];
+    for (destination, sources) in destination_conflicts(plans) {
+        let sources: Vec<String> = sources
+            .iter()
+            .map(|source| source.display().to_string())
+            .collect();
+        lines.push(format!(
+            "Conflict: {} <- {}",
+            destination.display(),
+            sources.join(", ")
+        ));
+    }
+    lines.join("\n")
+}
+
+fn plan_json(plan: &Plan, overwrite: bool, probe: bool) -> PlanJson {
+    let mut notes = plan.notes.clone();
+    let media_info = (probe && plan.backend == Some(Backend::Ffmpeg)).then(|| {
+        // Planning already probed ffmpeg sources; this only runs ffprobe if it did not.
+        let mut source_probe = plan.probe.clone();
+        source_probe.read(&plan.source);
+        match source_probe {
+            SourceProbe::Read(info) => Some(MediaInfoJson {
+                duration_seconds: info.duration_seconds,
+                video_codec: info.video_codec,
+                audio_codec: info.audio_codec,
+            }),
+            SourceProbe::Missing => {
+                notes.push("ffprobe not installed; media info unavailable".to_string());
+                None
+            }
+            SourceProbe::Failed(err) => {
+                notes.push(format!("ffprobe could not read the source: {err}"));
+                None
+            }
+            SourceProbe::NotRun => None,
+        }
+    });
+    PlanJson {
+        schema_version: crate::output::SCHEMA_VERSION,
+        source: plan.source.display().to_string(),
+        destination: plan.destination.display().to_string(),
+        source_bytes: plan.source_bytes,
+        detected_mime: plan.detected.mime.clone(),
+        detected_file_mime: plan.detected.file_mime.clone(),
+        detected_extension: plan.detected.ext_hint.clone(),
+        strategy: plan.strategy.label().to_string(),
+        backend: plan.backend.map(|backend| backend.label().to_string()),
+        backend_reason: plan.backend_reason.clone(),
+        destination_kind: plan.dest_kind.label().to_string(),
+        destination_extension: plan.dest_ext.clone(),
+        overwrite,
+        backup: plan.backup,
+        options: OptionsJson {
+            image_quality: plan.options.image_quality,
+            video_bitrate: plan.options.video_bitrate.clone(),
+            crf: plan.options.crf,
+            audio_bitrate: plan.options.audio_bitrate.clone(),
+            preset: plan.options.preset.clone(),
+            video_codec: plan.options.video_codec.clone(),
+            hwaccel: plan.options.hwaccel.clone(),
             audio_codec: plan.options.audio_codec.clone(),
             ffmpeg_mode: match plan.options.ffmpeg_preference {
                 FfmpegPreference::Auto => "auto".to_string(),
                 FfmpegPreference::StreamCopy => "stream-copy".to_string(),
                 FfmpegPreference::Transcode => "transcode".to_string(),
             },
+            two_pass: plan.options.two_pass,
+            mute: plan.options.mute,
+            speed: plan.options.speed,
+            start: plan.options.start.clone(),
+            duration: plan.options.duration.clone(),
+            to: plan.options.to.clone(),
+            normalize: plan.options.normalize,
+            loudness: plan
+                .options
+                .normalize
+                .then(|| target_loudness(&plan.options)),
+            grayscale: plan.options.grayscale,
+            srgb: plan.options.srgb,
+            icc_profile: plan
+                .options
+                .icc_profile
+                .as_ref()
+                .map(|path| path.display().to_string()),
+            backup_pad: plan.options.backup_pad,
+            fit: plan.options.fit.clone(),
+            resize: plan.options.resize.clone(),
+            density: plan.options.density,
+            lossless: plan.options.lossless,
+            effort: plan.options.effort,
+            max_width: plan.options.max_width,
+            max_height: plan.options.max_height,
+            rotate: plan.options.rotate.map(Rotation::label),
+            metadata: plan.options.metadata.label(),
+            check_space: plan.options.check_space,
+            timeout: plan.options.timeout,
+            prefer_ghostscript: plan.options.prefer_gs,
         },
-        notes: plan.notes.clone(),
+        notes,
         command_preview: command_preview(plan),
-    };
-    Ok(serde_json::to_string_pretty(&output)?)
+        media_info,
+    }
 }
 
-fn normalize_ext(path: &Path) -> Option<String> {
-    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
-    let normalized = match ext.as_str() {
-        "jpeg" => "jpg",
-        "htm" => "html",
-        _ => ext.as_str(),
-    };
-    Some(normalized.to_string())
+pub fn normalize_ext(path: &Path) -> Option<String> {
+    Some(normalize_ext_name(path.extension()?.to_str()?))
 }
 
-fn select_backend(source_ext: Option<&str>, dest_ext: Option<&str>) -> Option<Backend> {
-    if is_image_ext(source_ext) && is_image_ext(dest_ext) {
-        return Some(Backend::ImageMagick);
+pub fn normalize_ext_name(ext: &str) -> String {
+    let ext = ext.trim_start_matches('.').to_ascii_lowercase();
+    match ext.as_str() {
+        "jpeg" => "jpg".to_string(),
+        "htm" => "html".to_string(),
+        _ => ext,
     }
-    if is_pdf_image_pair(source_ext, dest_ext) {
-        return Some(Backend::ImageMagick);
+}
+
+const IMAGE_EXTS: &[&str] = &[
+    "jpg", "jpeg", "png", "gif", "webp", "bmp", "tiff", "tif", "heic", "avif", "ico",
+];
+/// Sources whose frames, layers or pages ImageMagick indexes with an `[N]` selector.
+const FRAME_EXTS: &[&str] = &["gif", "webp", "tiff", "tif", "ico", "pdf"];
+const AUDIO_EXTS: &[&str] = &["mp3", "wav", "flac", "aac", "ogg", "m4a", "opus"];
+const VIDEO_EXTS: &[&str] = &["mp4", "mov", "mkv", "webm", "avi"];
+const DOCUMENT_EXTS: &[&str] = &[
+    "doc", "docx", "ppt", "pptx", "xls", "xlsx", "odt", "odp", "ods", "rtf", "txt", "csv",
+];
+/// LibreOffice only converts within a document family, so document-to-document routes
+/// are defined per family.
+const TEXT_EXTS: &[&str] = &["doc", "docx", "odt", "rtf", "txt"];
+const SPREADSHEET_EXTS: &[&str] = &["xls", "xlsx", "ods", "csv"];
+const PRESENTATION_EXTS: &[&str] = &["ppt", "pptx", "odp"];
+/// Camera RAW formats; sources only, developed by a RAW tool.
+const RAW_EXTS: &[&str] = &["cr2", "cr3", "nef", "arw", "dng", "raf", "orf"];
+/// Vector images; they are rasterized to other image formats, never written.
+const VECTOR_EXTS: &[&str] = &["svg"];
+
+/// Extension groups that conversion routes are defined between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Image,
+    Media,
+    Pdf,
+    Document,
+    Text,
+    Spreadsheet,
+    Presentation,
+    Raw,
+    Video,
+    Gif,
+    Vector,
+}
+
+impl Category {
+    pub const ALL: [Category; 11] = [
+        Category::Image,
+        Category::Media,
+        Category::Pdf,
+        Category::Document,
+        Category::Text,
+        Category::Spreadsheet,
+        Category::Presentation,
+        Category::Raw,
+        Category::Video,
+        Category::Gif,
+        Category::Vector,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Image => "image",
+            Category::Media => "media",
+            Category::Pdf => "pdf",
+            Category::Document => "document",
+            Category::Text => "text",
+            Category::Spreadsheet => "spreadsheet",
+            Category::Presentation => "presentation",
+            Category::Raw => "raw",
+            Category::Video => "video",
+            Category::Gif => "gif",
+            Category::Vector => "vector",
+        }
     }
-    if is_media_ext(source_ext) && is_media_ext(dest_ext) {
-        return Some(Backend::Ffmpeg);
+
+    pub fn extensions(self) -> Vec<&'static str> {
+        match self {
+            Category::Image => IMAGE_EXTS.to_vec(),
+            Category::Media => [AUDIO_EXTS, VIDEO_EXTS].concat(),
+            Category::Pdf => vec!["pdf"],
+            Category::Document => DOCUMENT_EXTS.to_vec(),
+            Category::Text => TEXT_EXTS.to_vec(),
+            Category::Spreadsheet => SPREADSHEET_EXTS.to_vec(),
+            Category::Presentation => PRESENTATION_EXTS.to_vec(),
+            Category::Raw => RAW_EXTS.to_vec(),
+            Category::Video => VIDEO_EXTS.to_vec(),
+            Category::Gif => vec!["gif"],
+            Category::Vector => VECTOR_EXTS.to_vec(),
+        }
     }
-    if is_document_ext(source_ext) && dest_ext == Some("pdf") {
-        return Some(Backend::LibreOffice);
+
+    fn contains(self, ext: Option<&str>) -> bool {
+        let Some(ext) = ext else {
+            return false;
+        };
+        match self {
+            Category::Image => IMAGE_EXTS.contains(&ext),
+            Category::Media => AUDIO_EXTS.contains(&ext) || VIDEO_EXTS.contains(&ext),
+            Category::Pdf => ext == "pdf",
+            Category::Document => DOCUMENT_EXTS.contains(&ext),
+            Category::Text => TEXT_EXTS.contains(&ext),
+            Category::Spreadsheet => SPREADSHEET_EXTS.contains(&ext),
+            Category::Presentation => PRESENTATION_EXTS.contains(&ext),
+            Category::Raw => RAW_EXTS.contains(&ext),
+            Category::Video => VIDEO_EXTS.contains(&ext),
+            Category::Gif => ext == "gif",
+            Category::Vector => VECTOR_EXTS.contains(&ext),
+        }
     }
-    None
 }
 
-fn is_image_ext(ext: Option<&str>) -> bool {
-    matches!(
-        ext,
-        Some("jpg" | "jpeg" | "png" | "gif" | "webp" | "bmp" | "tiff" | "tif" | "heic" | "avif")
-    )
+/// A supported source to destination conversion and the backend that runs it.
+pub struct Route {
+    pub source: Category,
+    pub destination: Category,
+    pub backend: Backend,
+    pub reason: &'static str,
 }
 
-fn is_media_ext(ext: Option<&str>) -> bool {
-    matches!(
-        ext,
-        Some(
-            "mp3"
-                | "wav"
-                | "flac"
-                | "aac"
-                | "ogg"
-                | "m4a"
-                | "opus"
-                | "mp4"
-                | "mov"
-                | "mkv"
-                | "webm"
-                | "avi"
-        )
-    )
+/// Conversion routing, checked in order; `--list-conversions` prints the same table.
+pub const ROUTES: &[Route] = &[
+    Route {
+        source: Category::Image,
+        destination: Category::Image,
+        backend: Backend::ImageMagick,
+        reason: "both extensions are images",
+    },
+    Route {
+        source: Category::Pdf,
+        destination: Category::Image,
+        backend: Backend::ImageMagick,
+        reason: "PDF and image pages convert with ImageMagick",
+    },
+    Route {
+        source: Category::Image,
+        destination: Category::Pdf,
+        backend: Backend::ImageMagick,
+        reason: "PDF and image pages convert with ImageMagick",
+    },
+    Route {
+        source: Category::Media,
+        destination: Category::Media,
+        backend: Backend::Ffmpeg,
+        reason: "both extensions are media",
+    },
+    Route {
+        source: Category::Video,
+        destination: Category::Gif,
+        backend: Backend::Ffmpeg,
+        reason: "ffmpeg renders video frames into an animated GIF",
+    },
+    Route {
+        source: Category::Gif,
+        destination: Category::Video,
+        backend: Backend::Ffmpeg,
+        reason: "ffmpeg encodes animated GIF frames as video",
+    },
+    Route {
+        source: Category::Document,
+        destination: Category::Pdf,
+        backend: Backend::LibreOffice,
+        reason: "documents export to PDF",
+    },
+    Route {
+        source: Category::Document,
+        destination: Category::Image,
+        backend: Backend::LibreOffice,
+        reason: "documents export to PDF before the first page is rendered",
+    },
+    Route {
+        source: Category::Text,
+        destination: Category::Text,
+        backend: Backend::LibreOffice,
+        reason: "LibreOffice converts between text document formats",
+    },
+    Route {
+        source: Category::Spreadsheet,
+        destination: Category::Spreadsheet,
+        backend: Backend::LibreOffice,
+        reason: "LibreOffice converts between spreadsheet formats",
+    },
+    Route {
+        source: Category::Presentation,
+        destination: Category::Presentation,
+        backend: Backend::LibreOffice,
+        reason: "LibreOffice converts between presentation formats",
+    },
+    Route {
+        source: Category::Raw,
+        destination: Category::Image,
+        backend: Backend::Raw,
+        reason: "camera RAW files are developed before encoding the image",
+    },
+    Route {
+        source: Category::Vector,
+        destination: Category::Image,
+        backend: Backend::ImageMagick,
+        reason: "ImageMagick rasterizes SVG through its SVG delegate",
+    },
+];
+
+/// Picks the conversion backend along with a one-line explanation for the plan.
+fn select_backend(source_ext: Option<&str>, dest_ext: Option<&str>) -> (Option<Backend>, String) {
+    let route = ROUTES
+        .iter()
+        .find(|route| route.source.contains(source_ext) && route.destination.contains(dest_ext));
+    match (route, source_ext, dest_ext) {
+        (Some(route), _, _) => (
+            Some(route.backend),
+            format!("chose {} because {}", route.backend.label(), route.reason),
+        ),
+        (None, None, _) => (None, "no backend: source has no extension".to_string()),
+        (None, _, None) => (None, "no backend: destination has no extension".to_string()),
+        (None, Some(source), Some(dest)) => (
+            None,
+            format!("no backend: {source} -> {dest} not supported"),
+        ),
+    }
+}
+
+fn is_image_ext(ext: Option<&str>) -> bool {
+    Category::Image.contains(ext)
 }
 
 fn is_audio_ext(ext: Option<&str>) -> bool {
-    matches!(
-        ext,
-        Some("mp3" | "wav" | "flac" | "aac" | "ogg" | "m4a" | "opus")
-    )
+    ext.is_some_and(|ext| AUDIO_EXTS.contains(&ext))
 }
 
 fn is_video_ext(ext: Option<&str>) -> bool {
-    matches!(ext, Some("mp4" | "mov" | "mkv" | "webm" | "avi"))
+    ext.is_some_and(|ext| VIDEO_EXTS.contains(&ext))
 }
 
-fn classify_dest_kind(ext: Option<&str>) -> MediaKind {
-    if is_image_ext(ext) {
+/// Media kind of the output. A GIF rendered from video is a video output, so video
+/// options and warnings apply to it.
+fn classify_dest_kind(source_ext: Option<&str>, ext: Option<&str>) -> MediaKind {
+    if is_video_to_gif(source_ext, ext) {
+        MediaKind::Video
+    } else if is_image_ext(ext) || Category::Raw.contains(ext) {
         MediaKind::Image
     } else if is_audio_ext(ext) {
         MediaKind::Audio
@@ -409,22 +1621,30 @@ fn classify_dest_kind(ext: Option<&str>) -> MediaKind {
 }
 
 fn is_document_ext(ext: Option<&str>) -> bool {
-    matches!(
-        ext,
-        Some(
-            "doc"
-                | "docx"
-                | "ppt"
-                | "pptx"
-                | "xls"
-                | "xlsx"
-                | "odt"
-                | "odp"
-                | "ods"
-                | "rtf"
-                | "txt"
-        )
-    )
+    Category::Document.contains(ext)
+}
+
+fn is_video_to_gif(source_ext: Option<&str>, dest_ext: Option<&str>) -> bool {
+    is_video_ext(source_ext) && dest_ext == Some("gif")
+}
+
+fn is_gif_to_video(source_ext: Option<&str>, dest_ext: Option<&str>) -> bool {
+    source_ext == Some("gif") && is_video_ext(dest_ext)
+}
+
+fn is_vector_ext(ext: Option<&str>) -> bool {
+    Category::Vector.contains(ext)
+}
+
+/// Sources that ImageMagick rasterizes at a density: SVG and PDF, including the PDF a
+/// document is exported to first.
+fn rasterizes_at_density(source_ext: Option<&str>, dest_ext: Option<&str>) -> bool {
+    is_image_ext(dest_ext)
+        && (is_vector_ext(source_ext) || source_ext == Some("pdf") || is_document_ext(source_ext))
+}
+
+fn is_document_image_pair(source_ext: Option<&str>, dest_ext: Option<&str>) -> bool {
+    is_document_ext(source_ext) && is_image_ext(dest_ext)
 }
 
 fn is_pdf_image_pair(source_ext: Option<&str>, dest_ext: Option<&str>) -> bool {
@@ -444,7 +1664,26 @@ fn validate_options(options: &ConversionOptions) -> Result<()> {
     if let Some(bitrate) = options.audio_bitrate.as_deref() {
         validate_bitrate(bitrate).context("invalid audio bitrate")?;
     }
-    if let Some(preset) = options.preset.as_deref() {
+    if let Some(kind) = options.hwaccel.as_deref() {
+        parse_hwaccel(kind)?;
+    }
+    validate_trim(options)?;
+    if let Some(crf) = options.crf {
+        if crf > MAX_CRF {
+            bail!("CRF must be between 0 and {MAX_CRF}");
+        }
+        if options.video_bitrate.is_some() {
+            bail!("CRF and video bitrate cannot be combined; pick one rate control");
+        }
+    }
+    let svt_av1 = matches!(options.video_codec.as_deref(), Some("av1" | "libsvtav1"));
+    if let Some(preset) = options.preset.as_deref()
+        && svt_av1
+    {
+        if !matches!(preset.parse::<u8>(), Ok(0..=13)) {
+            bail!("preset for libsvtav1 must be a number from 0 (slowest) to 13 (fastest)");
+        }
+    } else if let Some(preset) = options.preset.as_deref() {
         let preset = preset.to_ascii_lowercase();
         let allowed = [
             "ultrafast",
@@ -473,348 +1712,3395 @@ fn validate_options(options: &ConversionOptions) -> Result<()> {
     {
         bail!("audio codec must be a non-empty string");
     }
-    Ok(())
-}
-
-fn validate_bitrate(bitrate: &str) -> Result<()> {
-    if bitrate.is_empty() {
-        bail!("bitrate is empty");
+    if options.two_pass {
+        if options.crf.is_some() {
+            bail!("two-pass encoding cannot be combined with CRF; it needs a target bitrate");
+        }
+        if options.video_bitrate.is_none() {
+            bail!("two-pass encoding requires a video bitrate");
+        }
+        if options.ffmpeg_preference == FfmpegPreference::StreamCopy {
+            bail!("two-pass encoding cannot be combined with stream copy");
+        }
     }
-    let (value, suffix) = bitrate.split_at(bitrate.len().saturating_sub(1));
-    let (digits, suffix) = if suffix.chars().all(|c| c.is_ascii_digit()) {
-        (bitrate, "")
-    } else {
-        (value, suffix)
-    };
-    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
-        bail!("bitrate must be numeric with optional k/m suffix");
+    if let Some(speed) = options.speed {
+        if !speed.is_finite() || speed <= 0.0 {
+            bail!("speed must be a positive number");
+        }
+        if options.ffmpeg_preference == FfmpegPreference::StreamCopy {
+            bail!("speed changes cannot be combined with stream copy");
+        }
     }
-    if !suffix.is_empty() && !matches!(suffix, "k" | "K" | "m" | "M") {
-        bail!("bitrate suffix must be k or m");
+    if let Some(loudness) = options.loudness
+        && !(MIN_LOUDNESS..=MAX_LOUDNESS).contains(&loudness)
+    {
+        bail!("loudness must be between {MIN_LOUDNESS} and {MAX_LOUDNESS} LUFS");
+    }
+    if options.normalize && options.ffmpeg_preference == FfmpegPreference::StreamCopy {
+        bail!("loudness normalization cannot be combined with stream copy");
+    }
+    if options.frame.is_some() && options.all_pages {
+        bail!("frame selection cannot be combined with all pages");
+    }
+    if options.grayscale && (options.srgb || options.icc_profile.is_some()) {
+        bail!("grayscale cannot be combined with sRGB or ICC profile conversion");
+    }
+    if let Some(width) = options.backup_pad
+        && (width == 0 || width > 9)
+    {
+        bail!("backup padding must be between 1 and 9");
+    }
+    if let Some(fit) = options.fit.as_deref() {
+        parse_fit(fit)?;
+    }
+    if let Some(geometry) = options.resize.as_deref() {
+        validate_resize(geometry)?;
+    }
+    if options.density == Some(0) {
+        bail!("density must be positive");
+    }
+    if options.timeout == Some(0) {
+        bail!("timeout must be positive");
+    }
+    if let Some(effort) = options.effort
+        && effort > MAX_EFFORT
+    {
+        bail!("effort must be between 0 and {MAX_EFFORT}");
+    }
+    if options.max_width == Some(0) || options.max_height == Some(0) {
+        bail!("max width and height must be positive");
+    }
+    if let Some(profile) = options.icc_profile.as_deref()
+        && !profile.is_file()
+    {
+        bail!("ICC profile not found: {}", profile.display());
     }
     Ok(())
 }
 
-fn option_warnings(
+/// Whether the options would change a same-format output, i.e. whether converting
+/// `a.jpg` to `b.jpg` is more than a copy.
+fn transforms_output(
     options: &ConversionOptions,
     dest_kind: MediaKind,
-    backend: Option<Backend>,
-    source_ext: Option<&str>,
     dest_ext: Option<&str>,
-) -> Vec<String> {
-    let mut notes = Vec::new();
-    if dest_kind != MediaKind::Image && options.image_quality.is_some() {
-        notes.push("image quality ignored for non-image output".to_string());
-    }
-    if dest_kind == MediaKind::Document
-        && !is_pdf_image_pair(source_ext, dest_ext)
-        && (options.image_quality.is_some()
-            || options.video_bitrate.is_some()
-            || options.audio_bitrate.is_some()
-            || options.preset.is_some()
-            || options.video_codec.is_some()
-            || options.audio_codec.is_some())
-    {
-        notes.push("media options ignored for document conversions".to_string());
-    }
-    if dest_kind == MediaKind::Audio {
-        if options.video_bitrate.is_some() {
-            notes.push("video bitrate ignored for audio-only output".to_string());
+) -> bool {
+    match dest_kind {
+        MediaKind::Image => {
+            options.image_quality.is_some()
+                || (has_encoder_controls(dest_ext)
+                    && (options.lossless || options.effort.is_some()))
+                || options.fit.is_some()
+                || options.resize.is_some()
+                || options.frame.is_some()
+                || options.grayscale
+                || options.srgb
+                || options.icc_profile.is_some()
+                || options.metadata == Metadata::Strip
         }
-        if options.preset.is_some() {
-            notes.push("preset ignored for audio-only output".to_string());
+        MediaKind::Video | MediaKind::Audio => {
+            // Stream copy keeps streams as they are; only dropping audio or trimming changes
+            // the output.
+            if options.ffmpeg_preference == FfmpegPreference::StreamCopy {
+                return (dest_kind == MediaKind::Video && options.mute)
+                    || trims(options)
+                    || options.metadata == Metadata::Strip;
+            }
+            let shared = options.ffmpeg_preference == FfmpegPreference::Transcode
+                || options.metadata == Metadata::Strip
+                || options.audio_bitrate.is_some()
+                || options.audio_codec.is_some()
+                || options.speed.is_some()
+                || options.normalize
+                || trims(options);
+            let video_only = options.video_bitrate.is_some()
+                || options.crf.is_some()
+                || options.video_codec.is_some()
+                || options.preset.is_some()
+                || options.two_pass
+                || options.mute
+                || options.grayscale
+                || has_video_caps(options)
+                || options.rotate.is_some()
+                || options.tonemap
+                || options
+                    .hwaccel
+                    .as_deref()
+                    .is_some_and(|kind| kind != "none");
+            let audio_only = options.sample_fmt.is_some();
+            shared
+                || (dest_kind == MediaKind::Video && video_only)
+                || (dest_kind == MediaKind::Audio && audio_only)
         }
+        MediaKind::Document | MediaKind::Other => false,
     }
-    if dest_kind == MediaKind::Image && options.video_bitrate.is_some() {
-        notes.push("video bitrate ignored for image output".to_string());
-    }
-    if dest_kind == MediaKind::Image && options.audio_bitrate.is_some() {
-        notes.push("audio bitrate ignored for image output".to_string());
+}
+
+fn has_transform_options(options: &ConversionOptions, dest_ext: Option<&str>) -> bool {
+    transforms_output(options, MediaKind::Image, dest_ext)
+        || transforms_output(options, MediaKind::Video, dest_ext)
+        || transforms_output(options, MediaKind::Audio, dest_ext)
+        || options.ffmpeg_preference != FfmpegPreference::Auto
+}
+
+/// Parses a `WxH` box such as `1920x1080`.
+pub fn parse_fit(value: &str) -> Result<(u32, u32)> {
+    let parsed = value
+        .split_once(['x', 'X'])
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+    match parsed {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => bail!("fit must be WIDTHxHEIGHT with positive numbers (e.g. 1920x1080)"),
     }
-    if dest_kind == MediaKind::Image && options.video_codec.is_some() {
-        notes.push("video codec ignored for image output".to_string());
+}
+
+/// Parses a trim timestamp, either plain seconds (`90`, `12.5`) or `[HH:]MM:SS[.ms]`,
+/// into seconds.
+pub fn parse_timestamp(value: &str) -> Result<f64> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "timestamp must be seconds or [HH:]MM:SS[.ms], such as 90 or 00:01:30.5 (got {value})"
+        )
+    };
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
     }
-    if dest_kind == MediaKind::Image && options.audio_codec.is_some() {
-        notes.push("audio codec ignored for image output".to_string());
+    let (seconds, whole) = parts.split_last().ok_or_else(invalid)?;
+    let number = |part: &str| {
+        (!part.is_empty()
+            && part
+                .bytes()
+                .all(|byte| byte.is_ascii_digit() || byte == b'.'))
+        .then(|| part.parse::<f64>().ok())
+        .flatten()
+    };
+    let seconds = number(seconds).ok_or_else(invalid)?;
+    if !whole.is_empty() && seconds >= 60.0 {
+        return Err(invalid());
     }
-    if dest_kind == MediaKind::Audio && options.video_codec.is_some() {
-        notes.push("video codec ignored for audio-only output".to_string());
+    let mut total = seconds;
+    for (index, part) in whole.iter().rev().enumerate() {
+        let value = number(part)
+            .filter(|value| value.fract() == 0.0)
+            .ok_or_else(invalid)?;
+        // Minutes must stay under an hour once an hour field is present.
+        if index == 0 && whole.len() == 2 && value >= 60.0 {
+            return Err(invalid());
+        }
+        total += value * 60f64.powi(index as i32 + 1);
     }
-    if backend != Some(Backend::Ffmpeg) && options.ffmpeg_preference != FfmpegPreference::Auto {
-        notes.push("ffmpeg mode preference ignored for non-ffmpeg backend".to_string());
+    Ok(total)
+}
+
+/// Whether `--start`, `--duration` or `--to` trims the output.
+pub fn trims(options: &ConversionOptions) -> bool {
+    options.start.is_some() || options.duration.is_some() || options.to.is_some()
+}
+
+fn validate_trim(options: &ConversionOptions) -> Result<()> {
+    let start = match options.start.as_deref() {
+        Some(value) => parse_timestamp(value).context("invalid start")?,
+        None => 0.0,
+    };
+    if let Some(value) = options.duration.as_deref()
+        && parse_timestamp(value).context("invalid duration")? <= 0.0
+    {
+        bail!("duration must be longer than zero");
     }
-    if options.ffmpeg_preference == FfmpegPreference::StreamCopy {
-        if options.video_bitrate.is_some() {
-            notes.push("video bitrate ignored when stream copy is forced".to_string());
-        }
-        if options.audio_bitrate.is_some() {
-            notes.push("audio bitrate ignored when stream copy is forced".to_string());
-        }
-        if options.preset.is_some() {
-            notes.push("preset ignored when stream copy is forced".to_string());
-        }
-        if options.video_codec.is_some() {
-            notes.push("video codec ignored when stream copy is forced".to_string());
+    if let Some(value) = options.to.as_deref() {
+        if options.duration.is_some() {
+            bail!("duration and to cannot be combined; pick one end for the trim");
         }
-        if options.audio_codec.is_some() {
-            notes.push("audio codec ignored when stream copy is forced".to_string());
+        if parse_timestamp(value).context("invalid end time")? <= start {
+            bail!("end time must come after the start");
         }
     }
-    notes
+    Ok(())
 }
 
-pub fn default_video_codec(dest_ext: Option<&str>) -> Option<&'static str> {
-    match dest_ext {
-        Some("mp4") | Some("mov") => Some("libx264"),
-        Some("webm") => Some("libvpx-vp9"),
-        Some("mkv") | Some("avi") => Some("libx264"),
-        _ => None,
+/// Length of the trimmed output in source seconds, given the full source duration when
+/// it is known.
+pub fn trimmed_seconds(options: &ConversionOptions, full: Option<f64>) -> Option<f64> {
+    let timestamp = |value: Option<&str>| value.and_then(|value| parse_timestamp(value).ok());
+    let start = timestamp(options.start.as_deref()).unwrap_or(0.0);
+    let end = match (
+        timestamp(options.duration.as_deref()),
+        timestamp(options.to.as_deref()),
+    ) {
+        (Some(duration), _) => Some(start + duration),
+        (None, Some(to)) => Some(to),
+        (None, None) => full,
+    };
+    let end = match (end, full) {
+        (Some(end), Some(full)) => Some(end.min(full)),
+        (end, _) => end,
+    };
+    end.map(|end| (end - start).max(0.0))
+}
+
+/// The `-t` length of a trim. An end time becomes the length from the start, since a
+/// seek before `-i` restarts the timeline at zero.
+fn trim_length(options: &ConversionOptions) -> Option<String> {
+    match (options.duration.as_deref(), options.to.as_deref()) {
+        (Some(duration), _) => Some(duration.to_string()),
+        (None, Some(_)) => trimmed_seconds(options, None).map(format_seconds),
+        (None, None) => None,
     }
 }
 
-pub fn default_audio_codec(dest_ext: Option<&str>, dest_kind: MediaKind) -> Option<&'static str> {
-    if dest_kind == MediaKind::Audio {
-        return match dest_ext {
-            Some("mp3") => Some("libmp3lame"),
-            Some("flac") => Some("flac"),
-            Some("wav") => Some("pcm_s16le"),
-            Some("opus") => Some("libopus"),
-            Some("ogg") => Some("libvorbis"),
-            Some("m4a") | Some("aac") => Some("aac"),
-            _ => None,
-        };
+/// Seconds rounded to milliseconds, without trailing zeros.
+fn format_seconds(seconds: f64) -> String {
+    let rounded = (seconds * 1000.0).round() / 1000.0;
+    format!("{rounded}")
+}
+
+fn describe_trim(options: &ConversionOptions) -> Option<String> {
+    if !trims(options) {
+        return None;
     }
-    match dest_ext {
-        Some("mp4") | Some("mov") => Some("aac"),
-        Some("webm") => Some("libopus"),
-        Some("mkv") | Some("avi") => Some("aac"),
-        _ => None,
+    let start = options.start.as_deref().unwrap_or("0");
+    Some(match (options.duration.as_deref(), options.to.as_deref()) {
+        (Some(duration), _) => format!("{duration} from {start}"),
+        (None, Some(to)) => format!("{start} to {to}"),
+        (None, None) => format!("from {start} to the end"),
+    })
+}
+
+/// Checks an ImageMagick resize geometry: a width, `x` and a height (either may be left
+/// out, not both), an optional `%`, then at most one of the `>`, `<`, `^` or `!` flags.
+pub fn validate_resize(value: &str) -> Result<()> {
+    let rest = value.strip_suffix(['>', '<', '^', '!']).unwrap_or(value);
+    let rest = rest.strip_suffix('%').unwrap_or(rest);
+    let (width, height) = rest.split_once(['x', 'X']).unwrap_or((rest, ""));
+    let dimension = |part: &str| {
+        part.is_empty()
+            || (part.bytes().all(|byte| byte.is_ascii_digit())
+                && part.parse::<u32>().is_ok_and(|size| size > 0))
+    };
+    if (width.is_empty() && height.is_empty()) || !dimension(width) || !dimension(height) {
+        bail!(
+            "resize must be an ImageMagick geometry such as 1920x1080, 800x, x600 or 50%, \
+             optionally ending in >, <, ^ or ! (got {value})"
+        );
     }
+    Ok(())
 }
 
-fn command_preview(plan: &Plan) -> Option<String> {
-    let backend = plan.backend?;
-    let source = plan.source.display();
-    let destination = plan.destination.display();
-    match backend {
-        Backend::ImageMagick => {
-            let mut args = vec![format!("magick {}", source)];
-            if let Some(quality) = plan.options.image_quality {
-                args.push(format!("-quality {}", quality));
-            }
-            args.push(format!("{}", destination));
-            Some(args.join(" "))
-        }
-        Backend::Ffmpeg => {
-            let mut base = vec![format!("ffmpeg -i {}", source)];
-            let dest_ext = plan.dest_ext.as_deref();
-            match plan.options.ffmpeg_preference {
-                FfmpegPreference::StreamCopy => {
-                    base.push("-c copy".to_string());
-                    base.push(format!("{}", destination));
-                    return Some(base.join(" "));
-                }
-                FfmpegPreference::Transcode => {}
-                FfmpegPreference::Auto => {
-                    let mut copy = base.clone();
-                    copy.push("-c copy".to_string());
-                    copy.push(format!("{}", destination));
-                    let transcode = ffmpeg_transcode_args(plan, dest_ext);
-                    let mut transcode_cmd = base;
-                    transcode_cmd.extend(transcode);
-                    transcode_cmd.push(format!("{}", destination));
-                    return Some(format!(
-                        "{} (if compatible), else {}",
-                        copy.join(" "),
-                        transcode_cmd.join(" ")
-                    ));
-                }
-            }
-            let transcode = ffmpeg_transcode_args(plan, dest_ext);
-            base.extend(transcode);
-            base.push(format!("{}", destination));
-            Some(base.join(" "))
+/// Explains the fit box. The source's size is read only when the plan runs (see
+/// `settle_fit`), so planning a large batch never starts ImageMagick.
+fn fit_note(
+    source_ext: Option<&str>,
+    dest_kind: MediaKind,
+    options: &ConversionOptions,
+) -> Option<String> {
+    let fit = options.fit.as_deref()?;
+    (dest_kind == MediaKind::Image && is_image_ext(source_ext)).then(|| {
+        format!(
+            "fit {fit}: checked against the source's size when converting; a source already \
+             inside the box is not resized, and a same-format output is copied instead"
+        )
+    })
+}
+
+/// Settles `--fit` once the plan runs, against the source's real size. A source already
+/// inside the box drops the resize, and a same-format output with nothing else to change
+/// becomes a copy (or a move). `None` leaves the plan as it is, including when the size
+/// cannot be read; ImageMagick's shrink-only resize then decides.
+pub fn settle_fit(plan: &Plan) -> Option<Plan> {
+    let fit = plan.options.fit.as_deref()?;
+    let source_ext = normalize_ext(&plan.source);
+    if plan.strategy != Strategy::Convert
+        || plan.dest_kind != MediaKind::Image
+        || !is_image_ext(source_ext.as_deref())
+    {
+        return None;
+    }
+    let (max_width, max_height) = parse_fit(fit).ok()?;
+    let (width, height) = image_dimensions(&plan.source).ok().flatten()?;
+    if width > max_width || height > max_height {
+        return None;
+    }
+    let mut settled = plan.clone();
+    settled.options.fit = None;
+    let same_format = source_ext.is_some() && source_ext == plan.dest_ext;
+    if same_format
+        && !transforms_output(
+            &settled.options,
+            settled.dest_kind,
+            settled.dest_ext.as_deref(),
+        )
+    {
+        settled.strategy = if plan.move_source {
+            Strategy::RenameOnly
+        } else {
+            Strategy::CopyOnly
+        };
+        settled.backend = None;
+        settled.backend_reason = None;
+    }
+    Some(settled)
+}
+
+/// Checks `--frame` against the source's frame (or page) count when ImageMagick or
+/// pdfinfo can report it, and drops it for sources and outputs without frames.
+fn resolve_frame(
+    source: &Path,
+    source_ext: Option<&str>,
+    dest_kind: MediaKind,
+    options: &mut ConversionOptions,
+) -> Result<Option<String>> {
+    let Some(frame) = options.frame else {
+        return Ok(None);
+    };
+    if dest_kind != MediaKind::Image || !source_ext.is_some_and(|ext| FRAME_EXTS.contains(&ext)) {
+        options.frame = None;
+        return Ok(Some(
+            "frame ignored; only GIF, WebP, TIFF, ICO and PDF sources going to an image have frames"
+                .to_string(),
+        ));
+    }
+    let pdf = source_ext == Some("pdf");
+    let count = if pdf {
+        pdf_page_count(source).ok().flatten()
+    } else {
+        image_frame_count(source).ok().flatten()
+    };
+    let unit = if pdf { "page" } else { "frame" };
+    match count {
+        Some(count) if frame >= count => bail!(
+            "frame {frame} is out of range: {} has {count} {unit}{} (frames count from 0)",
+            source.display(),
+            if count == 1 { "" } else { "s" }
+        ),
+        // The PDF notes already name the page and the page count.
+        _ if pdf => Ok(None),
+        Some(count) => Ok(Some(format!("converts frame {frame} of {count}"))),
+        None => Ok(Some(format!(
+            "converts frame {frame}; frame count unknown, so ImageMagick fails if it does not exist"
+        ))),
+    }
+}
+
+/// Keeps `--all-pages` only for PDFs going to an image that pdfinfo reports as having
+/// more than one page; otherwise the first page is converted as usual.
+fn resolve_all_pages(
+    source: &Path,
+    destination: &Path,
+    source_ext: Option<&str>,
+    dest_kind: MediaKind,
+    options: &mut ConversionOptions,
+) -> Option<String> {
+    if !options.all_pages {
+        return None;
+    }
+    options.all_pages = false;
+    if source_ext != Some("pdf") || dest_kind != MediaKind::Image {
+        return Some(
+            "all pages ignored; only PDF sources going to an image have pages".to_string(),
+        );
+    }
+    match pdf_page_count(source) {
+        Ok(Some(pages)) if pages > 1 => {
+            options.all_pages = true;
+            let name = |number: &str| {
+                numbered_destination(destination, number)
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            Some(format!(
+                "PDF to image converts all {pages} pages to {} … {}",
+                name("1"),
+                name(&pages.to_string())
+            ))
+        }
+        Ok(Some(_)) => None,
+        _ => Some(
+            "PDF page count unknown (install poppler-utils for pdfinfo); converting the \
+             first page only"
+                .to_string(),
+        ),
+    }
+}
+
+/// `destination` with `-<number>` appended to its stem, e.g. `scan-2.png`. With
+/// `PAGE_PATTERN` as the number it is the output pattern ImageMagick and Ghostscript fill in.
+pub fn numbered_destination(destination: &Path, number: &str) -> PathBuf {
+    let stem = destination
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let name = match destination.extension() {
+        Some(ext) => format!("{stem}-{number}.{}", ext.to_string_lossy()),
+        None => format!("{stem}-{number}"),
+    };
+    destination.with_file_name(name)
+}
+
+/// Page number placeholder in `--all-pages` output names; both tools count from 1 here.
+pub const PAGE_PATTERN: &str = "%d";
+
+/// Where the backend writes: the destination, or its numbered pattern with `--all-pages`.
+fn output_target(plan: &Plan) -> PathBuf {
+    if plan.options.all_pages {
+        numbered_destination(&plan.destination, PAGE_PATTERN)
+    } else {
+        plan.destination.clone()
+    }
+}
+
+fn has_video_caps(options: &ConversionOptions) -> bool {
+    options.max_width.is_some() || options.max_height.is_some()
+}
+
+/// Probes video sources and drops the resolution caps when the source already fits,
+/// so the conversion can stay a stream copy. Caps that do apply force a transcode.
+fn resolve_video_caps(
+    source: &Path,
+    source_ext: Option<&str>,
+    dest_kind: MediaKind,
+    options: &mut ConversionOptions,
+    probe: &mut SourceProbe,
+) -> Option<String> {
+    if !has_video_caps(options) || dest_kind != MediaKind::Video || !is_video_ext(source_ext) {
+        return None;
+    }
+    if options.ffmpeg_preference == FfmpegPreference::StreamCopy {
+        options.max_width = None;
+        options.max_height = None;
+        return Some("max width/height ignored with stream copy".to_string());
+    }
+    let dimensions = probe
+        .read(source)
+        .and_then(|info| Some((info.width?, info.height?)));
+    match dimensions {
+        Some((width, height))
+            if options.max_width.is_none_or(|max| width <= max)
+                && options.max_height.is_none_or(|max| height <= max) =>
+        {
+            options.max_width = None;
+            options.max_height = None;
+            Some(format!(
+                "source is {width}x{height}, within the max resolution; scaling skipped"
+            ))
         }
-        Backend::LibreOffice => Some(format!(
-            "soffice --headless --convert-to pdf --outdir <temp> {}",
-            source
+        Some((width, height)) => Some(format!(
+            "source is {width}x{height}; downscaling requires a transcode"
         )),
+        None => {
+            Some("source size unknown; video is transcoded and only shrunk if larger".to_string())
+        }
     }
 }
 
-fn ffmpeg_transcode_args(plan: &Plan, dest_ext: Option<&str>) -> Vec<String> {
-    let mut args = Vec::new();
-    if plan.dest_kind == MediaKind::Video {
-        let video_codec = plan
-            .options
-            .video_codec
-            .as_deref()
-            .or_else(|| default_video_codec(dest_ext));
-        if let Some(codec) = video_codec {
-            args.push(format!("-c:v {}", codec));
+/// Compares `--audio-bitrate` with the probed source bitrate: asking for more cannot
+/// add quality, so this notes it, or caps it with `--no-upscale-bitrate`.
+fn resolve_audio_bitrate(
+    source: &Path,
+    source_ext: Option<&str>,
+    dest_kind: MediaKind,
+    options: &mut ConversionOptions,
+    probe: &mut SourceProbe,
+) -> Option<String> {
+    let requested = parse_bitrate(options.audio_bitrate.as_deref()?)?;
+    if !matches!(dest_kind, MediaKind::Audio | MediaKind::Video)
+        || !(is_audio_ext(source_ext) || is_video_ext(source_ext))
+        || options.ffmpeg_preference == FfmpegPreference::StreamCopy
+    {
+        return None;
+    }
+    let source_bitrate = probe.read(source)?.audio_bitrate?;
+    cap_audio_bitrate(requested, source_bitrate, options)
+}
+
+fn cap_audio_bitrate(
+    requested: u64,
+    source_bitrate: u64,
+    options: &mut ConversionOptions,
+) -> Option<String> {
+    if requested <= source_bitrate {
+        return None;
+    }
+    let asked = options.audio_bitrate.clone()?;
+    let source_rate = format!("{}k", source_bitrate / 1000);
+    if options.no_upscale_bitrate {
+        options.audio_bitrate = Some(source_rate.clone());
+        return Some(format!(
+            "audio bitrate capped at the source's {source_rate} (requested {asked})"
+        ));
+    }
+    Some(format!(
+        "requested audio bitrate {asked} exceeds the source's {source_rate} and will not \
+         improve quality; --no-upscale-bitrate caps it"
+    ))
+}
+
+/// Bits per second for an ffmpeg bitrate such as `192k`, `1.5M` or `320000`.
+pub fn parse_bitrate(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, scale) = match value.char_indices().last()? {
+        (index, 'k' | 'K') => (&value[..index], 1_000.0),
+        (index, 'm' | 'M') => (&value[..index], 1_000_000.0),
+        _ => (value, 1.0),
+    };
+    let number: f64 = number.parse().ok()?;
+    (number > 0.0).then(|| (number * scale).round() as u64)
+}
+
+/// ffmpeg `scale` filter for `--max-width`/`--max-height`. `min()` keeps smaller
+/// frames at their size, and `-2` keeps the aspect ratio with an even dimension.
+pub fn scale_cap_filter(options: &ConversionOptions) -> Option<String> {
+    match (options.max_width, options.max_height) {
+        (Some(width), Some(height)) => Some(format!(
+            "scale='min(iw,{width})':'min(ih,{height})':force_original_aspect_ratio=decrease:force_divisible_by=2"
+        )),
+        (Some(width), None) => Some(format!("scale='min(iw,{width})':-2")),
+        (None, Some(height)) => Some(format!("scale=-2:'min(ih,{height})'")),
+        (None, None) => None,
+    }
+}
+
+fn validate_bitrate(bitrate: &str) -> Result<()> {
+    if bitrate.is_empty() {
+        bail!("bitrate is empty");
+    }
+    let (value, suffix) = bitrate.split_at(bitrate.len().saturating_sub(1));
+    let (digits, suffix) = if suffix.chars().all(|c| c.is_ascii_digit()) {
+        (bitrate, "")
+    } else {
+        (value, suffix)
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        bail!("bitrate must be numeric with optional k/m suffix");
+    }
+    if !suffix.is_empty() && !matches!(suffix, "k" | "K" | "m" | "M") {
+        bail!("bitrate suffix must be k or m");
+    }
+    Ok(())
+}
+
+fn option_warnings(
+    options: &ConversionOptions,
+    dest_kind: MediaKind,
+    backend: Option<Backend>,
+    source_ext: Option<&str>,
+    dest_ext: Option<&str>,
+) -> Vec<String> {
+    let mut notes = Vec::new();
+    let imagemagick = backend == Some(Backend::ImageMagick)
+        || (backend.is_some() && is_document_image_pair(source_ext, dest_ext));
+    if dest_kind != MediaKind::Image && options.image_quality.is_some() {
+        notes.push("image quality ignored for non-image output".to_string());
+    }
+    if dest_kind == MediaKind::Document
+        && !is_pdf_image_pair(source_ext, dest_ext)
+        && (options.image_quality.is_some()
+            || options.video_bitrate.is_some()
+            || options.audio_bitrate.is_some()
+            || options.preset.is_some()
+            || options.video_codec.is_some()
+            || options.audio_codec.is_some())
+    {
+        notes.push("media options ignored for document conversions".to_string());
+    }
+    if dest_kind == MediaKind::Audio {
+        if options.video_bitrate.is_some() {
+            notes.push("video bitrate ignored for audio-only output".to_string());
         }
-        if let Some(bitrate) = plan.options.video_bitrate.as_deref() {
-            args.push(format!("-b:v {}", bitrate));
+        if options.preset.is_some() {
+            notes.push("preset ignored for audio-only output".to_string());
         }
-        if let Some(preset) = plan.options.preset.as_deref() {
-            args.push(format!("-preset {}", preset));
+    }
+    if dest_kind == MediaKind::Image && options.video_bitrate.is_some() {
+        notes.push("video bitrate ignored for image output".to_string());
+    }
+    if dest_kind == MediaKind::Image && options.audio_bitrate.is_some() {
+        notes.push("audio bitrate ignored for image output".to_string());
+    }
+    if dest_kind == MediaKind::Image && options.video_codec.is_some() {
+        notes.push("video codec ignored for image output".to_string());
+    }
+    if dest_kind == MediaKind::Image && options.audio_codec.is_some() {
+        notes.push("audio codec ignored for image output".to_string());
+    }
+    if options.ogg_opus && dest_ext != Some("ogg") {
+        notes.push("Ogg Opus preference ignored for non-ogg output".to_string());
+    }
+    if dest_kind == MediaKind::Audio && options.video_codec.is_some() {
+        notes.push("video codec ignored for audio-only output".to_string());
+    }
+    if options.mute {
+        if dest_kind != MediaKind::Video {
+            notes.push("mute ignored for non-video output".to_string());
+        } else {
+            if options.audio_codec.is_some() {
+                notes.push("audio codec ignored when audio is muted".to_string());
+            }
+            if options.audio_bitrate.is_some() {
+                notes.push("audio bitrate ignored when audio is muted".to_string());
+            }
         }
-        let audio_codec = plan
-            .options
-            .audio_codec
-            .as_deref()
-            .or_else(|| default_audio_codec(dest_ext, plan.dest_kind));
-        if let Some(codec) = audio_codec {
-            args.push(format!("-c:a {}", codec));
+    }
+    if options.sample_fmt.is_some() && dest_kind != MediaKind::Audio {
+        notes.push("sample format ignored for non-audio output".to_string());
+    }
+    if options.dither && (options.sample_fmt.is_none() || dest_kind != MediaKind::Audio) {
+        notes.push("dither ignored without a sample format for audio output".to_string());
+    }
+    if options.speed.is_some() && !matches!(dest_kind, MediaKind::Video | MediaKind::Audio) {
+        notes.push("speed ignored for non-media output".to_string());
+    }
+    if options.grayscale && !imagemagick && dest_kind != MediaKind::Video {
+        notes.push("grayscale ignored for non-visual output".to_string());
+    }
+    let renders_pdf = dest_kind == MediaKind::Image
+        && (source_ext == Some("pdf") || is_document_ext(source_ext))
+        && backend.is_some();
+    if options.prefer_gs && !renders_pdf {
+        notes.push("Ghostscript preference ignored for non-PDF-to-image conversions".to_string());
+    }
+    if options.prefer_gs
+        && renders_pdf
+        && ghostscript_device(dest_ext, options.grayscale).is_some()
+        && (options.fit.is_some()
+            || options.resize.is_some()
+            || options.srgb
+            || options.icc_profile.is_some())
+    {
+        notes.push(
+            "fit, resize and color profile options are not applied by Ghostscript".to_string(),
+        );
+    }
+    if options.fit.is_some() && (!imagemagick || dest_kind != MediaKind::Image) {
+        notes.push("fit ignored for non-image output".to_string());
+    }
+    if options.resize.is_some() && (!imagemagick || dest_kind != MediaKind::Image) {
+        notes.push("resize ignored for non-image output".to_string());
+    }
+    if options.density.is_some() && !(imagemagick && rasterizes_at_density(source_ext, dest_ext)) {
+        notes.push("density ignored for non-vector sources".to_string());
+    }
+    if options.metadata == Metadata::Keep && backend.is_some() && backend != Some(Backend::Ffmpeg) {
+        notes.push("keep metadata only applies to ffmpeg conversions".to_string());
+    }
+    if options.metadata == Metadata::Strip && backend == Some(Backend::LibreOffice) && !imagemagick
+    {
+        notes.push("strip metadata ignored for LibreOffice conversions".to_string());
+    }
+    let tunable = imagemagick && has_encoder_controls(dest_ext);
+    if (options.lossless || options.effort.is_some()) && !tunable {
+        notes.push("lossless and effort only apply to WebP and AVIF output".to_string());
+    }
+    if options.lossless && tunable && options.image_quality.is_some() {
+        notes.push("image quality ignored for lossless output".to_string());
+    }
+    if has_video_caps(options) && dest_kind != MediaKind::Video {
+        notes.push("max width/height ignored for non-video output".to_string());
+    }
+    if is_video_to_gif(source_ext, dest_ext)
+        && (options.video_bitrate.is_some() || options.crf.is_some() || options.preset.is_some())
+    {
+        notes.push("video bitrate, CRF and preset ignored for GIF output".to_string());
+    }
+    if trims(options) && backend.is_some() && backend != Some(Backend::Ffmpeg) {
+        notes.push("start/duration/to ignored for non-ffmpeg conversions".to_string());
+    }
+    if options.rotate.is_some() && dest_kind != MediaKind::Video {
+        notes.push("rotation ignored for non-video output".to_string());
+    }
+    if options.tonemap && dest_kind != MediaKind::Video {
+        notes.push("tone mapping ignored for non-video output".to_string());
+    }
+    if options.rotate == Some(Rotation::Auto)
+        && dest_kind == MediaKind::Video
+        && backend == Some(Backend::Ffmpeg)
+    {
+        notes.push(
+            "source rotation is read at runtime; rotated clips are transcoded with a transpose \
+             filter"
+                .to_string(),
+        );
+    }
+    if !imagemagick && (options.srgb || options.icc_profile.is_some()) {
+        notes.push("color profile options ignored for non-ImageMagick conversions".to_string());
+    }
+    if dest_kind != MediaKind::Video && options.crf.is_some() {
+        notes.push("CRF ignored for non-video output".to_string());
+    }
+    if options.crf.is_some() && hw_encoder(options, dest_kind, dest_ext).is_some() {
+        notes.push("CRF ignored by hardware encoders".to_string());
+    }
+    if dest_kind != MediaKind::Video && options.two_pass {
+        notes.push("two-pass ignored for non-video output".to_string());
+    }
+    if options.normalize && !normalizes_audio(options, dest_kind, dest_ext) {
+        notes.push("normalize ignored for output without audio".to_string());
+    }
+    if options.loudness.is_some() && !options.normalize {
+        notes.push("loudness ignored without normalize".to_string());
+    }
+    if backend != Some(Backend::Ffmpeg) && options.ffmpeg_preference != FfmpegPreference::Auto {
+        notes.push("ffmpeg mode preference ignored for non-ffmpeg backend".to_string());
+    }
+    if options.ffmpeg_preference == FfmpegPreference::StreamCopy {
+        if options.video_bitrate.is_some() {
+            notes.push("video bitrate ignored when stream copy is forced".to_string());
         }
-        if let Some(bitrate) = plan.options.audio_bitrate.as_deref() {
-            args.push(format!("-b:a {}", bitrate));
+        if options.crf.is_some() {
+            notes.push("CRF ignored when stream copy is forced".to_string());
         }
-    } else if plan.dest_kind == MediaKind::Audio {
-        let audio_codec = plan
-            .options
-            .audio_codec
-            .as_deref()
-            .or_else(|| default_audio_codec(dest_ext, plan.dest_kind));
-        if let Some(codec) = audio_codec {
-            args.push(format!("-c:a {}", codec));
+        if options.audio_bitrate.is_some() {
+            notes.push("audio bitrate ignored when stream copy is forced".to_string());
+        }
+        if options.preset.is_some() {
+            notes.push("preset ignored when stream copy is forced".to_string());
+        }
+        if options.video_codec.is_some() {
+            notes.push("video codec ignored when stream copy is forced".to_string());
+        }
+        if options.audio_codec.is_some() {
+            notes.push("audio codec ignored when stream copy is forced".to_string());
         }
-        if let Some(bitrate) = plan.options.audio_bitrate.as_deref() {
-            args.push(format!("-b:a {}", bitrate));
+        if options.sample_fmt.is_some() {
+            notes.push("sample format ignored when stream copy is forced".to_string());
         }
+        if options.tonemap {
+            notes.push("tone mapping ignored when stream copy is forced".to_string());
+        }
+    }
+    notes
+}
+
+fn default_video_codec(dest_ext: Option<&str>) -> Option<&'static str> {
+    match dest_ext {
+        Some("mp4") | Some("mov") => Some("libx264"),
+        Some("webm") => Some("libvpx-vp9"),
+        Some("mkv") | Some("avi") => Some("libx264"),
+        _ => None,
+    }
+}
+
+/// `--video-codec` with `av1` meaning SVT-AV1, else the container default.
+pub fn video_encoder<'a>(
+    options: &'a ConversionOptions,
+    dest_ext: Option<&str>,
+) -> Option<&'a str> {
+    match options.video_codec.as_deref() {
+        Some("av1") => Some("libsvtav1"),
+        Some(codec) => Some(codec),
+        None => default_video_codec(dest_ext),
+    }
+}
+
+/// Constant-quality defaults for AV1 encoders. A `--video-bitrate` replaces the CRF, and
+/// libaom needs `-b:v 0` alongside a CRF to run in pure quality mode.
+pub fn av1_default_args(encoder: &str, options: &ConversionOptions) -> Vec<&'static str> {
+    let mut args = Vec::new();
+    let (crf, zero_bitrate) = match encoder {
+        "libsvtav1" => ("35", false),
+        "libaom-av1" => ("30", true),
+        _ => return args,
+    };
+    if options.video_bitrate.is_none() {
+        if options.crf.is_none() {
+            args.extend(["-crf", crf]);
+        }
+        if zero_bitrate {
+            args.extend(["-b:v", "0"]);
+        }
+    }
+    if encoder == "libsvtav1" && options.preset.is_none() {
+        args.extend(["-preset", "8"]);
+    }
+    args
+}
+
+/// DRM render node that VAAPI encodes on.
+const VAAPI_DEVICE: &str = "/dev/dri/renderD128";
+
+/// The hardware encoder `--hwaccel` swaps in for a video output's codec, when that kind
+/// of hardware has one; otherwise the software encoder stays.
+pub fn hw_encoder(
+    options: &ConversionOptions,
+    dest_kind: MediaKind,
+    dest_ext: Option<&str>,
+) -> Option<&'static str> {
+    if dest_kind != MediaKind::Video {
+        return None;
+    }
+    let kind = options.hwaccel.as_deref()?;
+    let codec = video_encoder(options, dest_ext).and_then(encoder_codec)?;
+    match (kind, codec) {
+        ("nvenc", "h264") => Some("h264_nvenc"),
+        ("nvenc", "hevc") => Some("hevc_nvenc"),
+        ("nvenc", "av1") => Some("av1_nvenc"),
+        ("vaapi", "h264") => Some("h264_vaapi"),
+        ("vaapi", "hevc") => Some("hevc_vaapi"),
+        ("vaapi", "av1") => Some("av1_vaapi"),
+        ("vaapi", "vp8") => Some("vp8_vaapi"),
+        ("vaapi", "vp9") => Some("vp9_vaapi"),
+        ("videotoolbox", "h264") => Some("h264_videotoolbox"),
+        ("videotoolbox", "hevc") => Some("hevc_videotoolbox"),
+        _ => None,
+    }
+}
+
+fn is_hw_encoder(encoder: &str) -> bool {
+    ["_nvenc", "_vaapi", "_videotoolbox"]
+        .iter()
+        .any(|suffix| encoder.ends_with(suffix))
+}
+
+/// VAAPI encoders take frames in GPU memory, so decoded and filtered frames are
+/// uploaded as the last filter.
+pub fn hw_upload_filter(hw_encoder: Option<&str>) -> Option<&'static str> {
+    hw_encoder
+        .is_some_and(|encoder| encoder.ends_with("_vaapi"))
+        .then_some("format=nv12,hwupload")
+}
+
+/// Highest `--crf` accepted; x264/x265 stop at 51, VP9 and AV1 at 63.
+pub const MAX_CRF: u8 = 63;
+
+/// `-crf` for `--crf`. libvpx also needs `-b:v 0`, or its default bitrate caps the quality.
+/// Hardware encoders have their own quality scales and get nothing.
+pub fn crf_args(options: &ConversionOptions, encoder: Option<&str>) -> Vec<String> {
+    let Some(crf) = options.crf else {
+        return Vec::new();
+    };
+    if encoder.is_some_and(is_hw_encoder) {
+        return Vec::new();
+    }
+    let mut args = vec!["-crf".to_string(), crf.to_string()];
+    if encoder
+        .and_then(encoder_codec)
+        .is_some_and(|codec| matches!(codec, "vp8" | "vp9"))
+    {
+        args.extend(["-b:v".to_string(), "0".to_string()]);
+    }
+    args
+}
+
+/// `--audio-codec`, else Opus for `--ogg-opus` `.ogg` outputs, else the container default.
+pub fn audio_encoder<'a>(
+    options: &'a ConversionOptions,
+    dest_ext: Option<&str>,
+    dest_kind: MediaKind,
+) -> Option<&'a str> {
+    if let Some(codec) = options.audio_codec.as_deref() {
+        return Some(codec);
+    }
+    if options.ogg_opus && dest_ext == Some("ogg") {
+        return Some("libopus");
+    }
+    if dest_kind == MediaKind::Audio
+        && dest_ext == Some("wav")
+        && let Some(codec) = options.sample_fmt.as_deref().and_then(wav_codec)
+    {
+        return Some(codec);
+    }
+    default_audio_codec(dest_ext, dest_kind)
+}
+
+/// Names the Ogg muxer for Opus in `.ogg`, which otherwise reads as a Vorbis file.
+pub fn audio_format_arg(encoder: &str, dest_ext: Option<&str>) -> Option<&'static str> {
+    (dest_ext == Some("ogg") && matches!(encoder, "libopus" | "opus")).then_some("ogg")
+}
+
+fn default_audio_codec(dest_ext: Option<&str>, dest_kind: MediaKind) -> Option<&'static str> {
+    if dest_kind == MediaKind::Audio {
+        return match dest_ext {
+            Some("mp3") => Some("libmp3lame"),
+            Some("flac") => Some("flac"),
+            Some("wav") => Some("pcm_s16le"),
+            Some("opus") => Some("libopus"),
+            Some("ogg") => Some("libvorbis"),
+            Some("m4a") | Some("aac") => Some("aac"),
+            _ => None,
+        };
+    }
+    match dest_ext {
+        Some("mp4") | Some("mov") => Some("aac"),
+        Some("webm") => Some("libopus"),
+        Some("mkv") | Some("avi") => Some("aac"),
+        _ => None,
+    }
+}
+
+pub fn command_preview(plan: &Plan) -> Option<String> {
+    let backend = plan.backend?;
+    let source = plan.source.display();
+    let destination = plan.destination.display();
+    match backend {
+        Backend::ImageMagick if uses_ghostscript(plan) => {
+            ghostscript_preview(plan, &source.to_string())
+        }
+        Backend::ImageMagick => {
+            let input = imagemagick_source(&plan.source, plan.dest_ext.as_deref(), &plan.options);
+            let mut args = vec!["magick".to_string()];
+            args.extend(density_args(&plan.source, &plan.options));
+            args.push(input.to_string_lossy().into_owned());
+            args.extend(imagemagick_args(&plan.options, plan.dest_ext.as_deref()));
+            args.push(format!("{}", output_target(plan).display()));
+            Some(args.join(" "))
+        }
+        Backend::Ffmpeg => {
+            let mut base = vec!["ffmpeg".to_string()];
+            base.extend(ffmpeg_input_args(
+                &plan.options,
+                sets_rotation(plan).is_some(),
+                hw_encoder(&plan.options, plan.dest_kind, plan.dest_ext.as_deref()),
+            ));
+            base.push(format!("-i {source}"));
+            base.extend(ffmpeg_metadata_args(&plan.options));
+            let dest_ext = plan.dest_ext.as_deref();
+            if uses_two_pass(plan) {
+                let mut first = base.clone();
+                first.extend(ffmpeg_video_args(plan, dest_ext));
+                first.push(format!("-pass 1 -passlogfile <temp>/{TWO_PASS_LOG_PREFIX}"));
+                first.push(format!("-an -f null {NULL_DEVICE}"));
+                base.extend(ffmpeg_transcode_args(plan, dest_ext));
+                base.push(format!("-pass 2 -passlogfile <temp>/{TWO_PASS_LOG_PREFIX}"));
+                base.push(format!("{}", destination));
+                return Some(format!("{} && {}", first.join(" "), base.join(" ")));
+            }
+            match plan.options.ffmpeg_preference {
+                _ if plan.strategy == Strategy::Remux => {
+                    base.extend(ffmpeg_copy_args(plan));
+                    base.push(format!("{}", destination));
+                    return Some(base.join(" "));
+                }
+                FfmpegPreference::StreamCopy => {
+                    base.extend(ffmpeg_copy_args(plan));
+                    base.push(format!("{}", destination));
+                    return Some(base.join(" "));
+                }
+                FfmpegPreference::Transcode => {}
+                FfmpegPreference::Auto if forces_transcode(plan) => {}
+                FfmpegPreference::Auto => {
+                    let mut copy = base.clone();
+                    copy.extend(ffmpeg_copy_args(plan));
+                    copy.push(format!("{}", destination));
+                    let transcode = ffmpeg_transcode_args(plan, dest_ext);
+                    let mut transcode_cmd = base;
+                    transcode_cmd.extend(transcode);
+                    transcode_cmd.push(format!("{}", destination));
+                    return Some(format!(
+                        "{} (if compatible), else {}",
+                        copy.join(" "),
+                        transcode_cmd.join(" ")
+                    ));
+                }
+            }
+            let transcode = ffmpeg_transcode_args(plan, dest_ext);
+            base.extend(transcode);
+            base.push(format!("{}", destination));
+            Some(base.join(" "))
+        }
+        Backend::LibreOffice => {
+            let target = if rasterizes_document(plan) {
+                "pdf".to_string()
+            } else {
+                libreoffice_target(plan.dest_ext.as_deref()?)
+            };
+            let soffice = format!(
+                "soffice --headless --convert-to {target} --outdir <temp> {}",
+                source
+            );
+            if !rasterizes_document(plan) {
+                return Some(soffice);
+            }
+            let pdf = document_intermediate(&plan.source);
+            let mut args = vec![format!("magick <temp>/{pdf}[0]")];
+            args.extend(imagemagick_args(&plan.options, plan.dest_ext.as_deref()));
+            args.push(format!("{}", destination));
+            Some(format!("{soffice} && {}", args.join(" ")))
+        }
+        Backend::Raw => {
+            let tool = find_raw_tool()?;
+            let tiff = format!("<temp>/{RAW_INTERMEDIATE}");
+            let (develop, input) = match develop_args(tool, &plan.source, Path::new(&tiff)) {
+                Some(args) => (format!("{} {} && ", tool.label(), args.join(" ")), tiff),
+                None => (String::new(), source.to_string()),
+            };
+            let mut args = vec![format!("{develop}magick {input}")];
+            args.extend(imagemagick_args(&plan.options, plan.dest_ext.as_deref()));
+            args.push(format!("{}", destination));
+            Some(args.join(" "))
+        }
+    }
+}
+
+/// `-map_metadata` for `--strip-metadata` and `--keep-metadata`; an output option, so it
+/// follows `-i`. One argv entry per element, shared by the preview and the real command.
+pub fn ffmpeg_metadata_args(options: &ConversionOptions) -> Vec<String> {
+    let source = match options.metadata {
+        Metadata::Default => return Vec::new(),
+        Metadata::Strip => "-1",
+        Metadata::Keep => "0",
+    };
+    vec!["-map_metadata".to_string(), source.to_string()]
+}
+
+/// File name of the PDF LibreOffice writes inside the temp directory before a document
+/// is rendered to an image. It is the name LibreOffice picks itself, so nothing is renamed.
+pub fn document_intermediate(source: &Path) -> String {
+    let stem = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("{stem}.pdf")
+}
+
+/// File name of the TIFF a RAW developer writes inside the temp directory.
+pub const RAW_INTERMEDIATE: &str = "raw.tiff";
+
+/// ffmpeg flags that describe how to read the input and so must precede `-i`; after it
+/// they would apply to the output. `noautorotate` is set when mvx writes the rotation
+/// itself, and the hardware encoder brings its decoder and device. One argv entry per element, shared by the preview and the real command.
+pub fn ffmpeg_input_args(
+    options: &ConversionOptions,
+    noautorotate: bool,
+    hw_encoder: Option<&str>,
+) -> Vec<String> {
+    let mut args = Vec::new();
+    // Trims apply to the input: seeking there jumps to the nearest keyframe instead of
+    // decoding up to it, and the length stays in source time even when `--speed`
+    // retimes the output.
+    if let Some(start) = options.start.as_deref() {
+        args.extend(["-ss".to_string(), start.to_string()]);
+    }
+    if let Some(length) = trim_length(options) {
+        args.extend(["-t".to_string(), length]);
+    }
+    match hw_encoder.and_then(|encoder| encoder.rsplit_once('_')) {
+        Some((_, "nvenc")) => args.extend(["-hwaccel".to_string(), "cuda".to_string()]),
+        Some((_, "vaapi")) => args.extend([
+            "-hwaccel".to_string(),
+            "vaapi".to_string(),
+            "-vaapi_device".to_string(),
+            VAAPI_DEVICE.to_string(),
+        ]),
+        Some((_, "videotoolbox")) => {
+            args.extend(["-hwaccel".to_string(), "videotoolbox".to_string()])
+        }
+        _ => {}
+    }
+    if noautorotate {
+        args.push("-noautorotate".to_string());
+    }
+    args
+}
+
+/// The input argument for ImageMagick: `--frame N` selects one frame with `[N]`, and
+/// PDFs going to an image are otherwise limited to the first page with `[0]` unless
+/// `--all-pages` asks for every page.
+pub fn imagemagick_source(
+    source: &Path,
+    dest_ext: Option<&str>,
+    options: &ConversionOptions,
+) -> std::ffi::OsString {
+    let mut arg = source.as_os_str().to_os_string();
+    if let Some(frame) = options.frame {
+        arg.push(format!("[{frame}]"));
+    } else if normalize_ext(source).as_deref() == Some("pdf")
+        && dest_ext.is_some_and(|ext| ext != "pdf")
+        && !options.all_pages
+    {
+        arg.push("[0]");
+    }
+    arg
+}
+
+/// `-density` for SVG and PDF inputs; it must precede the input to set the resolution
+/// they are read at.
+pub fn density_args(source: &Path, options: &ConversionOptions) -> Vec<String> {
+    let source_ext = normalize_ext(source);
+    match options.density {
+        Some(dpi)
+            if is_vector_ext(source_ext.as_deref()) || source_ext.as_deref() == Some("pdf") =>
+        {
+            vec!["-density".to_string(), dpi.to_string()]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Highest `--effort`, matching WebP's slowest `method`.
+pub const MAX_EFFORT: u8 = 6;
+
+/// Formats whose ImageMagick encoders take the lossless and effort defines.
+fn has_encoder_controls(dest_ext: Option<&str>) -> bool {
+    matches!(dest_ext, Some("webp" | "avif"))
+}
+
+/// The `-quality` to write with. Lossless WebP ignores quality, and lossless AVIF is
+/// quality 100, which ImageMagick's HEIF coder encodes losslessly.
+pub fn imagemagick_quality(options: &ConversionOptions, dest_ext: Option<&str>) -> Option<u8> {
+    match dest_ext {
+        Some("webp") if options.lossless => None,
+        Some("avif") if options.lossless => Some(100),
+        _ => options.image_quality,
+    }
+}
+
+/// `-define` values for `--lossless` and `--effort`. AVIF takes a speed from 9 (fastest)
+/// down to 0 rather than an effort, so the scale is flipped onto it.
+pub fn encoder_defines(options: &ConversionOptions, dest_ext: Option<&str>) -> Vec<String> {
+    let mut defines = Vec::new();
+    match dest_ext {
+        Some("webp") => {
+            if options.lossless {
+                defines.push("webp:lossless=true".to_string());
+            }
+            if let Some(effort) = options.effort {
+                defines.push(format!("webp:method={effort}"));
+            }
+        }
+        Some("avif") => {
+            if options.lossless {
+                defines.push("heic:chroma=444".to_string());
+            }
+            if let Some(effort) = options.effort {
+                defines.push(format!("heic:speed={}", 9 - effort * 3 / 2));
+            }
+        }
+        _ => {}
+    }
+    defines
+}
+
+fn imagemagick_args(options: &ConversionOptions, dest_ext: Option<&str>) -> Vec<String> {
+    let mut args = Vec::new();
+    // Stripping first keeps an ICC profile that `--icc-profile` assigns later.
+    if options.metadata == Metadata::Strip {
+        args.push("-strip".to_string());
+    }
+    if let Some(quality) = imagemagick_quality(options, dest_ext) {
+        args.push(format!("-quality {}", quality));
+    }
+    for define in encoder_defines(options, dest_ext) {
+        args.push(format!("-define {define}"));
+    }
+    if let Some(fit) = options.fit.as_deref() {
+        args.push(format!("-resize '{fit}>'"));
+    }
+    if let Some(geometry) = options.resize.as_deref() {
+        args.push(format!("-resize '{geometry}'"));
+    }
+    if options.grayscale {
+        args.push("-colorspace Gray".to_string());
+    }
+    args.extend(color_profile_args(options));
+    if options.all_pages {
+        args.push("-scene 1".to_string());
+    }
+    args
+}
+
+/// Resolution for Ghostscript rasterization; ImageMagick's PDF default of 72 DPI
+/// looks soft on screen.
+pub const GHOSTSCRIPT_DPI: u32 = 150;
+
+/// Ghostscript output device for an image extension, or `None` when Ghostscript
+/// cannot write that format.
+pub fn ghostscript_device(dest_ext: Option<&str>, grayscale: bool) -> Option<&'static str> {
+    let device = match (dest_ext?, grayscale) {
+        ("png", false) => "png16m",
+        ("png", true) => "pnggray",
+        ("jpg" | "jpeg", false) => "jpeg",
+        ("jpg" | "jpeg", true) => "jpeggray",
+        ("tif" | "tiff", false) => "tiff24nc",
+        ("tif" | "tiff", true) => "tiffgray",
+        ("bmp", false) => "bmp16m",
+        ("bmp", true) => "bmpgray",
+        _ => return None,
+    };
+    Some(device)
+}
+
+/// Ghostscript command line for rendering one page of `source` (the first, or the
+/// `--frame` page) into `dest`, or every page with `--all-pages`.
+pub fn ghostscript_args(
+    options: &ConversionOptions,
+    dest_ext: Option<&str>,
+) -> Option<Vec<String>> {
+    let device = ghostscript_device(dest_ext, options.grayscale)?;
+    let page = options.frame.map_or(1, |frame| frame + 1);
+    let mut args = vec![
+        "-dSAFER".to_string(),
+        "-dBATCH".to_string(),
+        "-dNOPAUSE".to_string(),
+        "-dQUIET".to_string(),
+        format!("-sDEVICE={device}"),
+        format!("-r{}", options.density.unwrap_or(GHOSTSCRIPT_DPI)),
+    ];
+    if !options.all_pages {
+        args.push(format!("-dFirstPage={page}"));
+        args.push(format!("-dLastPage={page}"));
+    }
+    if let Some(quality) = options.image_quality
+        && device.starts_with("jpeg")
+    {
+        args.push(format!("-dJPEGQ={quality}"));
+    }
+    Some(args)
+}
+
+/// PDF sources go straight to Ghostscript only when asked to and the format allows it;
+/// otherwise Ghostscript is the fallback for ImageMagick policy errors.
+pub fn uses_ghostscript(plan: &Plan) -> bool {
+    plan.options.prefer_gs
+        && plan.backend == Some(Backend::ImageMagick)
+        && normalize_ext(&plan.source).as_deref() == Some("pdf")
+        && ghostscript_device(plan.dest_ext.as_deref(), plan.options.grayscale).is_some()
+}
+
+fn ghostscript_preview(plan: &Plan, source: &str) -> Option<String> {
+    let args = ghostscript_args(&plan.options, plan.dest_ext.as_deref())?;
+    Some(format!(
+        "gs {} -sOutputFile={} {}",
+        args.join(" "),
+        output_target(plan).display(),
+        source
+    ))
+}
+
+fn pdf_renderer_note(options: &ConversionOptions, dest_ext: Option<&str>) -> String {
+    match ghostscript_device(dest_ext, options.grayscale) {
+        Some(_) if options.prefer_gs => "PDF pages are rendered with Ghostscript".to_string(),
+        Some(_) => "PDF pages are rendered with ImageMagick, falling back to Ghostscript if \
+                    its security policy blocks PDF"
+            .to_string(),
+        None => "PDF pages are rendered with ImageMagick (Ghostscript cannot write this format)"
+            .to_string(),
+    }
+}
+
+/// `--convert-to` value for a destination extension. Plain text needs its filter named,
+/// since LibreOffice would otherwise pick one by the source's family.
+pub fn libreoffice_target(dest_ext: &str) -> String {
+    match dest_ext {
+        "txt" => "txt:Text".to_string(),
+        ext => ext.to_string(),
+    }
+}
+
+/// Document to image runs LibreOffice into a PDF inside the temp directory, then hands
+/// that PDF to ImageMagick.
+pub fn rasterizes_document(plan: &Plan) -> bool {
+    plan.backend == Some(Backend::LibreOffice) && plan.dest_kind == MediaKind::Image
+}
+
+/// ImageMagick arguments for `--srgb` / `--icc`. Converting to sRGB without a target
+/// profile drops the embedded one, which would otherwise no longer match the pixels.
+pub fn color_profile_args(options: &ConversionOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    if options.srgb {
+        args.push("-colorspace sRGB".to_string());
+    }
+    match options.icc_profile.as_deref() {
+        Some(profile) => args.push(format!("-profile {}", profile.display())),
+        None if options.srgb => args.push("+profile icc".to_string()),
+        None => {}
+    }
+    args
+}
+
+/// Rough size of the output, used by `--check-space`. Without a better model the
+/// source size stands in for conversions as well as copies; renames need no space.
+pub fn estimated_output_bytes(plan: &Plan) -> Option<u64> {
+    if plan.strategy == Strategy::RenameOnly {
+        return Some(0);
+    }
+    std::fs::metadata(&plan.source)
+        .ok()
+        .map(|metadata| metadata.len())
+}
+
+/// Copies at or above this size use the buffered large-file path.
+pub const LARGE_COPY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Log file prefix passed to ffmpeg `-passlogfile`, relative to the temp directory.
+pub const TWO_PASS_LOG_PREFIX: &str = "ffmpeg2pass";
+
+/// Where the first pass of a two-pass encode writes its discarded output.
+pub const NULL_DEVICE: &str = if cfg!(windows) { "NUL" } else { "/dev/null" };
+
+pub fn uses_two_pass(plan: &Plan) -> bool {
+    plan.options.two_pass && plan.dest_kind == MediaKind::Video
+}
+
+/// Options that rewrite streams rule out stream copy even in auto mode.
+pub fn forces_transcode(plan: &Plan) -> bool {
+    let source_ext = normalize_ext(&plan.source);
+    uses_two_pass(plan)
+        || !gif_filters(source_ext.as_deref(), plan.dest_ext.as_deref()).is_empty()
+        || plan.options.speed.is_some()
+        || normalizes_audio(&plan.options, plan.dest_kind, plan.dest_ext.as_deref())
+        || (plan.dest_kind == MediaKind::Audio && plan.options.sample_fmt.is_some())
+        || (plan.dest_kind == MediaKind::Video
+            && (plan.options.grayscale || has_video_caps(&plan.options)))
+}
+
+/// Stream copy or transcode for an ffmpeg plan, given what ffprobe reported about the
+/// source. `build_plan` uses it to classify remuxes; execution re-checks at runtime.
+pub fn decide_ffmpeg_mode(plan: &Plan, info: Option<&MediaInfo>) -> FfmpegMode {
+    match plan.options.ffmpeg_preference {
+        FfmpegPreference::StreamCopy => return FfmpegMode::StreamCopy,
+        FfmpegPreference::Transcode => return FfmpegMode::Transcode,
+        FfmpegPreference::Auto => {}
+    }
+    if forces_transcode(plan) {
+        return FfmpegMode::Transcode;
+    }
+    // Turning a rotated clip upright, or tone-mapping HDR, re-encodes its pixels.
+    if auto_rotation_transposes(plan, info) || tonemaps(plan, info) {
+        return FfmpegMode::Transcode;
+    }
+    let dest_ext = match plan.dest_ext.as_deref() {
+        Some(ext) => ext,
+        None => return FfmpegMode::Transcode,
+    };
+    let Some(info) = info else {
+        return FfmpegMode::Transcode;
+    };
+    if plan.dest_kind == MediaKind::Audio || info.video_codec.is_none() {
+        return decide_audio_copy(plan, dest_ext, info.audio_codec.as_deref());
+    }
+    let Some(video) = info.video_codec.as_deref() else {
+        return FfmpegMode::Transcode;
+    };
+    // An explicit encoder only allows copying a stream it would have produced anyway.
+    if let Some(codec) = plan.options.video_codec.as_deref()
+        && encoder_codec(codec) != Some(video)
+    {
+        return FfmpegMode::Transcode;
+    }
+    // Muted outputs drop the audio track, so only video compatibility matters.
+    let audio = if mutes_audio(plan) {
+        None
+    } else {
+        info.audio_codec.as_deref()
+    };
+
+    if dest_ext == "mkv" {
+        return FfmpegMode::StreamCopy;
+    }
+
+    match dest_ext {
+        "mp4" | "mov" => {
+            let video_ok = matches!(video, "h264" | "hevc" | "mpeg4" | "av1");
+            let audio_ok = audio.is_none_or(|codec| container_takes_audio(dest_ext, codec));
+            if video_ok && audio_ok {
+                FfmpegMode::StreamCopy
+            } else {
+                FfmpegMode::Transcode
+            }
+        }
+        "webm" => {
+            let video_ok = matches!(video, "vp8" | "vp9" | "av1");
+            let audio_ok = audio.is_none_or(|codec| container_takes_audio(dest_ext, codec));
+            if video_ok && audio_ok {
+                FfmpegMode::StreamCopy
+            } else {
+                FfmpegMode::Transcode
+            }
+        }
+        _ => FfmpegMode::Transcode,
+    }
+}
+
+/// Audio-only outputs (and sources) copy when the container takes the codec as is and
+/// no requested encoder or bitrate asks for something else.
+fn decide_audio_copy(plan: &Plan, dest_ext: &str, audio: Option<&str>) -> FfmpegMode {
+    let Some(audio) = audio else {
+        return FfmpegMode::Transcode;
+    };
+    let requested = match plan.options.audio_codec.as_deref() {
+        Some(codec) => Some(codec),
+        None if plan.options.ogg_opus && dest_ext == "ogg" => Some("libopus"),
+        None => None,
+    };
+    let encoder_ok = requested.is_none_or(|codec| audio_encoder_codec(codec) == Some(audio));
+    if encoder_ok && plan.options.audio_bitrate.is_none() && container_takes_audio(dest_ext, audio)
+    {
+        FfmpegMode::StreamCopy
+    } else {
+        FfmpegMode::Transcode
+    }
+}
+
+/// Audio codecs each container can hold without re-encoding.
+fn container_takes_audio(dest_ext: &str, codec: &str) -> bool {
+    match dest_ext {
+        "opus" => codec == "opus",
+        "ogg" => matches!(codec, "opus" | "vorbis" | "flac"),
+        "webm" => matches!(codec, "opus" | "vorbis"),
+        "m4a" | "mp4" | "mov" => matches!(codec, "aac" | "alac" | "opus" | "mp3"),
+        "mp3" => codec == "mp3",
+        "flac" => codec == "flac",
+        "aac" => codec == "aac",
+        "mkv" => true,
+        _ => false,
+    }
+}
+
+/// ffprobe codec name produced by an `--audio-codec` encoder.
+fn audio_encoder_codec(encoder: &str) -> Option<&'static str> {
+    match encoder {
+        "opus" | "libopus" => Some("opus"),
+        "vorbis" | "libvorbis" => Some("vorbis"),
+        "aac" | "libfdk_aac" => Some("aac"),
+        "mp3" | "libmp3lame" => Some("mp3"),
+        "flac" => Some("flac"),
+        "alac" => Some("alac"),
+        _ => None,
+    }
+}
+
+/// `--rotate auto` on a video whose display matrix turns it, which needs a transpose
+/// filter.
+pub fn auto_rotation_transposes(plan: &Plan, info: Option<&MediaInfo>) -> bool {
+    plan.options.rotate == Some(Rotation::Auto)
+        && plan.dest_kind == MediaKind::Video
+        && info
+            .and_then(|info| info.rotation)
+            .and_then(transpose_filter)
+            .is_some()
+}
+
+/// ffprobe codec name produced by a `--video-codec` encoder.
+fn encoder_codec(encoder: &str) -> Option<&'static str> {
+    match encoder {
+        "av1" | "libsvtav1" | "libaom-av1" | "librav1e" => Some("av1"),
+        "h264" | "libx264" => Some("h264"),
+        "hevc" | "libx265" => Some("hevc"),
+        "vp9" | "libvpx-vp9" => Some("vp9"),
+        "vp8" | "libvpx" => Some("vp8"),
+        _ => None,
+    }
+}
+
+pub fn mutes_audio(plan: &Plan) -> bool {
+    drops_audio(&plan.options, plan.dest_kind, plan.dest_ext.as_deref())
+}
+
+/// `--mute` on a video output, or a GIF rendered from video, which has no audio track.
+pub fn drops_audio(
+    options: &ConversionOptions,
+    dest_kind: MediaKind,
+    dest_ext: Option<&str>,
+) -> bool {
+    dest_kind == MediaKind::Video && (options.mute || dest_ext == Some("gif"))
+}
+
+/// Frame rate of GIFs rendered from video; the source rate would balloon the file.
+pub const GIF_FPS: u32 = 10;
+
+/// Builds the palette from the clip itself, which looks far better than GIF's generic one.
+const GIF_PALETTE_FILTER: &str =
+    "split[frames][sample];[sample]palettegen[palette];[frames][palette]paletteuse";
+
+/// Filters GIF needs on either end of an ffmpeg conversion, applied after the shared
+/// video filters: video to GIF drops the frame rate and builds a palette, GIF to video
+/// rounds to the even dimensions yuv420p requires.
+pub fn gif_filters(source_ext: Option<&str>, dest_ext: Option<&str>) -> Vec<String> {
+    if is_video_to_gif(source_ext, dest_ext) {
+        vec![format!("fps={GIF_FPS}"), GIF_PALETTE_FILTER.to_string()]
+    } else if is_gif_to_video(source_ext, dest_ext) {
+        vec!["scale=trunc(iw/2)*2:trunc(ih/2)*2".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Pixel format for GIF to video: encoders would otherwise keep GIF's RGB colors in a
+/// 4:4:4 stream that most players cannot show.
+pub fn gif_pixel_format(source_ext: Option<&str>, dest_ext: Option<&str>) -> Option<&'static str> {
+    is_gif_to_video(source_ext, dest_ext).then_some("yuv420p")
+}
+
+fn ffmpeg_copy_args(plan: &Plan) -> Vec<String> {
+    let mut args = vec!["-c copy".to_string()];
+    if mutes_audio(plan) {
+        args.push("-an".to_string());
+    }
+    if plan.dest_kind == MediaKind::Audio {
+        args.push("-vn".to_string());
+    }
+    args.extend(rotation_metadata_arg(sets_rotation(plan)));
+    args
+}
+
+/// Explicit `--rotate` degrees for video outputs; `auto` is resolved at runtime.
+pub fn sets_rotation(plan: &Plan) -> Option<u16> {
+    match plan.options.rotate {
+        Some(Rotation::Set(degrees)) if plan.dest_kind == MediaKind::Video => Some(degrees),
+        _ => None,
+    }
+}
+
+fn rotation_metadata_arg(degrees: Option<u16>) -> Option<String> {
+    degrees.map(|degrees| format!("-metadata:s:v:0 rotate={degrees}"))
+}
+
+/// Filter that turns upright a frame whose display rotation is `degrees` clockwise.
+pub fn transpose_filter(degrees: u16) -> Option<&'static str> {
+    match degrees {
+        90 => Some("transpose=clock"),
+        180 => Some("hflip,vflip"),
+        270 => Some("transpose=cclock"),
+        _ => None,
+    }
+}
+
+/// Video filters applied when transcoding to a video destination.
+pub fn video_filters(options: &ConversionOptions) -> Vec<String> {
+    let mut filters = Vec::new();
+    if let Some(speed) = options.speed {
+        filters.push(format!("setpts=PTS/{}", speed));
+    }
+    filters.extend(scale_cap_filter(options));
+    if options.grayscale {
+        filters.push("format=gray".to_string());
+    }
+    filters
+}
+
+/// Audio filters applied when transcoding audio (standalone or inside a video).
+pub fn audio_filters(options: &ConversionOptions) -> Vec<String> {
+    let mut filters = Vec::new();
+    if let Some(speed) = options.speed {
+        filters.extend(atempo_chain(speed));
+    }
+    if options.normalize {
+        filters.push(format!(
+            "loudnorm=I={}:TP=-1.5:LRA=11",
+            target_loudness(options)
+        ));
+    }
+    filters
+}
+
+/// `--loudness` default: the -16 LUFS most podcast and streaming platforms expect.
+pub const DEFAULT_LOUDNESS: f64 = -16.0;
+/// The integrated loudness range `loudnorm` accepts.
+const MIN_LOUDNESS: f64 = -70.0;
+const MAX_LOUDNESS: f64 = -5.0;
+
+fn target_loudness(options: &ConversionOptions) -> f64 {
+    options.loudness.unwrap_or(DEFAULT_LOUDNESS)
+}
+
+/// Whether `--normalize` has an audio stream to work on.
+fn normalizes_audio(
+    options: &ConversionOptions,
+    dest_kind: MediaKind,
+    dest_ext: Option<&str>,
+) -> bool {
+    options.normalize
+        && matches!(dest_kind, MediaKind::Audio | MediaKind::Video)
+        && !drops_audio(options, dest_kind, dest_ext)
+}
+
+/// Splits a tempo factor into `atempo` stages, each within ffmpeg's 0.5-2.0 range.
+fn atempo_chain(speed: f64) -> Vec<String> {
+    let mut stages = Vec::new();
+    let mut remaining = speed;
+    while remaining > 2.0 {
+        stages.push("atempo=2".to_string());
+        remaining /= 2.0;
+    }
+    while remaining < 0.5 {
+        stages.push("atempo=0.5".to_string());
+        remaining /= 0.5;
+    }
+    stages.push(format!("atempo={}", remaining));
+    stages
+}
+
+fn ffmpeg_transcode_args(plan: &Plan, dest_ext: Option<&str>) -> Vec<String> {
+    let mut args = Vec::new();
+    if plan.dest_kind == MediaKind::Video {
+        args.extend(ffmpeg_video_args(plan, dest_ext));
+        if mutes_audio(plan) {
+            args.push("-an".to_string());
+        } else {
+            args.extend(ffmpeg_audio_args(plan, dest_ext));
+        }
+        args.extend(rotation_metadata_arg(sets_rotation(plan)));
+    } else if plan.dest_kind == MediaKind::Audio {
+        if extracts_audio(plan, None) {
+            args.push("-vn".to_string());
+        }
+        args.extend(ffmpeg_audio_args(plan, dest_ext));
+    }
+    args
+}
+
+fn ffmpeg_video_args(plan: &Plan, dest_ext: Option<&str>) -> Vec<String> {
+    let mut args = Vec::new();
+    let hw = hw_encoder(&plan.options, plan.dest_kind, dest_ext);
+    let encoder = hw.or_else(|| video_encoder(&plan.options, dest_ext));
+    if let Some(codec) = encoder {
+        args.push(format!("-c:v {}", codec));
+        let defaults = av1_default_args(codec, &plan.options);
+        if !defaults.is_empty() {
+            args.push(defaults.join(" "));
+        }
+    }
+    if dest_ext != Some("gif") {
+        if let Some(bitrate) = plan.options.video_bitrate.as_deref() {
+            args.push(format!("-b:v {}", bitrate));
+        }
+        let crf = crf_args(&plan.options, encoder);
+        if !crf.is_empty() {
+            args.push(crf.join(" "));
+        }
+        if let Some(preset) = plan.options.preset.as_deref() {
+            args.push(format!("-preset {}", preset));
+        }
+    }
+    let source_ext = normalize_ext(&plan.source);
+    if let Some(format) = gif_pixel_format(source_ext.as_deref(), dest_ext) {
+        args.push(format!("-pix_fmt {format}"));
+    }
+    let mut filters = video_filters(&plan.options);
+    filters.extend(gif_filters(source_ext.as_deref(), dest_ext));
+    filters.extend(hw_upload_filter(hw).map(str::to_string));
+    if !filters.is_empty() {
+        args.push(format!("-filter:v {}", filters.join(",")));
+    }
+    args
+}
+
+fn ffmpeg_audio_args(plan: &Plan, dest_ext: Option<&str>) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(codec) = audio_encoder(&plan.options, dest_ext, plan.dest_kind) {
+        args.push(format!("-c:a {}", codec));
+        if let Some(format) = audio_format_arg(codec, dest_ext) {
+            args.push(format!("-f {format}"));
+        }
+    }
+    if let Some(bitrate) = plan.options.audio_bitrate.as_deref() {
+        args.push(format!("-b:a {}", bitrate));
+    }
+    let sample_format = sample_format_args(&plan.options, plan.dest_kind);
+    if !sample_format.is_empty() {
+        args.push(sample_format.join(" "));
+    }
+    let filters = audio_filters(&plan.options);
+    if !filters.is_empty() {
+        args.push(format!("-filter:a {}", filters.join(",")));
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_extension_aliases() {
+        let jpeg = Path::new("photo.JPEG");
+        let html = Path::new("index.HTM");
+        let plain = Path::new("clip.mp4");
+
+        assert_eq!(normalize_ext(jpeg).as_deref(), Some("jpg"));
+        assert_eq!(normalize_ext(html).as_deref(), Some("html"));
+        assert_eq!(normalize_ext(plain).as_deref(), Some("mp4"));
+    }
+
+    #[test]
+    fn plan_selects_copy_vs_rename() {
+        let src = Path::new("a.jpg");
+        let dst = Path::new("b.jpeg");
+
+        let plan_copy = build_plan(src, dst, false, false, ConversionOptions::default()).unwrap();
+        assert_eq!(plan_copy.strategy, Strategy::CopyOnly);
+
+        let plan_rename = build_plan(src, dst, true, false, ConversionOptions::default()).unwrap();
+        assert_eq!(plan_rename.strategy, Strategy::RenameOnly);
+    }
+
+    #[test]
+    fn plan_selects_convert() {
+        let src = Path::new("a.png");
+        let dst = Path::new("b.jpg");
+        let plan = build_plan(src, dst, false, false, ConversionOptions::default()).unwrap();
+        assert_eq!(plan.strategy, Strategy::Convert);
+    }
+
+    #[test]
+    fn plan_selects_backend() {
+        let image_plan = build_plan(
+            Path::new("a.png"),
+            Path::new("b.jpg"),
+            false,
+            false,
+            ConversionOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(image_plan.backend, Some(Backend::ImageMagick));
+
+        let media_plan = build_plan(
+            Path::new("a.mp4"),
+            Path::new("b.webm"),
+            false,
+            false,
+            ConversionOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(media_plan.backend, Some(Backend::Ffmpeg));
+
+        let doc_plan = build_plan(
+            Path::new("a.docx"),
+            Path::new("b.pdf"),
+            false,
+            false,
+            ConversionOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(doc_plan.backend, Some(Backend::LibreOffice));
+    }
+
+    #[test]
+    fn document_to_image_goes_through_pdf() {
+        let options = ConversionOptions {
+            image_quality: Some(80),
+            grayscale: true,
+            ..ConversionOptions::default()
+        };
+        let plan = build_plan(
+            Path::new("deck.pptx"),
+            Path::new("deck.png"),
+            false,
+            false,
+            options,
+        )
+        .unwrap();
+        assert_eq!(plan.backend, Some(Backend::LibreOffice));
+        assert!(rasterizes_document(&plan));
+        assert!(
+            plan.notes
+                .iter()
+                .any(|note| note.contains("intermediate PDF"))
+        );
+        assert!(
+            !plan
+                .notes
+                .iter()
+                .any(|note| note.contains("grayscale ignored"))
+        );
+        assert_eq!(
+            command_preview(&plan).as_deref(),
+            Some(
+                "soffice --headless --convert-to pdf --outdir <temp> deck.pptx && \
+                 magick <temp>/deck.pdf[0] -quality 80 -colorspace Gray deck.png"
+            )
+        );
+    }
+
+    #[test]
+    fn image_quality_resolves_per_destination_extension() {
+        let mut options = ConversionOptions {
+            image_quality: Some(90),
+            ..ConversionOptions::default()
+        };
+        options.image_quality_by_ext.insert("jpg".to_string(), 85);
+        options.image_quality_by_ext.insert("webp".to_string(), 80);
+
+        let quality = |dest: &str| {
+            build_plan(
+                Path::new("photo.png"),
+                Path::new(dest),
+                false,
+                false,
+                options.clone(),
+            )
+            .unwrap()
+            .options
+            .image_quality
+        };
+        assert_eq!(quality("a.jpeg"), Some(85));
+        assert_eq!(quality("a.webp"), Some(80));
+        assert_eq!(quality("a.avif"), Some(90));
+    }
+
+    #[test]
+    fn parses_fit_box() {
+        assert_eq!(parse_fit("1920x1080").unwrap(), (1920, 1080));
+        assert_eq!(parse_fit("640X480").unwrap(), (640, 480));
+        assert!(parse_fit("0x480").is_err());
+        assert!(parse_fit("640").is_err());
+        assert!(parse_fit("wide x tall").is_err());
+    }
+
+    #[test]
+    fn fit_converts_same_format_images() {
+        let options = ConversionOptions {
+            fit: Some("800x600".to_string()),
+            ..ConversionOptions::default()
+        };
+        // The source's size is only read when the plan runs, so the resize stays in the plan.
+        let plan = build_plan(
+            Path::new("missing.jpg"),
+            Path::new("small.jpg"),
+            false,
+            false,
+            options,
+        )
+        .unwrap();
+        assert_eq!(plan.strategy, Strategy::Convert);
+        assert_eq!(plan.backend, Some(Backend::ImageMagick));
+        assert_eq!(
+            command_preview(&plan).as_deref(),
+            Some("magick missing.jpg -resize '800x600>' small.jpg")
+        );
+        assert!(
+            plan.notes
+                .iter()
+                .any(|note| note.contains("when converting"))
+        );
+        // Its size cannot be read either, so running the plan keeps it as well.
+        assert!(settle_fit(&plan).is_none());
+    }
+
+    #[test]
+    fn svg_sources_rasterize_with_imagemagick_at_a_density() {
+        let plan = |dest: &str, density: Option<u32>| {
+            build_plan(
+                Path::new("logo.svg"),
+                Path::new(dest),
+                false,
+                false,
+                ConversionOptions {
+                    density,
+                    resize: Some("512x".to_string()),
+                    ..ConversionOptions::default()
+                },
+            )
+            .unwrap()
+        };
+        let png = plan("logo.png", Some(300));
+        assert_eq!(png.backend, Some(Backend::ImageMagick));
+        assert_eq!(
+            command_preview(&png).unwrap(),
+            "magick -density 300 logo.svg -resize '512x' logo.png"
+        );
+        assert!(
+            png.notes.iter().any(|note| note
+                .starts_with("SVG rasterization requires an ImageMagick SVG delegate")
+                && note.ends_with("rendered at 300 DPI")),
+            "{:?}",
+            png.notes
+        );
+        assert_eq!(plan("copy.svg", None).strategy, Strategy::CopyOnly);
+
+        let clip = build_plan(
+            Path::new("in.mov"),
+            Path::new("out.mp4"),
+            false,
+            false,
+            ConversionOptions {
+                density: Some(300),
+                ..ConversionOptions::default()
+            },
+        )
+        .unwrap();
+        assert!(
+            clip.notes
+                .iter()
+                .any(|note| note == "density ignored for non-vector sources")
+        );
+        let pdf = ConversionOptions {
+            density: Some(200),
+            ..ConversionOptions::default()
+        };
+        assert!(
+            ghostscript_args(&pdf, Some("png"))
+                .unwrap()
+                .contains(&"-r200".to_string())
+        );
+        assert!(
+            validate_options(&ConversionOptions {
+                density: Some(0),
+                ..ConversionOptions::default()
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn metadata_choice_maps_to_backend_flags() {
+        let plan = |source: &str, dest: &str, metadata: Metadata| {
+            build_plan(
+                Path::new(source),
+                Path::new(dest),
+                false,
+                false,
+                ConversionOptions {
+                    metadata,
+                    ffmpeg_preference: FfmpegPreference::Transcode,
+                    ..ConversionOptions::default()
+                },
+            )
+            .unwrap()
+        };
+        let photo = plan("photo.jpg", "clean.jpg", Metadata::Strip);
+        assert_eq!(photo.strategy, Strategy::Convert);
+        assert_eq!(
+            command_preview(&photo).unwrap(),
+            "magick photo.jpg -strip clean.jpg"
+        );
+        let song = plan("song.wav", "song.mp3", Metadata::Strip);
+        assert!(
+            command_preview(&song)
+                .unwrap()
+                .starts_with("ffmpeg -i song.wav -map_metadata -1 ")
+        );
+        let kept = plan("song.wav", "song.mp3", Metadata::Keep);
+        assert!(command_preview(&kept).unwrap().contains("-map_metadata 0"));
+        let image = plan("photo.png", "photo.jpg", Metadata::Keep);
+        assert!(
+            image
+                .notes
+                .iter()
+                .any(|note| note == "keep metadata only applies to ffmpeg conversions")
+        );
+        assert_eq!(parse_metadata("Strip").unwrap(), Metadata::Strip);
+        assert!(parse_metadata("scrub").is_err());
+    }
+
+    #[test]
+    fn lossless_and_effort_become_encoder_defines() {
+        let plan = |dest: &str| {
+            build_plan(
+                Path::new("photo.png"),
+                Path::new(dest),
+                false,
+                false,
+                ConversionOptions {
+                    image_quality: Some(80),
+                    lossless: true,
+                    effort: Some(6),
+                    ..ConversionOptions::default()
+                },
+            )
+            .unwrap()
+        };
+        let webp = plan("photo.webp");
+        assert_eq!(
+            command_preview(&webp).unwrap(),
+            "magick photo.png -define webp:lossless=true -define webp:method=6 photo.webp"
+        );
+        assert!(
+            webp.notes
+                .iter()
+                .any(|note| note == "image quality ignored for lossless output")
+        );
+        assert_eq!(
+            command_preview(&plan("photo.avif")).unwrap(),
+            "magick photo.png -quality 100 -define heic:chroma=444 -define heic:speed=0 photo.avif"
+        );
+        let jpg = plan("photo.jpg");
+        assert_eq!(
+            command_preview(&jpg).unwrap(),
+            "magick photo.png -quality 80 photo.jpg"
+        );
+        assert!(
+            jpg.notes
+                .iter()
+                .any(|note| note == "lossless and effort only apply to WebP and AVIF output")
+        );
+        assert!(
+            validate_options(&ConversionOptions {
+                effort: Some(7),
+                ..ConversionOptions::default()
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn lossless_and_effort_convert_same_format_webp() {
+        for options in [
+            ConversionOptions {
+                lossless: true,
+                ..ConversionOptions::default()
+            },
+            ConversionOptions {
+                effort: Some(6),
+                ..ConversionOptions::default()
+            },
+        ] {
+            let plan = build_plan(
+                Path::new("a.webp"),
+                Path::new("b.webp"),
+                false,
+                false,
+                options,
+            )
+            .unwrap();
+            assert_eq!(plan.strategy, Strategy::Convert);
+            assert_eq!(plan.backend, Some(Backend::ImageMagick));
+            assert!(
+                !plan
+                    .notes
+                    .iter()
+                    .any(|note| note.contains("only apply to WebP"))
+            );
+        }
+        let copy = build_plan(
+            Path::new("a.png"),
+            Path::new("b.png"),
+            false,
+            false,
+            ConversionOptions {
+                lossless: true,
+                ..ConversionOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(copy.strategy, Strategy::CopyOnly);
+    }
+
+    #[test]
+    fn resize_accepts_imagemagick_geometry() {
+        for geometry in ["1920x1080", "50%", "800x", "x600", "1280x720>", "640X480^"] {
+            assert!(validate_resize(geometry).is_ok(), "{geometry}");
+        }
+        for geometry in ["abc", "x", "0x10", "10x10>>", "+5x5", ""] {
+            assert!(validate_resize(geometry).is_err(), "{geometry}");
+        }
+
+        let options = ConversionOptions {
+            resize: Some("800x".to_string()),
+            ..ConversionOptions::default()
+        };
+        let plan = build_plan(
+            Path::new("photo.png"),
+            Path::new("photo.jpg"),
+            false,
+            false,
+            options.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            command_preview(&plan).as_deref(),
+            Some("magick photo.png -resize '800x' photo.jpg")
+        );
+        let audio = build_plan(
+            Path::new("song.wav"),
+            Path::new("song.mp3"),
+            false,
+            false,
+            options,
+        )
+        .unwrap();
+        assert!(
+            audio
+                .notes
+                .iter()
+                .any(|note| note == "resize ignored for non-image output")
+        );
+    }
+
+    #[test]
+    fn same_format_converts_only_when_options_apply() {
+        let plan = |dest: &str, options: ConversionOptions| {
+            build_plan(Path::new("in.jpg"), Path::new(dest), false, false, options).unwrap()
+        };
+        let quality = ConversionOptions {
+            image_quality: Some(80),
+            ..ConversionOptions::default()
+        };
+        assert_eq!(plan("out.jpg", quality).strategy, Strategy::Convert);
+
+        let video_only = ConversionOptions {
+            video_bitrate: Some("2M".to_string()),
+            ..ConversionOptions::default()
+        };
+        let copied = plan("out.jpeg", video_only);
+        assert_eq!(copied.strategy, Strategy::CopyOnly);
+        assert!(
+            copied
+                .notes
+                .iter()
+                .any(|note| note.contains("copying instead of converting"))
+        );
+
+        let stream_copy = ConversionOptions {
+            ffmpeg_preference: FfmpegPreference::StreamCopy,
+            ..ConversionOptions::default()
+        };
+        let remux = build_plan(
+            Path::new("in.mp4"),
+            Path::new("out.mp4"),
+            true,
+            false,
+            stream_copy,
+        )
+        .unwrap();
+        assert_eq!(remux.strategy, Strategy::RenameOnly);
+    }
+
+    #[test]
+    fn rejects_invalid_quality() {
+        let options = ConversionOptions {
+            image_quality: Some(0),
+            ..ConversionOptions::default()
+        };
+        let result = build_plan(
+            Path::new("a.png"),
+            Path::new("b.jpg"),
+            false,
+            false,
+            options,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_preset() {
+        let options = ConversionOptions {
+            preset: Some("fastish".to_string()),
+            ..ConversionOptions::default()
+        };
+        let result = build_plan(
+            Path::new("a.mp4"),
+            Path::new("b.webm"),
+            false,
+            false,
+            options,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_bitrate() {
+        let options = ConversionOptions {
+            audio_bitrate: Some("12kbps".to_string()),
+            ..ConversionOptions::default()
+        };
+        let result = build_plan(
+            Path::new("a.wav"),
+            Path::new("b.mp3"),
+            false,
+            false,
+            options,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_empty_codec() {
+        let options = ConversionOptions {
+            video_codec: Some("  ".to_string()),
+            ..ConversionOptions::default()
+        };
+        let result = build_plan(
+            Path::new("a.mp4"),
+            Path::new("b.webm"),
+            false,
+            false,
+            options,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn plans_report_the_source_size_when_readable() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("notes.txt");
+        std::fs::write(&source, vec![b'x'; 2048]).unwrap();
+        let plan = |source: &Path| {
+            build_plan(
+                source,
+                &temp.path().join("copy.txt"),
+                false,
+                false,
+                ConversionOptions::default(),
+            )
+            .unwrap()
+        };
+        let found = plan(&source);
+        assert_eq!(found.source_bytes, Some(2048));
+        assert!(render_plan(&found, false).contains("Source size: 2 KB"));
+        let json: serde_json::Value =
+            serde_json::from_str(&render_plan_json(&found, false, false).unwrap()).unwrap();
+        assert_eq!(json["source_bytes"], 2048);
+
+        let missing = plan(&temp.path().join("missing.txt"));
+        assert_eq!(missing.source_bytes, None);
+        assert!(!render_plan(&missing, false).contains("Source size"));
+        let json: serde_json::Value =
+            serde_json::from_str(&render_plan_json(&missing, false, false).unwrap()).unwrap();
+        assert!(json.get("source_bytes").is_none());
+    }
+
+    #[test]
+    fn timestamps_accept_seconds_and_clock_times() {
+        assert_eq!(parse_timestamp("90").unwrap(), 90.0);
+        assert_eq!(parse_timestamp("1:30").unwrap(), 90.0);
+        assert_eq!(parse_timestamp("00:01:30.5").unwrap(), 90.5);
+        for bad in ["1:75", "abc", "1:2:3:4", "-5"] {
+            assert!(parse_timestamp(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn trims_seek_before_the_input_and_scale_progress() {
+        let trim =
+            |start: Option<&str>, duration: Option<&str>, to: Option<&str>| ConversionOptions {
+                start: start.map(str::to_string),
+                duration: duration.map(str::to_string),
+                to: to.map(str::to_string),
+                ffmpeg_preference: FfmpegPreference::StreamCopy,
+                ..ConversionOptions::default()
+            };
+        let plan = build_plan(
+            Path::new("in.mp4"),
+            Path::new("out.mkv"),
+            false,
+            false,
+            trim(Some("60"), Some("10"), None),
+        )
+        .unwrap();
+        let preview = command_preview(&plan).unwrap();
+        assert!(preview.contains("-ss 60 -t 10 -i in.mp4"), "{preview}");
+        assert!(preview.contains("-c copy"), "{preview}");
+
+        let to = trim(Some("1:00"), None, Some("1:30.5"));
+        assert_eq!(
+            ffmpeg_input_args(&to, false, None),
+            ["-ss", "1:00", "-t", "30.5"]
+        );
+        assert_eq!(trimmed_seconds(&to, Some(600.0)), Some(30.5));
+        assert_eq!(trimmed_seconds(&to, Some(75.0)), Some(15.0));
+        assert_eq!(
+            trimmed_seconds(&trim(Some("60"), None, None), Some(100.0)),
+            Some(40.0)
+        );
+        assert_eq!(trimmed_seconds(&trim(None, None, None), None), None);
+
+        assert!(validate_options(&trim(None, Some("5"), Some("10"))).is_err());
+        assert!(validate_options(&trim(Some("20"), None, Some("10"))).is_err());
+        assert!(validate_options(&trim(None, Some("0"), None)).is_err());
+    }
+
+    #[test]
+    fn crf_is_validated_and_surfaces_in_plans() {
+        let crf = |value: u8, dest: &str| {
+            build_plan(
+                Path::new("in.mov"),
+                Path::new(dest),
+                false,
+                false,
+                ConversionOptions {
+                    crf: Some(value),
+                    ffmpeg_preference: FfmpegPreference::Transcode,
+                    ..ConversionOptions::default()
+                },
+            )
+        };
+        assert!(crf(200, "out.mp4").is_err());
+        let both = ConversionOptions {
+            crf: Some(23),
+            video_bitrate: Some("2M".to_string()),
+            ..ConversionOptions::default()
+        };
+        assert!(validate_options(&both).is_err());
+
+        let plan = crf(23, "out.mp4").unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&render_plan_json(&plan, false, false).unwrap()).unwrap();
+        assert_eq!(json["options"]["crf"], 23);
+        let preview = command_preview(&plan).unwrap();
+        assert!(preview.contains("-c:v libx264 -crf 23"), "{preview}");
+
+        let webm = command_preview(&crf(31, "out.webm").unwrap()).unwrap();
+        assert!(webm.contains("-c:v libvpx-vp9 -crf 31 -b:v 0"), "{webm}");
+        let audio = crf(23, "out.mp3").unwrap();
+        assert!(
+            audio
+                .notes
+                .iter()
+                .any(|note| note == "CRF ignored for non-video output")
+        );
+    }
+
+    #[test]
+    fn hwaccel_swaps_in_a_hardware_encoder() {
+        assert_eq!(parse_hwaccel("VAAPI").unwrap(), "vaapi");
+        assert!(parse_hwaccel("cuda").is_err());
+        let plan = |dest: &str, kind: &str, codec: Option<&str>| {
+            build_plan(
+                Path::new("in.mov"),
+                Path::new(dest),
+                false,
+                false,
+                ConversionOptions {
+                    hwaccel: Some(kind.to_string()),
+                    video_codec: codec.map(str::to_string),
+                    crf: Some(23),
+                    ffmpeg_preference: FfmpegPreference::Transcode,
+                    ..ConversionOptions::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let nvenc = plan("out.mp4", "nvenc", None);
+        let preview = command_preview(&nvenc).unwrap();
+        assert!(
+            preview.starts_with("ffmpeg -hwaccel cuda -i in.mov -c:v h264_nvenc "),
+            "{preview}"
+        );
+        assert!(!preview.contains("-crf"), "{preview}");
+        assert!(
+            nvenc
+                .notes
+                .iter()
+                .any(|note| note.contains("h264_nvenc (nvenc)"))
+        );
+        assert!(
+            nvenc
+                .notes
+                .iter()
+                .any(|note| note == "CRF ignored by hardware encoders")
+        );
+
+        let vaapi = command_preview(&plan("out.mkv", "vaapi", Some("libx265"))).unwrap();
+        assert!(
+            vaapi.contains("-vaapi_device /dev/dri/renderD128 -i in.mov -c:v hevc_vaapi"),
+            "{vaapi}"
+        );
+        assert!(vaapi.contains("-filter:v format=nv12,hwupload"), "{vaapi}");
+
+        let webm = plan("out.webm", "nvenc", None);
+        assert!(
+            webm.notes
+                .iter()
+                .any(|note| note == "no nvenc encoder for libvpx-vp9; encoding in software")
+        );
+        let none = plan("out.mp4", "none", None);
+        assert!(
+            command_preview(&none)
+                .unwrap()
+                .contains("-c:v libx264 -crf 23")
+        );
+
+        // A hardware encoder request re-encodes a same-format video instead of copying it.
+        let same_format = |kind: &str| {
+            let options = ConversionOptions {
+                hwaccel: Some(kind.to_string()),
+                ..ConversionOptions::default()
+            };
+            build_plan(
+                Path::new("a.mp4"),
+                Path::new("b.mp4"),
+                false,
+                false,
+                options,
+            )
+            .unwrap()
+            .strategy
+        };
+        assert_eq!(same_format("nvenc"), Strategy::Convert);
+        assert_eq!(same_format("none"), Strategy::CopyOnly);
+    }
+
+    #[test]
+    fn two_pass_requires_video_bitrate() {
+        let options = ConversionOptions {
+            two_pass: true,
+            ..ConversionOptions::default()
+        };
+        let result = build_plan(
+            Path::new("a.mov"),
+            Path::new("b.mp4"),
+            false,
+            false,
+            options,
+        );
+        assert!(result.is_err());
+        let crf = ConversionOptions {
+            two_pass: true,
+            crf: Some(23),
+            ..ConversionOptions::default()
+        };
+        let err = validate_options(&crf).unwrap_err();
+        assert!(err.to_string().contains("CRF"), "{err}");
+    }
+
+    #[test]
+    fn two_pass_preview_lists_both_passes() {
+        let options = ConversionOptions {
+            two_pass: true,
+            video_bitrate: Some("2500k".to_string()),
+            ..ConversionOptions::default()
+        };
+        let plan = build_plan(
+            Path::new("a.mov"),
+            Path::new("b.mp4"),
+            false,
+            false,
+            options,
+        )
+        .unwrap();
+        let preview = command_preview(&plan).unwrap();
+        assert!(preview.contains("-pass 1"));
+        assert!(preview.contains("-an -f null /dev/null &&"));
+        assert!(preview.contains("-pass 2"));
+        assert!(!preview.contains("-c copy"));
+        assert!(
+            plan.notes
+                .iter()
+                .any(|note| note.starts_with("two-pass encode at 2500k"))
+        );
+    }
+
+    #[test]
+    fn mute_drops_audio_in_preview() {
+        let options = ConversionOptions {
+            mute: true,
+            ..ConversionOptions::default()
+        };
+        let plan = build_plan(
+            Path::new("a.mov"),
+            Path::new("b.mp4"),
+            false,
+            false,
+            options,
+        )
+        .unwrap();
+        let preview = command_preview(&plan).unwrap();
+        assert!(preview.contains("-c copy -an"));
+        assert!(!preview.contains("-c:a"));
+    }
+
+    #[test]
+    fn atempo_chain_stays_in_range() {
+        assert_eq!(atempo_chain(1.5), vec!["atempo=1.5"]);
+        assert_eq!(atempo_chain(8.0), vec!["atempo=2", "atempo=2", "atempo=2"]);
+        assert_eq!(atempo_chain(0.25), vec!["atempo=0.5", "atempo=0.5"]);
+    }
+
+    #[test]
+    fn rejects_non_positive_speed() {
+        let options = ConversionOptions {
+            speed: Some(0.0),
+            ..ConversionOptions::default()
+        };
+        let result = build_plan(
+            Path::new("a.wav"),
+            Path::new("b.mp3"),
+            false,
+            false,
+            options,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn normalize_adds_loudnorm_and_forces_transcode() {
+        let plan = |dest: &str, options: ConversionOptions| {
+            build_plan(
+                Path::new("episode.wav"),
+                Path::new(dest),
+                false,
+                false,
+                options,
+            )
+        };
+        let normalize = ConversionOptions {
+            normalize: true,
+            ..ConversionOptions::default()
+        };
+        let mp3 = plan("episode.mp3", normalize.clone()).unwrap();
+        assert!(forces_transcode(&mp3));
+        let preview = command_preview(&mp3).unwrap();
+        assert!(
+            preview.contains("-filter:a loudnorm=I=-16:TP=-1.5:LRA=11"),
+            "{preview}"
+        );
+        let quieter = plan(
+            "episode.m4a",
+            ConversionOptions {
+                loudness: Some(-23.0),
+                ..normalize.clone()
+            },
+        )
+        .unwrap();
+        assert!(
+            command_preview(&quieter)
+                .unwrap()
+                .contains("loudnorm=I=-23:")
+        );
+
+        let still = build_plan(
+            Path::new("cover.png"),
+            Path::new("cover.jpg"),
+            false,
+            false,
+            normalize.clone(),
+        )
+        .unwrap();
+        assert!(
+            still
+                .notes
+                .iter()
+                .any(|note| note == "normalize ignored for output without audio")
+        );
+        assert!(
+            plan(
+                "episode.mp3",
+                ConversionOptions {
+                    ffmpeg_preference: FfmpegPreference::StreamCopy,
+                    ..normalize.clone()
+                }
+            )
+            .is_err()
+        );
+        assert!(
+            plan(
+                "episode.mp3",
+                ConversionOptions {
+                    loudness: Some(0.0),
+                    ..normalize
+                }
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn grayscale_applies_to_images_and_video() {
+        let options = ConversionOptions {
+            grayscale: true,
+            ..ConversionOptions::default()
+        };
+        let image = build_plan(
+            Path::new("scan.png"),
+            Path::new("scan.jpg"),
+            false,
+            false,
+            options.clone(),
+        )
+        .unwrap();
+        assert!(
+            command_preview(&image)
+                .unwrap()
+                .contains("-colorspace Gray")
+        );
+        let video = build_plan(
+            Path::new("a.mov"),
+            Path::new("b.mp4"),
+            false,
+            false,
+            options.clone(),
+        )
+        .unwrap();
+        assert!(command_preview(&video).unwrap().contains("format=gray"));
+        let audio = build_plan(
+            Path::new("a.wav"),
+            Path::new("b.mp3"),
+            false,
+            false,
+            options,
+        )
+        .unwrap();
+        assert!(
+            audio
+                .notes
+                .contains(&"grayscale ignored for non-visual output".to_string())
+        );
+    }
+
+    #[test]
+    fn color_profile_args_for_srgb_and_icc() {
+        let srgb = ConversionOptions {
+            srgb: true,
+            ..ConversionOptions::default()
+        };
+        assert_eq!(
+            color_profile_args(&srgb),
+            vec!["-colorspace sRGB", "+profile icc"]
+        );
+        let icc = ConversionOptions {
+            icc_profile: Some(PathBuf::from("web.icc")),
+            ..ConversionOptions::default()
+        };
+        assert_eq!(color_profile_args(&icc), vec!["-profile web.icc"]);
+    }
+
+    #[test]
+    fn rejects_missing_icc_profile() {
+        let options = ConversionOptions {
+            icc_profile: Some(PathBuf::from("/nonexistent/profile.icc")),
+            ..ConversionOptions::default()
+        };
+        let result = build_plan(
+            Path::new("a.png"),
+            Path::new("b.jpg"),
+            false,
+            false,
+            options,
+        );
+        assert!(result.is_err());
     }
-    args
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn ghostscript_devices_follow_format_and_grayscale() {
+        assert_eq!(ghostscript_device(Some("png"), false), Some("png16m"));
+        assert_eq!(ghostscript_device(Some("jpeg"), true), Some("jpeggray"));
+        assert_eq!(ghostscript_device(Some("webp"), false), None);
+        assert_eq!(ghostscript_device(None, false), None);
+    }
 
     #[test]
-    fn normalize_extension_aliases() {
-        let jpeg = Path::new("photo.JPEG");
-        let html = Path::new("index.HTM");
-        let plain = Path::new("clip.mp4");
+    fn prefer_gs_previews_ghostscript_for_pdf() {
+        let options = ConversionOptions {
+            prefer_gs: true,
+            ..ConversionOptions::default()
+        };
+        let plan = build_plan(
+            Path::new("doc.pdf"),
+            Path::new("page.png"),
+            false,
+            false,
+            options,
+        )
+        .unwrap();
+        assert!(uses_ghostscript(&plan));
+        let preview = command_preview(&plan).unwrap();
+        assert!(preview.starts_with("gs -dSAFER"));
+        assert!(preview.contains("-sDEVICE=png16m"));
+        assert!(
+            plan.notes
+                .contains(&"PDF pages are rendered with Ghostscript".to_string())
+        );
+    }
 
-        assert_eq!(normalize_ext(jpeg).as_deref(), Some("jpg"));
-        assert_eq!(normalize_ext(html).as_deref(), Some("html"));
-        assert_eq!(normalize_ext(plain).as_deref(), Some("mp4"));
+    #[test]
+    fn frame_selects_a_pdf_page_or_is_dropped_without_frames() {
+        let frame = |source: &str, dest: &str| {
+            let options = ConversionOptions {
+                frame: Some(2),
+                prefer_gs: true,
+                ..ConversionOptions::default()
+            };
+            build_plan(Path::new(source), Path::new(dest), false, false, options).unwrap()
+        };
+        let pdf = frame("doc.pdf", "page.png");
+        assert!(
+            command_preview(&pdf)
+                .unwrap()
+                .contains("-dFirstPage=3 -dLastPage=3")
+        );
+        assert!(
+            pdf.notes
+                .contains(&"PDF to image converts page 3 only".to_string())
+        );
+        assert_eq!(
+            imagemagick_source(Path::new("doc.pdf"), Some("png"), &pdf.options),
+            "doc.pdf[2]"
+        );
+
+        let png = frame("photo.png", "photo.jpg");
+        assert_eq!(png.options.frame, None);
+        assert!(
+            png.notes
+                .iter()
+                .any(|note| note.starts_with("frame ignored"))
+        );
     }
 
     #[test]
-    fn plan_selects_copy_vs_rename() {
-        let src = Path::new("a.jpg");
-        let dst = Path::new("b.jpeg");
+    fn all_pages_needs_a_known_multi_page_pdf() {
+        assert_eq!(
+            numbered_destination(Path::new("out/scan.png"), PAGE_PATTERN),
+            Path::new("out/scan-%d.png")
+        );
+        let all_pages = |source: &str, dest: &str| {
+            let options = ConversionOptions {
+                all_pages: true,
+                ..ConversionOptions::default()
+            };
+            build_plan(Path::new(source), Path::new(dest), false, false, options).unwrap()
+        };
+        // The PDF does not exist, so pdfinfo cannot count its pages.
+        let pdf = all_pages("missing.pdf", "scan.png");
+        assert!(!pdf.options.all_pages);
+        assert!(
+            pdf.notes
+                .iter()
+                .any(|note| note.starts_with("PDF page count unknown"))
+        );
+        assert!(command_preview(&pdf).unwrap().contains("missing.pdf[0]"));
 
-        let plan_copy = build_plan(src, dst, false, false, ConversionOptions::default()).unwrap();
-        assert_eq!(plan_copy.strategy, Strategy::CopyOnly);
+        let png = all_pages("photo.png", "photo.jpg");
+        assert!(!png.options.all_pages);
+        assert!(
+            png.notes
+                .iter()
+                .any(|note| note.starts_with("all pages ignored"))
+        );
 
-        let plan_rename = build_plan(src, dst, true, false, ConversionOptions::default()).unwrap();
-        assert_eq!(plan_rename.strategy, Strategy::RenameOnly);
+        let both = ConversionOptions {
+            all_pages: true,
+            frame: Some(1),
+            ..ConversionOptions::default()
+        };
+        assert!(build_plan(Path::new("a.pdf"), Path::new("a.png"), false, false, both).is_err());
     }
 
     #[test]
-    fn plan_selects_convert() {
-        let src = Path::new("a.png");
-        let dst = Path::new("b.jpg");
-        let plan = build_plan(src, dst, false, false, ConversionOptions::default()).unwrap();
-        assert_eq!(plan.strategy, Strategy::Convert);
+    fn scale_cap_filter_never_upscales() {
+        let width_only = ConversionOptions {
+            max_width: Some(1280),
+            ..ConversionOptions::default()
+        };
+        assert_eq!(
+            scale_cap_filter(&width_only).as_deref(),
+            Some("scale='min(iw,1280)':-2")
+        );
+        let both = ConversionOptions {
+            max_width: Some(1280),
+            max_height: Some(720),
+            ..ConversionOptions::default()
+        };
+        assert!(
+            scale_cap_filter(&both)
+                .unwrap()
+                .starts_with("scale='min(iw,1280)':'min(ih,720)'")
+        );
+        assert_eq!(scale_cap_filter(&ConversionOptions::default()), None);
     }
 
     #[test]
-    fn plan_selects_backend() {
-        let image_plan = build_plan(
-            Path::new("a.png"),
-            Path::new("b.jpg"),
+    fn unknown_video_size_transcodes_with_scale_filter() {
+        let options = ConversionOptions {
+            max_height: Some(720),
+            ..ConversionOptions::default()
+        };
+        let plan = build_plan(
+            Path::new("/nonexistent/clip.mp4"),
+            Path::new("/nonexistent/small.mp4"),
             false,
             false,
-            ConversionOptions::default(),
+            options,
         )
         .unwrap();
-        assert_eq!(image_plan.backend, Some(Backend::ImageMagick));
+        assert_eq!(plan.strategy, Strategy::Convert);
+        assert!(forces_transcode(&plan));
+        let preview = command_preview(&plan).unwrap();
+        assert!(preview.contains("scale=-2:'min(ih,720)'"));
+        assert!(!preview.contains("(if compatible)"));
+    }
 
-        let media_plan = build_plan(
-            Path::new("a.mp4"),
-            Path::new("b.webm"),
-            false,
-            false,
-            ConversionOptions::default(),
-        )
-        .unwrap();
-        assert_eq!(media_plan.backend, Some(Backend::Ffmpeg));
+    #[test]
+    fn parses_rotation_values() {
+        assert_eq!(parse_rotation("auto").unwrap(), Rotation::Auto);
+        assert_eq!(parse_rotation("270").unwrap(), Rotation::Set(270));
+        assert!(parse_rotation("45").is_err());
+        assert_eq!(transpose_filter(90), Some("transpose=clock"));
+        assert_eq!(transpose_filter(0), None);
+    }
 
-        let doc_plan = build_plan(
-            Path::new("a.docx"),
-            Path::new("b.pdf"),
+    #[test]
+    fn parses_ffmpeg_loglevels() {
+        assert_eq!(parse_ffmpeg_loglevel(" Verbose ").unwrap(), "verbose");
+        assert!(parse_ffmpeg_loglevel("loud").is_err());
+        assert!(ffmpeg_loglevel_is_verbose("info"));
+        assert!(!ffmpeg_loglevel_is_verbose(DEFAULT_FFMPEG_LOGLEVEL));
+        assert!(!ffmpeg_loglevel_is_verbose("quiet"));
+    }
+
+    #[test]
+    fn explicit_rotation_sets_metadata_without_autorotate() {
+        let options = ConversionOptions {
+            rotate: Some(Rotation::Set(90)),
+            ..ConversionOptions::default()
+        };
+        let plan = build_plan(
+            Path::new("/nonexistent/clip.mp4"),
+            Path::new("/nonexistent/turned.mp4"),
             false,
             false,
-            ConversionOptions::default(),
+            options,
         )
         .unwrap();
-        assert_eq!(doc_plan.backend, Some(Backend::LibreOffice));
+        assert_eq!(plan.strategy, Strategy::Convert);
+        let preview = command_preview(&plan).unwrap();
+        assert!(preview.starts_with("ffmpeg -noautorotate -i"));
+        assert!(preview.contains("-c copy -metadata:s:v:0 rotate=90"));
     }
 
     #[test]
-    fn rejects_invalid_quality() {
+    fn destination_resolution_order() {
+        let source = Path::new("/media/in/clip.mov");
+        let out = Path::new("/srv/out");
+        // An explicit path always wins, even a relative one.
+        for explicit in ["/tmp/clip.mp4", "sub/clip.mp4", "./clip.mp4"] {
+            let (path, note) = resolve_destination(source, Path::new(explicit), Some(out));
+            assert_eq!(path, PathBuf::from(explicit));
+            assert!(note.is_none());
+        }
+        let bare = Path::new("clip.mp4");
+        let (path, note) = resolve_destination(source, bare, Some(out));
+        assert_eq!(path, PathBuf::from("/srv/out/clip.mp4"));
+        assert!(note.unwrap().contains("output directory /srv/out"));
+        let (path, note) = resolve_destination(source, bare, None);
+        assert_eq!(path, PathBuf::from("/media/in/clip.mp4"));
+        assert!(note.unwrap().contains("source directory"));
+        let (path, note) = resolve_destination(Path::new("clip.mov"), bare, None);
+        assert_eq!(path, PathBuf::from("clip.mp4"));
+        assert!(note.is_none());
+
         let options = ConversionOptions {
-            image_quality: Some(0),
+            output_dir: Some(out.to_path_buf()),
             ..ConversionOptions::default()
         };
-        let result = build_plan(
-            Path::new("a.png"),
-            Path::new("b.jpg"),
-            false,
-            false,
-            options,
+        let plan = build_plan(source, bare, false, false, options).unwrap();
+        assert_eq!(plan.destination, PathBuf::from("/srv/out/clip.mp4"));
+    }
+
+    #[test]
+    fn sanitize_replaces_illegal_characters() {
+        let rules = Sanitize::default();
+        assert_eq!(
+            sanitize_file_name("a<b>c:d\"e|f?g*h\\i.mp4", rules),
+            "a_b_c_d_e_f_g_h_i.mp4"
+        );
+        assert_eq!(sanitize_file_name("tab\there.txt", rules), "tab_here.txt");
+        assert_eq!(
+            sanitize_file_name("My Song: Live.mp3", rules),
+            "My Song_ Live.mp3"
         );
-        assert!(result.is_err());
     }
 
     #[test]
-    fn rejects_invalid_preset() {
+    fn sanitize_optional_rules() {
+        let rules = Sanitize {
+            lowercase: true,
+            underscore_spaces: true,
+        };
+        assert_eq!(
+            sanitize_file_name("My Song: Live.MP3", rules),
+            "my_song__live.mp3"
+        );
+    }
+
+    #[test]
+    fn sanitized_destination_is_noted() {
         let options = ConversionOptions {
-            preset: Some("fastish".to_string()),
+            sanitize: Some(Sanitize::default()),
             ..ConversionOptions::default()
         };
-        let result = build_plan(
-            Path::new("a.mp4"),
-            Path::new("b.webm"),
+        let plan = build_plan(
+            Path::new("/nonexistent/a.png"),
+            Path::new("/out/what?.jpg"),
             false,
             false,
-            options,
+            options.clone(),
+        )
+        .unwrap();
+        assert_eq!(plan.destination, PathBuf::from("/out/what_.jpg"));
+        assert!(
+            plan.notes
+                .contains(&"output name sanitized: what?.jpg -> what_.jpg".to_string())
         );
-        assert!(result.is_err());
+
+        let clean = build_plan(
+            Path::new("/nonexistent/a.png"),
+            Path::new("/out/fine.jpg"),
+            false,
+            false,
+            options,
+        )
+        .unwrap();
+        assert!(!clean.notes.iter().any(|note| note.contains("sanitized")));
     }
 
     #[test]
-    fn rejects_invalid_bitrate() {
-        let options = ConversionOptions {
-            audio_bitrate: Some("12kbps".to_string()),
+    fn av1_encoders_get_constant_quality_defaults() {
+        let plan = |codec: &str, bitrate: Option<&str>| {
+            let options = ConversionOptions {
+                video_codec: Some(codec.to_string()),
+                video_bitrate: bitrate.map(str::to_string),
+                ffmpeg_preference: FfmpegPreference::Transcode,
+                ..ConversionOptions::default()
+            };
+            build_plan(
+                Path::new("/nonexistent/clip.mp4"),
+                Path::new("/nonexistent/archive.mkv"),
+                false,
+                false,
+                options,
+            )
+            .unwrap()
+        };
+        let svt = command_preview(&plan("av1", None)).unwrap();
+        assert!(svt.contains("-c:v libsvtav1 -crf 35 -preset 8"));
+        let aom = command_preview(&plan("libaom-av1", None)).unwrap();
+        assert!(aom.contains("-c:v libaom-av1 -crf 30 -b:v 0"));
+        let target = command_preview(&plan("libaom-av1", Some("2M"))).unwrap();
+        assert!(target.contains("-c:v libaom-av1 -b:v 2M"));
+        assert!(!target.contains("-crf"));
+    }
+
+    #[test]
+    fn svt_av1_presets_are_numeric() {
+        let options = |preset: &str| ConversionOptions {
+            video_codec: Some("libsvtav1".to_string()),
+            preset: Some(preset.to_string()),
             ..ConversionOptions::default()
         };
-        let result = build_plan(
-            Path::new("a.wav"),
-            Path::new("b.mp3"),
+        let build = |preset: &str| {
+            build_plan(
+                Path::new("a.mp4"),
+                Path::new("b.mkv"),
+                false,
+                false,
+                options(preset),
+            )
+        };
+        assert!(build("6").is_ok());
+        assert!(build("medium").is_err());
+        assert!(build("14").is_err());
+    }
+
+    #[test]
+    fn ogg_opus_selects_libopus_and_ogg_muxer() {
+        let plan = |ogg_opus: bool| {
+            let options = ConversionOptions {
+                ogg_opus,
+                ffmpeg_preference: FfmpegPreference::Transcode,
+                ..ConversionOptions::default()
+            };
+            build_plan(
+                Path::new("/nonexistent/song.flac"),
+                Path::new("/nonexistent/song.ogg"),
+                false,
+                false,
+                options,
+            )
+            .unwrap()
+        };
+        assert!(
+            command_preview(&plan(false))
+                .unwrap()
+                .contains("-c:a libvorbis /nonexistent/song.ogg")
+        );
+        assert!(
+            command_preview(&plan(true))
+                .unwrap()
+                .contains("-c:a libopus -f ogg /nonexistent/song.ogg")
+        );
+    }
+
+    #[test]
+    fn plan_explains_backend_choice() {
+        let reason = |source: &str, dest: &str| {
+            build_plan(
+                Path::new(source),
+                Path::new(dest),
+                false,
+                false,
+                ConversionOptions::default(),
+            )
+            .unwrap()
+            .backend_reason
+        };
+        assert_eq!(
+            reason("a.mp4", "b.webm").as_deref(),
+            Some("chose ffmpeg because both extensions are media")
+        );
+        assert_eq!(
+            reason("a.png", "b.docx").as_deref(),
+            Some("no backend: png -> docx not supported")
+        );
+        assert_eq!(
+            reason("a.png", "b").as_deref(),
+            Some("no backend: destination has no extension")
+        );
+        assert_eq!(reason("a.png", "b.png"), None);
+    }
+
+    #[test]
+    fn raw_sources_route_to_the_raw_backend() {
+        for ext in RAW_EXTS {
+            let source = format!("shot.{}", ext.to_uppercase());
+            let plan = build_plan(
+                Path::new(&source),
+                Path::new("shot.jpg"),
+                false,
+                false,
+                ConversionOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(plan.backend, Some(Backend::Raw), "{source}");
+            assert!(plan.notes.iter().any(|note| note.contains("RAW")));
+        }
+        let (backend, _) = select_backend(Some("png"), Some("nef"));
+        assert_eq!(backend, None);
+    }
+
+    #[test]
+    fn sample_format_and_dither_transcode_audio() {
+        let build = |source: &str, dest: &str, format: &str| {
+            let options = ConversionOptions {
+                sample_fmt: Some(parse_sample_fmt(format).unwrap()),
+                dither: true,
+                ..ConversionOptions::default()
+            };
+            build_plan(Path::new(source), Path::new(dest), false, false, options).unwrap()
+        };
+        let plan = build("master.flac", "cd.wav", "S16");
+        assert!(forces_transcode(&plan));
+        assert_eq!(
+            command_preview(&plan).as_deref(),
+            Some(
+                "ffmpeg -i master.flac -c:a pcm_s16le -sample_fmt s16 -dither_method triangular cd.wav"
+            )
+        );
+        let plan = build("master.flac", "hires.wav", "s24");
+        let preview = command_preview(&plan).unwrap();
+        assert!(
+            preview.contains("-c:a pcm_s24le -sample_fmt s32"),
+            "{preview}"
+        );
+        let same = build("a.flac", "b.flac", "s16");
+        assert_eq!(same.strategy, Strategy::Convert);
+
+        let video = build("clip.mov", "clip.mp4", "s16");
+        assert!(!command_preview(&video).unwrap().contains("-sample_fmt"));
+        assert!(
+            video
+                .notes
+                .iter()
+                .any(|note| note == "sample format ignored for non-audio output")
+        );
+        assert!(parse_sample_fmt("s20").is_err());
+    }
+
+    #[test]
+    fn documents_convert_within_their_family() {
+        let plan = build_plan(
+            Path::new("report.docx"),
+            Path::new("report.odt"),
             false,
             false,
-            options,
+            ConversionOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(plan.backend, Some(Backend::LibreOffice));
+        assert!(!rasterizes_document(&plan));
+        assert_eq!(
+            command_preview(&plan).as_deref(),
+            Some("soffice --headless --convert-to odt --outdir <temp> report.docx")
         );
-        assert!(result.is_err());
+        let (backend, _) = select_backend(Some("xlsx"), Some("csv"));
+        assert_eq!(backend, Some(Backend::LibreOffice));
+        assert_eq!(libreoffice_target("txt"), "txt:Text");
+        let (backend, reason) = select_backend(Some("docx"), Some("xlsx"));
+        assert_eq!(backend, None);
+        assert_eq!(reason, "no backend: docx -> xlsx not supported");
     }
 
     #[test]
-    fn rejects_empty_codec() {
+    fn gifs_convert_to_and_from_video_with_ffmpeg() {
         let options = ConversionOptions {
-            video_codec: Some("  ".to_string()),
+            video_bitrate: Some("1M".to_string()),
             ..ConversionOptions::default()
         };
-        let result = build_plan(
-            Path::new("a.mp4"),
-            Path::new("b.webm"),
+        let to_gif = build_plan(
+            Path::new("clip.mp4"),
+            Path::new("clip.gif"),
+            false,
+            false,
+            options.clone(),
+        )
+        .unwrap();
+        assert_eq!(to_gif.backend, Some(Backend::Ffmpeg));
+        assert_eq!(to_gif.dest_kind, MediaKind::Video);
+        let preview = command_preview(&to_gif).unwrap();
+        assert!(
+            preview.contains("-filter:v fps=10,split[frames][sample]"),
+            "{preview}"
+        );
+        assert!(preview.contains("-an"), "{preview}");
+        assert!(!preview.contains("-b:v"), "{preview}");
+        assert!(to_gif.notes.iter().any(|note| note.contains("10 fps")));
+        assert!(
+            to_gif
+                .notes
+                .iter()
+                .any(|note| note == "video bitrate, CRF and preset ignored for GIF output")
+        );
+
+        let from_gif = build_plan(
+            Path::new("loop.gif"),
+            Path::new("loop.mp4"),
             false,
             false,
             options,
+        )
+        .unwrap();
+        assert_eq!(from_gif.backend, Some(Backend::Ffmpeg));
+        let preview = command_preview(&from_gif).unwrap();
+        assert!(
+            preview.contains("-c:v libx264 -b:v 1M -pix_fmt yuv420p"),
+            "{preview}"
         );
-        assert!(result.is_err());
+        assert!(
+            preview.contains("scale=trunc(iw/2)*2:trunc(ih/2)*2"),
+            "{preview}"
+        );
+
+        let still = build_plan(
+            Path::new("a.png"),
+            Path::new("a.gif"),
+            false,
+            false,
+            ConversionOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(still.backend, Some(Backend::ImageMagick));
+    }
+
+    #[test]
+    fn every_route_is_reachable() {
+        for route in ROUTES {
+            let source = route.source.extensions()[0];
+            let dest = route.destination.extensions()[0];
+            let (backend, _) = select_backend(Some(source), Some(dest));
+            assert_eq!(backend, Some(route.backend), "{source} -> {dest}");
+        }
+    }
+
+    #[test]
+    fn audit_counts_strategies_and_shared_destinations() {
+        let build = |source: &str, dest: &str| {
+            build_plan(
+                Path::new(source),
+                Path::new(dest),
+                false,
+                false,
+                ConversionOptions::default(),
+            )
+            .unwrap()
+        };
+        let plans = vec![
+            build("a.png", "out/a.png"),
+            build("a.jpg", "out/a.png"),
+            build("b.wav", "out/b.mp3"),
+        ];
+        let counts = PlanCounts::of(&plans);
+        assert_eq!(counts.copy, 1);
+        assert_eq!(counts.convert + counts.unsupported, 2);
+
+        let conflicts = destination_conflicts(&plans);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[Path::new("out/a.png")],
+            vec![Path::new("a.png"), Path::new("a.jpg")]
+        );
+        assert!(render_plan_audit(&plans).contains("Conflict: out/a.png <- a.png, a.jpg"));
+    }
+
+    #[test]
+    fn hdr_sources_warn_or_tonemap_on_sdr_transcodes() {
+        let info = |transfer: &str| MediaInfo {
+            duration_seconds: Some(10.0),
+            video_codec: Some("hevc".to_string()),
+            audio_codec: Some("aac".to_string()),
+            width: Some(3840),
+            height: Some(2160),
+            rotation: None,
+            audio_bitrate: None,
+            color_transfer: Some(transfer.to_string()),
+            color_primaries: Some("bt2020".to_string()),
+        };
+        let hdr = info("smpte2084");
+        let build = |dest: &str, options: ConversionOptions| {
+            build_plan(
+                Path::new("phone.mov"),
+                Path::new(dest),
+                false,
+                false,
+                options,
+            )
+            .unwrap()
+        };
+
+        let h264 = build(
+            "clip.mp4",
+            ConversionOptions {
+                video_codec: Some("libx264".to_string()),
+                ..ConversionOptions::default()
+            },
+        );
+        assert_eq!(decide_ffmpeg_mode(&h264, Some(&hdr)), FfmpegMode::Transcode);
+        let warning = hdr_warning(&h264, &hdr).unwrap();
+        assert!(warning.starts_with("HDR (PQ, bt2020) source"), "{warning}");
+        assert!(warning.contains("--tonemap"));
+
+        let hevc = build(
+            "clip.mp4",
+            ConversionOptions {
+                video_codec: Some("libx265".to_string()),
+                ..ConversionOptions::default()
+            },
+        );
+        assert!(hdr_warning(&hevc, &hdr).is_none());
+
+        // An HEVC stream copies into mp4 untouched, so it keeps its HDR metadata.
+        let copy = build("clip.mp4", ConversionOptions::default());
+        assert_eq!(
+            decide_ffmpeg_mode(&copy, Some(&hdr)),
+            FfmpegMode::StreamCopy
+        );
+        assert!(hdr_warning(&copy, &hdr).is_none());
+
+        let tonemapped = build(
+            "clip.mkv",
+            ConversionOptions {
+                tonemap: true,
+                ..ConversionOptions::default()
+            },
+        );
+        assert!(tonemaps(&tonemapped, Some(&hdr)));
+        assert_eq!(
+            decide_ffmpeg_mode(&tonemapped, Some(&hdr)),
+            FfmpegMode::Transcode
+        );
+        assert!(hdr_warning(&tonemapped, &hdr).is_none());
+        assert!(!tonemaps(&tonemapped, Some(&info("bt709"))));
+        assert!(TONEMAP_FILTER.contains(",tonemap=tonemap=hable:desat=0,"));
+    }
+
+    #[test]
+    fn compatible_container_changes_are_remuxes() {
+        let info = MediaInfo {
+            duration_seconds: Some(10.0),
+            video_codec: Some("h264".to_string()),
+            audio_codec: Some("aac".to_string()),
+            width: Some(1920),
+            height: Some(1080),
+            rotation: None,
+            audio_bitrate: None,
+            color_transfer: None,
+            color_primaries: None,
+        };
+        let build = |dest: &str, options: ConversionOptions| {
+            build_plan(
+                Path::new("clip.mov"),
+                Path::new(dest),
+                false,
+                false,
+                options,
+            )
+            .unwrap()
+        };
+
+        let mut plan = build("clip.mp4", ConversionOptions::default());
+        assert_eq!(plan.strategy, Strategy::Convert);
+        apply_remux(&mut plan, &info);
+        assert_eq!(plan.strategy, Strategy::Remux);
+        assert!(plan.notes.iter().any(|note| note.contains("remuxing")));
+        assert!(!plan.notes.iter().any(|note| note == RUNTIME_PROBE_NOTE));
+        assert_eq!(
+            command_preview(&plan).as_deref(),
+            Some("ffmpeg -i clip.mov -c copy clip.mp4")
+        );
+
+        let mut webm = build("clip.webm", ConversionOptions::default());
+        apply_remux(&mut webm, &info);
+        assert_eq!(webm.strategy, Strategy::Convert);
+
+        let hevc = ConversionOptions {
+            video_codec: Some("libx265".to_string()),
+            ..ConversionOptions::default()
+        };
+        let mut reencode = build("clip.mp4", hevc);
+        apply_remux(&mut reencode, &info);
+        assert_eq!(reencode.strategy, Strategy::Convert);
+    }
+
+    #[test]
+    fn parses_ffmpeg_bitrates() {
+        assert_eq!(parse_bitrate("192k"), Some(192_000));
+        assert_eq!(parse_bitrate("1.5M"), Some(1_500_000));
+        assert_eq!(parse_bitrate("320000"), Some(320_000));
+        assert_eq!(parse_bitrate("fast"), None);
+        assert_eq!(parse_bitrate("0k"), None);
+    }
+
+    #[test]
+    fn audio_bitrate_above_source_is_noted_or_capped() {
+        let mut options = ConversionOptions {
+            audio_bitrate: Some("320k".to_string()),
+            ..ConversionOptions::default()
+        };
+        assert!(cap_audio_bitrate(320_000, 320_000, &mut options).is_none());
+        let note = cap_audio_bitrate(320_000, 128_000, &mut options).unwrap();
+        assert!(note.contains("exceeds the source's 128k"), "{note}");
+        assert_eq!(options.audio_bitrate.as_deref(), Some("320k"));
+
+        options.no_upscale_bitrate = true;
+        let note = cap_audio_bitrate(320_000, 128_000, &mut options).unwrap();
+        assert!(note.contains("capped"), "{note}");
+        assert_eq!(options.audio_bitrate.as_deref(), Some("128k"));
+    }
+
+    #[test]
+    fn content_contradicting_the_extension_is_noted_or_refused() {
+        let temp = tempfile::tempdir().unwrap();
+        let png_bytes = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let misnamed = temp.path().join("photo.jpg");
+        std::fs::write(&misnamed, png_bytes).unwrap();
+        let dest = temp.path().join("photo.webp");
+        let plan =
+            build_plan(&misnamed, &dest, false, false, ConversionOptions::default()).unwrap();
+        assert!(
+            plan.notes
+                .iter()
+                .any(|note| note == "source extension says jpg but content is png"),
+            "{:?}",
+            plan.notes
+        );
+        let strict = ConversionOptions {
+            strict_detect: true,
+            ..ConversionOptions::default()
+        };
+        let err = build_plan(&misnamed, &dest, false, false, strict.clone()).unwrap_err();
+        assert!(err.to_string().contains("content is png"), "{err}");
+
+        // `.jpeg` and `.tiff` are aliases of what infer names `jpg` and `tif`, and an SVG
+        // with an XML prolog is detected as plain XML.
+        let jpeg = temp.path().join("scan.JPEG");
+        std::fs::write(&jpeg, b"\xff\xd8\xff\xe0\0\x10JFIF\0").unwrap();
+        let tiff = temp.path().join("scan.tiff");
+        std::fs::write(&tiff, b"II*\0\x08\0\0\0").unwrap();
+        let svg = temp.path().join("logo.svg");
+        std::fs::write(
+            &svg,
+            "<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>\n",
+        )
+        .unwrap();
+        for source in [jpeg, tiff, svg] {
+            let plan = build_plan(&source, &dest, false, false, strict.clone()).unwrap();
+            assert!(
+                !plan.notes.iter().any(|note| note.contains("content is")),
+                "{:?}",
+                plan.notes
+            );
+        }
     }
 }
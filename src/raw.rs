@@ -0,0 +1,90 @@
+use std::path::Path;
+
+/// Programs that can develop camera RAW files, in the order mvx tries them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawTool {
+    /// LibRaw's `dcraw_emu`, writing a 16-bit TIFF.
+    DcrawEmu,
+    /// darktable's command-line exporter, writing a TIFF with the default pipeline.
+    Darktable,
+    /// ImageMagick reading the RAW file itself, which needs a delegate such as ufraw.
+    ImageMagick,
+}
+
+impl RawTool {
+    const ALL: [RawTool; 3] = [RawTool::DcrawEmu, RawTool::Darktable, RawTool::ImageMagick];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RawTool::DcrawEmu => "dcraw_emu",
+            RawTool::Darktable => "darktable-cli",
+            RawTool::ImageMagick => "ImageMagick (RAW delegate)",
+        }
+    }
+
+    fn programs(self) -> &'static [&'static str] {
+        match self {
+            RawTool::DcrawEmu => &["dcraw_emu"],
+            RawTool::Darktable => &["darktable-cli"],
+            RawTool::ImageMagick => &["magick", "convert"],
+        }
+    }
+
+    /// Whether the tool develops into an intermediate TIFF that ImageMagick then turns
+    /// into the destination format.
+    pub fn uses_intermediate(self) -> bool {
+        self != RawTool::ImageMagick
+    }
+}
+
+pub const MISSING_RAW_TOOL: &str = "no RAW developer found; install LibRaw's dcraw_emu \
+     (e.g., apt install libraw-bin), darktable, or ImageMagick with a RAW delegate";
+
+/// The first installed RAW developer, if any.
+pub fn find_raw_tool() -> Option<RawTool> {
+    RawTool::ALL
+        .into_iter()
+        .find(|tool| tool.programs().iter().any(|program| on_path(program)))
+}
+
+/// Arguments that develop `source` into the TIFF `dest`, for tools that use one.
+pub fn develop_args(tool: RawTool, source: &Path, dest: &Path) -> Option<Vec<String>> {
+    let source = source.display().to_string();
+    let dest = dest.display().to_string();
+    match tool {
+        RawTool::DcrawEmu => Some(vec!["-T".to_string(), "-Z".to_string(), dest, source]),
+        // An in-memory library keeps darktable-cli from fighting a running darktable
+        // over its database lock.
+        RawTool::Darktable => Some(vec![
+            source,
+            dest,
+            "--core".to_string(),
+            "--library".to_string(),
+            ":memory:".to_string(),
+        ]),
+        RawTool::ImageMagick => None,
+    }
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn develop_args_name_the_tiff_output() {
+        let source = Path::new("shot.cr2");
+        let tiff = Path::new("/tmp/raw.tiff");
+        assert_eq!(
+            develop_args(RawTool::DcrawEmu, source, tiff).unwrap(),
+            vec!["-T", "-Z", "/tmp/raw.tiff", "shot.cr2"]
+        );
+        let darktable = develop_args(RawTool::Darktable, source, tiff).unwrap();
+        assert_eq!(darktable[..2], ["shot.cr2", "/tmp/raw.tiff"]);
+        assert!(develop_args(RawTool::ImageMagick, source, tiff).is_none());
+    }
+}
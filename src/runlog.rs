@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Lines of tool output kept in the log when a command finishes.
+pub const TAIL_LINES: usize = 20;
+
+/// Human-readable transcript for `--log-file`. Clones share the file, so parallel jobs
+/// append to one log; every entry is flushed as it is written so a crash still leaves
+/// everything up to the failing step.
+#[derive(Clone)]
+pub struct RunLog {
+    file: Arc<Mutex<File>>,
+}
+
+impl RunLog {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("create log directory {}", parent.display()))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("open log file {}", path.display()))?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Appends one entry: a timestamped header line, then `body` indented beneath it.
+    /// Logging never fails a conversion, so write errors are dropped.
+    pub fn entry(&self, label: &str, heading: &str, body: &str) {
+        let mut text = format!("[{}] {label}: {heading}\n", timestamp(SystemTime::now()));
+        for line in body.lines() {
+            text.push_str("    ");
+            text.push_str(line);
+            text.push('\n');
+        }
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(text.as_bytes());
+            let _ = file.flush();
+        }
+    }
+}
+
+/// The last `count` lines of `text`.
+pub fn tail(text: &str, count: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    lines[lines.len().saturating_sub(count)..].join("\n")
+}
+
+/// UTC time as `YYYY-MM-DD HH:MM:SSZ`.
+fn timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_date((secs / 86_400) as i64);
+    let of_day = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}Z",
+        of_day / 3600,
+        of_day % 3600 / 60,
+        of_day % 60
+    )
+}
+
+/// Gregorian date for a count of days since 1970-01-01 (Howard Hinnant's algorithm).
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn timestamps_are_utc_calendar_times() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01 00:00:00Z");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(timestamp(leap_day), "2024-02-29 12:34:56Z");
+    }
+
+    #[test]
+    fn entries_append_with_indented_bodies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs/run.log");
+        let log = RunLog::open(&path).unwrap();
+        log.entry("a.png", "command", "magick a.png b.jpg");
+        RunLog::open(&path)
+            .unwrap()
+            .entry("a.png", "result: ok", "");
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("] a.png: command"), "{text}");
+        assert_eq!(lines[1], "    magick a.png b.jpg");
+        assert!(lines[2].ends_with("] a.png: result: ok"), "{text}");
+        assert_eq!(tail("1\n2\n3\n", 2), "2\n3");
+    }
+}
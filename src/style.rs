@@ -0,0 +1,31 @@
+use clap::ValueEnum;
+use crossterm::style::{Color, Stylize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// `auto` follows the NO_COLOR convention (any non-empty value disables color) and
+    /// only styles output going to a terminal.
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        }
+    }
+}
+
+pub fn paint(text: &str, color: Color, enabled: bool) -> String {
+    if enabled {
+        text.with(color).to_string()
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Prefix of the temp files and directories mvx writes next to destinations. A crash
+/// skips the tempfile guards, so anything with this prefix can be left behind.
+pub const TEMP_PREFIX: &str = ".mvx.tmp";
+
+/// Lists leftover temp entries in `dir`, descending into subdirectories when `recursive`.
+pub fn find_leftovers(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        anyhow::bail!("not a directory: {}", dir.display());
+    }
+    let mut found = Vec::new();
+    let mut walker = WalkDir::new(dir).min_depth(1).sort_by_file_name();
+    if !recursive {
+        walker = walker.max_depth(1);
+    }
+    let mut entries = walker.into_iter();
+    while let Some(entry) = entries.next() {
+        let entry = entry.with_context(|| format!("scan {}", dir.display()))?;
+        if is_leftover(entry.path()) {
+            if entry.file_type().is_dir() {
+                entries.skip_current_dir();
+            }
+            found.push(entry.into_path());
+        }
+    }
+    Ok(found)
+}
+
+pub fn remove_leftover(path: &Path) -> Result<()> {
+    let metadata =
+        fs::symlink_metadata(path).with_context(|| format!("stat {}", path.display()))?;
+    if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+    .with_context(|| format!("remove {}", path.display()))
+}
+
+fn is_leftover(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(TEMP_PREFIX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn finds_temp_files_and_directories() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        fs::write(dir.join(".mvx.tmpA1b2"), "x").unwrap();
+        fs::create_dir(dir.join(".mvx.tmpDir")).unwrap();
+        fs::write(dir.join(".mvx.tmpDir").join("output.png"), "x").unwrap();
+        fs::write(dir.join("keep.png"), "x").unwrap();
+        fs::create_dir(dir.join("nested")).unwrap();
+        fs::write(dir.join("nested").join(".mvx.tmpN"), "x").unwrap();
+
+        let shallow = find_leftovers(dir, false).unwrap();
+        assert_eq!(
+            shallow,
+            vec![dir.join(".mvx.tmpA1b2"), dir.join(".mvx.tmpDir")]
+        );
+        let deep = find_leftovers(dir, true).unwrap();
+        assert_eq!(deep.len(), 3);
+
+        for path in &deep {
+            remove_leftover(path).unwrap();
+        }
+        assert!(find_leftovers(dir, true).unwrap().is_empty());
+        assert!(dir.join("keep.png").exists());
+    }
+}
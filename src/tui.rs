@@ -1,9 +1,13 @@
 use crate::execute;
 use crate::execute::{ProgressEvent, ProgressReporter};
-use crate::plan::{FfmpegPreference, Plan};
-use crate::{batch, config, plan};
+use crate::plan::{FfmpegPreference, Metadata, Plan, Rotation, Sanitize, parse_rotation};
+use crate::runlog::RunLog;
+use crate::{batch, cancel, config, fuzzy, plan};
 use anyhow::{Context, Result};
-use crossterm::event::{self, Event as CEvent, KeyCode};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyModifiers,
+    MouseButton, MouseEvent, MouseEventKind,
+};
 use crossterm::execute as crossterm_execute;
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
@@ -11,7 +15,7 @@ use crossterm::terminal::{
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Wrap};
 use std::collections::{HashMap, VecDeque};
-use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, mpsc};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -20,22 +24,68 @@ pub struct InteractiveDefaults {
     pub destination: Option<std::path::PathBuf>,
     pub batch: bool,
     pub dest_dir: Option<std::path::PathBuf>,
+    pub output_dir: Option<std::path::PathBuf>,
     pub inputs: Vec<String>,
     pub recursive: bool,
     pub to_ext: Option<String>,
+    pub name_template: Option<String>,
     pub move_source: bool,
     pub overwrite: bool,
+    pub overwrite_newer: bool,
     pub backup: bool,
     pub image_quality: Option<u8>,
     pub video_bitrate: Option<String>,
+    pub crf: Option<u8>,
     pub audio_bitrate: Option<String>,
     pub preset: Option<String>,
     pub video_codec: Option<String>,
+    pub hwaccel: Option<String>,
     pub audio_codec: Option<String>,
+    pub ogg_opus: bool,
+    pub no_upscale_bitrate: bool,
+    pub sample_fmt: Option<String>,
+    pub dither: bool,
     pub ffmpeg_preference: FfmpegPreference,
+    /// Not editable in the form; carried from the command line or config.
+    pub ffmpeg_loglevel: Option<String>,
+    pub two_pass: bool,
+    pub mute: bool,
+    pub speed: Option<f64>,
+    pub start: Option<String>,
+    pub duration: Option<String>,
+    pub to: Option<String>,
+    pub normalize: bool,
+    pub loudness: Option<f64>,
+    pub grayscale: bool,
+    pub srgb: bool,
+    pub icc_profile: Option<std::path::PathBuf>,
+    pub backup_pad: Option<usize>,
+    pub fit: Option<String>,
+    pub resize: Option<String>,
+    pub density: Option<u32>,
+    pub lossless: bool,
+    pub effort: Option<u8>,
+    pub frame: Option<u32>,
+    pub all_pages: bool,
+    pub jobs: usize,
+    pub check_space: bool,
+    pub timeout: Option<u64>,
+    pub verify: bool,
+    pub strict_detect: bool,
+    pub preserve: bool,
+    pub prefer_gs: bool,
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    pub rotate: Option<Rotation>,
+    pub tonemap: bool,
+    pub metadata: Metadata,
+    pub sanitize: Option<Sanitize>,
     pub config_path: Option<std::path::PathBuf>,
     pub profile: Option<String>,
     pub plan_only: bool,
+    pub theme: Theme,
+    pub history: HistorySettings,
+    pub log: Option<RunLog>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -93,16 +143,95 @@ impl TaskState {
             finished_at: None,
         }
     }
+
+    /// Seconds since the task started, falling back to the last spinner report so the
+    /// indeterminate indicators keep moving between events.
+    fn activity_elapsed(&self) -> f32 {
+        self.started_at
+            .map(|start| start.elapsed().as_secs_f32())
+            .unwrap_or(self.spinner_elapsed)
+    }
 }
 
 struct UiState {
     tasks: Vec<TaskState>,
     task_map: HashMap<String, usize>,
+    /// The task progress events follow.
     active_index: usize,
+    /// A task the user clicked, shown instead of `active_index` until it is clicked again.
+    pinned: Option<usize>,
     logs: VecDeque<String>,
+    queue_filter: String,
+    editing_filter: bool,
+    status_filter: StatusFilter,
+    paused: bool,
+    log_scroll: usize,
+}
+
+/// Lets the run screen hold the worker between conversions.
+struct PauseGate {
+    paused: Mutex<bool>,
+    resumed: Condvar,
+}
+
+impl PauseGate {
+    fn new() -> Self {
+        Self {
+            paused: Mutex::new(false),
+            resumed: Condvar::new(),
+        }
+    }
+
+    fn set_paused(&self, paused: bool) {
+        if let Ok(mut guard) = self.paused.lock() {
+            *guard = paused;
+        }
+        self.resumed.notify_all();
+    }
+
+    fn wait_while_paused(&self) {
+        let Ok(guard) = self.paused.lock() else {
+            return;
+        };
+        let _guard = self.resumed.wait_while(guard, |paused| *paused);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StatusFilter {
+    All,
+    Failed,
+    Running,
+}
+
+impl StatusFilter {
+    fn next(self) -> Self {
+        match self {
+            StatusFilter::All => StatusFilter::Failed,
+            StatusFilter::Failed => StatusFilter::Running,
+            StatusFilter::Running => StatusFilter::All,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            StatusFilter::All => "all",
+            StatusFilter::Failed => "failed",
+            StatusFilter::Running => "running",
+        }
+    }
+
+    fn matches(self, status: TaskStatus) -> bool {
+        match self {
+            StatusFilter::All => true,
+            StatusFilter::Failed => status == TaskStatus::Failed,
+            StatusFilter::Running => status == TaskStatus::Running,
+        }
+    }
 }
 
-struct Theme {
+#[derive(Clone, Copy)]
+pub struct Theme {
     primary: Color,
     accent: Color,
     muted: Color,
@@ -111,17 +240,39 @@ struct Theme {
 }
 
 impl Theme {
-    fn new() -> Self {
+    pub fn new(config: &config::TuiConfig) -> Result<Self> {
+        Ok(Self {
+            primary: parse_color("primary", config.primary.as_deref(), Color::Cyan)?,
+            accent: parse_color("accent", config.accent.as_deref(), Color::Yellow)?,
+            muted: parse_color("muted", config.muted.as_deref(), Color::DarkGray)?,
+            good: parse_color("good", config.good.as_deref(), Color::Green)?,
+            bad: parse_color("bad", config.bad.as_deref(), Color::Red)?,
+        })
+    }
+
+    /// Used for `--color never` and NO_COLOR; bold/reverse highlights still apply.
+    pub fn monochrome() -> Self {
         Self {
-            primary: Color::Cyan,
-            accent: Color::Yellow,
-            muted: Color::DarkGray,
-            good: Color::Green,
-            bad: Color::Red,
+            primary: Color::Reset,
+            accent: Color::Reset,
+            muted: Color::Reset,
+            good: Color::Reset,
+            bad: Color::Reset,
         }
     }
 }
 
+fn parse_color(name: &str, value: Option<&str>, fallback: Color) -> Result<Color> {
+    let Some(value) = value else {
+        return Ok(fallback);
+    };
+    value.trim().parse::<Color>().map_err(|_| {
+        anyhow::anyhow!(
+            "invalid tui.{name} color: {value} (use a name like cyan or a hex value like #00ffff)"
+        )
+    })
+}
+
 impl UiState {
     fn new(plans: &[Plan]) -> Self {
         let mut tasks = Vec::with_capacity(plans.len());
@@ -135,10 +286,33 @@ impl UiState {
             tasks,
             task_map,
             active_index: 0,
+            pinned: None,
             logs: VecDeque::with_capacity(200),
+            queue_filter: String::new(),
+            editing_filter: false,
+            status_filter: StatusFilter::All,
+            paused: false,
+            log_scroll: 0,
         }
     }
 
+    /// Indices into `tasks` that pass the queue name and status filters.
+    fn visible_tasks(&self) -> Vec<usize> {
+        let needle = self.queue_filter.to_lowercase();
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| self.status_filter.matches(task.status))
+            .filter(|(_, task)| needle.is_empty() || task.name.to_lowercase().contains(&needle))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// The task the queue highlights and the details panel describes.
+    fn selected_index(&self) -> usize {
+        self.pinned.unwrap_or(self.active_index)
+    }
+
     fn task_stats(&self) -> (usize, usize, usize, usize) {
         let mut pending = 0;
         let mut running = 0;
@@ -162,6 +336,26 @@ impl UiState {
         self.logs.push_back(line);
     }
 
+    /// Logs the console batch summary's per-backend and per-kind counts. `plans` are the
+    /// ones `tasks` was built from, in the same order.
+    fn push_group_summary(&mut self, plans: &[Plan]) {
+        let mut groups = batch::Groups::default();
+        for (plan, task) in plans.iter().zip(&self.tasks) {
+            match task.status {
+                TaskStatus::Ok => groups.record(Some(plan), true),
+                TaskStatus::Failed => groups.record(Some(plan), false),
+                TaskStatus::Pending | TaskStatus::Running => {}
+            }
+        }
+        if !groups.is_empty() {
+            self.push_log(format!(
+                "By backend: {}",
+                batch::groups_text(&groups.by_backend)
+            ));
+            self.push_log(format!("By kind: {}", batch::groups_text(&groups.by_kind)));
+        }
+    }
+
     fn handle_event(&mut self, event: ProgressEvent) {
         let label = match &event {
             ProgressEvent::Started { label }
@@ -172,7 +366,15 @@ impl UiState {
         let Some(&index) = self.task_map.get(label) else {
             return;
         };
-        self.active_index = index;
+        // With parallel jobs, stay on the task being watched until it stops running
+        // instead of jumping to whichever task reported last.
+        let watched_running = self
+            .tasks
+            .get(self.active_index)
+            .is_some_and(|task| task.status == TaskStatus::Running);
+        if !watched_running {
+            self.active_index = index;
+        }
         let mut log_line = None;
         {
             let task = &mut self.tasks[index];
@@ -210,7 +412,10 @@ impl UiState {
                     task.finished_at = Some(Instant::now());
                     task.message = message.clone();
                     if ok {
-                        log_line = Some(format!("Done: {}", task.name));
+                        log_line = Some(match message.strip_prefix("ok, ") {
+                            Some(sizes) => format!("Done: {} ({sizes})", task.name),
+                            None => format!("Done: {}", task.name),
+                        });
                     } else {
                         log_line = Some(format!("Failed: {} ({})", task.name, message));
                     }
@@ -259,9 +464,11 @@ enum Panel {
 enum InputField {
     Source,
     Destination,
+    OutputDir,
     BatchInputs,
     DestDir,
     ToExt,
+    NameTemplate,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -269,14 +476,52 @@ enum OptionField {
     Recursive,
     MoveSource,
     Overwrite,
+    OverwriteNewer,
     Backup,
     ImageQuality,
     VideoBitrate,
+    Crf,
     AudioBitrate,
     Preset,
     VideoCodec,
+    HwAccel,
     AudioCodec,
+    OggOpus,
+    NoUpscaleBitrate,
+    SampleFmt,
+    Dither,
     FfmpegPref,
+    TwoPass,
+    Mute,
+    Speed,
+    Start,
+    Duration,
+    To,
+    Normalize,
+    Loudness,
+    Grayscale,
+    Srgb,
+    IccProfile,
+    BackupPad,
+    Fit,
+    Resize,
+    Density,
+    Lossless,
+    Effort,
+    Frame,
+    AllPages,
+    CheckSpace,
+    Timeout,
+    Verify,
+    StrictDetect,
+    Preserve,
+    PreferGs,
+    MaxWidth,
+    MaxHeight,
+    Rotate,
+    Tonemap,
+    Metadata,
+    Sanitize,
     ConfigPath,
     Profile,
     PlanOnly,
@@ -286,17 +531,40 @@ enum OptionField {
 enum TextField {
     Source,
     Destination,
+    OutputDir,
     BatchInputs,
     DestDir,
     ToExt,
+    NameTemplate,
     ImageQuality,
     VideoBitrate,
+    Crf,
     AudioBitrate,
     Preset,
     VideoCodec,
+    HwAccel,
     AudioCodec,
+    SampleFmt,
+    Speed,
+    Start,
+    Duration,
+    To,
+    Loudness,
+    IccProfile,
+    BackupPad,
+    Fit,
+    Resize,
+    Density,
+    Effort,
+    Frame,
+    Timeout,
+    MaxWidth,
+    MaxHeight,
+    Rotate,
     ConfigPath,
     Profile,
+    /// Name prompt for saving the form's options as a config profile.
+    SaveProfile,
 }
 
 enum Modal {
@@ -309,6 +577,23 @@ struct BrowserEntry {
     name: String,
     path: std::path::PathBuf,
     is_dir: bool,
+    /// File size from `fs::metadata`; `None` for directories and unreadable entries.
+    size: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BrowserSort {
+    Name,
+    Size,
+}
+
+impl BrowserSort {
+    fn label(self) -> &'static str {
+        match self {
+            BrowserSort::Name => "name",
+            BrowserSort::Size => "size",
+        }
+    }
 }
 
 struct BrowserState {
@@ -317,6 +602,9 @@ struct BrowserState {
     entries: Vec<BrowserEntry>,
     selected: usize,
     filter: String,
+    sort: BrowserSort,
+    /// Paths checked with Space for batch inputs; kept across directory changes.
+    selected_set: std::collections::BTreeSet<std::path::PathBuf>,
 }
 
 struct RecentState {
@@ -337,18 +625,59 @@ struct FormState {
     destination: String,
     batch_inputs: String,
     dest_dir: String,
+    output_dir: String,
     to_ext: String,
+    name_template: String,
     recursive: bool,
     move_source: bool,
     overwrite: bool,
+    overwrite_newer: bool,
     backup: bool,
     image_quality: String,
     video_bitrate: String,
+    crf: String,
     audio_bitrate: String,
     preset: String,
     video_codec: String,
+    hwaccel: String,
     audio_codec: String,
+    ogg_opus: bool,
+    no_upscale_bitrate: bool,
+    sample_fmt: String,
+    dither: bool,
     ffmpeg_pref: FfmpegPreference,
+    ffmpeg_loglevel: Option<String>,
+    two_pass: bool,
+    mute: bool,
+    speed: String,
+    start: String,
+    duration: String,
+    to: String,
+    normalize: bool,
+    loudness: String,
+    grayscale: bool,
+    srgb: bool,
+    icc_profile: String,
+    backup_pad: String,
+    fit: String,
+    resize: String,
+    density: String,
+    lossless: bool,
+    effort: String,
+    frame: String,
+    all_pages: bool,
+    check_space: bool,
+    timeout: String,
+    verify: bool,
+    strict_detect: bool,
+    preserve: bool,
+    prefer_gs: bool,
+    max_width: String,
+    max_height: String,
+    rotate: String,
+    tonemap: bool,
+    metadata: Metadata,
+    sanitize: Option<Sanitize>,
     config_path: String,
     profile: String,
     plan_only: bool,
@@ -378,21 +707,100 @@ impl FormState {
                 .as_ref()
                 .map(|p| p.display().to_string())
                 .unwrap_or_default(),
+            output_dir: defaults
+                .output_dir
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
             to_ext: defaults.to_ext.clone().unwrap_or_default(),
+            name_template: defaults.name_template.clone().unwrap_or_default(),
             recursive: defaults.recursive,
             move_source: defaults.move_source,
             overwrite: defaults.overwrite,
+            overwrite_newer: defaults.overwrite_newer,
             backup: defaults.backup,
             image_quality: defaults
                 .image_quality
                 .map(|q| q.to_string())
                 .unwrap_or_default(),
             video_bitrate: defaults.video_bitrate.clone().unwrap_or_default(),
+            crf: defaults
+                .crf
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
             audio_bitrate: defaults.audio_bitrate.clone().unwrap_or_default(),
             preset: defaults.preset.clone().unwrap_or_default(),
             video_codec: defaults.video_codec.clone().unwrap_or_default(),
+            hwaccel: defaults.hwaccel.clone().unwrap_or_default(),
             audio_codec: defaults.audio_codec.clone().unwrap_or_default(),
+            ogg_opus: defaults.ogg_opus,
+            no_upscale_bitrate: defaults.no_upscale_bitrate,
+            sample_fmt: defaults.sample_fmt.clone().unwrap_or_default(),
+            dither: defaults.dither,
             ffmpeg_pref: defaults.ffmpeg_preference,
+            ffmpeg_loglevel: defaults.ffmpeg_loglevel.clone(),
+            two_pass: defaults.two_pass,
+            mute: defaults.mute,
+            speed: defaults.speed.map(|s| s.to_string()).unwrap_or_default(),
+            start: defaults.start.clone().unwrap_or_default(),
+            duration: defaults.duration.clone().unwrap_or_default(),
+            to: defaults.to.clone().unwrap_or_default(),
+            normalize: defaults.normalize,
+            loudness: defaults
+                .loudness
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            grayscale: defaults.grayscale,
+            srgb: defaults.srgb,
+            icc_profile: defaults
+                .icc_profile
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            backup_pad: defaults
+                .backup_pad
+                .map(|w| w.to_string())
+                .unwrap_or_default(),
+            fit: defaults.fit.clone().unwrap_or_default(),
+            resize: defaults.resize.clone().unwrap_or_default(),
+            density: defaults
+                .density
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            lossless: defaults.lossless,
+            effort: defaults
+                .effort
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            frame: defaults
+                .frame
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            all_pages: defaults.all_pages,
+            check_space: defaults.check_space,
+            timeout: defaults
+                .timeout
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            verify: defaults.verify,
+            strict_detect: defaults.strict_detect,
+            preserve: defaults.preserve,
+            prefer_gs: defaults.prefer_gs,
+            max_width: defaults
+                .max_width
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            max_height: defaults
+                .max_height
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            rotate: defaults
+                .rotate
+                .map(|value| value.label())
+                .unwrap_or_default(),
+            tonemap: defaults.tonemap,
+            metadata: defaults.metadata,
+            sanitize: defaults.sanitize,
             config_path: defaults
                 .config_path
                 .as_ref()
@@ -413,8 +821,12 @@ struct WizardState {
     edit: Option<EditState>,
     modal: Option<Modal>,
     error: Option<String>,
+    /// One-shot confirmation for the footer, cleared by the next key.
+    notice: Option<String>,
     form: FormState,
     history: Vec<String>,
+    history_settings: HistorySettings,
+    theme: Theme,
 }
 
 impl WizardState {
@@ -428,8 +840,11 @@ impl WizardState {
             edit: None,
             modal: None,
             error: None,
+            notice: None,
             form: FormState::new(defaults),
-            history: load_history().unwrap_or_default(),
+            history: load_history(&defaults.history).unwrap_or_default(),
+            history_settings: defaults.history.clone(),
+            theme: defaults.theme,
         }
     }
 }
@@ -451,9 +866,16 @@ pub fn run_interactive(defaults: InteractiveDefaults) -> Result<()> {
                     return Ok(());
                 }
                 let outcome = if plans.len() == 1 {
-                    run_single_tui(&plans[0], overwrite)?
+                    run_single_tui(&plans[0], overwrite, defaults.theme, defaults.log.clone())?
                 } else {
-                    run_batch_tui(plans, overwrite)?
+                    run_batch_tui(
+                        plans,
+                        overwrite,
+                        defaults.theme,
+                        defaults.jobs,
+                        None,
+                        defaults.log.clone(),
+                    )?
                 };
                 if matches!(outcome, RunOutcome::Exit) {
                     return Ok(());
@@ -463,12 +885,24 @@ pub fn run_interactive(defaults: InteractiveDefaults) -> Result<()> {
     }
 }
 
-pub fn run_single_tui(plan: &Plan, overwrite: bool) -> Result<RunOutcome> {
-    run_tui(vec![plan.clone()], overwrite)
+pub fn run_single_tui(
+    plan: &Plan,
+    overwrite: bool,
+    theme: Theme,
+    log: Option<RunLog>,
+) -> Result<RunOutcome> {
+    run_tui(vec![plan.clone()], overwrite, theme, 1, None, log)
 }
 
-pub fn run_batch_tui(plans: Vec<Plan>, overwrite: bool) -> Result<RunOutcome> {
-    run_tui(plans, overwrite)
+pub fn run_batch_tui(
+    plans: Vec<Plan>,
+    overwrite: bool,
+    theme: Theme,
+    jobs: usize,
+    state: Option<Arc<batch::StateFile>>,
+    log: Option<RunLog>,
+) -> Result<RunOutcome> {
+    run_tui(plans, overwrite, theme, jobs, state, log)
 }
 
 fn run_wizard_tui(defaults: &InteractiveDefaults) -> Result<FormOutcome> {
@@ -480,91 +914,118 @@ fn run_wizard_tui(defaults: &InteractiveDefaults) -> Result<FormOutcome> {
     loop {
         terminal.draw(|frame| render_wizard(frame, &state))?;
 
-        if event::poll(tick_rate)?
-            && let CEvent::Key(key) = event::read()?
-        {
-            if state.edit.is_some() {
-                handle_edit_key(&mut state, key.code)?;
-                continue;
-            }
-            if state.modal.is_some() {
-                handle_modal_key(&mut state, key.code)?;
+        if !event::poll(tick_rate)? {
+            continue;
+        }
+        let key = match event::read()? {
+            CEvent::Key(key) => key,
+            CEvent::Mouse(mouse) => {
+                let size = terminal.size()?;
+                let area = Rect::new(0, 0, size.width, size.height);
+                if let Some(outcome) = handle_wizard_mouse(&mut state, mouse, area) {
+                    return Ok(outcome);
+                }
                 continue;
             }
+            _ => continue,
+        };
+        if state.edit.is_some() {
+            handle_edit_key(&mut state, key.code)?;
+            continue;
+        }
+        if state.modal.is_some() {
+            handle_modal_key(&mut state, key.code)?;
+            continue;
+        }
+        if state
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains('\n'))
+        {
+            state.error = None;
+            continue;
+        }
+        state.notice = None;
 
-            match state.screen {
-                Screen::Welcome => match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => return Ok(FormOutcome::Quit),
-                    KeyCode::Up => {
-                        if state.welcome_selected > 0 {
-                            state.welcome_selected -= 1;
-                        }
+        match state.screen {
+            Screen::Welcome => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(FormOutcome::Quit),
+                KeyCode::Up if state.welcome_selected > 0 => {
+                    state.welcome_selected -= 1;
+                }
+                KeyCode::Down if state.welcome_selected < 2 => {
+                    state.welcome_selected += 1;
+                }
+                KeyCode::Enter => match state.welcome_selected {
+                    0 => {
+                        state.form.mode = FormMode::Single;
+                        state.screen = Screen::Configure;
                     }
-                    KeyCode::Down => {
-                        if state.welcome_selected < 2 {
-                            state.welcome_selected += 1;
-                        }
+                    1 => {
+                        state.form.mode = FormMode::Batch;
+                        state.screen = Screen::Configure;
                     }
-                    KeyCode::Enter => match state.welcome_selected {
-                        0 => {
-                            state.form.mode = FormMode::Single;
-                            state.screen = Screen::Configure;
-                        }
-                        1 => {
-                            state.form.mode = FormMode::Batch;
-                            state.screen = Screen::Configure;
-                        }
-                        _ => return Ok(FormOutcome::Quit),
-                    },
-                    _ => {}
+                    _ => return Ok(FormOutcome::Quit),
                 },
-                Screen::Configure => match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        state.screen = Screen::Welcome;
-                    }
-                    KeyCode::Tab => {
-                        state.focus = match state.focus {
-                            Panel::Inputs => Panel::Options,
-                            Panel::Options => Panel::Inputs,
-                        };
+                _ => {}
+            },
+            Screen::Configure => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    state.screen = Screen::Welcome;
+                }
+                KeyCode::Tab => {
+                    state.focus = match state.focus {
+                        Panel::Inputs => Panel::Options,
+                        Panel::Options => Panel::Inputs,
+                    };
+                }
+                KeyCode::Up => move_selection(&mut state, -1),
+                KeyCode::Down => move_selection(&mut state, 1),
+                KeyCode::Left => cycle_enum(&mut state, -1),
+                KeyCode::Right => cycle_enum(&mut state, 1),
+                KeyCode::Char(' ') => toggle_field(&mut state),
+                KeyCode::Char('b') => open_browser(&mut state),
+                KeyCode::Char('r') => open_recent(&mut state),
+                KeyCode::Char('s') => {
+                    state.edit = Some(EditState {
+                        field: TextField::SaveProfile,
+                        buffer: state.form.profile.clone(),
+                    });
+                }
+                KeyCode::Enter => {
+                    if let Some(field) = selected_text_field(&state) {
+                        let buffer = get_text_value(&state.form, field);
+                        state.edit = Some(EditState { field, buffer });
                     }
-                    KeyCode::Up => move_selection(&mut state, -1),
-                    KeyCode::Down => move_selection(&mut state, 1),
-                    KeyCode::Left => cycle_enum(&mut state, -1),
-                    KeyCode::Right => cycle_enum(&mut state, 1),
-                    KeyCode::Char(' ') => toggle_field(&mut state),
-                    KeyCode::Char('b') => open_browser(&mut state),
-                    KeyCode::Char('r') => open_recent(&mut state),
-                    KeyCode::Enter => {
-                        if let Some(field) = selected_text_field(&state) {
-                            let buffer = get_text_value(&state.form, field);
-                            state.edit = Some(EditState { field, buffer });
-                        }
+                }
+                KeyCode::F(5) => match build_plans(&mut state) {
+                    Ok((plans, overwrite, plan_only)) => {
+                        return Ok(FormOutcome::Run {
+                            plans,
+                            overwrite,
+                            plan_only,
+                        });
                     }
-                    KeyCode::F(5) => match build_plans(&mut state) {
-                        Ok((plans, overwrite, plan_only)) => {
-                            return Ok(FormOutcome::Run {
-                                plans,
-                                overwrite,
-                                plan_only,
-                            });
-                        }
-                        Err(err) => state.error = Some(err.to_string()),
-                    },
-                    _ => {}
+                    Err(err) => state.error = Some(err.to_string()),
                 },
-            }
+                _ => {}
+            },
         }
     }
 }
 
 fn input_fields(mode: FormMode) -> Vec<InputField> {
     match mode {
-        FormMode::Single => vec![InputField::Source, InputField::Destination],
+        FormMode::Single => vec![
+            InputField::Source,
+            InputField::Destination,
+            InputField::OutputDir,
+        ],
         FormMode::Batch => vec![
             InputField::BatchInputs,
             InputField::DestDir,
             InputField::ToExt,
+            InputField::NameTemplate,
         ],
     }
 }
@@ -577,14 +1038,52 @@ fn option_fields(mode: FormMode) -> Vec<OptionField> {
     fields.extend([
         OptionField::MoveSource,
         OptionField::Overwrite,
+        OptionField::OverwriteNewer,
         OptionField::Backup,
         OptionField::ImageQuality,
         OptionField::VideoBitrate,
+        OptionField::Crf,
         OptionField::AudioBitrate,
         OptionField::Preset,
         OptionField::VideoCodec,
+        OptionField::HwAccel,
         OptionField::AudioCodec,
+        OptionField::OggOpus,
+        OptionField::NoUpscaleBitrate,
+        OptionField::SampleFmt,
+        OptionField::Dither,
         OptionField::FfmpegPref,
+        OptionField::TwoPass,
+        OptionField::Mute,
+        OptionField::Speed,
+        OptionField::Start,
+        OptionField::Duration,
+        OptionField::To,
+        OptionField::Normalize,
+        OptionField::Loudness,
+        OptionField::Grayscale,
+        OptionField::Srgb,
+        OptionField::IccProfile,
+        OptionField::BackupPad,
+        OptionField::Fit,
+        OptionField::Resize,
+        OptionField::Density,
+        OptionField::Lossless,
+        OptionField::Effort,
+        OptionField::Frame,
+        OptionField::AllPages,
+        OptionField::CheckSpace,
+        OptionField::Timeout,
+        OptionField::Verify,
+        OptionField::StrictDetect,
+        OptionField::Preserve,
+        OptionField::PreferGs,
+        OptionField::MaxWidth,
+        OptionField::MaxHeight,
+        OptionField::Rotate,
+        OptionField::Tonemap,
+        OptionField::Metadata,
+        OptionField::Sanitize,
         OptionField::ConfigPath,
         OptionField::Profile,
         OptionField::PlanOnly,
@@ -627,19 +1126,40 @@ fn selected_text_field(state: &WizardState) -> Option<TextField> {
             .map(|field| match field {
                 InputField::Source => TextField::Source,
                 InputField::Destination => TextField::Destination,
+                InputField::OutputDir => TextField::OutputDir,
                 InputField::BatchInputs => TextField::BatchInputs,
                 InputField::DestDir => TextField::DestDir,
                 InputField::ToExt => TextField::ToExt,
+                InputField::NameTemplate => TextField::NameTemplate,
             }),
         Panel::Options => option_fields(state.form.mode)
             .get(state.option_index)
             .and_then(|field| match field {
                 OptionField::ImageQuality => Some(TextField::ImageQuality),
                 OptionField::VideoBitrate => Some(TextField::VideoBitrate),
+                OptionField::Crf => Some(TextField::Crf),
                 OptionField::AudioBitrate => Some(TextField::AudioBitrate),
                 OptionField::Preset => Some(TextField::Preset),
                 OptionField::VideoCodec => Some(TextField::VideoCodec),
+                OptionField::HwAccel => Some(TextField::HwAccel),
                 OptionField::AudioCodec => Some(TextField::AudioCodec),
+                OptionField::SampleFmt => Some(TextField::SampleFmt),
+                OptionField::Speed => Some(TextField::Speed),
+                OptionField::Start => Some(TextField::Start),
+                OptionField::Duration => Some(TextField::Duration),
+                OptionField::To => Some(TextField::To),
+                OptionField::Loudness => Some(TextField::Loudness),
+                OptionField::IccProfile => Some(TextField::IccProfile),
+                OptionField::BackupPad => Some(TextField::BackupPad),
+                OptionField::Fit => Some(TextField::Fit),
+                OptionField::Resize => Some(TextField::Resize),
+                OptionField::Density => Some(TextField::Density),
+                OptionField::Effort => Some(TextField::Effort),
+                OptionField::Frame => Some(TextField::Frame),
+                OptionField::Timeout => Some(TextField::Timeout),
+                OptionField::MaxWidth => Some(TextField::MaxWidth),
+                OptionField::MaxHeight => Some(TextField::MaxHeight),
+                OptionField::Rotate => Some(TextField::Rotate),
                 OptionField::ConfigPath => Some(TextField::ConfigPath),
                 OptionField::Profile => Some(TextField::Profile),
                 _ => None,
@@ -653,15 +1173,36 @@ fn get_text_value(form: &FormState, field: TextField) -> String {
         TextField::Destination => form.destination.clone(),
         TextField::BatchInputs => form.batch_inputs.clone(),
         TextField::DestDir => form.dest_dir.clone(),
+        TextField::OutputDir => form.output_dir.clone(),
         TextField::ToExt => form.to_ext.clone(),
+        TextField::NameTemplate => form.name_template.clone(),
         TextField::ImageQuality => form.image_quality.clone(),
         TextField::VideoBitrate => form.video_bitrate.clone(),
+        TextField::Crf => form.crf.clone(),
         TextField::AudioBitrate => form.audio_bitrate.clone(),
         TextField::Preset => form.preset.clone(),
         TextField::VideoCodec => form.video_codec.clone(),
+        TextField::HwAccel => form.hwaccel.clone(),
         TextField::AudioCodec => form.audio_codec.clone(),
+        TextField::SampleFmt => form.sample_fmt.clone(),
+        TextField::Speed => form.speed.clone(),
+        TextField::Start => form.start.clone(),
+        TextField::Duration => form.duration.clone(),
+        TextField::To => form.to.clone(),
+        TextField::Loudness => form.loudness.clone(),
+        TextField::IccProfile => form.icc_profile.clone(),
+        TextField::BackupPad => form.backup_pad.clone(),
+        TextField::Fit => form.fit.clone(),
+        TextField::Resize => form.resize.clone(),
+        TextField::Density => form.density.clone(),
+        TextField::Effort => form.effort.clone(),
+        TextField::Frame => form.frame.clone(),
+        TextField::Timeout => form.timeout.clone(),
+        TextField::MaxWidth => form.max_width.clone(),
+        TextField::MaxHeight => form.max_height.clone(),
+        TextField::Rotate => form.rotate.clone(),
         TextField::ConfigPath => form.config_path.clone(),
-        TextField::Profile => form.profile.clone(),
+        TextField::Profile | TextField::SaveProfile => form.profile.clone(),
     }
 }
 
@@ -671,15 +1212,36 @@ fn apply_text_value(form: &mut FormState, field: TextField, value: String) {
         TextField::Destination => form.destination = value,
         TextField::BatchInputs => form.batch_inputs = value,
         TextField::DestDir => form.dest_dir = value,
+        TextField::OutputDir => form.output_dir = value,
         TextField::ToExt => form.to_ext = value,
+        TextField::NameTemplate => form.name_template = value,
         TextField::ImageQuality => form.image_quality = value,
         TextField::VideoBitrate => form.video_bitrate = value,
+        TextField::Crf => form.crf = value,
         TextField::AudioBitrate => form.audio_bitrate = value,
         TextField::Preset => form.preset = value,
         TextField::VideoCodec => form.video_codec = value,
+        TextField::HwAccel => form.hwaccel = value,
         TextField::AudioCodec => form.audio_codec = value,
+        TextField::SampleFmt => form.sample_fmt = value,
+        TextField::Speed => form.speed = value,
+        TextField::Start => form.start = value,
+        TextField::Duration => form.duration = value,
+        TextField::To => form.to = value,
+        TextField::Loudness => form.loudness = value,
+        TextField::IccProfile => form.icc_profile = value,
+        TextField::BackupPad => form.backup_pad = value,
+        TextField::Fit => form.fit = value,
+        TextField::Resize => form.resize = value,
+        TextField::Density => form.density = value,
+        TextField::Effort => form.effort = value,
+        TextField::Frame => form.frame = value,
+        TextField::Timeout => form.timeout = value,
+        TextField::MaxWidth => form.max_width = value,
+        TextField::MaxHeight => form.max_height = value,
+        TextField::Rotate => form.rotate = value,
         TextField::ConfigPath => form.config_path = value,
-        TextField::Profile => form.profile = value,
+        TextField::Profile | TextField::SaveProfile => form.profile = value,
     }
 }
 
@@ -694,8 +1256,15 @@ fn handle_edit_key(state: &mut WizardState, key: KeyCode) -> Result<()> {
         KeyCode::Enter => {
             let value = edit.buffer.clone();
             let field = edit.field;
-            apply_text_value(&mut state.form, field, value);
             state.edit = None;
+            if field == TextField::SaveProfile {
+                match save_form_profile(state, &value) {
+                    Ok(message) => state.notice = Some(message),
+                    Err(err) => state.error = Some(format!("{err:#}")),
+                }
+            } else {
+                apply_text_value(&mut state.form, field, value);
+            }
         }
         KeyCode::Tab => {
             if let Some(updated) = autocomplete_edit(edit.field, &edit.buffer) {
@@ -720,7 +1289,9 @@ fn autocomplete_edit(field: TextField, buffer: &str) -> Option<String> {
             | TextField::Destination
             | TextField::BatchInputs
             | TextField::DestDir
+            | TextField::OutputDir
             | TextField::ConfigPath
+            | TextField::IccProfile
     ) {
         return None;
     }
@@ -849,15 +1420,11 @@ fn handle_browser_key(
         KeyCode::Esc => {
             return Ok(true);
         }
-        KeyCode::Up => {
-            if browser.selected > 0 {
-                browser.selected -= 1;
-            }
+        KeyCode::Up if browser.selected > 0 => {
+            browser.selected -= 1;
         }
-        KeyCode::Down => {
-            if browser.selected + 1 < browser.entries.len() {
-                browser.selected += 1;
-            }
+        KeyCode::Down if browser.selected + 1 < browser.entries.len() => {
+            browser.selected += 1;
         }
         KeyCode::Backspace => {
             if !browser.filter.is_empty() {
@@ -868,6 +1435,25 @@ fn handle_browser_key(
                 refresh_browser_entries(browser)?;
             }
         }
+        KeyCode::Enter
+            if browser.target == TextField::BatchInputs && !browser.selected_set.is_empty() =>
+        {
+            for path in std::mem::take(&mut browser.selected_set) {
+                append_to_batch_inputs(&mut state.form, path.display().to_string());
+            }
+            return Ok(true);
+        }
+        KeyCode::Char(' ') if browser.target == TextField::BatchInputs => {
+            if let Some(entry) = browser.entries.get(browser.selected)
+                && entry.name != ".."
+                && !browser.selected_set.remove(&entry.path)
+            {
+                browser.selected_set.insert(entry.path.clone());
+            }
+            if browser.selected + 1 < browser.entries.len() {
+                browser.selected += 1;
+            }
+        }
         KeyCode::Enter => {
             if let Some(entry) = browser.entries.get(browser.selected).cloned() {
                 if browser.target == TextField::BatchInputs {
@@ -878,7 +1464,7 @@ fn handle_browser_key(
                         append_to_batch_inputs(&mut state.form, entry.path.display().to_string());
                         return Ok(true);
                     }
-                } else if browser.target == TextField::DestDir {
+                } else if matches!(browser.target, TextField::DestDir | TextField::OutputDir) {
                     if entry.is_dir {
                         apply_text_value(
                             &mut state.form,
@@ -907,6 +1493,13 @@ fn handle_browser_key(
                 }
             }
         }
+        KeyCode::Tab => {
+            browser.sort = match browser.sort {
+                BrowserSort::Name => BrowserSort::Size,
+                BrowserSort::Size => BrowserSort::Name,
+            };
+            refresh_browser_entries(browser)?;
+        }
         KeyCode::Char('a') => {
             if browser.target == TextField::BatchInputs
                 && let Some(entry) = browser.entries.get(browser.selected)
@@ -932,21 +1525,15 @@ fn handle_recent_key(
         KeyCode::Esc => {
             return Ok(true);
         }
-        KeyCode::Up => {
-            if recent.selected > 0 {
-                recent.selected -= 1;
-            }
+        KeyCode::Up if recent.selected > 0 => {
+            recent.selected -= 1;
         }
-        KeyCode::Down => {
-            if recent.selected + 1 < recent.entries.len() {
-                recent.selected += 1;
-            }
+        KeyCode::Down if recent.selected + 1 < recent.entries.len() => {
+            recent.selected += 1;
         }
-        KeyCode::Backspace => {
-            if !recent.filter.is_empty() {
-                recent.filter.pop();
-                refresh_recent_entries(state, recent);
-            }
+        KeyCode::Backspace if !recent.filter.is_empty() => {
+            recent.filter.pop();
+            refresh_recent_entries(state, recent);
         }
         KeyCode::Enter => {
             if let Some(entry) = recent.entries.get(recent.selected).cloned() {
@@ -973,12 +1560,45 @@ fn toggle_field(state: &mut WizardState) {
                 state.form.overwrite = !state.form.overwrite;
                 if state.form.overwrite {
                     state.form.backup = false;
+                    state.form.overwrite_newer = false;
+                }
+            }
+            Some(OptionField::OverwriteNewer) => {
+                state.form.overwrite_newer = !state.form.overwrite_newer;
+                if state.form.overwrite_newer {
+                    state.form.overwrite = false;
+                    state.form.backup = false;
                 }
             }
             Some(OptionField::Backup) => {
                 state.form.backup = !state.form.backup;
                 if state.form.backup {
                     state.form.overwrite = false;
+                    state.form.overwrite_newer = false;
+                }
+            }
+            Some(OptionField::OggOpus) => state.form.ogg_opus = !state.form.ogg_opus,
+            Some(OptionField::NoUpscaleBitrate) => {
+                state.form.no_upscale_bitrate = !state.form.no_upscale_bitrate
+            }
+            Some(OptionField::Dither) => state.form.dither = !state.form.dither,
+            Some(OptionField::TwoPass) => state.form.two_pass = !state.form.two_pass,
+            Some(OptionField::Mute) => state.form.mute = !state.form.mute,
+            Some(OptionField::Normalize) => state.form.normalize = !state.form.normalize,
+            Some(OptionField::Grayscale) => state.form.grayscale = !state.form.grayscale,
+            Some(OptionField::Srgb) => state.form.srgb = !state.form.srgb,
+            Some(OptionField::AllPages) => state.form.all_pages = !state.form.all_pages,
+            Some(OptionField::Lossless) => state.form.lossless = !state.form.lossless,
+            Some(OptionField::CheckSpace) => state.form.check_space = !state.form.check_space,
+            Some(OptionField::Verify) => state.form.verify = !state.form.verify,
+            Some(OptionField::StrictDetect) => state.form.strict_detect = !state.form.strict_detect,
+            Some(OptionField::Preserve) => state.form.preserve = !state.form.preserve,
+            Some(OptionField::PreferGs) => state.form.prefer_gs = !state.form.prefer_gs,
+            Some(OptionField::Tonemap) => state.form.tonemap = !state.form.tonemap,
+            Some(OptionField::Sanitize) => {
+                state.form.sanitize = match state.form.sanitize {
+                    Some(_) => None,
+                    None => Some(Sanitize::default()),
                 }
             }
             Some(OptionField::PlanOnly) => state.form.plan_only = !state.form.plan_only,
@@ -991,7 +1611,22 @@ fn cycle_enum(state: &mut WizardState, delta: i8) {
     if state.focus != Panel::Options {
         return;
     }
-    if option_fields(state.form.mode).get(state.option_index) != Some(&OptionField::FfmpegPref) {
+    let field = option_fields(state.form.mode)
+        .get(state.option_index)
+        .copied();
+    if field == Some(OptionField::Metadata) {
+        state.form.metadata = match (state.form.metadata, delta) {
+            (Metadata::Default, 1) => Metadata::Strip,
+            (Metadata::Strip, 1) => Metadata::Keep,
+            (Metadata::Keep, 1) => Metadata::Default,
+            (Metadata::Default, -1) => Metadata::Keep,
+            (Metadata::Strip, -1) => Metadata::Default,
+            (Metadata::Keep, -1) => Metadata::Strip,
+            (value, _) => value,
+        };
+        return;
+    }
+    if field != Some(OptionField::FfmpegPref) {
         return;
     }
     state.form.ffmpeg_pref = match (state.form.ffmpeg_pref, delta) {
@@ -1016,6 +1651,8 @@ fn open_browser(state: &mut WizardState) {
         entries: Vec::new(),
         selected: 0,
         filter: String::new(),
+        sort: BrowserSort::Name,
+        selected_set: Default::default(),
     };
     if refresh_browser_entries(&mut browser).is_ok() {
         state.modal = Some(Modal::Browser(browser));
@@ -1045,12 +1682,14 @@ fn selected_path_field(state: &WizardState) -> Option<TextField> {
                 InputField::Destination => Some(TextField::Destination),
                 InputField::BatchInputs => Some(TextField::BatchInputs),
                 InputField::DestDir => Some(TextField::DestDir),
-                InputField::ToExt => None,
+                InputField::OutputDir => Some(TextField::OutputDir),
+                InputField::ToExt | InputField::NameTemplate => None,
             }),
         Panel::Options => option_fields(state.form.mode)
             .get(state.option_index)
             .and_then(|field| match field {
                 OptionField::ConfigPath => Some(TextField::ConfigPath),
+                OptionField::IccProfile => Some(TextField::IccProfile),
                 _ => None,
             }),
     }
@@ -1079,20 +1718,27 @@ fn refresh_browser_entries(browser: &mut BrowserState) -> Result<()> {
             name: "..".to_string(),
             path: parent.to_path_buf(),
             is_dir: true,
+            size: None,
         });
     }
-    let filter = browser.filter.to_lowercase();
     let mut dirs = Vec::new();
     let mut files = Vec::new();
     if let Ok(read_dir) = std::fs::read_dir(&browser.cwd) {
         for entry in read_dir.flatten() {
             let path = entry.path();
             let name = entry.file_name().to_string_lossy().to_string();
-            if !filter.is_empty() && !name.to_lowercase().contains(&filter) {
-                continue;
-            }
             let is_dir = path.is_dir();
-            let entry = BrowserEntry { name, path, is_dir };
+            let size = if is_dir {
+                None
+            } else {
+                std::fs::metadata(&path).ok().map(|metadata| metadata.len())
+            };
+            let entry = BrowserEntry {
+                name,
+                path,
+                is_dir,
+                size,
+            };
             if is_dir {
                 dirs.push(entry);
             } else {
@@ -1101,9 +1747,12 @@ fn refresh_browser_entries(browser: &mut BrowserState) -> Result<()> {
         }
     }
     dirs.sort_by(|a, b| a.name.cmp(&b.name));
-    files.sort_by(|a, b| a.name.cmp(&b.name));
-    entries.extend(dirs);
-    entries.extend(files);
+    match browser.sort {
+        BrowserSort::Name => files.sort_by(|a, b| a.name.cmp(&b.name)),
+        BrowserSort::Size => files.sort_by(|a, b| b.size.cmp(&a.size).then(a.name.cmp(&b.name))),
+    }
+    dirs.extend(files);
+    entries.extend(fuzzy::rank(&browser.filter, dirs, |entry| &entry.name));
     browser.entries = entries;
     if browser.selected >= browser.entries.len() {
         browser.selected = browser.entries.len().saturating_sub(1);
@@ -1112,14 +1761,7 @@ fn refresh_browser_entries(browser: &mut BrowserState) -> Result<()> {
 }
 
 fn refresh_recent_entries(state: &WizardState, recent: &mut RecentState) {
-    let filter = recent.filter.to_lowercase();
-    let mut entries = Vec::new();
-    for item in &state.history {
-        if filter.is_empty() || item.to_lowercase().contains(&filter) {
-            entries.push(item.clone());
-        }
-    }
-    recent.entries = entries;
+    recent.entries = fuzzy::rank(&recent.filter, state.history.clone(), |item| item);
     if recent.selected >= recent.entries.len() {
         recent.selected = recent.entries.len().saturating_sub(1);
     }
@@ -1130,7 +1772,7 @@ fn apply_recent_selection(state: &mut WizardState, target: TextField, value: Str
         append_to_batch_inputs(&mut state.form, value);
         return;
     }
-    if target == TextField::DestDir {
+    if matches!(target, TextField::DestDir | TextField::OutputDir) {
         let path = expand_tilde(&value);
         if path.is_file()
             && let Some(parent) = path.parent()
@@ -1151,10 +1793,21 @@ fn append_to_batch_inputs(form: &mut FormState, value: String) {
     }
 }
 
-fn build_plans(state: &mut WizardState) -> Result<(Vec<Plan>, bool, bool)> {
-    state.error = None;
-    let config_path = state.form.config_path.trim();
-    let profile = state.form.profile.trim();
+/// The destination extension that picks the config's `[ext.*]` block.
+fn form_dest_ext(form: &FormState) -> Option<String> {
+    match form.mode {
+        FormMode::Single => plan::normalize_ext(std::path::Path::new(form.destination.trim())),
+        FormMode::Batch => {
+            let to_ext = form.to_ext.trim();
+            (!to_ext.is_empty()).then(|| plan::normalize_ext_name(to_ext))
+        }
+    }
+}
+
+/// Conversion options from the selected config/profile with the form's fields on top.
+fn form_options(form: &FormState) -> Result<plan::ConversionOptions> {
+    let config_path = form.config_path.trim();
+    let profile = form.profile.trim();
     let mut options = if !config_path.is_empty() || !profile.is_empty() {
         config::load_options(
             if config_path.is_empty() {
@@ -1167,13 +1820,14 @@ fn build_plans(state: &mut WizardState) -> Result<(Vec<Plan>, bool, bool)> {
             } else {
                 Some(profile)
             },
+            form_dest_ext(form).as_deref(),
         )?
         .unwrap_or_default()
     } else {
         plan::ConversionOptions::default()
     };
 
-    let image_quality = state.form.image_quality.trim();
+    let image_quality = form.image_quality.trim();
     if !image_quality.is_empty() {
         let value: u8 = image_quality
             .parse()
@@ -1181,39 +1835,221 @@ fn build_plans(state: &mut WizardState) -> Result<(Vec<Plan>, bool, bool)> {
         if value == 0 || value > 100 {
             anyhow::bail!("image quality must be between 1 and 100");
         }
-        options.image_quality = Some(value);
+        // The field is prefilled from the config; only an edited value overrides
+        // `[ext.*]` qualities.
+        if options.image_quality != Some(value) {
+            options.image_quality = Some(value);
+            options.image_quality_by_ext.clear();
+        }
     }
-    let video_bitrate = state.form.video_bitrate.trim();
+    let video_bitrate = form.video_bitrate.trim();
     options.video_bitrate = if video_bitrate.is_empty() {
         None
     } else {
         Some(video_bitrate.to_string())
     };
-    let audio_bitrate = state.form.audio_bitrate.trim();
+    let crf = form.crf.trim();
+    options.crf = if crf.is_empty() {
+        None
+    } else {
+        Some(crf.parse().context("CRF must be a number")?)
+    };
+    let audio_bitrate = form.audio_bitrate.trim();
     options.audio_bitrate = if audio_bitrate.is_empty() {
         None
     } else {
         Some(audio_bitrate.to_string())
     };
-    let preset = state.form.preset.trim();
+    let preset = form.preset.trim();
     options.preset = if preset.is_empty() {
         None
     } else {
         Some(preset.to_string())
     };
-    let video_codec = state.form.video_codec.trim();
+    let video_codec = form.video_codec.trim();
     options.video_codec = if video_codec.is_empty() {
         None
     } else {
         Some(video_codec.to_string())
     };
-    let audio_codec = state.form.audio_codec.trim();
+    let hwaccel = form.hwaccel.trim();
+    options.hwaccel = if hwaccel.is_empty() {
+        None
+    } else {
+        Some(plan::parse_hwaccel(hwaccel)?)
+    };
+    let audio_codec = form.audio_codec.trim();
     options.audio_codec = if audio_codec.is_empty() {
         None
     } else {
         Some(audio_codec.to_string())
     };
-    options.ffmpeg_preference = state.form.ffmpeg_pref;
+    options.ogg_opus = form.ogg_opus;
+    options.no_upscale_bitrate = form.no_upscale_bitrate;
+    let sample_fmt = form.sample_fmt.trim();
+    options.sample_fmt = if sample_fmt.is_empty() {
+        None
+    } else {
+        Some(plan::parse_sample_fmt(sample_fmt)?)
+    };
+    options.dither = form.dither;
+    options.ffmpeg_preference = form.ffmpeg_pref;
+    options.ffmpeg_loglevel = form.ffmpeg_loglevel.clone();
+    options.two_pass = form.two_pass;
+    options.mute = form.mute;
+    let speed = form.speed.trim();
+    options.speed = if speed.is_empty() {
+        None
+    } else {
+        Some(speed.parse().context("speed must be a number")?)
+    };
+    let start = form.start.trim();
+    options.start = if start.is_empty() {
+        None
+    } else {
+        Some(start.to_string())
+    };
+    let duration = form.duration.trim();
+    options.duration = if duration.is_empty() {
+        None
+    } else {
+        Some(duration.to_string())
+    };
+    let to = form.to.trim();
+    options.to = if to.is_empty() {
+        None
+    } else {
+        Some(to.to_string())
+    };
+    options.normalize = form.normalize;
+    let loudness = form.loudness.trim();
+    options.loudness = if loudness.is_empty() {
+        None
+    } else {
+        Some(loudness.parse().context("loudness must be a number")?)
+    };
+    options.grayscale = form.grayscale;
+    options.srgb = form.srgb;
+    let icc_profile = form.icc_profile.trim();
+    options.icc_profile = if icc_profile.is_empty() {
+        None
+    } else {
+        Some(expand_tilde(icc_profile))
+    };
+    let backup_pad = form.backup_pad.trim();
+    options.backup_pad = if backup_pad.is_empty() {
+        None
+    } else {
+        Some(
+            backup_pad
+                .parse()
+                .context("backup padding must be a number")?,
+        )
+    };
+    let fit = form.fit.trim();
+    options.fit = if fit.is_empty() {
+        None
+    } else {
+        Some(fit.to_string())
+    };
+    let resize = form.resize.trim();
+    options.resize = if resize.is_empty() {
+        None
+    } else {
+        Some(resize.to_string())
+    };
+    let density = form.density.trim();
+    options.density = if density.is_empty() {
+        None
+    } else {
+        Some(density.parse().context("density must be a number")?)
+    };
+    options.lossless = form.lossless;
+    let effort = form.effort.trim();
+    options.effort = if effort.is_empty() {
+        None
+    } else {
+        Some(
+            effort
+                .parse()
+                .context("effort must be a number from 0 to 6")?,
+        )
+    };
+    let frame = form.frame.trim();
+    options.frame = if frame.is_empty() {
+        None
+    } else {
+        Some(frame.parse().context("frame must be a number")?)
+    };
+    options.all_pages = form.all_pages;
+    options.check_space = form.check_space;
+    let timeout = form.timeout.trim();
+    options.timeout = if timeout.is_empty() {
+        None
+    } else {
+        Some(
+            timeout
+                .parse()
+                .context("timeout must be a number of seconds")?,
+        )
+    };
+    options.verify = form.verify;
+    options.strict_detect = form.strict_detect;
+    options.preserve = form.preserve;
+    options.overwrite_newer = form.overwrite_newer;
+    options.prefer_gs = form.prefer_gs;
+    let max_width = form.max_width.trim();
+    options.max_width = if max_width.is_empty() {
+        None
+    } else {
+        Some(max_width.parse().context("max width must be a number")?)
+    };
+    let max_height = form.max_height.trim();
+    options.max_height = if max_height.is_empty() {
+        None
+    } else {
+        Some(max_height.parse().context("max height must be a number")?)
+    };
+    let rotate = form.rotate.trim();
+    options.rotate = if rotate.is_empty() {
+        None
+    } else {
+        Some(parse_rotation(rotate)?)
+    };
+    options.tonemap = form.tonemap;
+    options.metadata = form.metadata;
+    options.sanitize = form.sanitize;
+    let output_dir = form.output_dir.trim();
+    options.output_dir = if output_dir.is_empty() {
+        None
+    } else {
+        Some(expand_tilde(output_dir))
+    };
+    Ok(options)
+}
+
+/// Saves the form's options as `[profile.NAME]` in the form's config (or the default
+/// config) and selects that profile.
+fn save_form_profile(state: &mut WizardState, name: &str) -> Result<String> {
+    state.error = None;
+    let options = form_options(&state.form)?;
+    let config_path = state.form.config_path.trim();
+    let written = config::save_profile(
+        (!config_path.is_empty()).then(|| std::path::Path::new(config_path)),
+        name,
+        &options,
+    )?;
+    state.form.profile = name.trim().to_string();
+    Ok(format!(
+        "Saved profile {} to {}",
+        state.form.profile,
+        written.display()
+    ))
+}
+
+fn build_plans(state: &mut WizardState) -> Result<(Vec<Plan>, bool, bool)> {
+    state.error = None;
+    let options = form_options(&state.form)?;
 
     let mut plans = Vec::new();
     match state.form.mode {
@@ -1230,6 +2066,14 @@ fn build_plans(state: &mut WizardState) -> Result<(Vec<Plan>, bool, bool)> {
                 state.form.backup,
                 options,
             )?;
+            let mut problems = Vec::new();
+            if !plan.source.exists() {
+                problems.push(format!("input not found: {source}"));
+            }
+            problems.extend(unsupported_problem(&plan));
+            if !problems.is_empty() {
+                anyhow::bail!(preflight_error(&problems));
+            }
             plans.push(plan);
         }
         FormMode::Batch => {
@@ -1241,8 +2085,24 @@ fn build_plans(state: &mut WizardState) -> Result<(Vec<Plan>, bool, bool)> {
             if inputs.is_empty() {
                 anyhow::bail!("at least one input is required");
             }
-            let sources = batch::collect_sources(&inputs, Vec::new(), state.form.recursive)?;
-            if sources.is_empty() {
+            let missing = batch::missing_inputs(&inputs);
+            let mut problems: Vec<String> = missing
+                .iter()
+                .map(|input| format!("input not found: {input}"))
+                .collect();
+            let present: Vec<String> = inputs
+                .iter()
+                .filter(|input| !missing.contains(&input.as_str()))
+                .cloned()
+                .collect();
+            let sources = batch::collect_sources(
+                &present,
+                [],
+                state.form.recursive,
+                &batch::Excludes::default(),
+            )?
+            .sources;
+            if sources.is_empty() && problems.is_empty() {
                 anyhow::bail!("no inputs resolved for batch mode");
             }
             let batch_input = batch::BatchInput {
@@ -1252,17 +2112,33 @@ fn build_plans(state: &mut WizardState) -> Result<(Vec<Plan>, bool, bool)> {
                 } else {
                     Some(state.form.to_ext.trim().to_string())
                 },
+                name_template: if state.form.name_template.trim().is_empty() {
+                    None
+                } else {
+                    Some(batch::NameTemplate::parse(state.form.name_template.trim())?)
+                },
             };
-            for source in sources {
-                let destination = batch::dest_for_source(&batch_input, &source)?;
-                let plan = plan::build_plan(
-                    &source,
-                    &destination,
-                    state.form.move_source,
-                    state.form.backup,
-                    options.clone(),
-                )?;
-                plans.push(plan);
+            for (index, source) in sources.into_iter().enumerate() {
+                let planned =
+                    batch::dest_for_source(&batch_input, &source, index + 1).and_then(|dest| {
+                        plan::build_plan(
+                            &source,
+                            &dest,
+                            state.form.move_source,
+                            state.form.backup,
+                            options.clone(),
+                        )
+                    });
+                match planned {
+                    Ok(plan) => {
+                        problems.extend(unsupported_problem(&plan));
+                        plans.push(plan);
+                    }
+                    Err(err) => problems.push(format!("{}: {err:#}", source.display())),
+                }
+            }
+            if !problems.is_empty() {
+                anyhow::bail!(preflight_error(&problems));
             }
         }
     }
@@ -1272,6 +2148,7 @@ fn build_plans(state: &mut WizardState) -> Result<(Vec<Plan>, bool, bool)> {
         FormMode::Single => {
             additions.push(state.form.source.clone());
             additions.push(state.form.destination.clone());
+            additions.push(state.form.output_dir.clone());
         }
         FormMode::Batch => {
             additions.push(state.form.dest_dir.clone());
@@ -1283,6 +2160,23 @@ fn build_plans(state: &mut WizardState) -> Result<(Vec<Plan>, bool, bool)> {
     Ok((plans, state.form.overwrite, state.form.plan_only))
 }
 
+/// Names a plan that would fail at run time because no backend handles it.
+fn unsupported_problem(plan: &Plan) -> Option<String> {
+    if plan.strategy != plan::Strategy::Convert || plan.backend.is_some() {
+        return None;
+    }
+    let reason = plan.backend_reason.as_deref().unwrap_or("no backend");
+    Some(format!("{}: {reason}", plan.source.display()))
+}
+
+/// One problem stays on the footer line; several become a list the form shows in a popup.
+fn preflight_error(problems: &[String]) -> String {
+    match problems {
+        [only] => only.clone(),
+        _ => format!("{} problems:\n{}", problems.len(), problems.join("\n")),
+    }
+}
+
 fn parse_inputs(raw: &str) -> Vec<String> {
     raw.lines()
         .flat_map(|line| line.split(','))
@@ -1299,8 +2193,8 @@ fn render_wizard(frame: &mut Frame<'_>, state: &WizardState) {
             render_config(frame, state);
             if let Some(modal) = &state.modal {
                 match modal {
-                    Modal::Browser(browser) => render_browser_modal(frame, browser),
-                    Modal::Recent(recent) => render_recent_modal(frame, recent),
+                    Modal::Browser(browser) => render_browser_modal(frame, browser, &state.theme),
+                    Modal::Recent(recent) => render_recent_modal(frame, recent, &state.theme),
                 }
             }
         }
@@ -1308,16 +2202,8 @@ fn render_wizard(frame: &mut Frame<'_>, state: &WizardState) {
 }
 
 fn render_welcome(frame: &mut Frame<'_>, state: &WizardState) {
-    let theme = Theme::new();
-    let area = frame.area();
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(5),
-            Constraint::Min(6),
-            Constraint::Length(3),
-        ])
-        .split(area);
+    let theme = &state.theme;
+    let layout = welcome_layout(frame.area());
 
     let title = Paragraph::new(vec![
         Line::from(Span::styled(
@@ -1389,16 +2275,9 @@ fn render_welcome(frame: &mut Frame<'_>, state: &WizardState) {
 }
 
 fn render_config(frame: &mut Frame<'_>, state: &WizardState) {
-    let theme = Theme::new();
+    let theme = &state.theme;
     let area = frame.area();
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Min(8),
-            Constraint::Length(3),
-        ])
-        .split(area);
+    let (layout, body) = config_layout(area);
 
     let summary = match state.form.mode {
         FormMode::Single => format!(
@@ -1426,11 +2305,6 @@ fn render_config(frame: &mut Frame<'_>, state: &WizardState) {
     );
     frame.render_widget(header, layout[0]);
 
-    let body = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(layout[1]);
-
     let input_items = input_fields(state.form.mode);
     let input_list: Vec<ListItem> = input_items
         .iter()
@@ -1498,10 +2372,15 @@ fn render_config(frame: &mut Frame<'_>, state: &WizardState) {
             "Edit: {} (Enter save, Tab autocomplete, Esc cancel)",
             edit_label(edit.field)
         )
+    } else if let Some(notice) = state.notice.as_deref() {
+        notice.to_string()
     } else if let Some(error) = state.error.as_deref() {
-        format!("Error: {error}")
+        match error.split_once('\n') {
+            Some((summary, _)) => format!("Error: {summary} (press any key to dismiss)"),
+            None => format!("Error: {error}"),
+        }
     } else {
-        "Tab switch panel, Enter edit, b browse, r recent, Space toggle, F5 run, Esc back"
+        "Tab switch panel, Enter edit, b browse, r recent, s save profile, Space toggle, F5 run, Esc back"
             .to_string()
     };
     let footer = Paragraph::new(Line::from(Span::styled(
@@ -1515,6 +2394,26 @@ fn render_config(frame: &mut Frame<'_>, state: &WizardState) {
     );
     frame.render_widget(footer, layout[2]);
 
+    if let Some((_, problems)) = state
+        .error
+        .as_deref()
+        .and_then(|error| error.split_once('\n'))
+    {
+        let problems_area = centered_rect(80, 50, area);
+        let lines: Vec<Line> = problems
+            .lines()
+            .map(|problem| Line::from(Span::styled(problem, Style::default().fg(theme.bad))))
+            .collect();
+        let problems_text = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(Span::styled(
+                "Cannot run",
+                Style::default().fg(theme.bad).add_modifier(Modifier::BOLD),
+            )))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(ratatui::widgets::Clear, problems_area);
+        frame.render_widget(problems_text, problems_area);
+    }
+
     if let Some(edit) = &state.edit {
         let edit_area = centered_rect(70, 20, area);
         let edit_block = Block::default().borders(Borders::ALL).title(Span::styled(
@@ -1533,8 +2432,7 @@ fn render_config(frame: &mut Frame<'_>, state: &WizardState) {
     }
 }
 
-fn render_browser_modal(frame: &mut Frame<'_>, browser: &BrowserState) {
-    let theme = Theme::new();
+fn render_browser_modal(frame: &mut Frame<'_>, browser: &BrowserState, theme: &Theme) {
     let area = centered_rect(80, 70, frame.area());
     let layout = Layout::default()
         .direction(Direction::Vertical)
@@ -1547,7 +2445,11 @@ fn render_browser_modal(frame: &mut Frame<'_>, browser: &BrowserState) {
         .split(area);
 
     let header = Paragraph::new(Line::from(Span::styled(
-        format!("Browse: {}", browser.cwd.display()),
+        format!(
+            "Browse: {}  (sorted by {})",
+            browser.cwd.display(),
+            browser.sort.label()
+        ),
         Style::default()
             .fg(theme.primary)
             .add_modifier(Modifier::BOLD),
@@ -1571,16 +2473,33 @@ fn render_browser_modal(frame: &mut Frame<'_>, browser: &BrowserState) {
     .block(Block::default().borders(Borders::ALL).title("Filter"));
     frame.render_widget(filter, layout[1]);
 
+    let multi_select = browser.target == TextField::BatchInputs;
     let items: Vec<ListItem> = browser
         .entries
         .iter()
         .map(|entry| {
-            let name = if entry.is_dir {
-                format!("{}/", entry.name)
+            let mark = match (multi_select, browser.selected_set.contains(&entry.path)) {
+                (false, _) => "",
+                (true, true) => "[x] ",
+                (true, false) => "[ ] ",
+            };
+            let (name, kind) = if entry.is_dir {
+                (format!("{}/", entry.name), "dir")
             } else {
-                entry.name.clone()
+                (
+                    entry.name.clone(),
+                    plan::MediaKind::from_path(&entry.path).label(),
+                )
             };
-            ListItem::new(Line::from(name))
+            let size = entry.size.map(execute::format_size).unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(mark, Style::default().fg(theme.good)),
+                Span::raw(format!("{name:<40} ")),
+                Span::styled(
+                    format!("{kind:<8} {size:>8}"),
+                    Style::default().fg(theme.muted),
+                ),
+            ]))
         })
         .collect();
     let mut list_state = ratatui::widgets::ListState::default();
@@ -1603,7 +2522,14 @@ fn render_browser_modal(frame: &mut Frame<'_>, browser: &BrowserState) {
         );
     frame.render_stateful_widget(list, layout[2], &mut list_state);
 
-    let help = "Enter open/select, a add (batch), Backspace filter/up, Esc close";
+    let help = if multi_select {
+        format!(
+            "Space check, Enter add {} checked/open, a add, Tab sort, Backspace filter/up, Esc close",
+            browser.selected_set.len()
+        )
+    } else {
+        "Enter open/select, Tab sort name/size, Backspace filter/up, Esc close".to_string()
+    };
     let footer = Paragraph::new(Line::from(Span::styled(
         help,
         Style::default().fg(theme.muted),
@@ -1612,8 +2538,7 @@ fn render_browser_modal(frame: &mut Frame<'_>, browser: &BrowserState) {
     frame.render_widget(footer, layout[3]);
 }
 
-fn render_recent_modal(frame: &mut Frame<'_>, recent: &RecentState) {
-    let theme = Theme::new();
+fn render_recent_modal(frame: &mut Frame<'_>, recent: &RecentState, theme: &Theme) {
     let area = centered_rect(70, 60, frame.area());
     let layout = Layout::default()
         .direction(Direction::Vertical)
@@ -1681,7 +2606,12 @@ fn input_label_value(field: &InputField, form: &FormState) -> (String, String) {
         InputField::Destination => ("Destination".to_string(), short_value(&form.destination)),
         InputField::BatchInputs => ("Inputs".to_string(), summarize_inputs(&form.batch_inputs)),
         InputField::DestDir => ("Dest dir".to_string(), short_value(&form.dest_dir)),
+        InputField::OutputDir => ("Output dir".to_string(), short_value(&form.output_dir)),
         InputField::ToExt => ("To ext".to_string(), short_value(&form.to_ext)),
+        InputField::NameTemplate => (
+            "Name template".to_string(),
+            short_value(&form.name_template),
+        ),
     }
 }
 
@@ -1690,6 +2620,10 @@ fn option_label_value(field: &OptionField, form: &FormState) -> (String, String)
         OptionField::Recursive => ("Recursive".to_string(), yes_no(form.recursive)),
         OptionField::MoveSource => ("Move source".to_string(), yes_no(form.move_source)),
         OptionField::Overwrite => ("Overwrite".to_string(), yes_no(form.overwrite)),
+        OptionField::OverwriteNewer => (
+            "Overwrite if newer".to_string(),
+            yes_no(form.overwrite_newer),
+        ),
         OptionField::Backup => ("Backup".to_string(), yes_no(form.backup)),
         OptionField::ImageQuality => (
             "Image quality".to_string(),
@@ -1699,13 +2633,21 @@ fn option_label_value(field: &OptionField, form: &FormState) -> (String, String)
             "Video bitrate".to_string(),
             short_value(&form.video_bitrate),
         ),
+        OptionField::Crf => ("CRF".to_string(), short_value(&form.crf)),
         OptionField::AudioBitrate => (
             "Audio bitrate".to_string(),
             short_value(&form.audio_bitrate),
         ),
         OptionField::Preset => ("Preset".to_string(), short_value(&form.preset)),
         OptionField::VideoCodec => ("Video codec".to_string(), short_value(&form.video_codec)),
+        OptionField::HwAccel => ("HW accel".to_string(), short_value(&form.hwaccel)),
         OptionField::AudioCodec => ("Audio codec".to_string(), short_value(&form.audio_codec)),
+        OptionField::OggOpus => ("Opus in .ogg".to_string(), yes_no(form.ogg_opus)),
+        OptionField::NoUpscaleBitrate => {
+            ("Cap abitrate".to_string(), yes_no(form.no_upscale_bitrate))
+        }
+        OptionField::SampleFmt => ("Sample format".to_string(), short_value(&form.sample_fmt)),
+        OptionField::Dither => ("Dither".to_string(), yes_no(form.dither)),
         OptionField::FfmpegPref => (
             "FFmpeg mode".to_string(),
             match form.ffmpeg_pref {
@@ -1714,6 +2656,43 @@ fn option_label_value(field: &OptionField, form: &FormState) -> (String, String)
                 FfmpegPreference::Transcode => "transcode".to_string(),
             },
         ),
+        OptionField::TwoPass => ("Two-pass".to_string(), yes_no(form.two_pass)),
+        OptionField::Mute => ("Mute audio".to_string(), yes_no(form.mute)),
+        OptionField::Speed => ("Speed".to_string(), short_value(&form.speed)),
+        OptionField::Start => ("Start".to_string(), short_value(&form.start)),
+        OptionField::Duration => ("Duration".to_string(), short_value(&form.duration)),
+        OptionField::To => ("To".to_string(), short_value(&form.to)),
+        OptionField::Normalize => ("Normalize".to_string(), yes_no(form.normalize)),
+        OptionField::Loudness => ("Loudness".to_string(), short_value(&form.loudness)),
+        OptionField::Grayscale => ("Grayscale".to_string(), yes_no(form.grayscale)),
+        OptionField::Srgb => ("sRGB".to_string(), yes_no(form.srgb)),
+        OptionField::IccProfile => ("ICC profile".to_string(), short_value(&form.icc_profile)),
+        OptionField::BackupPad => ("Backup pad".to_string(), short_value(&form.backup_pad)),
+        OptionField::Fit => ("Fit (WxH)".to_string(), short_value(&form.fit)),
+        OptionField::Resize => ("Resize".to_string(), short_value(&form.resize)),
+        OptionField::Density => ("Density".to_string(), short_value(&form.density)),
+        OptionField::Lossless => ("Lossless".to_string(), yes_no(form.lossless)),
+        OptionField::Effort => ("Effort".to_string(), short_value(&form.effort)),
+        OptionField::Frame => ("Frame".to_string(), short_value(&form.frame)),
+        OptionField::AllPages => ("All PDF pages".to_string(), yes_no(form.all_pages)),
+        OptionField::CheckSpace => ("Check space".to_string(), yes_no(form.check_space)),
+        OptionField::Timeout => ("Timeout".to_string(), short_value(&form.timeout)),
+        OptionField::Verify => ("Verify copies".to_string(), yes_no(form.verify)),
+        OptionField::StrictDetect => ("Strict detection".to_string(), yes_no(form.strict_detect)),
+        OptionField::Preserve => ("Preserve time/mode".to_string(), yes_no(form.preserve)),
+        OptionField::PreferGs => ("Prefer Ghostscript".to_string(), yes_no(form.prefer_gs)),
+        OptionField::MaxWidth => ("Max width".to_string(), short_value(&form.max_width)),
+        OptionField::MaxHeight => ("Max height".to_string(), short_value(&form.max_height)),
+        OptionField::Rotate => (
+            "Rotate (0/90/180/270/auto)".to_string(),
+            short_value(&form.rotate),
+        ),
+        OptionField::Tonemap => ("Tone-map HDR".to_string(), yes_no(form.tonemap)),
+        OptionField::Metadata => ("Metadata".to_string(), form.metadata.label().to_string()),
+        OptionField::Sanitize => (
+            "Sanitize names".to_string(),
+            yes_no(form.sanitize.is_some()),
+        ),
         OptionField::ConfigPath => ("Config path".to_string(), short_value(&form.config_path)),
         OptionField::Profile => ("Profile".to_string(), short_value(&form.profile)),
         OptionField::PlanOnly => ("Plan only".to_string(), yes_no(form.plan_only)),
@@ -1726,15 +2705,37 @@ fn edit_label(field: TextField) -> &'static str {
         TextField::Destination => "Destination",
         TextField::BatchInputs => "Inputs",
         TextField::DestDir => "Dest dir",
+        TextField::OutputDir => "Output dir (for bare destination names)",
         TextField::ToExt => "To ext",
+        TextField::NameTemplate => "Name template ({stem} {ext} {parent} {n})",
         TextField::ImageQuality => "Image quality",
         TextField::VideoBitrate => "Video bitrate",
+        TextField::Crf => "CRF",
         TextField::AudioBitrate => "Audio bitrate",
         TextField::Preset => "Preset",
         TextField::VideoCodec => "Video codec",
+        TextField::HwAccel => "HW accel (nvenc/vaapi/videotoolbox/none)",
         TextField::AudioCodec => "Audio codec",
+        TextField::SampleFmt => "Sample format (s16/s24/s32/flt)",
+        TextField::Speed => "Speed",
+        TextField::Start => "Start (seconds or HH:MM:SS)",
+        TextField::Duration => "Duration (seconds or HH:MM:SS)",
+        TextField::To => "To (seconds or HH:MM:SS)",
+        TextField::Loudness => "Loudness (LUFS, default -16)",
+        TextField::IccProfile => "ICC profile",
+        TextField::BackupPad => "Backup pad",
+        TextField::Fit => "Fit (WxH)",
+        TextField::Resize => "Resize",
+        TextField::Density => "Density (DPI)",
+        TextField::Effort => "Effort (0-6)",
+        TextField::Frame => "Frame",
+        TextField::Timeout => "Timeout (seconds)",
+        TextField::MaxWidth => "Max width",
+        TextField::MaxHeight => "Max height",
+        TextField::Rotate => "Rotate (0/90/180/270/auto)",
         TextField::ConfigPath => "Config path",
         TextField::Profile => "Profile",
+        TextField::SaveProfile => "Save options as profile",
     }
 }
 
@@ -1768,19 +2769,40 @@ fn yes_no(value: bool) -> String {
     }
 }
 
-fn history_path() -> Result<std::path::PathBuf> {
-    let base = match std::env::var("XDG_CONFIG_HOME") {
-        Ok(path) => std::path::PathBuf::from(path),
-        Err(_) => {
-            let home = std::env::var("HOME").context("HOME not set")?;
-            std::path::PathBuf::from(home).join(".config")
+/// Recent-paths file and size from `[tui] history_path` / `history_size`.
+#[derive(Clone, Debug)]
+pub struct HistorySettings {
+    path: Option<std::path::PathBuf>,
+    size: usize,
+}
+
+impl HistorySettings {
+    pub fn new(config: &config::TuiConfig) -> Self {
+        Self {
+            path: config.history_path.as_deref().map(expand_tilde),
+            size: config.history_size.unwrap_or(DEFAULT_HISTORY_SIZE),
         }
-    };
-    Ok(base.join("mvx").join("history.txt"))
+    }
+
+    pub fn path(&self) -> Result<std::path::PathBuf> {
+        if let Some(path) = self.path.as_ref() {
+            return Ok(path.clone());
+        }
+        let base = match std::env::var("XDG_CONFIG_HOME") {
+            Ok(path) => std::path::PathBuf::from(path),
+            Err(_) => {
+                let home = std::env::var("HOME").context("HOME not set")?;
+                std::path::PathBuf::from(home).join(".config")
+            }
+        };
+        Ok(base.join("mvx").join("history.txt"))
+    }
 }
 
-fn load_history() -> Result<Vec<String>> {
-    let path = history_path()?;
+const DEFAULT_HISTORY_SIZE: usize = 50;
+
+fn load_history(settings: &HistorySettings) -> Result<Vec<String>> {
+    let path = settings.path()?;
     if !path.exists() {
         return Ok(Vec::new());
     }
@@ -1795,11 +2817,12 @@ fn load_history() -> Result<Vec<String>> {
         }
         items.push(value.to_string());
     }
+    items.truncate(settings.size);
     Ok(items)
 }
 
-fn save_history(items: &[String]) -> Result<()> {
-    let path = history_path()?;
+fn save_history(settings: &HistorySettings, items: &[String]) -> Result<()> {
+    let path = settings.path()?;
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -1808,6 +2831,15 @@ fn save_history(items: &[String]) -> Result<()> {
     Ok(())
 }
 
+/// Empties the recent-paths file for `--clear-history`, returning its path.
+pub fn clear_history(settings: &HistorySettings) -> Result<std::path::PathBuf> {
+    let path = settings.path()?;
+    if path.exists() {
+        std::fs::write(&path, "").with_context(|| format!("clear {}", path.display()))?;
+    }
+    Ok(path)
+}
+
 fn update_history(state: &mut WizardState, additions: Vec<String>) -> Result<()> {
     let mut items = state.history.clone();
     for item in additions {
@@ -1818,12 +2850,198 @@ fn update_history(state: &mut WizardState, additions: Vec<String>) -> Result<()>
         items.retain(|existing| existing != &value);
         items.insert(0, value);
     }
-    items.truncate(50);
-    save_history(&items)?;
+    items.truncate(state.history_settings.size);
+    save_history(&state.history_settings, &items)?;
     state.history = items;
     Ok(())
 }
 
+type Areas = std::rc::Rc<[Rect]>;
+
+fn welcome_layout(area: Rect) -> Areas {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5),
+            Constraint::Min(6),
+            Constraint::Length(3),
+        ])
+        .split(area)
+}
+
+fn config_layout(area: Rect) -> (Areas, Areas) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(3),
+        ])
+        .split(area);
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(layout[1]);
+    (layout, body)
+}
+
+fn run_layout(area: Rect) -> (Areas, Areas, Areas) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Min(4),
+            Constraint::Length(2),
+        ])
+        .split(area);
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(layout[2]);
+    let right = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(7), Constraint::Min(4)])
+        .split(body[1]);
+    (layout, body, right)
+}
+
+fn spinner_frame(elapsed: f32) -> char {
+    const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+    FRAMES[(elapsed * 8.0) as usize % FRAMES.len()]
+}
+
+/// Renders a text gauge for the details pane. Tasks without a percent (LibreOffice,
+/// ffmpeg on inputs of unknown length) get a block bouncing across the track instead
+/// of a bar stuck at 0%.
+fn progress_bar(task: &TaskState, width: usize) -> String {
+    let width = width.max(10);
+    if let Some(percent) = task.percent {
+        let filled = ((percent.clamp(0.0, 100.0) / 100.0) * width as f64).round() as usize;
+        return format!("{}{}", "█".repeat(filled), "░".repeat(width - filled));
+    }
+    if task.status != TaskStatus::Running {
+        return String::new();
+    }
+    let block = (width / 5).max(2);
+    let travel = width - block;
+    let step = (task.activity_elapsed() * 12.0) as usize % (travel * 2);
+    let offset = if step > travel {
+        travel * 2 - step
+    } else {
+        step
+    };
+    format!(
+        "{}{}{}",
+        "░".repeat(offset),
+        "█".repeat(block),
+        "░".repeat(travel - offset)
+    )
+}
+
+/// Maps a click inside a bordered list to an item index, mirroring the scroll offset
+/// ratatui picks when the selected row would otherwise fall below the viewport.
+fn list_index_at(area: Rect, column: u16, row: u16, selected: usize, len: usize) -> Option<usize> {
+    let inner = Rect::new(
+        area.x + 1,
+        area.y + 1,
+        area.width.saturating_sub(2),
+        area.height.saturating_sub(2),
+    );
+    if !inner.contains(Position::new(column, row)) {
+        return None;
+    }
+    let height = inner.height as usize;
+    let offset = (selected + 1).saturating_sub(height);
+    let index = offset + (row - inner.y) as usize;
+    (index < len).then_some(index)
+}
+
+fn handle_wizard_mouse(
+    state: &mut WizardState,
+    mouse: MouseEvent,
+    area: Rect,
+) -> Option<FormOutcome> {
+    if state.edit.is_some() || state.modal.is_some() {
+        return None;
+    }
+    if mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+        return None;
+    }
+    match state.screen {
+        Screen::Welcome => {
+            let layout = welcome_layout(area);
+            let index = list_index_at(
+                layout[1],
+                mouse.column,
+                mouse.row,
+                state.welcome_selected,
+                3,
+            )?;
+            state.welcome_selected = index;
+            match index {
+                0 => {
+                    state.form.mode = FormMode::Single;
+                    state.screen = Screen::Configure;
+                }
+                1 => {
+                    state.form.mode = FormMode::Batch;
+                    state.screen = Screen::Configure;
+                }
+                _ => return Some(FormOutcome::Quit),
+            }
+        }
+        Screen::Configure => {
+            let (_, body) = config_layout(area);
+            let inputs = input_fields(state.form.mode).len();
+            let options = option_fields(state.form.mode).len();
+            if let Some(index) =
+                list_index_at(body[0], mouse.column, mouse.row, state.input_index, inputs)
+            {
+                state.focus = Panel::Inputs;
+                state.input_index = index;
+            } else if let Some(index) = list_index_at(
+                body[1],
+                mouse.column,
+                mouse.row,
+                state.option_index,
+                options,
+            ) {
+                state.focus = Panel::Options;
+                state.option_index = index;
+            }
+        }
+    }
+    None
+}
+
+fn handle_run_mouse(ui_state: &mut UiState, mouse: MouseEvent, area: Rect) {
+    let (_, body, right) = run_layout(area);
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let visible = ui_state.visible_tasks();
+            let selected = visible
+                .iter()
+                .position(|&idx| idx == ui_state.selected_index())
+                .unwrap_or(0);
+            if let Some(position) =
+                list_index_at(body[0], mouse.column, mouse.row, selected, visible.len())
+            {
+                let clicked = visible[position];
+                ui_state.pinned = (ui_state.pinned != Some(clicked)).then_some(clicked);
+            }
+        }
+        MouseEventKind::ScrollUp if right[1].contains(Position::new(mouse.column, mouse.row)) => {
+            ui_state.log_scroll =
+                (ui_state.log_scroll + 1).min(ui_state.logs.len().saturating_sub(1));
+        }
+        MouseEventKind::ScrollDown if right[1].contains(Position::new(mouse.column, mouse.row)) => {
+            ui_state.log_scroll = ui_state.log_scroll.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -1843,21 +3061,60 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
-fn run_tui(plans: Vec<Plan>, overwrite: bool) -> Result<RunOutcome> {
+fn run_tui(
+    plans: Vec<Plan>,
+    overwrite: bool,
+    theme: Theme,
+    jobs: usize,
+    state: Option<Arc<batch::StateFile>>,
+    log: Option<RunLog>,
+) -> Result<RunOutcome> {
     let (event_tx, event_rx) = mpsc::channel();
     let (done_tx, done_rx) = mpsc::channel();
     let is_batch = plans.len() > 1;
     let plans_for_worker = plans.clone();
+    let pause_gate = Arc::new(PauseGate::new());
+    let worker_gate = Arc::clone(&pause_gate);
+
+    let jobs = jobs.clamp(1, plans.len().max(1));
 
     thread::spawn(move || {
-        let reporter = ProgressReporter::tui(event_tx);
-        let mut failed = Vec::new();
-        for plan in plans_for_worker {
-            if let Err(err) = execute::execute_plan_with_reporter(&plan, overwrite, &reporter) {
-                failed.push((plan.source.display().to_string(), err.to_string()));
+        // Every job shares one reporter; events are keyed by source label, so the UI
+        // tracks several running tasks at once.
+        let reporter = ProgressReporter::tui(event_tx).with_log(log);
+        let queue = Mutex::new(plans_for_worker.into_iter());
+        let failed = Mutex::new(Vec::new());
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| {
+                    loop {
+                        worker_gate.wait_while_paused();
+                        if cancel::requested() {
+                            break;
+                        }
+                        let Some(plan) = queue.lock().ok().and_then(|mut queue| queue.next())
+                        else {
+                            break;
+                        };
+                        let result =
+                            execute::execute_plan_with_reporter(&plan, overwrite, &reporter)
+                                .and_then(|_| match state.as_deref() {
+                                    Some(state) => state.record(&plan.source),
+                                    None => Ok(()),
+                                });
+                        if let Err(err) = result
+                            && let Ok(mut failed) = failed.lock()
+                        {
+                            failed.push((plan.source.display().to_string(), err.to_string()));
+                        }
+                    }
+                });
             }
-        }
-        let result = if failed.is_empty() {
+        });
+        let failed = failed.into_inner().unwrap_or_default();
+        let result = if cancel::requested() {
+            Err(anyhow::Error::new(cancel::Interrupted))
+        } else if failed.is_empty() {
             Ok(())
         } else if is_batch {
             Err(anyhow::anyhow!(format!(
@@ -1885,26 +3142,80 @@ fn run_tui(plans: Vec<Plan>, overwrite: bool) -> Result<RunOutcome> {
         if !done && let Ok(result) = done_rx.try_recv() {
             done = true;
             done_result = Some(result);
+            if is_batch {
+                ui_state.push_group_summary(&plans);
+            }
+        }
+        // A cancelled run has nothing left to show; leave once the workers have stopped.
+        if done && cancel::requested() {
+            break;
         }
 
-        terminal.draw(|frame| render_ui(frame, &ui_state, done))?;
+        terminal.draw(|frame| render_ui(frame, &ui_state, done, &theme))?;
 
-        if event::poll(tick_rate)?
-            && let CEvent::Key(key) = event::read()?
-        {
+        if !event::poll(tick_rate)? {
+            continue;
+        }
+        let key = match event::read()? {
+            CEvent::Key(key) => key,
+            CEvent::Mouse(mouse) => {
+                let size = terminal.size()?;
+                handle_run_mouse(
+                    &mut ui_state,
+                    mouse,
+                    Rect::new(0, 0, size.width, size.height),
+                );
+                continue;
+            }
+            _ => continue,
+        };
+        // Raw mode delivers Ctrl-C as a key rather than SIGINT, so raise the same flag
+        // the signal handler would; paused workers are released to see it.
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if done {
+                break;
+            }
+            cancel::request();
+            ui_state.paused = false;
+            pause_gate.set_paused(false);
+            ui_state.push_log("Cancelling: stopping running conversions".to_string());
+            continue;
+        }
+        if ui_state.editing_filter {
             match key.code {
-                KeyCode::Char('q') | KeyCode::Esc => {
-                    if done {
-                        break;
-                    }
+                KeyCode::Enter => ui_state.editing_filter = false,
+                KeyCode::Esc => {
+                    ui_state.queue_filter.clear();
+                    ui_state.editing_filter = false;
                 }
-                KeyCode::Char('b') => {
-                    if done {
-                        return Ok(RunOutcome::Back);
-                    }
+                KeyCode::Backspace => {
+                    ui_state.queue_filter.pop();
                 }
+                KeyCode::Char(ch) => ui_state.queue_filter.push(ch),
                 _ => {}
             }
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('/') => ui_state.editing_filter = true,
+            KeyCode::Char('f') => ui_state.status_filter = ui_state.status_filter.next(),
+            KeyCode::Char('p') if !done => {
+                ui_state.paused = true;
+                pause_gate.set_paused(true);
+                ui_state.push_log("Paused after current conversion".to_string());
+            }
+            KeyCode::Char('r') if !done && ui_state.paused => {
+                ui_state.paused = false;
+                pause_gate.set_paused(false);
+                ui_state.push_log("Resumed".to_string());
+            }
+            KeyCode::Char('q') | KeyCode::Esc if done => {
+                break;
+            }
+            KeyCode::Char('b') if done => {
+                return Ok(RunOutcome::Back);
+            }
+            _ => {}
         }
     }
 
@@ -1914,23 +3225,13 @@ fn run_tui(plans: Vec<Plan>, overwrite: bool) -> Result<RunOutcome> {
     Ok(RunOutcome::Exit)
 }
 
-fn render_ui(frame: &mut Frame<'_>, ui_state: &UiState, done: bool) {
-    let theme = Theme::new();
-    let area = frame.area();
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(2),
-            Constraint::Length(1),
-            Constraint::Min(4),
-            Constraint::Length(2),
-        ])
-        .split(area);
+fn render_ui(frame: &mut Frame<'_>, ui_state: &UiState, done: bool, theme: &Theme) {
+    let (layout, body, right) = run_layout(frame.area());
 
     let (pending, running, ok, failed) = ui_state.task_stats();
     let total = ui_state.tasks.len().max(1);
     let completed = ok + failed;
-    let header = Paragraph::new(Line::from(vec![
+    let mut header_spans = vec![
         Span::styled(
             "mvx",
             Style::default()
@@ -1947,8 +3248,16 @@ fn render_ui(frame: &mut Frame<'_>, ui_state: &UiState, done: bool) {
         Span::styled(completed.to_string(), Style::default().fg(theme.good)),
         Span::raw("  failed "),
         Span::styled(failed.to_string(), Style::default().fg(theme.bad)),
-    ]))
-    .block(
+    ];
+    if ui_state.paused && !done {
+        header_spans.push(Span::styled(
+            "  PAUSED",
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    let header = Paragraph::new(Line::from(header_spans)).block(
         Block::default().borders(Borders::ALL).title(Span::styled(
             "Status",
             Style::default()
@@ -1972,19 +3281,18 @@ fn render_ui(frame: &mut Frame<'_>, ui_state: &UiState, done: bool) {
         .percent(overall_percent.round() as u16);
     frame.render_widget(gauge, layout[1]);
 
-    let body = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
-        .split(layout[2]);
-
-    let items: Vec<ListItem> = ui_state
-        .tasks
+    let visible = ui_state.visible_tasks();
+    let items: Vec<ListItem> = visible
         .iter()
-        .map(|task| {
-            let progress = task
-                .percent
-                .map(|p| format!("{:>3.0}%", p))
-                .unwrap_or_else(|| "    ".to_string());
+        .map(|&idx| {
+            let task = &ui_state.tasks[idx];
+            let progress = match task.percent {
+                Some(p) => format!("{:>3.0}%", p),
+                None if task.status == TaskStatus::Running => {
+                    format!("  {} ", spinner_frame(task.activity_elapsed()))
+                }
+                None => "    ".to_string(),
+            };
             let line = Line::from(format!(
                 "{} {} {}",
                 task.status.short(),
@@ -1995,13 +3303,26 @@ fn render_ui(frame: &mut Frame<'_>, ui_state: &UiState, done: bool) {
         })
         .collect();
     let mut list_state = ratatui::widgets::ListState::default();
-    if !ui_state.tasks.is_empty() {
-        list_state.select(Some(ui_state.active_index));
-    }
+    let selected = ui_state.selected_index();
+    list_state.select(visible.iter().position(|&idx| idx == selected));
+    let queue_title = if ui_state.queue_filter.is_empty()
+        && !ui_state.editing_filter
+        && ui_state.status_filter == StatusFilter::All
+    {
+        "Queue".to_string()
+    } else {
+        format!(
+            "Queue [{}] /{}{} ({} shown)",
+            ui_state.status_filter.label(),
+            ui_state.queue_filter,
+            if ui_state.editing_filter { "_" } else { "" },
+            visible.len()
+        )
+    };
     let list = List::new(items)
         .block(
             Block::default().borders(Borders::ALL).title(Span::styled(
-                "Queue",
+                queue_title,
                 Style::default()
                     .fg(theme.primary)
                     .add_modifier(Modifier::BOLD),
@@ -2014,12 +3335,7 @@ fn render_ui(frame: &mut Frame<'_>, ui_state: &UiState, done: bool) {
         );
     frame.render_stateful_widget(list, body[0], &mut list_state);
 
-    let right = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(6), Constraint::Min(4)])
-        .split(body[1]);
-
-    let active = ui_state.tasks.get(ui_state.active_index);
+    let active = ui_state.tasks.get(selected);
     let detail_lines = if let Some(task) = active {
         let eta = task
             .eta
@@ -2036,6 +3352,10 @@ fn render_ui(frame: &mut Frame<'_>, ui_state: &UiState, done: bool) {
                     .unwrap_or_else(|| "-".to_string())
             )),
             Line::from(format!("ETA: {eta}  Note: {}", task.message)),
+            Line::from(progress_bar(
+                task,
+                right[0].width.saturating_sub(4) as usize,
+            )),
         ]
     } else {
         vec![Line::from("No tasks")]
@@ -2057,6 +3377,7 @@ fn render_ui(frame: &mut Frame<'_>, ui_state: &UiState, done: bool) {
         .logs
         .iter()
         .rev()
+        .skip(ui_state.log_scroll)
         .take(log_height)
         .cloned()
         .collect::<Vec<_>>()
@@ -2074,10 +3395,14 @@ fn render_ui(frame: &mut Frame<'_>, ui_state: &UiState, done: bool) {
     );
     frame.render_widget(logs, right[1]);
 
-    let footer_text = if done {
-        "Completed. Press q to exit or b to go back."
+    let footer_text = if ui_state.editing_filter {
+        "Filter queue by name (Enter apply, Esc clear)"
+    } else if done {
+        "Completed. Press q to exit or b to go back. / filter, f status filter"
+    } else if ui_state.paused {
+        "Paused. r resume, Ctrl-C cancel, / filter, f status filter"
     } else {
-        "Running... (press q after completion to exit)"
+        "Running... (press q after completion to exit) p pause, Ctrl-C cancel, / filter, f status filter"
     };
     let footer = Paragraph::new(Line::from(Span::styled(
         footer_text,
@@ -2097,7 +3422,7 @@ impl TerminalGuard {
     fn new() -> Result<Self> {
         enable_raw_mode()?;
         let mut stdout = std::io::stdout();
-        crossterm_execute!(stdout, EnterAlternateScreen)?;
+        crossterm_execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
         Ok(Self)
     }
 }
@@ -2106,6 +3431,6 @@ impl Drop for TerminalGuard {
     fn drop(&mut self) {
         let _ = disable_raw_mode();
         let mut stdout = std::io::stdout();
-        let _ = crossterm_execute!(stdout, LeaveAlternateScreen);
+        let _ = crossterm_execute!(stdout, DisableMouseCapture, LeaveAlternateScreen);
     }
 }
@@ -0,0 +1,86 @@
+use crate::exit::is_missing_tool;
+use crate::ffprobe::probe_media;
+use crate::plan::MediaKind;
+use std::fs;
+use std::path::Path;
+
+/// State of an existing output as seen by `--verify-only`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Health {
+    /// Non-empty and readable by ffprobe.
+    Verified,
+    /// Non-empty, but not probed for the given reason.
+    Unverified(&'static str),
+    Missing,
+    Empty,
+    Broken(String),
+}
+
+impl Health {
+    /// What is wrong with the output, or `None` when it looks fine.
+    pub fn problem(&self) -> Option<String> {
+        match self {
+            Health::Verified | Health::Unverified(_) => None,
+            Health::Missing => Some("output is missing".to_string()),
+            Health::Empty => Some("output is empty".to_string()),
+            Health::Broken(reason) => Some(format!("output is broken: {reason}")),
+        }
+    }
+}
+
+const NOT_MEDIA: &str = "only audio and video outputs are probed";
+const NO_FFPROBE: &str = "ffprobe not installed";
+
+/// Checks that `destination` exists and is non-empty, then probes audio and video
+/// outputs with ffprobe. Nothing is converted or modified.
+pub fn check_output(destination: &Path, kind: MediaKind) -> Health {
+    let Ok(metadata) = fs::metadata(destination) else {
+        return Health::Missing;
+    };
+    if !metadata.is_file() {
+        return Health::Broken("not a regular file".to_string());
+    }
+    if metadata.len() == 0 {
+        return Health::Empty;
+    }
+    if !matches!(kind, MediaKind::Audio | MediaKind::Video) {
+        return Health::Unverified(NOT_MEDIA);
+    }
+    match probe_media(destination) {
+        Ok(info) if info.audio_codec.is_none() && info.video_codec.is_none() => {
+            Health::Broken("no audio or video stream".to_string())
+        }
+        Ok(_) => Health::Verified,
+        Err(err) if is_missing_tool(&err) => Health::Unverified(NO_FFPROBE),
+        Err(err) => Health::Broken(format!("{err:#}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reports_missing_empty_and_unprobed_outputs() {
+        let temp = TempDir::new().unwrap();
+        let missing = temp.path().join("missing.jpg");
+        assert_eq!(check_output(&missing, MediaKind::Image), Health::Missing);
+
+        let empty = temp.path().join("empty.jpg");
+        fs::write(&empty, "").unwrap();
+        assert_eq!(check_output(&empty, MediaKind::Image), Health::Empty);
+        assert_eq!(Health::Empty.problem().as_deref(), Some("output is empty"));
+
+        let present = temp.path().join("present.pdf");
+        fs::write(&present, "%PDF").unwrap();
+        let health = check_output(&present, MediaKind::Document);
+        assert_eq!(health, Health::Unverified(NOT_MEDIA));
+        assert!(health.problem().is_none());
+
+        assert!(matches!(
+            check_output(temp.path(), MediaKind::Image),
+            Health::Broken(_)
+        ));
+    }
+}
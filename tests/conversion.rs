@@ -143,6 +143,145 @@ fn converts_audio_with_ffmpeg() {
     ensure_non_empty(&output);
 }
 
+#[test]
+fn ffmpeg_failure_error_quotes_its_stderr() {
+    if !tool_available("ffmpeg") {
+        eprintln!("skipping ffmpeg stderr test; ffmpeg not available");
+        return;
+    }
+
+    let temp_dir = TempDir::new().expect("temp dir");
+    let input = temp_dir.path().join("input.wav");
+    let create_status = Command::new("ffmpeg")
+        .args(["-y", "-f", "lavfi", "-i", "sine=duration=0.2"])
+        .arg(&input)
+        .status()
+        .expect("ffmpeg failed to run");
+    if !create_status.success() {
+        eprintln!("skipping ffmpeg stderr test; ffmpeg cannot create wav");
+        return;
+    }
+
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--transcode", "--audio-codec", "nosuchcodec"])
+        .arg(&input)
+        .arg(temp_dir.path().join("output.mp3"))
+        .output()
+        .expect("mvx failed to run");
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ffmpeg exited with status"), "{stderr}");
+    assert!(stderr.contains("nosuchcodec"), "{stderr}");
+}
+
+#[cfg(unix)]
+#[test]
+fn ffmpeg_failure_quotes_stderr_past_bad_bytes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().expect("tempdir");
+    // A stand-in ffmpeg that rejects the codec the way the real one does, after a long
+    // banner with a byte that is not UTF-8.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let ffmpeg = bin.join("ffmpeg");
+    std::fs::write(
+        &ffmpeg,
+        "#!/bin/sh
+i=0
+while [ $i -lt 2000 ]; do echo \"banner line $i\" >&2; i=$((i+1)); done
+\
+         printf 'Metadata: \\377\\n' >&2
+\
+         echo \"Unknown encoder 'nosuchcodec'\" >&2
+exit 1
+",
+    )
+    .expect("write fake ffmpeg");
+    std::fs::set_permissions(&ffmpeg, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let input = temp.path().join("input.wav");
+    std::fs::write(&input, b"RIFF\x24\0\0\0WAVEfmt ").expect("write input");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--transcode", "--audio-codec", "nosuchcodec"])
+        .arg(&input)
+        .arg(temp.path().join("output.mp3"))
+        .env("PATH", std::env::join_paths(paths).expect("join PATH"))
+        .output()
+        .expect("mvx failed to run");
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let error = &stderr[stderr.find("ffmpeg exited with status").expect("error")..];
+    assert!(error.contains("Unknown encoder 'nosuchcodec'"), "{error}");
+    assert!(!error.contains("banner line 0\n"), "{error}");
+}
+
+#[test]
+fn extracts_audio_track_from_video() {
+    if !tool_available("ffmpeg") || !tool_available("ffprobe") {
+        eprintln!("skipping audio extraction test; ffmpeg or ffprobe not available");
+        return;
+    }
+
+    let temp_dir = TempDir::new().expect("temp dir");
+    let input = temp_dir.path().join("input.mp4");
+    let output = temp_dir.path().join("output.mp3");
+
+    let create_status = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-f",
+            "lavfi",
+            "-i",
+            "testsrc=size=32x32:rate=10",
+            "-f",
+            "lavfi",
+            "-i",
+            "sine=frequency=1000:duration=0.2",
+            "-shortest",
+            "-c:v",
+            "libx264",
+            "-pix_fmt",
+            "yuv420p",
+            "-c:a",
+            "aac",
+        ])
+        .arg(&input)
+        .status()
+        .expect("ffmpeg failed to run");
+    if !create_status.success() {
+        eprintln!("skipping audio extraction test; ffmpeg cannot create mp4");
+        return;
+    }
+
+    let status = Command::new(mvx_bin())
+        .arg(&input)
+        .arg(&output)
+        .status()
+        .expect("mvx failed to run");
+    assert!(status.success(), "mvx audio extraction failed");
+    ensure_non_empty(&output);
+
+    let probe = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "stream=codec_type",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(&output)
+        .output()
+        .expect("ffprobe failed to run");
+    let streams = String::from_utf8_lossy(&probe.stdout);
+    assert!(streams.contains("audio"), "{streams}");
+    assert!(!streams.contains("video"), "{streams}");
+}
+
 #[test]
 fn stream_copy_forced_audio_fails() {
     if !tool_available("ffmpeg") {
@@ -310,6 +449,28 @@ fn converts_document_with_libreoffice() {
     ensure_non_empty(&output);
 }
 
+#[test]
+fn converts_document_between_formats_with_libreoffice() {
+    if !tool_available_with_args("soffice", &["--version"]) {
+        eprintln!("skipping document format test; LibreOffice not available");
+        return;
+    }
+
+    let temp_dir = TempDir::new().expect("temp dir");
+    let input = temp_dir.path().join("input.txt");
+    let output = temp_dir.path().join("output.odt");
+
+    std::fs::write(&input, "mvx test document").expect("write input");
+
+    let status = Command::new(mvx_bin())
+        .arg(&input)
+        .arg(&output)
+        .status()
+        .expect("mvx failed to run");
+    assert!(status.success(), "mvx document format conversion failed");
+    ensure_non_empty(&output);
+}
+
 #[test]
 fn converts_image_to_pdf_with_imagemagick() {
     let (_pdf_read, pdf_write) = imagemagick_pdf_support();
@@ -388,3 +549,1513 @@ fn converts_pdf_to_image_with_imagemagick() {
     assert!(status.success(), "mvx pdf->image conversion failed");
     ensure_non_empty(&output);
 }
+
+#[test]
+fn conflicting_flags_exit_with_usage_code() {
+    let status = Command::new(mvx_bin())
+        .args(["--overwrite", "--backup", "a.png", "b.png"])
+        .status()
+        .expect("mvx failed to run");
+    assert_eq!(status.code(), Some(2));
+}
+
+#[test]
+fn partial_batch_failure_exit_code() {
+    let temp = TempDir::new().expect("tempdir");
+    let good = temp.path().join("good.png");
+    let bad = temp.path().join("bad.zzz");
+    std::fs::write(&good, b"png").expect("write input");
+    std::fs::write(&bad, b"zzz").expect("write input");
+    let out_dir = temp.path().join("out");
+
+    let status = Command::new(mvx_bin())
+        .args([
+            "--no-tui",
+            "--batch",
+            "--create-dirs",
+            "--to-ext",
+            "png",
+            "--dest-dir",
+        ])
+        .arg(&out_dir)
+        .arg("--input")
+        .arg(&good)
+        .arg("--input")
+        .arg(&bad)
+        .status()
+        .expect("mvx failed to run");
+    assert_eq!(status.code(), Some(5));
+    assert!(out_dir.join("good.png").exists());
+}
+
+#[test]
+fn batch_json_lines_streams_events() {
+    let temp = TempDir::new().expect("tempdir");
+    let good = temp.path().join("good.png");
+    let bad = temp.path().join("bad.zzz");
+    std::fs::write(&good, b"png").expect("write input");
+    std::fs::write(&bad, b"zzz").expect("write input");
+
+    let output = Command::new(mvx_bin())
+        .args([
+            "--no-tui",
+            "--batch",
+            "--json-lines",
+            "--create-dirs",
+            "--to-ext",
+            "png",
+            "--dest-dir",
+        ])
+        .arg(temp.path().join("out"))
+        .arg("--input")
+        .arg(&good)
+        .arg("--input")
+        .arg(&bad)
+        .output()
+        .expect("mvx failed to run");
+    assert_eq!(output.status.code(), Some(5));
+    let events: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("one json object per line"))
+        .collect();
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|event| event["event"].as_str().expect("event"))
+        .collect();
+    assert_eq!(kinds, ["start", "result", "start", "result", "summary"]);
+    assert_eq!(events[1]["status"], "failed");
+    assert!(events[1]["error"].is_string());
+    assert_eq!(events[3]["status"], "ok");
+    assert_eq!(events[3]["output_bytes"], 3);
+    assert_eq!(events[4]["succeeded"], 1);
+    assert_eq!(events[4]["failed"], 1);
+
+    let status = Command::new(mvx_bin())
+        .args(["--batch", "--json", "--json-lines", "--dest-dir", "out"])
+        .arg(&good)
+        .status()
+        .expect("mvx failed to run");
+    assert_eq!(status.code(), Some(2));
+}
+
+#[test]
+fn batch_json_summary_groups_by_backend_and_kind() {
+    let temp = TempDir::new().expect("tempdir");
+    let good = temp.path().join("good.png");
+    let fake = temp.path().join("fake.png");
+    std::fs::write(&good, b"png").expect("write input");
+    std::fs::write(&fake, b"%PDF-1.4\n").expect("write input");
+
+    let output = Command::new(mvx_bin())
+        .args([
+            "--no-tui",
+            "--batch",
+            "--json",
+            "--strict-detect",
+            "--create-dirs",
+            "--to-ext",
+            "png",
+            "--dest-dir",
+        ])
+        .arg(temp.path().join("out"))
+        .arg("--input")
+        .arg(&good)
+        .arg("--input")
+        .arg(&fake)
+        .output()
+        .expect("mvx failed to run");
+    assert_eq!(output.status.code(), Some(5));
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).expect("summary json");
+    // The copy runs no tool; the refused source never got a plan.
+    assert_eq!(
+        summary["by_backend"],
+        serde_json::json!({
+            "none": {"ok": 1, "failed": 0},
+            "unknown": {"ok": 0, "failed": 1},
+        })
+    );
+    assert_eq!(
+        summary["by_kind"],
+        serde_json::json!({
+            "image": {"ok": 1, "failed": 0},
+            "unknown": {"ok": 0, "failed": 1},
+        })
+    );
+}
+
+#[test]
+fn batch_json_lines_reports_planning_failures() {
+    let temp = TempDir::new().expect("tempdir");
+    let good = temp.path().join("good.png");
+    let fake = temp.path().join("fake.png");
+    std::fs::write(&good, b"png").expect("write input");
+    std::fs::write(&fake, b"%PDF-1.4\n").expect("write input");
+    let out_dir = temp.path().join("out");
+
+    let output = Command::new(mvx_bin())
+        .args([
+            "--no-tui",
+            "--batch",
+            "--json-lines",
+            "--strict-detect",
+            "--create-dirs",
+            "--to-ext",
+            "png",
+            "--dest-dir",
+        ])
+        .arg(&out_dir)
+        .arg("--input")
+        .arg(&good)
+        .arg("--input")
+        .arg(&fake)
+        .output()
+        .expect("mvx failed to run");
+    assert_eq!(output.status.code(), Some(5));
+    let events: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("one json object per line"))
+        .collect();
+    let refused = events
+        .iter()
+        .find(|event| event["source"] == fake.display().to_string())
+        .expect("an event for the refused source");
+    assert_eq!(refused["event"], "result");
+    assert_eq!(refused["status"], "failed");
+    assert_eq!(
+        refused["destination"],
+        out_dir.join("fake.png").display().to_string()
+    );
+    assert!(
+        refused["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("--strict-detect")),
+        "{refused}"
+    );
+    let summary = events.last().expect("summary");
+    assert_eq!(summary["event"], "summary");
+    assert_eq!(summary["failed"], 1);
+}
+
+#[test]
+fn config_image_quality_per_destination_extension() {
+    let temp = TempDir::new().expect("tempdir");
+    let config = temp.path().join("config.toml");
+    std::fs::write(
+        &config,
+        "[default]\nimage_quality = 90\n\n[ext.jpg]\nimage_quality = 85\n\n[ext.webp]\nimage_quality = 80\n",
+    )
+    .expect("write config");
+
+    let planned_quality = |dest: &str| {
+        let output = Command::new(mvx_bin())
+            .args(["--no-tui", "--plan", "--json", "--config"])
+            .arg(&config)
+            .arg(temp.path().join("photo.png"))
+            .arg(temp.path().join(dest))
+            .output()
+            .expect("mvx failed to run");
+        assert!(output.status.success(), "mvx plan failed");
+        let plan: serde_json::Value = serde_json::from_slice(&output.stdout).expect("plan json");
+        plan["options"]["image_quality"].as_u64()
+    };
+    assert_eq!(planned_quality("photo.jpeg"), Some(85));
+    assert_eq!(planned_quality("photo.webp"), Some(80));
+    assert_eq!(planned_quality("photo.tiff"), Some(90));
+}
+
+#[test]
+fn config_behavior_defaults_merge_with_flags() {
+    let temp = TempDir::new().expect("tempdir");
+    let config = temp.path().join("config.toml");
+    std::fs::write(&config, "[default]\nbackup = true\nmove_source = true\n")
+        .expect("write config");
+    let source = temp.path().join("notes.txt");
+    std::fs::write(&source, "notes").expect("write input");
+
+    let plan = |extra: &[&str]| {
+        Command::new(mvx_bin())
+            .args(["--no-tui", "--plan", "--json", "--config"])
+            .arg(&config)
+            .args(extra)
+            .arg(&source)
+            .arg(temp.path().join("copy.txt"))
+            .output()
+            .expect("mvx failed to run")
+    };
+    let output = plan(&[]);
+    assert!(output.status.success(), "mvx plan failed");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("plan json");
+    assert_eq!(json["backup"], true);
+    assert_eq!(json["strategy"], "rename");
+
+    let output = plan(&["--overwrite"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "--overwrite and --backup are mutually exclusive (backup = true in the config)"
+        ),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn fit_skips_resize_for_small_images() {
+    let has_magick = tool_available("magick");
+    if !has_magick && !tool_available("convert") {
+        eprintln!("skipping fit test; ImageMagick not available");
+        return;
+    }
+    let temp = TempDir::new().expect("tempdir");
+    let source = temp.path().join("small.png");
+    let mut create = Command::new(if has_magick { "magick" } else { "convert" });
+    create.args(["-size", "16x16", "xc:orange"]).arg(&source);
+    assert!(run_status(create), "failed to create input image");
+
+    let run = |fit: &str, dest: &str| {
+        let output = Command::new(mvx_bin())
+            .args(["--no-tui", "--json", "--fit", fit])
+            .arg(&source)
+            .arg(temp.path().join(dest))
+            .output()
+            .expect("mvx failed to run");
+        assert!(output.status.success(), "mvx --fit {fit} failed");
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("result json")
+    };
+    assert_eq!(run("64x64", "copy.png")["strategy"], "copy");
+    assert_eq!(run("8x8", "shrunk.png")["strategy"], "convert");
+}
+
+#[cfg(unix)]
+#[test]
+fn ffmpeg_plans_probe_the_source_once() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().expect("tempdir");
+    // Stand-ins: ffprobe records each call and reports a 1080p HDR video with 128k
+    // audio; ffmpeg writes a placeholder output.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let calls = temp.path().join("ffprobe.log");
+    let probe_json = r#"{"format":{"duration":"2.0"},"streams":[{"codec_type":"video","codec_name":"hevc","width":1920,"height":1080,"color_transfer":"smpte2084"},{"codec_type":"audio","codec_name":"aac","bit_rate":"128000"}]}"#;
+    let scripts = [
+        (
+            "ffprobe",
+            format!(
+                "#!/bin/sh\necho \"$@\" >> '{}'\necho '{probe_json}'\n",
+                calls.display()
+            ),
+        ),
+        (
+            "ffmpeg",
+            "#!/bin/sh\nfor last; do :; done\n\
+             case \"$last\" in *.mp4) echo video > \"$last\";; esac\n"
+                .to_string(),
+        ),
+    ];
+    for (name, script) in scripts {
+        let path = bin.join(name);
+        std::fs::write(&path, script).expect("write fake tool");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    }
+    let source = temp.path().join("clip.mkv");
+    std::fs::write(&source, b"mkv").expect("write input");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let path = std::env::join_paths(paths).expect("join PATH");
+    let probes = |args: &[&str]| {
+        let _ = std::fs::remove_file(&calls);
+        let output = Command::new(mvx_bin())
+            .args(["--no-tui", "--max-width", "640", "--audio-bitrate", "320k"])
+            .args(args)
+            .arg(&source)
+            .arg(temp.path().join("clip.mp4"))
+            .env("PATH", &path)
+            .output()
+            .expect("mvx failed to run");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        std::fs::read_to_string(&calls)
+            .expect("ffprobe ran")
+            .lines()
+            .count()
+    };
+
+    assert_eq!(probes(&["--plan", "--json", "--probe"]), 1);
+    assert_eq!(probes(&["--overwrite"]), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn fit_reads_the_source_size_only_when_converting() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().expect("tempdir");
+    // A stand-in ImageMagick that records every call: `identify` reports a 16x16 image,
+    // a conversion writes its arguments into the output.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let calls = temp.path().join("calls.log");
+    let magick = bin.join("magick");
+    std::fs::write(
+        &magick,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\n\
+             if [ \"$1\" = identify ]; then printf '16 16'; exit 0; fi\n\
+             for last; do :; done\necho \"$@\" > \"$last\"\n",
+            calls.display()
+        ),
+    )
+    .expect("write fake magick");
+    std::fs::set_permissions(&magick, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let source = temp.path().join("small.png");
+    std::fs::write(&source, b"png").expect("write input");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let path = std::env::join_paths(paths).expect("join PATH");
+    let run = |args: &[&str], dest: &str| {
+        Command::new(mvx_bin())
+            .arg("--no-tui")
+            .args(args)
+            .arg(&source)
+            .arg(temp.path().join(dest))
+            .env("PATH", &path)
+            .output()
+            .expect("mvx failed to run")
+    };
+
+    let plan = run(&["--plan", "--fit", "64x64"], "copy.png");
+    assert!(plan.status.success());
+    assert!(!calls.exists(), "planning must not run ImageMagick");
+
+    let copied = run(&["--json", "--fit", "64x64"], "copy.png");
+    assert!(copied.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&copied.stdout).expect("json");
+    assert_eq!(result["strategy"], "copy");
+    assert_eq!(
+        std::fs::read(temp.path().join("copy.png")).expect("read copy"),
+        b"png"
+    );
+
+    let shrunk = run(&["--fit", "8x8"], "shrunk.png");
+    assert!(shrunk.status.success());
+    let args = std::fs::read_to_string(temp.path().join("shrunk.png")).expect("read output");
+    assert!(args.contains("-resize 8x8>"), "{args}");
+}
+
+#[test]
+fn strip_metadata_removes_exif() {
+    let has_magick = tool_available("magick");
+    if !has_magick && !tool_available("convert") {
+        eprintln!("skipping metadata test; ImageMagick not available");
+        return;
+    }
+    if !tool_available_with_args("exiftool", &["-ver"]) {
+        eprintln!("skipping metadata test; exiftool not available");
+        return;
+    }
+    let temp = TempDir::new().expect("tempdir");
+    let source = temp.path().join("tagged.jpg");
+    let mut create = Command::new(if has_magick { "magick" } else { "convert" });
+    create.args(["-size", "16x16", "xc:teal"]).arg(&source);
+    assert!(run_status(create), "failed to create input image");
+    let mut tag = Command::new("exiftool");
+    tag.args([
+        "-q",
+        "-overwrite_original",
+        "-Artist=mvx test",
+        "-GPSLatitude=48.85",
+    ])
+    .arg(&source);
+    assert!(run_status(tag), "failed to write EXIF");
+
+    let tags = |path: &Path| {
+        let output = Command::new("exiftool")
+            .args(["-s3", "-Artist", "-GPSLatitude"])
+            .arg(path)
+            .output()
+            .expect("exiftool failed to run");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    assert!(tags(&source).contains("mvx test"));
+
+    let output = temp.path().join("clean.jpg");
+    let status = Command::new(mvx_bin())
+        .args(["--no-tui", "--strip-metadata"])
+        .arg(&source)
+        .arg(&output)
+        .status()
+        .expect("mvx failed to run");
+    assert!(status.success(), "mvx conversion failed");
+    assert_eq!(tags(&output), "");
+
+    let status = Command::new(mvx_bin())
+        .args(["--strip-metadata", "--keep-metadata"])
+        .arg(&source)
+        .arg(temp.path().join("both.jpg"))
+        .status()
+        .expect("mvx failed to run");
+    assert_eq!(status.code(), Some(2));
+}
+
+#[test]
+fn state_file_resumes_interrupted_batch() {
+    let temp = TempDir::new().expect("tempdir");
+    let first = temp.path().join("first.png");
+    let second = temp.path().join("second.png");
+    let broken = temp.path().join("broken.zzz");
+    for path in [&first, &second, &broken] {
+        std::fs::write(path, b"data").expect("write input");
+    }
+    let out_dir = temp.path().join("out");
+    let state = temp.path().join("state.txt");
+    let batch = |inputs: &[&Path]| {
+        let mut command = Command::new(mvx_bin());
+        command
+            .args([
+                "--no-tui",
+                "--batch",
+                "--create-dirs",
+                "--to-ext",
+                "png",
+                "--dest-dir",
+            ])
+            .arg(&out_dir)
+            .arg("--state")
+            .arg(&state);
+        for input in inputs {
+            command.arg("--input").arg(input);
+        }
+        command.status().expect("mvx failed to run")
+    };
+
+    // The first run stops short: only `first.png` completes.
+    let status = batch(&[&first, &broken]);
+    assert_eq!(status.code(), Some(5));
+    assert!(out_dir.join("first.png").exists());
+    std::fs::remove_file(out_dir.join("first.png")).expect("remove output");
+
+    let status = batch(&[&first, &second]);
+    assert!(status.success(), "resumed batch failed");
+    assert!(
+        !out_dir.join("first.png").exists(),
+        "completed source was redone"
+    );
+    assert!(out_dir.join("second.png").exists());
+    let recorded = std::fs::read_to_string(&state).expect("read state");
+    assert_eq!(recorded.lines().count(), 2);
+}
+
+#[test]
+fn json_outputs_carry_schema_version() {
+    let temp = TempDir::new().expect("tempdir");
+    let source = temp.path().join("photo.png");
+    std::fs::write(&source, b"png").expect("write input");
+    let dest = temp.path().join("copy.png");
+
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--json"])
+        .arg(&source)
+        .arg(&dest)
+        .output()
+        .expect("mvx failed to run");
+    assert!(output.status.success(), "mvx copy failed");
+    let success: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(success["schema_version"], 1);
+    assert_eq!(success["status"], "ok");
+    assert_eq!(success["destination"], dest.display().to_string());
+    assert_eq!(success["source_bytes"], 3);
+    assert_eq!(success["output_bytes"], 3);
+    assert_eq!(success["size_ratio"], 1.0);
+
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--plan", "--json"])
+        .arg(&source)
+        .arg(temp.path().join("other.png"))
+        .output()
+        .expect("mvx failed to run");
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout).expect("plan json");
+    assert_eq!(plan["schema_version"], 1);
+}
+
+#[test]
+fn batch_plan_json_is_a_single_array() {
+    let temp = TempDir::new().expect("tempdir");
+    let first = temp.path().join("first.png");
+    let second = temp.path().join("second.wav");
+    std::fs::write(&first, b"png").expect("write input");
+    std::fs::write(&second, b"wav").expect("write input");
+
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--batch", "--plan", "--json", "--to-ext", "png"])
+        .arg("--dest-dir")
+        .arg(temp.path().join("out"))
+        .arg("--input")
+        .arg(&first)
+        .arg("--input")
+        .arg(&second)
+        .output()
+        .expect("mvx failed to run");
+    let plans: serde_json::Value = serde_json::from_slice(&output.stdout).expect("batch plan json");
+    let plans = plans.as_array().expect("plan array");
+    assert!(!plans.is_empty());
+    assert!(plans.iter().all(|plan| plan["schema_version"] == 1));
+}
+
+#[test]
+fn batch_only_image_skips_other_kinds() {
+    let temp = TempDir::new().expect("tempdir");
+    let photos = temp.path().join("photos");
+    std::fs::create_dir_all(&photos).expect("create photos");
+    for name in ["a.png", "b.jpg", "notes.txt", "clip.mov"] {
+        std::fs::write(photos.join(name), b"data").expect("write input");
+    }
+
+    let output = Command::new(mvx_bin())
+        .args([
+            "--no-tui", "--batch", "--plan", "--json", "--to-ext", "webp",
+        ])
+        .args(["--only", "image", "--dest-dir"])
+        .arg(temp.path().join("out"))
+        .arg("--input")
+        .arg(&photos)
+        .output()
+        .expect("mvx failed to run");
+    assert!(output.status.success(), "batch plan failed");
+    let plans: serde_json::Value = serde_json::from_slice(&output.stdout).expect("batch plan json");
+    let mut sources: Vec<String> = plans
+        .as_array()
+        .expect("plan array")
+        .iter()
+        .map(|plan| plan["source"].as_str().expect("source").to_string())
+        .collect();
+    sources.sort();
+    assert_eq!(
+        sources,
+        vec![
+            photos.join("a.png").display().to_string(),
+            photos.join("b.jpg").display().to_string()
+        ]
+    );
+}
+
+#[test]
+fn batch_plan_audit_reports_destination_conflicts() {
+    let temp = TempDir::new().expect("tempdir");
+    let png = temp.path().join("a.png");
+    let jpg = temp.path().join("a.jpg");
+    std::fs::write(&png, b"png").expect("write input");
+    std::fs::write(&jpg, b"jpg").expect("write input");
+
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--batch", "--plan", "--json", "--audit"])
+        .args(["--to-ext", "png", "--dest-dir"])
+        .arg(temp.path().join("out"))
+        .arg("--input")
+        .arg(&png)
+        .arg("--input")
+        .arg(&jpg)
+        .output()
+        .expect("mvx failed to run");
+    let audit: serde_json::Value = serde_json::from_slice(&output.stdout).expect("audit json");
+    assert_eq!(audit["summary"]["total"], 2);
+    assert_eq!(audit["summary"]["copy"], 1);
+    assert_eq!(audit["summary"]["conflicts"], 1);
+    let conflict = &audit["conflicts"][0];
+    assert!(
+        conflict["destination"]
+            .as_str()
+            .expect("destination")
+            .ends_with("a.png")
+    );
+    assert_eq!(conflict["sources"].as_array().expect("sources").len(), 2);
+    let plans = audit["plans"].as_array().expect("plans");
+    assert!(plans.iter().all(|plan| {
+        plan["conflicts_with"]
+            .as_array()
+            .is_some_and(|others| others.len() == 1)
+    }));
+}
+
+#[test]
+fn list_conversions_json_matches_routing() {
+    let output = Command::new(mvx_bin())
+        .args(["--list-conversions", "--json"])
+        .output()
+        .expect("mvx failed to run");
+    assert!(output.status.success(), "mvx --list-conversions failed");
+    let listing: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    let conversions = listing["conversions"].as_array().expect("conversions");
+    assert!(conversions.iter().any(|route| route["source"] == "document"
+        && route["destination"] == "pdf"
+        && route["backend"] == "libreoffice"));
+    let media = listing["categories"]["media"].as_array().expect("media");
+    assert!(media.iter().any(|ext| ext == "opus"));
+}
+
+#[test]
+fn batch_limit_processes_first_sources() {
+    let temp = TempDir::new().expect("tempdir");
+    let inputs = temp.path().join("in");
+    std::fs::create_dir(&inputs).expect("create input dir");
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        std::fs::write(inputs.join(name), name).expect("write input");
+    }
+    let out_dir = temp.path().join("out");
+
+    let output = Command::new(mvx_bin())
+        .args([
+            "--no-tui",
+            "--batch",
+            "--create-dirs",
+            "--limit",
+            "2",
+            "--dest-dir",
+        ])
+        .arg(&out_dir)
+        .arg("--input")
+        .arg(&inputs)
+        .output()
+        .expect("mvx failed to run");
+    assert!(output.status.success(), "mvx --limit failed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Processing 2 of 3 inputs (limited)"),
+        "{stdout}"
+    );
+    assert!(out_dir.join("a.txt").exists());
+    assert!(out_dir.join("b.txt").exists());
+    assert!(!out_dir.join("c.txt").exists());
+}
+
+#[test]
+fn detect_warns_about_a_misnamed_file() {
+    let temp = TempDir::new().expect("tempdir");
+    let renamed = temp.path().join("holiday.jpg");
+    std::fs::write(&renamed, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").expect("write input");
+
+    let output = Command::new(mvx_bin())
+        .args(["--json", "--detect"])
+        .arg(&renamed)
+        .output()
+        .expect("mvx failed to run");
+    assert!(output.status.success(), "mvx --detect failed");
+    let detected: serde_json::Value = serde_json::from_slice(&output.stdout).expect("detect json");
+    assert_eq!(detected["mime"], "image/png");
+    assert_eq!(detected["extension"], "jpg");
+    assert_eq!(detected["extension_mismatch"], true);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("looks like .png"), "{stderr}");
+
+    let status = Command::new(mvx_bin())
+        .args(["--plan", "--detect"])
+        .arg(&renamed)
+        .status()
+        .expect("mvx failed to run");
+    assert_eq!(status.code(), Some(2));
+}
+
+#[test]
+fn bad_tui_colors_only_fail_tui_runs() {
+    let temp = TempDir::new().expect("tempdir");
+    let config = temp.path().join("config.toml");
+    std::fs::write(&config, "[tui]\nprimary = \"not-a-color\"\n").expect("write config");
+    let input = temp.path().join("notes.txt");
+    std::fs::write(&input, "hello").expect("write input");
+
+    let status = Command::new(mvx_bin())
+        .arg("--no-tui")
+        .arg("--config")
+        .arg(&config)
+        .arg(&input)
+        .arg(temp.path().join("copy.txt"))
+        .status()
+        .expect("mvx failed to run");
+    assert!(
+        status.success(),
+        "a plain copy should not need the TUI theme"
+    );
+
+    let output = Command::new(mvx_bin())
+        .arg("--tui")
+        .arg("--color")
+        .arg("never")
+        .arg("--config")
+        .arg(&config)
+        .arg(&input)
+        .arg(temp.path().join("copy.txt"))
+        .output()
+        .expect("mvx failed to run");
+    assert!(
+        !output.status.success(),
+        "--color never must not skip validation"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("invalid tui.primary color"), "{stderr}");
+}
+
+#[test]
+fn log_file_records_plan_and_result() {
+    let temp = TempDir::new().expect("tempdir");
+    let input = temp.path().join("notes.txt");
+    std::fs::write(&input, "hello").expect("write input");
+    let output = temp.path().join("copy.txt");
+    let log = temp.path().join("logs/mvx.log");
+
+    let status = Command::new(mvx_bin())
+        .arg("--no-tui")
+        .arg("--log-file")
+        .arg(&log)
+        .arg(&input)
+        .arg(&output)
+        .status()
+        .expect("mvx failed to run");
+    assert!(status.success(), "mvx --log-file failed");
+    let text = std::fs::read_to_string(&log).expect("read log");
+    assert!(text.contains(": plan\n"), "{text}");
+    assert!(text.contains("    Strategy: copy"), "{text}");
+    assert!(text.contains(": result: ok\n"), "{text}");
+}
+
+#[test]
+fn verify_only_reports_missing_and_empty_outputs() {
+    let temp = TempDir::new().expect("tempdir");
+    let inputs = temp.path().join("in");
+    std::fs::create_dir(&inputs).expect("create input dir");
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        std::fs::write(inputs.join(name), name).expect("write input");
+    }
+    let out_dir = temp.path().join("out");
+    std::fs::create_dir(&out_dir).expect("create output dir");
+    std::fs::write(out_dir.join("a.txt"), "a.txt").expect("write output");
+    std::fs::write(out_dir.join("b.txt"), "").expect("write empty output");
+
+    let output = Command::new(mvx_bin())
+        .args(["--batch", "--verify-only", "--json", "--dest-dir"])
+        .arg(&out_dir)
+        .arg("--input")
+        .arg(&inputs)
+        .output()
+        .expect("mvx failed to run");
+    assert_eq!(output.status.code(), Some(5), "partial failure exit code");
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(summary["succeeded"], 1);
+    assert_eq!(summary["failed"], 2);
+    let errors: Vec<&str> = summary["failures"]
+        .as_array()
+        .expect("failures")
+        .iter()
+        .filter_map(|failure| failure["error"].as_str())
+        .collect();
+    assert!(
+        errors
+            .iter()
+            .any(|error| error.starts_with("output is empty"))
+    );
+    assert!(
+        errors
+            .iter()
+            .any(|error| error.starts_with("output is missing"))
+    );
+    assert!(
+        !out_dir.join("c.txt").exists(),
+        "verify-only must not convert"
+    );
+}
+
+#[test]
+fn batch_skips_existing_destinations_unless_strict() {
+    let temp = TempDir::new().expect("tempdir");
+    let inputs = temp.path().join("in");
+    std::fs::create_dir(&inputs).expect("create input dir");
+    for name in ["a.txt", "b.txt"] {
+        std::fs::write(inputs.join(name), name).expect("write input");
+    }
+    let out_dir = temp.path().join("out");
+    std::fs::create_dir(&out_dir).expect("create output dir");
+    std::fs::write(out_dir.join("a.txt"), "earlier run").expect("write existing output");
+
+    let run = |strict: bool| {
+        let mut command = Command::new(mvx_bin());
+        command
+            .args(["--no-tui", "--batch", "--json", "--dest-dir"])
+            .arg(&out_dir)
+            .arg("--input")
+            .arg(&inputs);
+        if strict {
+            command.arg("--strict");
+        }
+        command.output().expect("mvx failed to run")
+    };
+
+    let output = run(false);
+    assert!(
+        output.status.success(),
+        "existing destinations should not fail"
+    );
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(summary["succeeded"], 1);
+    assert_eq!(summary["failed"], 0);
+    assert_eq!(summary["skipped_existing"], 1);
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("a.txt")).expect("read output"),
+        "earlier run"
+    );
+
+    let output = run(true);
+    assert_eq!(output.status.code(), Some(4), "every source failed");
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(summary["failed"], 2);
+    assert_eq!(summary["skipped_existing"], 0);
+}
+
+#[test]
+fn skip_existing_keeps_finished_outputs_and_redoes_empty_ones() {
+    let temp = TempDir::new().expect("tempdir");
+    let inputs = temp.path().join("in");
+    std::fs::create_dir(&inputs).expect("create input dir");
+    for name in ["a.txt", "b.txt"] {
+        std::fs::write(inputs.join(name), name).expect("write input");
+    }
+    let out_dir = temp.path().join("out");
+    std::fs::create_dir(&out_dir).expect("create output dir");
+    std::fs::write(out_dir.join("a.txt"), "earlier run").expect("write finished output");
+    // An interrupted run left b.txt empty.
+    std::fs::write(out_dir.join("b.txt"), "").expect("write empty output");
+
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--batch", "--skip-existing", "--dest-dir"])
+        .arg(&out_dir)
+        .arg("--input")
+        .arg(&inputs)
+        .output()
+        .expect("mvx failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Batch summary: total 1, succeeded 1, failed 0, skipped 1"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Skipped (destination exists): 1"),
+        "{stdout}"
+    );
+    let read = |name: &str| std::fs::read_to_string(out_dir.join(name)).expect("read output");
+    assert_eq!(read("a.txt"), "earlier run");
+    assert_eq!(read("b.txt"), "b.txt");
+
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--json", "--skip-existing"])
+        .arg(inputs.join("a.txt"))
+        .arg(out_dir.join("a.txt"))
+        .output()
+        .expect("mvx failed to run");
+    assert!(output.status.success());
+    let single: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(single["status"], "skipped");
+    assert_eq!(read("a.txt"), "earlier run");
+}
+
+#[test]
+fn batch_reads_trimmed_stdin_lines() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let temp = TempDir::new().expect("tempdir");
+    let a = temp.path().join("a.txt");
+    let b = temp.path().join("b.txt");
+    std::fs::write(&a, "a").expect("write input");
+    std::fs::write(&b, "b").expect("write input");
+    let out_dir = temp.path().join("out");
+
+    let mut child = Command::new(mvx_bin())
+        .args([
+            "--no-tui",
+            "--batch",
+            "--create-dirs",
+            "--stdin",
+            "--json",
+            "--dest-dir",
+        ])
+        .arg(&out_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("mvx failed to run");
+    let list = format!("  {}  \n\n   \n{}\r\n", a.display(), b.display());
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all(list.as_bytes())
+        .expect("write stdin");
+    let output = child.wait_with_output().expect("wait for mvx");
+    assert!(output.status.success(), "mvx --stdin failed");
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(summary["succeeded"], 2);
+    assert_eq!(summary["bytes_in"], 2);
+    assert_eq!(summary["bytes_out"], 2);
+    assert!(out_dir.join("a.txt").exists());
+    assert!(out_dir.join("b.txt").exists());
+}
+
+#[test]
+fn batch_refuses_colliding_destinations_unless_deduped() {
+    let temp = TempDir::new().expect("tempdir");
+    for dir in ["a", "b"] {
+        std::fs::create_dir(temp.path().join(dir)).expect("create input dir");
+        std::fs::write(temp.path().join(dir).join("notes.txt"), dir).expect("write input");
+    }
+    let out_dir = temp.path().join("out");
+    let batch = |extra: &[&str]| {
+        Command::new(mvx_bin())
+            .args(["--no-tui", "--batch", "--create-dirs", "--dest-dir"])
+            .arg(&out_dir)
+            .args(extra)
+            .arg("--input")
+            .arg(temp.path().join("a/notes.txt"))
+            .arg("--input")
+            .arg(temp.path().join("b/notes.txt"))
+            .output()
+            .expect("mvx failed to run")
+    };
+
+    let output = batch(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("batch destinations collide"), "{stderr}");
+    assert!(!out_dir.join("notes.txt").exists());
+
+    let output = batch(&["--dedupe-names"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("notes.txt")).expect("first output"),
+        "a"
+    );
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("notes-1.txt")).expect("second output"),
+        "b"
+    );
+}
+
+#[test]
+fn manifest_jobs_name_their_destinations() {
+    let temp = TempDir::new().expect("tempdir");
+    std::fs::write(temp.path().join("a.txt"), "a").expect("write input");
+    std::fs::write(temp.path().join("b.txt"), "b").expect("write input");
+    let manifest = temp.path().join("jobs.toml");
+    std::fs::write(
+        &manifest,
+        "[[job]]\nsource = \"a.txt\"\ndestination = \"renamed/first.txt\"\n\n[[job]]\nsource = \"b.txt\"\n",
+    )
+    .expect("write manifest");
+    let out_dir = temp.path().join("out");
+
+    let status = Command::new(mvx_bin())
+        .args(["--no-tui", "--batch", "--create-dirs", "--manifest"])
+        .arg(&manifest)
+        .arg("--dest-dir")
+        .arg(&out_dir)
+        .status()
+        .expect("mvx failed to run");
+    assert!(status.success(), "mvx --manifest failed");
+    assert!(out_dir.join("renamed/first.txt").exists());
+    assert!(out_dir.join("b.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn sigint_kills_the_tool_and_removes_temp_files() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    let temp = TempDir::new().expect("tempdir");
+    // A stand-in ImageMagick that never finishes on its own.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let magick = bin.join("magick");
+    std::fs::write(&magick, "#!/bin/sh\nexec sleep 30\n").expect("write fake magick");
+    std::fs::set_permissions(&magick, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let out = temp.path().join("out");
+    std::fs::create_dir(&out).expect("create out dir");
+    let source = temp.path().join("a.png");
+    std::fs::write(&source, b"png").expect("write input");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let mut child = Command::new(mvx_bin())
+        .arg("--no-tui")
+        .arg(&source)
+        .arg(out.join("a.jpg"))
+        .env("PATH", std::env::join_paths(paths).expect("join PATH"))
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("mvx failed to run");
+    std::thread::sleep(Duration::from_millis(500));
+    let signalled = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .expect("kill failed to run");
+    assert!(signalled.success());
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().expect("wait for mvx") {
+            break status;
+        }
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "mvx kept running after SIGINT"
+        );
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    assert_eq!(status.code(), Some(130));
+    let leftovers: Vec<_> = std::fs::read_dir(&out)
+        .expect("read out dir")
+        .map(|entry| entry.expect("entry").file_name())
+        .collect();
+    assert!(leftovers.is_empty(), "left behind: {leftovers:?}");
+}
+
+#[test]
+fn batch_refuses_missing_dest_dir_without_create_dirs() {
+    let temp = TempDir::new().expect("tempdir");
+    let source = temp.path().join("a.png");
+    std::fs::write(&source, b"png").expect("write input");
+    let dest_dir = temp.path().join("outptu");
+
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--batch", "--to-ext", "png", "--dest-dir"])
+        .arg(&dest_dir)
+        .arg("--input")
+        .arg(&source)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("mvx failed to run");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--create-dirs"));
+    assert!(!dest_dir.exists());
+
+    let output = Command::new(mvx_bin())
+        .args([
+            "--no-tui",
+            "--batch",
+            "--create-dirs",
+            "--to-ext",
+            "png",
+            "--dest-dir",
+        ])
+        .arg(&dest_dir)
+        .arg("--input")
+        .arg(&source)
+        .output()
+        .expect("mvx failed to run");
+    assert!(output.status.success());
+    assert!(dest_dir.join("a.png").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn frame_selects_one_frame_of_an_animated_gif() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().expect("tempdir");
+    // A stand-in ImageMagick: `identify` reports three frames, a conversion writes its
+    // arguments into the output.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let magick = bin.join("magick");
+    std::fs::write(
+        &magick,
+        "#!/bin/sh\nif [ \"$1\" = identify ]; then printf '3\\n3\\n3\\n'; exit 0; fi\n\
+         for last; do :; done\necho \"$@\" > \"$last\"\n",
+    )
+    .expect("write fake magick");
+    std::fs::set_permissions(&magick, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let source = temp.path().join("anim.gif");
+    std::fs::write(&source, b"GIF89a").expect("write input");
+    let dest = temp.path().join("still.png");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let path = std::env::join_paths(paths).expect("join PATH");
+    let run = |frame: &str, plan: bool| {
+        let mut command = Command::new(mvx_bin());
+        command
+            .args(["--no-tui", "--frame", frame])
+            .arg(&source)
+            .arg(&dest)
+            .env("PATH", &path);
+        if plan {
+            command.arg("--plan");
+        }
+        command.output().expect("mvx failed to run")
+    };
+
+    let output = run("1", true);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("anim.gif[1]"), "{stdout}");
+    assert!(stdout.contains("converts frame 1 of 3"), "{stdout}");
+
+    let output = run("2", false);
+    assert!(output.status.success());
+    let args = std::fs::read_to_string(&dest).expect("read output");
+    assert!(
+        args.starts_with(&format!("{}[2] ", source.display())),
+        "{args}"
+    );
+
+    let output = run("3", true);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("frame 3 is out of range"), "{stderr}");
+    assert!(stderr.contains("has 3 frames"), "{stderr}");
+}
+
+#[cfg(unix)]
+#[test]
+fn document_to_image_renders_the_pdf_the_preview_names() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().expect("tempdir");
+    // Stand-ins: LibreOffice writes `<stem>.pdf` into `--outdir`, and ImageMagick writes
+    // its arguments into the output.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let scripts = [
+        (
+            "soffice",
+            "#!/bin/sh
+while [ \"$1\" != --outdir ]; do shift; done
+\
+             name=$(basename \"$3\")
+echo pdf > \"$2/${name%.*}.pdf\"
+",
+        ),
+        (
+            "magick",
+            "#!/bin/sh
+for last; do :; done
+echo \"$@\" > \"$last\"
+",
+        ),
+    ];
+    for (name, script) in scripts {
+        let path = bin.join(name);
+        std::fs::write(&path, script).expect("write fake tool");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    }
+    let source = temp.path().join("deck.odp");
+    std::fs::write(&source, b"slides").expect("write input");
+    let dest = temp.path().join("deck.png");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let path = std::env::join_paths(paths).expect("join PATH");
+    let plan = Command::new(mvx_bin())
+        .args(["--no-tui", "--plan"])
+        .arg(&source)
+        .arg(&dest)
+        .env("PATH", &path)
+        .output()
+        .expect("mvx failed to run");
+    assert!(plan.status.success());
+    let plan = String::from_utf8_lossy(&plan.stdout);
+    assert!(plan.contains("magick <temp>/deck.pdf[0]"), "{plan}");
+
+    let status = Command::new(mvx_bin())
+        .arg("--no-tui")
+        .arg(&source)
+        .arg(&dest)
+        .env("PATH", &path)
+        .status()
+        .expect("mvx failed to run");
+    assert!(status.success());
+    let args = std::fs::read_to_string(&dest).expect("read output");
+    assert!(
+        args.starts_with('/') && args.contains("/deck.pdf[0] "),
+        "{args}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn all_pages_writes_one_numbered_image_per_pdf_page() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().expect("tempdir");
+    // Stand-ins: pdfinfo reports three pages, and ImageMagick fills in the `%d` output
+    // pattern for each of them.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let scripts = [
+        ("pdfinfo", "#!/bin/sh\necho 'Pages:          3'\n"),
+        (
+            "magick",
+            "#!/bin/sh\nfor last; do :; done\n\
+             for n in 1 2 3; do echo \"$@\" > \"$(echo \"$last\" | sed \"s/%d/$n/\")\"; done\n",
+        ),
+    ];
+    for (name, script) in scripts {
+        let path = bin.join(name);
+        std::fs::write(&path, script).expect("write fake tool");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    }
+    let source = temp.path().join("scan.pdf");
+    std::fs::write(&source, b"%PDF").expect("write input");
+    let out = temp.path().join("out");
+    std::fs::create_dir(&out).expect("create out dir");
+    let dest = out.join("page.png");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let path = std::env::join_paths(paths).expect("join PATH");
+    let run = |plan: bool| {
+        let mut command = Command::new(mvx_bin());
+        command
+            .args(["--no-tui", "--all-pages"])
+            .arg(&source)
+            .arg(&dest)
+            .env("PATH", &path);
+        if plan {
+            command.arg("--plan");
+        }
+        command.output().expect("mvx failed to run")
+    };
+
+    let output = run(true);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("all 3 pages to page-1.png … page-3.png"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("-scene 1"), "{stdout}");
+    assert!(
+        stdout.contains(&format!("{} ", source.display())),
+        "{stdout}"
+    );
+    assert!(stdout.contains("page-%d.png"), "{stdout}");
+
+    let output = run(false);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut written: Vec<_> = std::fs::read_dir(&out)
+        .expect("read out dir")
+        .map(|entry| entry.expect("entry").file_name())
+        .collect();
+    written.sort();
+    assert_eq!(written, ["page-1.png", "page-2.png", "page-3.png"]);
+
+    // The pages now exist, so a second run refuses to replace them.
+    let output = run(false);
+    assert!(!output.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn hwaccel_failures_carry_ffmpeg_stderr() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().expect("tempdir");
+    // A stand-in ffmpeg without a usable GPU, failing the way a real one does.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let ffmpeg = bin.join("ffmpeg");
+    std::fs::write(
+        &ffmpeg,
+        "#!/bin/sh
+echo 'OpenEncodeSessionEx failed: no capable devices found' >&2
+exit 1
+",
+    )
+    .expect("write fake ffmpeg");
+    std::fs::set_permissions(&ffmpeg, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let source = temp.path().join("clip.mov");
+    std::fs::write(&source, b"not really a movie").expect("write input");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let output = Command::new(mvx_bin())
+        .args(["--no-tui", "--transcode", "--hwaccel", "nvenc"])
+        .arg(&source)
+        .arg(temp.path().join("clip.mp4"))
+        .env("PATH", std::env::join_paths(paths).expect("join PATH"))
+        .output()
+        .expect("mvx failed to run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("could not encode with h264_nvenc"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("no capable devices found"), "{stderr}");
+}
+
+#[cfg(unix)]
+#[test]
+fn probe_adds_media_info_to_json_plans() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().expect("tempdir");
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let ffprobe = bin.join("ffprobe");
+    std::fs::write(
+        &ffprobe,
+        r#"#!/bin/sh
+echo '{"format":{"duration":"12.5"},"streams":[{"codec_type":"video","codec_name":"h264"},{"codec_type":"audio","codec_name":"aac"}]}'
+"#,
+    )
+    .expect("write fake ffprobe");
+    std::fs::set_permissions(&ffprobe, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let source = temp.path().join("clip.mov");
+    std::fs::write(&source, b"not really a movie").expect("write input");
+
+    let plan = |path: &Path, probe: bool| {
+        let mut command = Command::new(mvx_bin());
+        command
+            .args(["--no-tui", "--plan", "--json"])
+            .arg(&source)
+            .arg(temp.path().join("clip.mp4"))
+            .env("PATH", path);
+        if probe {
+            command.arg("--probe");
+        }
+        let output = command.output().expect("mvx failed to run");
+        assert!(output.status.success(), "{output:?}");
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("plan json")
+    };
+
+    let json = plan(&bin, true);
+    assert_eq!(json["media_info"]["duration_seconds"], 12.5);
+    assert_eq!(json["media_info"]["video_codec"], "h264");
+    assert_eq!(json["media_info"]["audio_codec"], "aac");
+    assert!(plan(&bin, false).get("media_info").is_none());
+
+    let empty = temp.path().join("empty");
+    std::fs::create_dir(&empty).expect("create empty dir");
+    let json = plan(&empty, true);
+    assert!(json["media_info"].is_null());
+    let notes = json["notes"].as_array().expect("notes");
+    assert!(
+        notes
+            .iter()
+            .any(|note| note == "ffprobe not installed; media info unavailable"),
+        "{json}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn timeout_kills_a_hung_ffmpeg_and_fails_the_job() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    let temp = TempDir::new().expect("tempdir");
+    // A stand-in ffmpeg that hangs the way one does on a malformed input.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let ffmpeg = bin.join("ffmpeg");
+    std::fs::write(&ffmpeg, "#!/bin/sh\nexec sleep 30\n").expect("write fake ffmpeg");
+    std::fs::set_permissions(&ffmpeg, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let source = temp.path().join("stuck.wav");
+    std::fs::write(&source, b"not really audio").expect("write input");
+    let out_dir = temp.path().join("out");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let started = Instant::now();
+    let output = Command::new(mvx_bin())
+        .args([
+            "--no-tui",
+            "--batch",
+            "--json",
+            "--create-dirs",
+            "--transcode",
+            "--timeout",
+            "1",
+            "--to-ext",
+            "mp3",
+            "--dest-dir",
+        ])
+        .arg(&out_dir)
+        .arg("--input")
+        .arg(&source)
+        .env("PATH", std::env::join_paths(paths).expect("join PATH"))
+        .output()
+        .expect("mvx failed to run");
+    assert!(started.elapsed() < Duration::from_secs(20), "mvx hung");
+    assert_eq!(output.status.code(), Some(4));
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).expect("summary json");
+    assert_eq!(summary["failed"], 1);
+    let error = summary["failures"][0]["error"].as_str().unwrap_or_default();
+    assert!(error.contains("timed out after 1s"), "{error}");
+    let leftovers: Vec<_> = std::fs::read_dir(&out_dir)
+        .expect("read out dir")
+        .map(|entry| entry.expect("entry").file_name())
+        .collect();
+    assert!(leftovers.is_empty(), "left behind: {leftovers:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn failed_conversion_reports_and_logs_tool_stderr() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().expect("tempdir");
+    // A stand-in ImageMagick that rejects its input the way a real one does.
+    let bin = temp.path().join("bin");
+    std::fs::create_dir(&bin).expect("create bin dir");
+    let magick = bin.join("magick");
+    std::fs::write(
+        &magick,
+        "#!/bin/sh\necho 'magick: improper image header `in.png' >&2\nexit 1\n",
+    )
+    .expect("write fake magick");
+    std::fs::set_permissions(&magick, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let source = temp.path().join("in.png");
+    std::fs::write(&source, b"not really a png").expect("write input");
+    let log = temp.path().join("mvx.log");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    let output = Command::new(mvx_bin())
+        .arg("--no-tui")
+        .arg("--log-file")
+        .arg(&log)
+        .arg(&source)
+        .arg(temp.path().join("out.jpg"))
+        .env("PATH", std::env::join_paths(paths).expect("join PATH"))
+        .output()
+        .expect("mvx failed to run");
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("ImageMagick exited with status"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("improper image header"), "{stderr}");
+    let text = std::fs::read_to_string(&log).expect("read log");
+    assert!(text.contains("ImageMagick stderr (tail)"), "{text}");
+    assert!(text.contains("    magick: improper image header"), "{text}");
+}